@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS evaluations (
+    id UUID PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL,
+    description TEXT NOT NULL,
+    request JSONB NOT NULL,
+    risks JSONB NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT,
+    parse_path TEXT,
+    latency_ms BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_created_at ON evaluations (created_at);
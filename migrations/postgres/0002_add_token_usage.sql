@@ -0,0 +1,2 @@
+ALTER TABLE evaluations ADD COLUMN IF NOT EXISTS prompt_tokens INTEGER;
+ALTER TABLE evaluations ADD COLUMN IF NOT EXISTS completion_tokens INTEGER;
@@ -0,0 +1,4 @@
+ALTER TABLE evaluations ADD COLUMN IF NOT EXISTS archived BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE evaluations ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_deleted_at ON evaluations (deleted_at);
@@ -0,0 +1,3 @@
+ALTER TABLE evaluations ADD COLUMN IF NOT EXISTS tags JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_tags ON evaluations USING GIN (tags);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS projects (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+ALTER TABLE evaluations ADD COLUMN IF NOT EXISTS project_id UUID REFERENCES projects (id);
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_project_id ON evaluations (project_id, created_at);
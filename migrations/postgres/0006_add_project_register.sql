@@ -0,0 +1 @@
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS register JSONB NOT NULL DEFAULT '[]'::jsonb;
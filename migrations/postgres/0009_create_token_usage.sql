@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS token_usage (
+    key_id TEXT NOT NULL,
+    day DATE NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    completion_tokens BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, day)
+);
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS monthly_token_budget BIGINT;
@@ -0,0 +1 @@
+ALTER TABLE token_usage ADD COLUMN IF NOT EXISTS cost_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS batch_id UUID;
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS batch_row INTEGER;
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS label TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_batch ON jobs (batch_id, batch_row);
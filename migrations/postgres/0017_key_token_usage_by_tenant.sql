@@ -0,0 +1,4 @@
+-- A key ID like `config:<name>` or `jwt:<sub>` can exist in several tenants, so each tenant
+-- keeps its own row per day.
+ALTER TABLE token_usage DROP CONSTRAINT IF EXISTS token_usage_pkey;
+ALTER TABLE token_usage ADD PRIMARY KEY (tenant_id, key_id, day);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS evaluations (
+    id TEXT PRIMARY KEY NOT NULL,
+    created_at TEXT NOT NULL,
+    description TEXT NOT NULL,
+    request TEXT NOT NULL,
+    risks TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT,
+    parse_path TEXT,
+    latency_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_created_at ON evaluations (created_at);
@@ -0,0 +1,2 @@
+ALTER TABLE evaluations ADD COLUMN prompt_tokens INTEGER;
+ALTER TABLE evaluations ADD COLUMN completion_tokens INTEGER;
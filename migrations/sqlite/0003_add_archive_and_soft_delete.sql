@@ -0,0 +1,4 @@
+ALTER TABLE evaluations ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE evaluations ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_deleted_at ON evaluations (deleted_at);
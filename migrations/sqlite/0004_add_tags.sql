@@ -0,0 +1 @@
+ALTER TABLE evaluations ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
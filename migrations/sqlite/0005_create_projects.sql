@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS projects (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE evaluations ADD COLUMN project_id TEXT REFERENCES projects (id);
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_project_id ON evaluations (project_id, created_at);
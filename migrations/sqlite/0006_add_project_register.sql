@@ -0,0 +1 @@
+ALTER TABLE projects ADD COLUMN register TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS token_usage (
+    key_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, day)
+);
+
+ALTER TABLE api_keys ADD COLUMN monthly_token_budget INTEGER;
@@ -0,0 +1,10 @@
+-- Rows created before tenants existed belong to the default tenant.
+ALTER TABLE evaluations ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE projects ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE api_keys ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE token_usage ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE audit_log ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_tenant_created_at ON evaluations (tenant_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_projects_tenant ON projects (tenant_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_tenant_created_at ON audit_log (tenant_id, created_at);
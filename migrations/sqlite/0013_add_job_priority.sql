@@ -0,0 +1 @@
+ALTER TABLE jobs ADD COLUMN priority TEXT NOT NULL DEFAULT 'interactive';
@@ -0,0 +1,5 @@
+ALTER TABLE evaluations ADD COLUMN request_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_evaluations_request_id ON evaluations (request_id);
+
+ALTER TABLE audit_log ADD COLUMN request_id TEXT;
@@ -0,0 +1 @@
+ALTER TABLE token_usage ADD COLUMN cost_usd REAL NOT NULL DEFAULT 0;
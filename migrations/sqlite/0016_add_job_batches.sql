@@ -0,0 +1,5 @@
+ALTER TABLE jobs ADD COLUMN batch_id TEXT;
+ALTER TABLE jobs ADD COLUMN batch_row INTEGER;
+ALTER TABLE jobs ADD COLUMN label TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_batch ON jobs (batch_id, batch_row);
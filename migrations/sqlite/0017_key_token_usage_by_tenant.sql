@@ -0,0 +1,18 @@
+-- A key ID like `config:<name>` or `jwt:<sub>` can exist in several tenants, so each tenant
+-- keeps its own row per day. SQLite can't change a primary key in place.
+CREATE TABLE token_usage_by_tenant (
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    key_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    PRIMARY KEY (tenant_id, key_id, day)
+);
+
+INSERT INTO token_usage_by_tenant (tenant_id, key_id, day, requests, prompt_tokens, completion_tokens, cost_usd)
+    SELECT tenant_id, key_id, day, requests, prompt_tokens, completion_tokens, cost_usd FROM token_usage;
+
+DROP TABLE token_usage;
+ALTER TABLE token_usage_by_tenant RENAME TO token_usage;
@@ -0,0 +1,715 @@
+use std::{fmt, sync::Arc, time::{Duration, Instant}};
+
+use anyhow::Result;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::{info, info_span, trace, warn, Instrument, Span};
+use utoipa::ToSchema;
+
+use crate::chunking::{Chunking, RiskMerger};
+use crate::config::AnalysisConfig;
+use crate::cost::{self, ProjectBudget};
+use crate::evidence;
+use crate::extraction::{self, ExtractionMode, ParsePath, RiskStream};
+use crate::providers::{
+    retry, ChatMessage, Completion, CompletionRequest, OutputFormat, Provider, ProviderRegistry, RetryBudget, TokenUsage,
+    ToolSpec,
+};
+use crate::reporting;
+use crate::severity::{Severity, SeverityScale};
+use crate::taxonomy::Taxonomy;
+use crate::tokens::InputLimit;
+use crate::RiskItem;
+
+/// Risks extracted by one provider, along with which backend and model produced them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Analysis {
+    pub risks: Vec<RiskItem>,
+    pub provider: String,
+    pub model: String,
+    pub parse_path: ParsePath,
+    /// Summed over the initial call and any correction re-prompts.
+    pub usage: Option<TokenUsage>,
+}
+
+/// Caller's choice of backend for one evaluation; `None` fields mean the registry defaults.
+#[derive(Debug, Default)]
+pub struct ProviderSelection {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+/// What a request's `timeout_ms` does to an evaluation the model hasn't finished.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OnTimeout {
+    /// Return the risks streamed so far, marked partial.
+    #[default]
+    Partial,
+    /// Fail with [`DeadlineExceeded`].
+    Error,
+}
+
+/// A caller's limit on how long the model may take.
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    pub limit: Duration,
+    pub on_timeout: OnTimeout,
+}
+
+/// The caller's deadline passed before the model finished, and they asked for an error.
+#[derive(Debug)]
+pub struct DeadlineExceeded(pub Duration);
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The model did not finish within {} ms", self.0.as_millis())
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+/// A provider didn't answer within the registry's per-call timeout.
+#[derive(Debug)]
+pub struct ProviderTimedOut(pub String);
+
+impl fmt::Display for ProviderTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Provider '{}' timed out", self.0)
+    }
+}
+
+impl std::error::Error for ProviderTimedOut {}
+
+const DEFAULT_JSON_RETRIES: u32 = 1;
+const DEFAULT_TEMPERATURE: f32 = 0.3;
+const DEFAULT_MAX_TOKENS: u32 = 500;
+
+const BASE_SYSTEM_PROMPT: &str = "You are a risk evaluator assistant. Extract project risks with their {severity}, likelihood (1 = rare, 2 = unlikely, 3 = possible, 4 = likely, 5 = almost certain), impact (1 = negligible, 2 = minor, 3 = moderate, 4 = major, 5 = severe), confidence (0.0 to 1.0, how strongly the description supports the risk), evidence (exact sentences quoted verbatim from the description that motivated the risk) and suggested mitigation strategies in JSON format as an array of objects with fields: {fields}.";
+
+const BASE_FIELDS: &str = "severity, category, mitigation, likelihood, impact, confidence, evidence";
+
+/// Pipeline settings shared by every attempt in the fallback chain. The global settings come
+/// from the `[analysis]` configuration; `max_risks`, `min_severity` and `budget` are filled in
+/// per request.
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    /// System prompt template; `{severity}` and `{fields}` are filled in from the scale.
+    pub system_prompt: Arc<str>,
+    pub temperature: f32,
+    /// Upper bound on the tokens in each reply.
+    pub max_tokens: u32,
+    pub extraction: ExtractionMode,
+    /// How many times the model is asked to correct a reply that neither parses nor repairs.
+    pub json_retries: u32,
+    /// Allowed categories; free-form when unset.
+    pub taxonomy: Option<Arc<Taxonomy>>,
+    pub severity_scale: SeverityScale,
+    /// Attach a 0–10 score to every risk even when the scale isn't numeric.
+    pub severity_scores: bool,
+    pub max_risks: Option<usize>,
+    pub min_severity: Option<Severity>,
+    /// Asks for a cost impact range per risk; its currency is already resolved.
+    pub budget: Option<ProjectBudget>,
+    /// What a request's budget is in when it doesn't say.
+    pub cost_currency: Arc<str>,
+    /// Extra guidance appended to the system prompt, e.g. a tenant's focus areas.
+    pub instructions: Option<String>,
+    pub chunking: Chunking,
+    pub input_limit: InputLimit,
+    /// Retries of transient provider failures left to this evaluation; clones share it.
+    pub retry_budget: RetryBudget,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            system_prompt: Arc::from(BASE_SYSTEM_PROMPT),
+            temperature: DEFAULT_TEMPERATURE,
+            max_tokens: DEFAULT_MAX_TOKENS,
+            extraction: ExtractionMode::default(),
+            json_retries: DEFAULT_JSON_RETRIES,
+            taxonomy: None,
+            severity_scale: SeverityScale::default(),
+            severity_scores: false,
+            max_risks: None,
+            min_severity: None,
+            budget: None,
+            cost_currency: Arc::from(cost::DEFAULT_CURRENCY),
+            instructions: None,
+            chunking: Chunking::default(),
+            input_limit: InputLimit::default(),
+            retry_budget: RetryBudget::default(),
+        }
+    }
+}
+
+impl AnalysisOptions {
+    /// Unknown extraction modes and severity scales, and a taxonomy file that doesn't load,
+    /// are logged and replaced by the defaults.
+    pub fn from_config(config: &AnalysisConfig) -> Self {
+        let extraction = match &config.extraction_mode {
+            Some(mode) => mode.parse().unwrap_or_else(|e| {
+                warn!("⚠️ {}, using default extraction mode.", e);
+                ExtractionMode::default()
+            }),
+            None => ExtractionMode::default(),
+        };
+        let taxonomy = config.risk_taxonomy_file.as_ref().and_then(|path| match Taxonomy::load(path) {
+            Ok(taxonomy) => {
+                info!("🗂️ Loaded {} risk categories from {}", taxonomy.categories.len(), path);
+                Some(Arc::new(taxonomy))
+            }
+            Err(e) => {
+                warn!("⚠️ {:?}, categories will not be constrained.", e);
+                None
+            }
+        });
+        let severity_scale = match &config.severity_scale {
+            Some(scale) => scale.parse().unwrap_or_else(|e| {
+                warn!("⚠️ {}, using the default scale.", e);
+                SeverityScale::default()
+            }),
+            None => SeverityScale::default(),
+        };
+        let cost_currency = match &config.cost_currency {
+            Some(code) => cost::parse_currency(code).unwrap_or_else(|| {
+                warn!("⚠️ '{}' is not an ISO 4217 currency code, using {}.", code, cost::DEFAULT_CURRENCY);
+                cost::DEFAULT_CURRENCY.to_string()
+            }),
+            None => cost::DEFAULT_CURRENCY.to_string(),
+        };
+        Self {
+            system_prompt: Arc::from(config.system_prompt.as_deref().unwrap_or(BASE_SYSTEM_PROMPT)),
+            temperature: config.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: config.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            extraction,
+            json_retries: config.json_repair_retries.unwrap_or(DEFAULT_JSON_RETRIES),
+            taxonomy,
+            severity_scale,
+            severity_scores: config.severity_scores,
+            cost_currency: Arc::from(cost_currency),
+            chunking: Chunking::from_config(config),
+            input_limit: InputLimit::from_config(config),
+            ..Self::default()
+        }
+    }
+
+    fn uses_score(&self) -> bool {
+        self.severity_scale.uses_score(self.severity_scores)
+    }
+
+    /// Post-processing applied to every provider's output: maps categories
+    /// onto the taxonomy and severities onto the configured scale, and tidies cost estimates.
+    pub fn normalize(&self, risks: &mut [RiskItem]) {
+        if let Some(taxonomy) = &self.taxonomy {
+            taxonomy.normalize(risks);
+        }
+        cost::normalize(risks, self.budget.as_ref());
+        for risk in risks {
+            (risk.severity, risk.score) = self.severity_scale.normalize(risk.severity, risk.score, self.severity_scores);
+        }
+    }
+}
+
+/// Runs the evaluation against each provider in the registry's chain until one succeeds. The
+/// model override only applies to the first provider; fallbacks use their own defaults.
+/// Texts longer than `chunk_max_chars` are evaluated in chunks, see [`analyze_chunks`].
+/// Dropping the future cancels the evaluation, aborting whichever provider request is in flight.
+pub async fn analyze_with_fallback(
+    registry: &ProviderRegistry,
+    selection: &ProviderSelection,
+    options: &AnalysisOptions,
+    project_text: &str,
+) -> Result<Analysis> {
+    let chain = registry.chain(selection.provider.as_deref());
+    let chunks = options.chunking.split(project_text);
+    if chunks.len() > 1 {
+        return analyze_chunks(registry, &chain, selection.model.as_deref(), options, project_text, &chunks, None).await;
+    }
+    analyze_chain(registry, &chain, selection.model.as_deref(), options, project_text).await
+}
+
+/// Like [`analyze_with_fallback`], but streams the first provider's reply and sends each risk
+/// to `risks` as soon as its object is complete. Streamed risks are a preview: if the whole
+/// reply doesn't parse, the rest of the chain runs without streaming and its risks are sent
+/// once it finishes, so the returned [`Analysis`] is what callers should keep. Chunked texts
+/// aren't streamed token by token; each chunk's new risks are sent as it finishes.
+pub async fn analyze_streaming(
+    registry: &ProviderRegistry,
+    selection: &ProviderSelection,
+    options: &AnalysisOptions,
+    project_text: &str,
+    risks: UnboundedSender<RiskItem>,
+) -> Result<Analysis> {
+    let chain = registry.chain(selection.provider.as_deref());
+    let chunks = options.chunking.split(project_text);
+    if chunks.len() > 1 {
+        return analyze_chunks(registry, &chain, selection.model.as_deref(), options, project_text, &chunks, Some(&risks)).await;
+    }
+    let Some((primary, fallbacks)) = chain.split_first() else {
+        anyhow::bail!("No LLM providers configured");
+    };
+    let error = match stream_primary(registry, primary.as_ref(), selection, options, project_text, &risks).await {
+        Ok(analysis) => return Ok(analysis),
+        Err(e) => e,
+    };
+    if fallbacks.is_empty() {
+        return Err(error);
+    }
+
+    let analysis = analyze_chain(registry, fallbacks, None, options, project_text).await?;
+    for risk in &analysis.risks {
+        let _ = risks.send(risk.clone());
+    }
+    Ok(analysis)
+}
+
+/// The streamed attempt at the chain's first provider, unless its circuit is open.
+async fn stream_primary(
+    registry: &ProviderRegistry,
+    primary: &dyn Provider,
+    selection: &ProviderSelection,
+    options: &AnalysisOptions,
+    project_text: &str,
+    risks: &UnboundedSender<RiskItem>,
+) -> Result<Analysis> {
+    let admission = registry.admit(primary).inspect_err(|_| {
+        warn!(provider = primary.name(), "🚧 Circuit open, skipping provider");
+    })?;
+    let started = Instant::now();
+    let attempt = stream_risks_ai(registry, primary, selection.model.as_deref(), options, project_text, risks)
+        .instrument(llm_span(primary, selection.model.as_deref()));
+    let outcome = tokio::time::timeout(registry.timeout(), attempt).await;
+    record_attempt(registry, primary, selection.model.as_deref(), &outcome, started);
+    admission.record(healthy(&outcome));
+    match outcome {
+        Ok(Ok(analysis)) => Ok(analysis),
+        Ok(Err(e)) => {
+            warn!(provider = primary.name(), error = ?e, "⚠️ Provider failed while streaming");
+            Err(e)
+        }
+        Err(_) => {
+            warn!(provider = primary.name(), timeout = ?registry.timeout(), "⚠️ Provider timed out");
+            let e = anyhow::Error::new(ProviderTimedOut(primary.name().to_string()));
+            reporting::call_failed(primary.name(), selection.model.as_deref(), &e);
+            Err(e)
+        }
+    }
+}
+
+/// [`analyze_streaming`] under the caller's deadline. When it passes, the evaluation is
+/// cancelled and, unless the caller asked for [`DeadlineExceeded`], the risks previewed so far
+/// come back as a [`ParsePath::Partial`] analysis. Token usage isn't reported for a cut-off
+/// stream, so a partial analysis carries none.
+pub async fn analyze_until(
+    registry: &ProviderRegistry,
+    selection: &ProviderSelection,
+    options: &AnalysisOptions,
+    project_text: &str,
+    deadline: Deadline,
+    risks: UnboundedSender<RiskItem>,
+) -> Result<Analysis> {
+    let (preview_tx, mut preview_rx) = mpsc::unbounded_channel::<RiskItem>();
+    let mut seen = Vec::new();
+    let analysis = analyze_streaming(registry, selection, options, project_text, preview_tx);
+    let collect = async {
+        while let Some(risk) = preview_rx.recv().await {
+            seen.push(risk.clone());
+            let _ = risks.send(risk);
+        }
+    };
+    let finished = tokio::time::timeout(deadline.limit, async { tokio::join!(analysis, collect).0 }).await;
+    if let Ok(result) = finished {
+        return result;
+    }
+
+    info!(timeout_ms = deadline.limit.as_millis() as u64, risks = seen.len(), "⏰ Deadline passed");
+    if deadline.on_timeout == OnTimeout::Error {
+        return Err(DeadlineExceeded(deadline.limit).into());
+    }
+    let primary = registry.chain(selection.provider.as_deref()).into_iter().next();
+    Ok(Analysis {
+        risks: seen,
+        provider: primary.as_ref().map_or("unknown", |p| p.name()).to_string(),
+        model: selection
+            .model
+            .clone()
+            .or_else(|| primary.map(|p| p.default_model().to_string()))
+            .unwrap_or_default(),
+        parse_path: ParsePath::Partial,
+        usage: None,
+    })
+}
+
+/// Map-reduce over a text too long for one call: each chunk runs through the whole `chain`,
+/// `chunk_concurrency` at a time, and their risks are merged as they come in, with evidence
+/// located in the whole text. Risks that aren't merged into an earlier one are sent to
+/// `preview`. If any chunk fails on every provider, so does the evaluation, since a missing
+/// chunk would silently drop its risks. Token usage is summed; the provider and model are
+/// those of the first chunk.
+async fn analyze_chunks(
+    registry: &ProviderRegistry,
+    chain: &[Arc<dyn Provider>],
+    model: Option<&str>,
+    options: &AnalysisOptions,
+    project_text: &str,
+    chunks: &[&str],
+    preview: Option<&UnboundedSender<RiskItem>>,
+) -> Result<Analysis> {
+    info!(chunks = chunks.len(), chars = project_text.chars().count(), "✂️ Evaluating a long project text in chunks");
+    let texts: Vec<String> = chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("[Part {} of {} of a longer project description]\n\n{}", i + 1, chunks.len(), chunk))
+        .collect();
+    // Collected first: a closure kept inside the stream makes the future not provably `Send`.
+    let calls: Vec<_> = texts.iter().map(|text| analyze_chain(registry, chain, model, options, text)).collect();
+    let mut analyses = futures::stream::iter(calls).buffered(options.chunking.concurrency);
+
+    let mut merger = RiskMerger::default();
+    let mut merged: Option<Analysis> = None;
+    while let Some(analysis) = analyses.next().await {
+        let mut analysis = analysis?;
+        let mut risks = std::mem::take(&mut analysis.risks);
+        evidence::locate_evidence(&mut risks, project_text);
+        for risk in risks {
+            if merger.add(risk.clone()) {
+                if let Some(preview) = preview {
+                    let _ = preview.send(risk);
+                }
+            }
+        }
+        merged = Some(match merged {
+            None => analysis,
+            Some(mut total) => {
+                total.usage = match (total.usage, analysis.usage) {
+                    (Some(total), Some(more)) => Some(total + more),
+                    (total, more) => total.or(more),
+                };
+                total.parse_path = total.parse_path.max(analysis.parse_path);
+                total
+            }
+        });
+    }
+    let mut analysis = merged.ok_or_else(|| anyhow::anyhow!("No chunks to evaluate"))?;
+    analysis.risks = merger.into_risks();
+    info!(chunks = chunks.len(), risks = analysis.risks.len(), "🧩 Merged chunk risks");
+    Ok(analysis)
+}
+
+/// Tries `chain` in order; `model` only applies to its first provider.
+async fn analyze_chain(
+    registry: &ProviderRegistry,
+    chain: &[Arc<dyn Provider>],
+    model: Option<&str>,
+    options: &AnalysisOptions,
+    project_text: &str,
+) -> Result<Analysis> {
+    let mut last_error = anyhow::anyhow!("No LLM providers configured");
+
+    for (i, provider) in chain.iter().enumerate() {
+        let model = if i == 0 { model } else { None };
+        let admission = match registry.admit(provider.as_ref()) {
+            Ok(admission) => admission,
+            Err(open) => {
+                warn!(provider = provider.name(), "🚧 Circuit open, skipping provider");
+                last_error = open.into();
+                continue;
+            }
+        };
+        let started = Instant::now();
+        let attempt =
+            analyze_risks_ai(registry, provider.as_ref(), model, options, project_text).instrument(llm_span(provider.as_ref(), model));
+        let outcome = tokio::time::timeout(registry.timeout(), attempt).await;
+        record_attempt(registry, provider.as_ref(), model, &outcome, started);
+        admission.record(healthy(&outcome));
+
+        match outcome {
+            Ok(Ok(analysis)) => return Ok(analysis),
+            Ok(Err(e)) => {
+                warn!(provider = provider.name(), error = ?e, "⚠️ Provider failed");
+                last_error = e;
+            }
+            Err(_) => {
+                warn!(provider = provider.name(), timeout = ?registry.timeout(), "⚠️ Provider timed out");
+                last_error = anyhow::Error::new(ProviderTimedOut(provider.name().to_string()));
+                reporting::call_failed(provider.name(), model, &last_error);
+            }
+        }
+    }
+
+    Err(last_error)
+}
+
+/// The client span around one provider attempt, re-prompts included.
+fn llm_span(provider: &dyn Provider, model: Option<&str>) -> Span {
+    info_span!(
+        "llm.request",
+        otel.kind = "client",
+        gen_ai.system = provider.name(),
+        gen_ai.request.model = model.unwrap_or(provider.default_model()),
+    )
+}
+
+/// Whether an attempt's outcome shows the provider is up, for its circuit breaker: anything
+/// but a timeout or a failure [`retry`] would have retried.
+fn healthy<T, E>(outcome: &Result<Result<T>, E>) -> bool {
+    match outcome {
+        Ok(Ok(_)) => true,
+        Ok(Err(e)) => retry::transient(e).is_none(),
+        Err(_) => false,
+    }
+}
+
+fn record_attempt<T, E>(
+    registry: &ProviderRegistry,
+    provider: &dyn Provider,
+    model: Option<&str>,
+    outcome: &Result<Result<T>, E>,
+    started: Instant,
+) {
+    let outcome = match outcome {
+        Ok(Ok(_)) => "ok",
+        Ok(Err(_)) => "error",
+        Err(_) => "timeout",
+    };
+    let elapsed = started.elapsed();
+    registry.calls.latency.observe(&[provider.name(), outcome], elapsed);
+    let model = model.unwrap_or(provider.default_model());
+    registry.calls.model_stats.record(&[provider.name(), model], elapsed, outcome != "ok");
+}
+
+async fn analyze_risks_ai(
+    registry: &ProviderRegistry,
+    provider: &dyn Provider,
+    model: Option<&str>,
+    options: &AnalysisOptions,
+    project_text: &str,
+) -> Result<Analysis> {
+    let mut request = completion_request(model, options, project_text);
+
+    let mut completion = complete_with_retries(registry, provider, options, &request)
+        .await
+        .inspect_err(|e| reporting::call_failed(provider.name(), model, e))?;
+
+    trace!(model = %completion.model, content = %completion.content, "📄 Extracted content");
+
+    let mut usage = completion.usage;
+    let mut parsed = parse_span().in_scope(|| extraction::parse_risks(&completion.content, provider.relaxed_json()));
+    let mut retries = 0;
+
+    while let Err(e) = &parsed {
+        registry.calls.parse_failures.inc(&[provider.name()]);
+        if retries >= options.json_retries {
+            break;
+        }
+        retries += 1;
+        info!(provider = provider.name(), attempt = retries, "🔁 Asking the model to correct malformed JSON");
+
+        let correction = format!(
+            "Your previous reply could not be parsed ({}). Reply again with only the corrected JSON and no other text.",
+            e
+        );
+        request.messages.push(ChatMessage::assistant(completion.content.clone()));
+        request.messages.push(ChatMessage::user(correction));
+
+        completion = complete_with_retries(registry, provider, options, &request)
+            .await
+            .inspect_err(|e| reporting::call_failed(provider.name(), model, e))?;
+        usage = match (usage, completion.usage) {
+            (Some(total), Some(more)) => Some(total + more),
+            (total, more) => total.or(more),
+        };
+        parsed = parse_span()
+            .in_scope(|| extraction::parse_risks(&completion.content, provider.relaxed_json()))
+            .map(|(risks, _)| (risks, ParsePath::Reprompted));
+    }
+
+    let (mut risks, parse_path) =
+        parsed.inspect_err(|e| reporting::parse_failed(provider.name(), &completion.model, e))?;
+    evidence::locate_evidence(&mut risks, project_text);
+    options.normalize(&mut risks);
+
+    Ok(Analysis {
+        risks,
+        provider: provider.name().to_string(),
+        model: completion.model,
+        parse_path,
+        usage,
+    })
+}
+
+/// One call to `provider`, retried while it fails transiently (a 429, a 5xx, a dropped
+/// connection), the retry policy allows another attempt and the evaluation's budget has one
+/// left. The backoff counts against the attempt's timeout like the calls themselves.
+async fn complete_with_retries(
+    registry: &ProviderRegistry,
+    provider: &dyn Provider,
+    options: &AnalysisOptions,
+    request: &CompletionRequest,
+) -> Result<Completion> {
+    let policy = registry.retry_policy();
+    let mut attempt = 0;
+    loop {
+        let error = match provider.complete(request).await {
+            Ok(completion) => return Ok(completion),
+            Err(e) => e,
+        };
+        let Some(transient) = retry::transient(&error) else {
+            return Err(error);
+        };
+        if attempt >= policy.max_retries {
+            return Err(error);
+        }
+        attempt += 1;
+        let Some(delay) = policy.delay(attempt, transient.retry_after) else {
+            warn!(provider = provider.name(), retry_after = ?transient.retry_after, "⚠️ Provider asked to wait longer than the retry backoff allows");
+            return Err(error);
+        };
+        if !options.retry_budget.take() {
+            registry.calls.retries_exhausted.inc(&[provider.name()]);
+            warn!(provider = provider.name(), "⚠️ Retry budget spent, not retrying: {}", error);
+            return Err(error);
+        }
+        registry.calls.retries.inc(&[provider.name(), transient.reason]);
+        warn!(provider = provider.name(), attempt, reason = transient.reason, delay = ?delay, "🔁 Retrying after a transient failure: {}", error);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Streams one provider's reply, previewing risks as they complete, then parses the whole
+/// reply. There is no correction re-prompt: a reply that doesn't parse falls through to the
+/// rest of the chain instead. Nor are failed calls retried, since the risks already previewed
+/// can't be taken back.
+async fn stream_risks_ai(
+    registry: &ProviderRegistry,
+    provider: &dyn Provider,
+    model: Option<&str>,
+    options: &AnalysisOptions,
+    project_text: &str,
+    risks: &UnboundedSender<RiskItem>,
+) -> Result<Analysis> {
+    let request = completion_request(model, options, project_text);
+    let (chunk_tx, mut chunk_rx) = mpsc::unbounded_channel::<String>();
+
+    let preview = async {
+        let mut stream = RiskStream::default();
+        while let Some(chunk) = chunk_rx.recv().await {
+            let mut found = stream.feed(&chunk);
+            evidence::locate_evidence(&mut found, project_text);
+            options.normalize(&mut found);
+            for risk in found {
+                let _ = risks.send(risk);
+            }
+        }
+    };
+    let (completion, ()) = tokio::join!(provider.complete_streaming(&request, chunk_tx), preview);
+    let completion = completion.inspect_err(|e| reporting::call_failed(provider.name(), model, e))?;
+
+    trace!(model = %completion.model, content = %completion.content, "📄 Streamed content");
+
+    let (mut found, parse_path) = parse_span()
+        .in_scope(|| extraction::parse_risks(&completion.content, provider.relaxed_json()))
+        .inspect_err(|e| {
+            registry.calls.parse_failures.inc(&[provider.name()]);
+            reporting::parse_failed(provider.name(), &completion.model, e);
+        })?;
+    evidence::locate_evidence(&mut found, project_text);
+    options.normalize(&mut found);
+
+    Ok(Analysis {
+        risks: found,
+        provider: provider.name().to_string(),
+        model: completion.model,
+        parse_path,
+        usage: completion.usage,
+    })
+}
+
+fn parse_span() -> Span {
+    info_span!("risks.parse")
+}
+
+/// A short hash of everything besides the project text that shapes the reply: the rendered
+/// system prompt, the output schema, the sampling settings and how long texts are chunked.
+/// Cached analyses are only reused while it's unchanged, so editing the prompt or reloading
+/// the taxonomy invalidates them.
+pub fn prompt_version(options: &AnalysisOptions) -> String {
+    let request = completion_request(None, options, "");
+    let digest = Sha256::digest(format!("{:?} {:?} {}", request, options.chunking, options.json_retries));
+    hex::encode(&digest[..8])
+}
+
+pub(crate) fn completion_request(model: Option<&str>, options: &AnalysisOptions, project_text: &str) -> CompletionRequest {
+    let system_msg = ChatMessage::system(system_prompt(options));
+
+    let user_msg = ChatMessage::user(format!(
+        "Analyze the following project description and return risks:\n\n{}",
+        project_text
+    ));
+
+    CompletionRequest {
+        messages: vec![system_msg, user_msg],
+        model: model.map(str::to_string),
+        max_tokens: options.max_tokens,
+        temperature: options.temperature,
+        output: output_format(options),
+    }
+}
+
+fn system_prompt(options: &AnalysisOptions) -> String {
+    let mut fields = BASE_FIELDS.to_string();
+    if options.uses_score() {
+        fields.push_str(", score");
+    }
+    if options.budget.is_some() {
+        fields.push_str(", cost_impact");
+    }
+    let mut prompt = options
+        .system_prompt
+        .replace("{severity}", &options.severity_scale.prompt_fragment(options.severity_scores))
+        .replace("{fields}", &fields);
+    if let Some(max) = options.max_risks {
+        prompt.push_str(&format!(" Return at most {} risks, keeping the most severe.", max));
+    }
+    if let Some(min) = options.min_severity {
+        let min = options.severity_scale.clamp(min);
+        prompt.push_str(&format!(" Only include risks of severity {} or higher.", min.as_str().to_lowercase()));
+    }
+    if let Some(budget) = &options.budget {
+        prompt.push_str(&budget.prompt_fragment());
+    }
+    if let Some(taxonomy) = &options.taxonomy {
+        prompt.push_str(&taxonomy.prompt_fragment());
+    }
+    if let Some(instructions) = &options.instructions {
+        prompt.push(' ');
+        prompt.push_str(instructions.trim());
+    }
+    prompt
+}
+
+fn output_format(options: &AnalysisOptions) -> OutputFormat {
+    let categories = options.taxonomy.as_ref().map(|taxonomy| taxonomy.names());
+    let schema = || {
+        extraction::risk_report_schema(
+            options.severity_scale.levels(),
+            options.uses_score(),
+            options.budget.is_some(),
+            categories.as_deref(),
+        )
+    };
+
+    match options.extraction {
+        ExtractionMode::Prompt => OutputFormat::Text,
+        ExtractionMode::JsonSchema => OutputFormat::JsonSchema(schema()),
+        ExtractionMode::Tool => OutputFormat::ToolCall(ToolSpec {
+            name: "report_risks".to_string(),
+            description: "Report the risks identified in the project description.".to_string(),
+            parameters: schema(),
+        }),
+    }
+}
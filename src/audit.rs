@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::auth::Identity;
+use crate::request_id::RequestId;
+use crate::storage::AuditEntry;
+use crate::AppState;
+
+/// Set on a response by handlers that create something, so the audit entry names the new
+/// resource; otherwise the `:id` in the route is used.
+#[derive(Debug, Clone)]
+pub struct AuditResource(pub String);
+
+/// Appends every authenticated request to the audit log once it has been answered, refusals
+/// included. Must run inside [`crate::auth::require_auth`]; a failed write is logged but
+/// doesn't fail the request.
+pub async fn record(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(identity) = request.extensions().get::<Identity>().cloned() else {
+        return next.run(request).await;
+    };
+    let pattern = request.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string());
+    let path = request.uri().path().to_string();
+    let action = format!("{} {}", request.method(), pattern.as_deref().unwrap_or(&path));
+    let path_resource = pattern.as_deref().and_then(|pattern| path_resource(pattern, &path));
+    let request_id = request.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+    let response = next.run(request).await;
+
+    let entry = AuditEntry {
+        id: Uuid::new_v4(),
+        created_at: chrono::Utc::now(),
+        tenant_id: identity.tenant,
+        key_id: identity.key_id,
+        actor: identity.name,
+        action,
+        resource_id: response.extensions().get::<AuditResource>().map(|r| r.0.clone()).or(path_resource),
+        status: response.status().as_u16() as i32,
+        request_id,
+    };
+    if let Err(e) = state.storage.insert_audit_entry(&entry).await {
+        error!("❌ Failed to write audit entry for {}: {:?}", entry.action, e);
+    }
+    response
+}
+
+/// The path segment matched by the route's `:id` parameter.
+fn path_resource(pattern: &str, path: &str) -> Option<String> {
+    pattern
+        .split('/')
+        .zip(path.split('/'))
+        .find(|(param, _)| *param == ":id")
+        .map(|(_, value)| value.to_string())
+}
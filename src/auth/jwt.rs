@@ -0,0 +1,148 @@
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::JwkSet,
+    Algorithm, DecodingKey, Validation,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::Role;
+use crate::tenant::DEFAULT_TENANT;
+
+/// Refetching the key set on an unknown `kid` is rate-limited so bogus tokens can't make us
+/// hammer the identity provider.
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_ROLE_CLAIM: &str = "role";
+const DEFAULT_TENANT_CLAIM: &str = "tenant";
+
+/// The claims we rely on, plus the rest for the configurable role claim.
+#[derive(Debug, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Space-separated OAuth scopes; everything the role grants when absent.
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    other: HashMap<String, Value>,
+}
+
+enum KeySource {
+    /// Shared secret for HMAC-signed tokens.
+    Secret(DecodingKey),
+    /// The identity provider's published keys, fetched lazily.
+    Jwks { url: String, client: Client, cache: RwLock<Option<(JwkSet, Instant)>> },
+}
+
+/// Validates bearer tokens from an external identity provider.
+pub struct JwtValidator {
+    issuer: String,
+    audience: String,
+    role_claim: String,
+    tenant_claim: String,
+    source: KeySource,
+}
+
+impl JwtValidator {
+    /// Enabled by `JWT_ISSUER` and `JWT_AUDIENCE` together with either `JWT_JWKS_URL` or
+    /// `JWT_SECRET` (HS256). `JWT_ROLE_CLAIM` and `JWT_TENANT_CLAIM` name the claims holding
+    /// the caller's role and tenant (default `role` and `tenant`).
+    pub fn from_env() -> Option<Self> {
+        let issuer = env::var("JWT_ISSUER").ok()?;
+        let Ok(audience) = env::var("JWT_AUDIENCE") else {
+            warn!("⚠️ JWT_ISSUER is set without JWT_AUDIENCE, JWT auth disabled.");
+            return None;
+        };
+        let source = if let Ok(url) = env::var("JWT_JWKS_URL") {
+            KeySource::Jwks { url, client: Client::new(), cache: RwLock::new(None) }
+        } else if let Ok(secret) = env::var("JWT_SECRET") {
+            KeySource::Secret(DecodingKey::from_secret(secret.as_bytes()))
+        } else {
+            warn!("⚠️ Neither JWT_JWKS_URL nor JWT_SECRET is set, JWT auth disabled.");
+            return None;
+        };
+        info!("🔐 Accepting JWTs issued by {}", issuer);
+        let role_claim = env::var("JWT_ROLE_CLAIM").unwrap_or_else(|_| DEFAULT_ROLE_CLAIM.to_string());
+        let tenant_claim = env::var("JWT_TENANT_CLAIM").unwrap_or_else(|_| DEFAULT_TENANT_CLAIM.to_string());
+        Some(Self { issuer, audience, role_claim, tenant_claim, source })
+    }
+
+    /// Checks signature, expiry, issuer and audience.
+    pub async fn validate(&self, token: &str) -> Result<Claims> {
+        let header = decode_header(token).context("Malformed token")?;
+        let (key, algorithm) = match &self.source {
+            KeySource::Secret(key) => (key.clone(), Algorithm::HS256),
+            KeySource::Jwks { .. } => {
+                let kid = header.kid.as_deref().context("Token has no key ID")?;
+                // The algorithm comes from the header, but only asymmetric ones are accepted so a
+                // public key can never be used as an HMAC secret.
+                if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+                    anyhow::bail!("HMAC-signed tokens are not accepted");
+                }
+                (self.jwks_key(kid).await?, header.alg)
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        Ok(decode::<Claims>(token, &key, &validation)?.claims)
+    }
+
+    /// The role claim may be a single name or a list (as with group claims); the most
+    /// privileged recognized role wins and tokens without one are viewers.
+    pub fn role(&self, claims: &Claims) -> Role {
+        let names: Vec<&str> = match claims.other.get(&self.role_claim) {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        names.into_iter().filter_map(|name| name.parse().ok()).max().unwrap_or(Role::Viewer)
+    }
+
+    /// Tokens without a tenant claim belong to the default tenant.
+    pub fn tenant(&self, claims: &Claims) -> String {
+        claims
+            .other
+            .get(&self.tenant_claim)
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_TENANT)
+            .to_string()
+    }
+
+    async fn jwks_key(&self, kid: &str) -> Result<DecodingKey> {
+        let KeySource::Jwks { url, client, cache } = &self.source else {
+            unreachable!("only called for JWKS sources")
+        };
+
+        if let Some((jwks, _)) = cache.read().await.as_ref() {
+            if let Some(jwk) = jwks.find(kid) {
+                return Ok(DecodingKey::from_jwk(jwk)?);
+            }
+        }
+
+        let mut cache = cache.write().await;
+        let stale = cache.as_ref().is_none_or(|(_, fetched)| fetched.elapsed() >= JWKS_REFRESH_INTERVAL);
+        if stale {
+            info!("🔑 Fetching JWKS from {}", url);
+            let jwks: JwkSet = client.get(url).send().await?.error_for_status()?.json().await?;
+            *cache = Some((jwks, Instant::now()));
+        }
+        let jwk = cache
+            .as_ref()
+            .and_then(|(jwks, _)| jwks.find(kid))
+            .with_context(|| format!("Unknown signing key '{}'", kid))?;
+        Ok(DecodingKey::from_jwk(jwk)?)
+    }
+}
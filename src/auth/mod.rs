@@ -0,0 +1,261 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    sync::Arc,
+};
+
+use axum::{
+    body::{self, Body},
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::tenant::DEFAULT_TENANT;
+use crate::AppState;
+
+mod jwt;
+mod oauth;
+mod permissions;
+mod scope;
+mod signing;
+
+pub use jwt::JwtValidator;
+pub use oauth::{OAuthError, OAuthServer};
+pub use permissions::{authorize, check, Role};
+pub use scope::{parse_scopes, Scope};
+pub use signing::SignatureVerifier;
+
+const API_KEY_HEADER: &str = "x-api-key";
+/// Signed request bodies are buffered in full to verify them.
+const MAX_SIGNED_BODY_BYTES: usize = 2 * 1024 * 1024;
+/// Prefix of generated keys, so they are recognizable in logs and secret scanners.
+const KEY_PREFIX: &str = "rk_";
+
+/// The caller a request was authenticated as, attached to the request extensions for logging
+/// and quotas.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    /// Stable key identifier: the database ID, `config:<name>` for keys from `API_KEYS`, or
+    /// `jwt:<sub>` / `oauth:<client>` for bearer tokens, or `hmac:<client>` for signed requests.
+    pub key_id: String,
+    pub name: String,
+    pub role: Role,
+    /// Tenant whose data the caller sees.
+    pub tenant: String,
+    /// Scopes a token was narrowed to; `None` means everything the role grants.
+    pub scopes: Option<HashSet<Scope>>,
+    /// Per-key override of the default monthly token budget.
+    pub monthly_token_budget: Option<i64>,
+}
+
+impl Identity {
+    fn anonymous() -> Self {
+        Self {
+            key_id: "anonymous".to_string(),
+            name: "anonymous".to_string(),
+            role: Role::Admin,
+            tenant: DEFAULT_TENANT.to_string(),
+            scopes: None,
+            monthly_token_budget: None,
+        }
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.as_ref().is_none_or(|scopes| scopes.contains(&scope))
+    }
+}
+
+/// Static keys from the environment, checked before the database, and the optional bearer
+/// token and request signature validators.
+pub struct AuthConfig {
+    /// SHA-256 of each key → its name, role and tenant.
+    static_keys: HashMap<String, StaticKey>,
+    jwt: Option<JwtValidator>,
+    pub oauth: Option<OAuthServer>,
+    signatures: Option<SignatureVerifier>,
+    pub disabled: bool,
+}
+
+struct StaticKey {
+    name: String,
+    role: Role,
+    tenant: String,
+}
+
+impl AuthConfig {
+    /// Reads `API_KEYS` as comma-separated `name:key[:role[:tenant]]` entries (role defaults to
+    /// admin, tenant to the default tenant),
+    /// the `JWT_*`, `OAUTH_*` and `HMAC_*` settings, and `AUTH_DISABLED` to turn authentication off for
+    /// local development.
+    pub fn from_env() -> Self {
+        let static_keys = env::var("API_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.trim().splitn(4, ':');
+                let (name, key) = (parts.next()?.trim(), parts.next()?.trim());
+                let role = match parts.next() {
+                    Some(role) => role.parse().map_err(|e| warn!("⚠️ Skipping API key '{}': {}", name, e)).ok()?,
+                    None => Role::Admin,
+                };
+                let tenant = parts.next().map_or(DEFAULT_TENANT, str::trim).to_string();
+                Some((hash_key(key), StaticKey { name: name.to_string(), role, tenant }))
+            })
+            .collect();
+        let disabled = env::var("AUTH_DISABLED").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        if disabled {
+            warn!("⚠️ Authentication is disabled.");
+        }
+        Self {
+            static_keys,
+            jwt: JwtValidator::from_env(),
+            oauth: OAuthServer::from_env(),
+            signatures: SignatureVerifier::from_env(),
+            disabled,
+        }
+    }
+}
+
+/// Keys are only ever stored and compared as hashes.
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// A new random key; shown to the caller once and stored hashed.
+pub fn generate_key() -> String {
+    format!("{}{}{}", KEY_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Rejects requests without a valid `X-Api-Key`, `Authorization: Bearer` token or request
+/// signature and attaches the caller's [`Identity`]; [`authorize`] then checks what they may do.
+pub async fn require_auth(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, String)> {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let signed = request.headers().contains_key(signing::CLIENT_HEADER);
+
+    let identity = if state.auth.disabled {
+        Identity::anonymous()
+    } else if signed {
+        let (identity, verified) = authenticate_signed(&state.auth, request).await?;
+        request = verified;
+        identity
+    } else if let Some(token) = bearer {
+        authenticate_bearer(&state.auth, token).await.map_err(|e| {
+            info!("🔐 Rejected bearer token: {:#}", e);
+            (StatusCode::UNAUTHORIZED, "Invalid bearer token".to_string())
+        })?
+    } else {
+        let key = request
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Missing X-Api-Key header".to_string()))?;
+        authenticate(&state, key)
+            .await?
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid API key".to_string()))?
+    };
+
+    request.extensions_mut().insert(identity);
+    Ok(next.run(request).await)
+}
+
+/// Tokens from our own token endpoint are tried first, then the external identity provider.
+async fn authenticate_bearer(auth: &AuthConfig, token: &str) -> anyhow::Result<Identity> {
+    let mut last_error = anyhow::anyhow!("Bearer tokens are not accepted");
+
+    if let Some(oauth) = &auth.oauth {
+        match oauth.validate(token) {
+            Ok(token) => {
+                return Ok(Identity {
+                    key_id: format!("oauth:{}", token.client_id),
+                    name: token.client_id,
+                    role: token.role,
+                    tenant: token.tenant,
+                    scopes: Some(token.scopes),
+                    monthly_token_budget: None,
+                });
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    if let Some(jwt) = &auth.jwt {
+        let claims = jwt.validate(token).await?;
+        let role = jwt.role(&claims);
+        let tenant = jwt.tenant(&claims);
+        return Ok(Identity {
+            key_id: format!("jwt:{}", claims.sub),
+            name: claims.name.unwrap_or(claims.sub),
+            role,
+            tenant,
+            scopes: claims.scope.as_deref().map(parse_scopes),
+            monthly_token_budget: None,
+        });
+    }
+    Err(last_error)
+}
+
+/// Buffers the body to check its signature, then hands back an equivalent request.
+async fn authenticate_signed(auth: &AuthConfig, request: Request) -> Result<(Identity, Request), (StatusCode, String)> {
+    let verifier = auth
+        .signatures
+        .as_ref()
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Signed requests are not accepted".to_string()))?;
+    let (parts, body) = request.into_parts();
+    let bytes = body::to_bytes(body, MAX_SIGNED_BODY_BYTES)
+        .await
+        .map_err(|_| (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large".to_string()))?;
+
+    let (client, role, tenant) = verifier.verify(&parts.method, &parts.uri, &parts.headers, &bytes).map_err(|e| {
+        info!("🔏 Rejected signed request: {:#}", e);
+        (StatusCode::UNAUTHORIZED, "Invalid request signature".to_string())
+    })?;
+    let identity = Identity {
+        key_id: format!("hmac:{}", client),
+        name: client,
+        role,
+        tenant,
+        scopes: None,
+        monthly_token_budget: None,
+    };
+    Ok((identity, Request::from_parts(parts, Body::from(bytes))))
+}
+
+async fn authenticate(state: &AppState, key: &str) -> Result<Option<Identity>, (StatusCode, String)> {
+    let hash = hash_key(key);
+    if let Some(key) = state.auth.static_keys.get(&hash) {
+        return Ok(Some(Identity {
+            key_id: format!("config:{}", key.name),
+            name: key.name.clone(),
+            role: key.role,
+            tenant: key.tenant.clone(),
+            scopes: None,
+            monthly_token_budget: None,
+        }));
+    }
+
+    let record = state.storage.find_api_key(&hash).await.map_err(|e| {
+        error!("❌ Failed to look up API key: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to verify API key".to_string())
+    })?;
+    Ok(record.map(|record| Identity {
+        key_id: record.id.to_string(),
+        name: record.name,
+        role: record.role,
+        tenant: record.tenant_id,
+        scopes: None,
+        monthly_token_budget: record.monthly_token_budget,
+    }))
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+};
+
+use anyhow::{Context, Result};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use super::scope::{format_scopes, parse_scopes, Scope};
+use super::{hash_key, Role};
+use crate::tenant::DEFAULT_TENANT;
+
+const DEFAULT_ISSUER: &str = "ai-risk-evaluator";
+const DEFAULT_TOKEN_TTL_SECS: i64 = 900;
+
+/// Machine clients allowed to use the client-credentials grant, loaded from a TOML file:
+///
+/// ```toml
+/// [[clients]]
+/// id = "ci"
+/// secret_sha256 = "9f86d0..."  # sha256 of the client secret, hex-encoded
+/// role = "evaluator"            # optional, defaults to evaluator
+/// tenant = "research"           # optional, defaults to the default tenant
+/// scopes = ["evaluate:write", "evaluations:read"]
+/// ```
+#[derive(Debug, Deserialize)]
+struct ClientsFile {
+    clients: Vec<ClientConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientConfig {
+    id: String,
+    secret_sha256: String,
+    #[serde(default = "default_client_role")]
+    role: Role,
+    #[serde(default = "default_tenant")]
+    tenant: String,
+    scopes: Vec<String>,
+}
+
+fn default_client_role() -> Role {
+    Role::Evaluator
+}
+
+fn default_tenant() -> String {
+    DEFAULT_TENANT.to_string()
+}
+
+struct OAuthClient {
+    secret_hash: String,
+    role: Role,
+    tenant: String,
+    scopes: HashSet<Scope>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TokenClaims {
+    sub: String,
+    iss: String,
+    aud: String,
+    iat: i64,
+    exp: i64,
+    role: Role,
+    #[serde(default = "default_tenant")]
+    tenant: String,
+    scope: String,
+}
+
+/// RFC 6749 error codes the token endpoint can return.
+#[derive(Debug, Clone, Copy)]
+pub enum OAuthError {
+    InvalidClient,
+    InvalidScope,
+    ServerError,
+}
+
+impl OAuthError {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::InvalidScope => "invalid_scope",
+            OAuthError::ServerError => "server_error",
+        }
+    }
+}
+
+/// Who a validated token was issued to and what it may do.
+pub struct TokenSubject {
+    pub client_id: String,
+    pub role: Role,
+    pub tenant: String,
+    pub scopes: HashSet<Scope>,
+}
+
+pub struct IssuedToken {
+    pub access_token: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+/// Issues and validates the service's own short-lived tokens for the client-credentials grant.
+pub struct OAuthServer {
+    clients: HashMap<String, OAuthClient>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    issuer: String,
+    ttl_secs: i64,
+}
+
+impl OAuthServer {
+    /// Enabled by `OAUTH_SIGNING_SECRET` and `OAUTH_CLIENTS_FILE`; `OAUTH_ISSUER` and
+    /// `OAUTH_TOKEN_TTL_SECS` (default 900) are optional.
+    pub fn from_env() -> Option<Self> {
+        let secret = env::var("OAUTH_SIGNING_SECRET").ok()?;
+        let Ok(path) = env::var("OAUTH_CLIENTS_FILE") else {
+            warn!("⚠️ OAUTH_SIGNING_SECRET is set without OAUTH_CLIENTS_FILE, token endpoint disabled.");
+            return None;
+        };
+        let clients = match load_clients(&path) {
+            Ok(clients) => clients,
+            Err(e) => {
+                warn!("⚠️ {:?}, token endpoint disabled.", e);
+                return None;
+            }
+        };
+        info!("🔐 Token endpoint enabled for {} OAuth clients", clients.len());
+
+        Some(Self {
+            clients,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            issuer: env::var("OAUTH_ISSUER").unwrap_or_else(|_| DEFAULT_ISSUER.to_string()),
+            ttl_secs: env::var("OAUTH_TOKEN_TTL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_TOKEN_TTL_SECS),
+        })
+    }
+
+    /// Authenticates the client and issues a token for the requested scopes, or for all of the
+    /// client's scopes when none are requested.
+    pub fn issue(&self, client_id: &str, client_secret: &str, requested: Option<&str>) -> Result<IssuedToken, OAuthError> {
+        let client = self
+            .clients
+            .get(client_id)
+            .filter(|client| client.secret_hash == hash_key(client_secret))
+            .ok_or(OAuthError::InvalidClient)?;
+
+        let scopes = match requested {
+            Some(requested) => {
+                let scopes = requested
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<HashSet<Scope>, _>>()
+                    .map_err(|_| OAuthError::InvalidScope)?;
+                if !scopes.is_subset(&client.scopes) {
+                    return Err(OAuthError::InvalidScope);
+                }
+                scopes
+            }
+            None => client.scopes.clone(),
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let claims = TokenClaims {
+            sub: client_id.to_string(),
+            iss: self.issuer.clone(),
+            aud: self.issuer.clone(),
+            iat: now,
+            exp: now + self.ttl_secs,
+            role: client.role,
+            tenant: client.tenant.clone(),
+            scope: format_scopes(&scopes),
+        };
+        let access_token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key).map_err(|e| {
+            error!("❌ Failed to sign token: {:?}", e);
+            OAuthError::ServerError
+        })?;
+
+        Ok(IssuedToken { access_token, expires_in: self.ttl_secs, scope: claims.scope })
+    }
+
+    /// The client, role, tenant and granted scopes of a token this server issued.
+    pub fn validate(&self, token: &str) -> Result<TokenSubject> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.issuer]);
+        let claims = decode::<TokenClaims>(token, &self.decoding_key, &validation)?.claims;
+        Ok(TokenSubject {
+            client_id: claims.sub,
+            role: claims.role,
+            tenant: claims.tenant,
+            scopes: parse_scopes(&claims.scope),
+        })
+    }
+}
+
+fn load_clients(path: &str) -> Result<HashMap<String, OAuthClient>> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read OAuth clients file {}", path))?;
+    let file: ClientsFile = toml::from_str(&text).with_context(|| format!("Failed to parse OAuth clients file {}", path))?;
+
+    file.clients
+        .into_iter()
+        .map(|client| {
+            let scopes = client
+                .scopes
+                .iter()
+                .map(|s| s.parse())
+                .collect::<Result<HashSet<Scope>, String>>()
+                .map_err(|e| anyhow::anyhow!("Client '{}': {}", client.id, e))?;
+            let secret_hash = client.secret_sha256.to_lowercase();
+            Ok((client.id, OAuthClient { secret_hash, role: client.role, tenant: client.tenant, scopes }))
+        })
+        .collect()
+}
@@ -0,0 +1,116 @@
+use std::{fmt, str::FromStr};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Identity, Scope};
+
+/// Coarse access level attached to every key and token. A caller may do something only if
+/// their role grants the route's [`Scope`] and, for tokens, the token carries it too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Reads stored evaluations and projects.
+    Viewer,
+    /// Runs evaluations and curates stored ones.
+    Evaluator,
+    /// Everything, including projects, keys and configuration.
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Evaluator => "evaluator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn grants(self, scope: Scope) -> bool {
+        match self {
+            Role::Viewer => matches!(scope, Scope::EvaluationsRead),
+            Role::Evaluator => matches!(scope, Scope::EvaluationsRead | Scope::EvaluateWrite | Scope::EvaluationsWrite),
+            Role::Admin => true,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "evaluator" => Ok(Role::Evaluator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("unknown role '{}'", s)),
+        }
+    }
+}
+
+/// The scope a route requires, keyed by its matched path pattern. Anything not listed needs
+/// [`Scope::ApiKeysManage`], which only admins hold, so a new route is locked down until it is
+/// added here.
+pub fn required_scope(method: &Method, path: &str) -> Scope {
+    match (method.as_str(), path) {
+        ("POST", "/evaluate")
+        | ("POST", "/evaluate/stream")
+        | ("POST", "/evaluate/upload")
+        | ("POST", "/evaluate/url")
+        | ("POST", "/evaluate/repo")
+        | ("POST", "/evaluate/manifest")
+        | ("POST", "/evaluate/async")
+        | ("POST", "/evaluate/batch")
+        | ("GET", "/ws") => Scope::EvaluateWrite,
+        // Mutations check their own scope; see [`check`].
+        ("POST", "/graphql") => Scope::EvaluationsRead,
+        ("GET", "/jobs/:id") | ("GET", "/batches/:id") | ("GET", "/batches/:id/results") => Scope::EvaluationsRead,
+        // Callers can always see their own consumption; other keys' usage is admin-only.
+        ("GET", "/usage") => Scope::EvaluationsRead,
+        // Only reads the evaluation it simulates.
+        ("POST", "/simulate") => Scope::EvaluationsRead,
+        ("GET", p) if p.starts_with("/evaluations") || p.starts_with("/projects") => Scope::EvaluationsRead,
+        (_, p) if p.starts_with("/evaluations") => Scope::EvaluationsWrite,
+        ("POST", "/projects") | ("PUT", "/projects/:id/register") => Scope::ProjectsWrite,
+        _ => Scope::ApiKeysManage,
+    }
+}
+
+/// Enforces [`required_scope`] for the authenticated [`Identity`]; must run inside
+/// [`super::require_auth`].
+pub async fn authorize(request: Request, next: Next) -> Result<Response, (StatusCode, String)> {
+    let identity = request
+        .extensions()
+        .get::<Identity>()
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+
+    if let Some(path) = request.extensions().get::<MatchedPath>() {
+        check(identity, required_scope(request.method(), path.as_str()))?;
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Whether both the identity's role and its token's scopes allow `scope`; for endpoints like
+/// `POST /graphql` whose operations need different scopes.
+pub fn check(identity: &Identity, scope: Scope) -> Result<(), (StatusCode, String)> {
+    if !identity.role.grants(scope) {
+        return Err((StatusCode::FORBIDDEN, format!("Role '{}' may not use {}", identity.role, scope)));
+    }
+    if !identity.has_scope(scope) {
+        return Err((StatusCode::FORBIDDEN, format!("Missing scope '{}'", scope)));
+    }
+    Ok(())
+}
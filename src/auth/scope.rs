@@ -0,0 +1,61 @@
+use std::{collections::HashSet, fmt, str::FromStr};
+
+/// A permission. Roles grant a fixed set of them; tokens may narrow that further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Run new evaluations.
+    EvaluateWrite,
+    /// Read stored evaluations and projects.
+    EvaluationsRead,
+    /// Delete, archive and tag stored evaluations.
+    EvaluationsWrite,
+    /// Create projects and import their registers.
+    ProjectsWrite,
+    /// Issue and revoke API keys.
+    ApiKeysManage,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::EvaluateWrite => "evaluate:write",
+            Scope::EvaluationsRead => "evaluations:read",
+            Scope::EvaluationsWrite => "evaluations:write",
+            Scope::ProjectsWrite => "projects:write",
+            Scope::ApiKeysManage => "api-keys:manage",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "evaluate:write" => Ok(Scope::EvaluateWrite),
+            "evaluations:read" => Ok(Scope::EvaluationsRead),
+            "evaluations:write" => Ok(Scope::EvaluationsWrite),
+            "projects:write" => Ok(Scope::ProjectsWrite),
+            "api-keys:manage" => Ok(Scope::ApiKeysManage),
+            _ => Err(format!("unknown scope '{}'", s)),
+        }
+    }
+}
+
+/// Parses an OAuth-style space-separated scope string, ignoring scopes this service doesn't
+/// define (identity providers add their own, such as `openid`).
+pub fn parse_scopes(scopes: &str) -> HashSet<Scope> {
+    scopes.split_whitespace().filter_map(|s| s.parse().ok()).collect()
+}
+
+pub fn format_scopes(scopes: &HashSet<Scope>) -> String {
+    let mut names: Vec<&str> = scopes.iter().map(|s| s.as_str()).collect();
+    names.sort_unstable();
+    names.join(" ")
+}
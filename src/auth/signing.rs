@@ -0,0 +1,196 @@
+use std::{collections::HashMap, env, sync::Mutex};
+
+use anyhow::{Context, Result};
+use axum::http::{HeaderMap, Method, Uri};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use super::Role;
+use crate::tenant::DEFAULT_TENANT;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const CLIENT_HEADER: &str = "x-signature-client";
+const TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+const NONCE_HEADER: &str = "x-signature-nonce";
+const SIGNATURE_HEADER: &str = "x-signature";
+const DEFAULT_MAX_SKEW_SECS: i64 = 300;
+/// Seen nonces are swept once the map grows past this many entries.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct SigningClient {
+    secret: String,
+    role: Role,
+    tenant: String,
+}
+
+/// Verifies HMAC-SHA256 request signatures from server-to-server callers sharing a secret
+/// with us. The signature covers
+///
+/// ```text
+/// <timestamp>\n<nonce>\n<METHOD>\n<path and query>\n<body>
+/// ```
+///
+/// where the timestamp is Unix seconds. Requests older (or newer) than the allowed skew are
+/// refused, and a nonce may only be used once within that window.
+pub struct SignatureVerifier {
+    clients: HashMap<String, SigningClient>,
+    max_skew_secs: i64,
+    /// `client:nonce` → the timestamp it was signed with.
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl SignatureVerifier {
+    /// Enabled by `HMAC_CLIENTS`, comma-separated `name:secret[:role[:tenant]]` entries (role
+    /// defaults to evaluator, tenant to the default tenant). `HMAC_MAX_SKEW_SECS` bounds clock
+    /// drift (default 300).
+    pub fn from_env() -> Option<Self> {
+        let clients: HashMap<_, _> = env::var("HMAC_CLIENTS")
+            .ok()?
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.trim().splitn(4, ':');
+                let (name, secret) = (parts.next()?.trim(), parts.next()?.trim());
+                let role = match parts.next() {
+                    Some(role) => role.parse().map_err(|e| warn!("⚠️ Skipping HMAC client '{}': {}", name, e)).ok()?,
+                    None => Role::Evaluator,
+                };
+                let tenant = parts.next().map_or(DEFAULT_TENANT, str::trim).to_string();
+                Some((name.to_string(), SigningClient { secret: secret.to_string(), role, tenant }))
+            })
+            .collect();
+        if clients.is_empty() {
+            return None;
+        }
+        let max_skew_secs = env::var("HMAC_MAX_SKEW_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_SKEW_SECS);
+        info!("🔏 Accepting signed requests from {} client(s)", clients.len());
+        Some(Self { clients, max_skew_secs, seen: Mutex::new(HashMap::new()) })
+    }
+
+    /// Checks the signature headers against the request and returns the client, its role and
+    /// its tenant.
+    pub fn verify(&self, method: &Method, uri: &Uri, headers: &HeaderMap, body: &[u8]) -> Result<(String, Role, String)> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .with_context(|| format!("Missing {} header", name))
+        };
+        let client = header(CLIENT_HEADER)?;
+        let timestamp = header(TIMESTAMP_HEADER)?;
+        let nonce = header(NONCE_HEADER)?;
+        let signature = hex::decode(header(SIGNATURE_HEADER)?).context("Signature is not hex")?;
+
+        let signer = self.clients.get(client).with_context(|| format!("Unknown client '{}'", client))?;
+        let signed_at: i64 = timestamp.parse().context("Timestamp is not a Unix time")?;
+        let now = chrono::Utc::now().timestamp();
+        // The header is the caller's: no arithmetic on it may overflow.
+        if now.abs_diff(signed_at) > self.max_skew_secs.max(0) as u64 {
+            anyhow::bail!("Timestamp is outside the allowed window");
+        }
+
+        let path = uri.path_and_query().map_or(uri.path(), |p| p.as_str());
+        let mut mac = HmacSha256::new_from_slice(signer.secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}\n{}\n{}\n{}\n", timestamp, nonce, method, path).as_bytes());
+        mac.update(body);
+        mac.verify_slice(&signature).map_err(|_| anyhow::anyhow!("Signature mismatch"))?;
+
+        // Only remembered once the signature checks out, so forged requests can't burn nonces.
+        let mut seen = self.seen.lock().unwrap();
+        if seen.len() > SWEEP_THRESHOLD {
+            seen.retain(|_, at| now - *at <= self.max_skew_secs);
+        }
+        if seen.insert(format!("{}:{}", client, nonce), signed_at).is_some() {
+            anyhow::bail!("Nonce has already been used");
+        }
+        Ok((client.to_string(), signer.role, signer.tenant.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "shared-secret";
+
+    fn verifier() -> SignatureVerifier {
+        let client = SigningClient { secret: SECRET.to_string(), role: Role::Admin, tenant: "acme".to_string() };
+        SignatureVerifier {
+            clients: HashMap::from([("billing".to_string(), client)]),
+            max_skew_secs: DEFAULT_MAX_SKEW_SECS,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn headers(timestamp: &str, nonce: &str, secret: &str, body: &[u8]) -> HeaderMap {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}\n{}\nPOST\n/evaluate?format=csv\n", timestamp, nonce).as_bytes());
+        mac.update(body);
+        let mut headers = HeaderMap::new();
+        headers.insert(CLIENT_HEADER, "billing".parse().unwrap());
+        headers.insert(TIMESTAMP_HEADER, timestamp.parse().unwrap());
+        headers.insert(NONCE_HEADER, nonce.parse().unwrap());
+        headers.insert(SIGNATURE_HEADER, hex::encode(mac.finalize().into_bytes()).parse().unwrap());
+        headers
+    }
+
+    fn verify(verifier: &SignatureVerifier, headers: &HeaderMap, body: &[u8]) -> Result<(String, Role, String)> {
+        verifier.verify(&Method::POST, &"/evaluate?format=csv".parse().unwrap(), headers, body)
+    }
+
+    fn now_offset(secs: i64) -> String {
+        (chrono::Utc::now().timestamp() + secs).to_string()
+    }
+
+    #[test]
+    fn accepts_a_valid_signature() {
+        let body = br#"{"description": "x"}"#;
+        let (client, role, tenant) = verify(&verifier(), &headers(&now_offset(0), "n1", SECRET, body), body).unwrap();
+        assert_eq!((client.as_str(), role, tenant.as_str()), ("billing", Role::Admin, "acme"));
+    }
+
+    #[test]
+    fn refuses_a_mismatched_signature() {
+        let verifier = verifier();
+        let err = verify(&verifier, &headers(&now_offset(0), "n1", "wrong-secret", b"{}"), b"{}").unwrap_err();
+        assert_eq!(err.to_string(), "Signature mismatch");
+        // A tampered body doesn't match either, and neither failure burns the nonce.
+        assert!(verify(&verifier, &headers(&now_offset(0), "n1", SECRET, b"{}"), b"{ }").is_err());
+        assert!(verify(&verifier, &headers(&now_offset(0), "n1", SECRET, b"{}"), b"{}").is_ok());
+    }
+
+    #[test]
+    fn refuses_timestamps_outside_the_window() {
+        let verifier = verifier();
+        for offset in [-DEFAULT_MAX_SKEW_SECS - 10, DEFAULT_MAX_SKEW_SECS + 10] {
+            let err = verify(&verifier, &headers(&now_offset(offset), "n1", SECRET, b""), b"").unwrap_err();
+            assert_eq!(err.to_string(), "Timestamp is outside the allowed window", "offset {}", offset);
+        }
+        for offset in [-DEFAULT_MAX_SKEW_SECS + 10, DEFAULT_MAX_SKEW_SECS - 10] {
+            let nonce = format!("n{}", offset);
+            assert!(verify(&verifier, &headers(&now_offset(offset), &nonce, SECRET, b""), b"").is_ok(), "offset {}", offset);
+        }
+    }
+
+    #[test]
+    fn refuses_extreme_timestamps_without_overflowing() {
+        let verifier = verifier();
+        for timestamp in [i64::MIN.to_string(), i64::MAX.to_string()] {
+            let err = verify(&verifier, &headers(&timestamp, "n1", SECRET, b""), b"").unwrap_err();
+            assert_eq!(err.to_string(), "Timestamp is outside the allowed window");
+        }
+    }
+
+    #[test]
+    fn refuses_a_replayed_nonce() {
+        let verifier = verifier();
+        let headers = headers(&now_offset(0), "once", SECRET, b"{}");
+        assert!(verify(&verifier, &headers, b"{}").is_ok());
+        let err = verify(&verifier, &headers, b"{}").unwrap_err();
+        assert_eq!(err.to_string(), "Nonce has already been used");
+    }
+}
@@ -0,0 +1,267 @@
+use std::{borrow::Cow, sync::Arc};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::sync::Semaphore;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::auth::Identity;
+use crate::config::LimitsConfig;
+use crate::jobs::{self, QueuedJob};
+use crate::negotiate::csv_cell;
+use crate::rating::Rating;
+use crate::request_id::RequestId;
+use crate::severity::Severity;
+use crate::storage::{Job, JobPriority, JobStatus};
+use crate::{prepare_evaluation, AppState, RiskItem, RiskRequest};
+
+const DEFAULT_MAX_ROWS: usize = 500;
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Column names accepted for the description and the row's name, compared case-insensitively,
+/// as for register imports.
+const DESCRIPTION_COLUMNS: &[&str] = &["description", "project description", "brief", "details"];
+const NAME_COLUMNS: &[&str] = &["name", "project", "project name", "title", "id"];
+/// `POST /evaluate` fields a column may set for its row, under the field's own name.
+const OPTION_COLUMNS: &[&str] =
+    &["provider", "model", "min_confidence", "max_risks", "min_severity", "tags", "project_id", "timeout_ms", "on_timeout"];
+const NUMBER_COLUMNS: &[&str] = &["min_confidence", "max_risks", "timeout_ms"];
+
+/// A row of a batch CSV: the request it makes, or why it can't be made.
+pub struct BatchRow {
+    /// Line number in the CSV, counting the header, so callers can find the row.
+    pub line: i32,
+    pub name: Option<String>,
+    pub request: Result<RiskRequest, String>,
+}
+
+/// Runs the rows of `POST /evaluate/batch` CSVs as batch-priority jobs. A batch only has
+/// `batch_concurrency` rows queued or running at a time, so a large one neither fills the job
+/// queue nor keeps other callers' batches waiting until it's done; the rest wait their turn as
+/// queued jobs.
+pub struct BatchRunner {
+    max_rows: usize,
+    concurrency: usize,
+}
+
+impl BatchRunner {
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        Self {
+            max_rows: limits.batch_max_rows.filter(|&n| n > 0).unwrap_or(DEFAULT_MAX_ROWS),
+            concurrency: limits.batch_concurrency.filter(|&n| n > 0).unwrap_or(DEFAULT_CONCURRENCY),
+        }
+    }
+
+    /// Parses a CSV with a header row and a description column. Other columns named after
+    /// `POST /evaluate` fields set them for their row, with `tags` split on `;`; `options` sets
+    /// them for every row that leaves them empty. Unknown columns are ignored. A row that
+    /// doesn't make a valid request is kept with its error so it can be reported.
+    pub fn parse(&self, data: &str, options: &Map<String, Value>) -> Result<Vec<BatchRow>> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(data.as_bytes());
+        let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_lowercase()).collect();
+        let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+        let description = column(DESCRIPTION_COLUMNS).context("The CSV needs a description column")?;
+        let name = column(NAME_COLUMNS);
+        let fields: Vec<(usize, &str)> = OPTION_COLUMNS.iter().filter_map(|&field| Some((column(&[field])?, field))).collect();
+
+        let mut rows = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let line = i as i32 + 2;
+            let record = record.with_context(|| format!("Invalid CSV row {}", line))?;
+            let cell = |index: usize| record.get(index).filter(|value| !value.is_empty());
+            let mut request = options.clone();
+            for &(index, field) in &fields {
+                if let Some(value) = cell(index) {
+                    request.insert(field.to_string(), option_value(field, value));
+                }
+            }
+            let request = match cell(description) {
+                Some(text) => {
+                    request.insert("description".to_string(), Value::String(text.to_string()));
+                    serde_json::from_value(Value::Object(request)).map_err(|e| format!("Invalid row: {}", e))
+                }
+                None => Err("The row has no description".to_string()),
+            };
+            rows.push(BatchRow { line, name: name.and_then(cell).map(str::to_string), request });
+            if rows.len() > self.max_rows {
+                anyhow::bail!("The CSV has more than {} rows", self.max_rows);
+            }
+        }
+        if rows.is_empty() {
+            anyhow::bail!("The CSV has no rows");
+        }
+        Ok(rows)
+    }
+
+    /// Queues the batch's rows in order, each once one of the batch's slots is free. Every
+    /// job is already stored as queued; rows refused here, by validation, the budget or a
+    /// full queue, are marked failed with the reason and the batch carries on.
+    pub fn spawn(&self, state: Arc<AppState>, identity: Identity, request_id: RequestId, rows: Vec<(Job, RiskRequest)>) {
+        let slots = Arc::new(Semaphore::new(self.concurrency));
+        tokio::spawn(async move {
+            let batch_id = rows.first().and_then(|(job, _)| job.batch_id);
+            for (mut job, payload) in rows {
+                let Ok(slot) = slots.clone().acquire_owned().await else { return };
+                let prepared = match prepare_evaluation(&state, &identity, &request_id, &payload).await {
+                    Ok(prepared) => prepared,
+                    Err((_, message)) => {
+                        job.error = Some(message);
+                        jobs::update(&state, &mut job, JobStatus::Failed).await;
+                        continue;
+                    }
+                };
+                let queued =
+                    QueuedJob { job: job.clone(), identity: identity.clone(), payload, prepared, callback_url: None, slot: Some(slot) };
+                if let Err(message) = state.jobs.enqueue(queued) {
+                    job.error = Some(message);
+                    jobs::update(&state, &mut job, JobStatus::Failed).await;
+                }
+            }
+            if let Some(batch_id) = batch_id {
+                info!(%batch_id, "📥 Queued the last row of batch");
+            }
+        });
+    }
+}
+
+/// Cells are text; numeric fields are passed on as numbers when they parse as one, so a bad
+/// value is reported against the field rather than as a type error.
+fn option_value(field: &str, value: &str) -> Value {
+    if field == "tags" {
+        return value.split(';').map(str::trim).filter(|tag| !tag.is_empty()).map(|tag| Value::String(tag.to_string())).collect();
+    }
+    if NUMBER_COLUMNS.contains(&field) {
+        if let Ok(number) = serde_json::from_str::<serde_json::Number>(value) {
+            return Value::Number(number);
+        }
+    }
+    Value::String(value.to_string())
+}
+
+/// A batch row's job, queued, or failed right away when the row isn't a valid request.
+pub fn row_job(identity: &Identity, batch_id: Uuid, row: &BatchRow) -> Job {
+    let mut job = Job::queued(identity, JobPriority::Batch);
+    job.batch_id = Some(batch_id);
+    job.batch_row = Some(row.line);
+    job.label = row.name.clone();
+    if let Err(message) = &row.request {
+        job.status = JobStatus::Failed;
+        job.error = Some(message.clone());
+    }
+    job
+}
+
+/// The part of a job's stored `RiskResponse` the results file reports.
+#[derive(Deserialize)]
+pub struct RowResult {
+    pub id: Uuid,
+    pub risks: Vec<RiskItem>,
+}
+
+impl RowResult {
+    pub fn of(job: &Job) -> Option<Self> {
+        serde_json::from_value(job.result.clone()?).ok()
+    }
+}
+
+/// A line of the results file: one per risk, or one for a row without any. Text cells go
+/// through [`csv_cell`].
+#[derive(Clone, Serialize)]
+struct ResultLine<'a> {
+    row: Option<i32>,
+    name: Option<Cow<'a, str>>,
+    status: &'static str,
+    evaluation_id: Option<Uuid>,
+    severity: Option<Severity>,
+    category: Option<Cow<'a, str>>,
+    likelihood: Option<Rating>,
+    impact: Option<Rating>,
+    confidence: Option<f32>,
+    mitigation: Option<Cow<'a, str>>,
+    covered_by: Option<Cow<'a, str>>,
+    error: Option<Cow<'a, str>>,
+}
+
+/// The batch's results as CSV, in row order, one line per risk so they can be sorted and
+/// filtered in a spreadsheet. Rows that are unfinished, failed or found no risks get a line
+/// with just their status.
+pub fn results_csv(jobs: &[Job]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for job in jobs {
+        let result = RowResult::of(job);
+        let line = ResultLine {
+            row: job.batch_row,
+            name: job.label.as_deref().map(csv_cell),
+            status: job.status.as_str(),
+            evaluation_id: result.as_ref().map(|result| result.id),
+            severity: None,
+            category: None,
+            likelihood: None,
+            impact: None,
+            confidence: None,
+            mitigation: None,
+            covered_by: None,
+            error: job.error.as_deref().map(csv_cell),
+        };
+        let risks = result.as_ref().map(|result| result.risks.as_slice()).unwrap_or_default();
+        if risks.is_empty() {
+            writer.serialize(&line)?;
+        }
+        for risk in risks {
+            writer.serialize(ResultLine {
+                severity: Some(risk.severity),
+                category: Some(csv_cell(&risk.category)),
+                likelihood: risk.likelihood,
+                impact: risk.impact,
+                confidence: Some(risk.confidence),
+                mitigation: Some(csv_cell(&risk.mitigation)),
+                covered_by: risk.covered_by.as_deref().map(csv_cell),
+                ..line.clone()
+            })?;
+        }
+    }
+    writer.into_inner().map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    fn job(label: &str, status: JobStatus, result: Option<Value>, error: Option<&str>) -> Job {
+        Job {
+            id: Uuid::new_v4(),
+            tenant_id: "default".to_string(),
+            key_id: "key".to_string(),
+            status,
+            priority: JobPriority::default(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            result,
+            error: error.map(str::to_string),
+            batch_id: Some(Uuid::new_v4()),
+            batch_row: Some(2),
+            label: Some(label.to_string()),
+        }
+    }
+
+    #[test]
+    fn results_csv_escapes_text_cells() {
+        let failed = job("=1+1", JobStatus::Failed, None, Some("-no provider"));
+        let result = json!({
+            "id": Uuid::new_v4(),
+            "risks": [{ "severity": "High", "category": "@Vendor", "mitigation": "+Renegotiate", "covered_by": "\tR-1" }],
+        });
+        let succeeded = job("Billing", JobStatus::Succeeded, Some(result), None);
+
+        let csv = String::from_utf8(results_csv(&[failed, succeeded]).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], "2,'=1+1,failed,,,,,,,,,'-no provider");
+        assert!(lines[2].starts_with("2,Billing,succeeded,"), "{}", lines[2]);
+        assert!(lines[2].ends_with(",High,'@Vendor,,,0.5,'+Renegotiate,'\tR-1,"), "{}", lines[2]);
+    }
+}
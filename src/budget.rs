@@ -0,0 +1,66 @@
+use axum::http::StatusCode;
+use chrono::{Datelike, NaiveDate, Utc};
+use tracing::{error, info};
+
+use crate::auth::Identity;
+use crate::config::LimitsConfig;
+use crate::tenant::Tenants;
+use crate::AppState;
+
+/// Monthly LLM token allowance per key. Months are calendar months in UTC.
+pub struct TokenBudget {
+    /// `token_budget_per_month`; keys without their own budget are unlimited when unset.
+    default_monthly: Option<i64>,
+}
+
+impl TokenBudget {
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        let default_monthly = limits.token_budget_per_month;
+        if let Some(budget) = default_monthly {
+            info!("💰 Default token budget: {} per key per month", budget);
+        }
+        Self { default_monthly }
+    }
+
+    /// The caller's budget: their key's own, if set, else their tenant's, else the default.
+    pub fn for_identity(&self, identity: &Identity, tenants: &Tenants) -> Option<i64> {
+        identity
+            .monthly_token_budget
+            .or_else(|| tenants.token_budget(&identity.tenant))
+            .or(self.default_monthly)
+    }
+}
+
+/// First day of the current month and of the next one, i.e. the half-open range budgets
+/// are counted over.
+pub fn current_month() -> (NaiveDate, NaiveDate) {
+    let today = Utc::now().date_naive();
+    let start = today.with_day(1).unwrap_or(today);
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    };
+    (start, next.unwrap_or(start))
+}
+
+/// Refuses with 402 once the caller has used up this month's budget. Requests already in
+/// flight may overshoot it slightly; nothing is enforced when authentication is disabled.
+pub async fn check(state: &AppState, identity: &Identity) -> Result<(), (StatusCode, String)> {
+    let Some(budget) = state.budget.for_identity(identity, &state.tenants).filter(|_| !state.auth.disabled) else {
+        return Ok(());
+    };
+    let (start, next) = current_month();
+    let used = state.storage.usage_between(&identity.tenant, &identity.key_id, start, next).await.map_err(|e| {
+        error!("❌ Failed to load usage of {}: {:?}", identity.key_id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to check token budget".to_string())
+    })?;
+    if used.total_tokens() >= budget {
+        info!("💰 '{}' is over budget ({} of {} tokens)", identity.name, used.total_tokens(), budget);
+        return Err((
+            StatusCode::PAYMENT_REQUIRED,
+            format!("Monthly token budget of {} exhausted; it resets on {}", budget, next),
+        ));
+    }
+    Ok(())
+}
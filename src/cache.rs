@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::analysis::Analysis;
+use crate::config::AnalysisConfig;
+use crate::shared::SharedStore;
+
+const DEFAULT_TTL_SECS: u64 = 3600;
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Whether an evaluation's risks were served from the cache or shared with an identical
+/// evaluation in flight (see [`crate::coalesce`]) rather than asked of a provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+    Coalesced,
+}
+
+impl CacheStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hit => "hit",
+            Self::Miss => "miss",
+            Self::Coalesced => "coalesced",
+        }
+    }
+}
+
+/// Successful analyses for `cache_ttl_secs`, so evaluating a text again, as a "re-run" does,
+/// costs no tokens and answers at once. Kept in Redis when it's configured, so replicas share
+/// them, and otherwise in memory, where the oldest entry makes room once `cache_max_entries`
+/// is reached.
+pub struct EvaluationCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (Instant, Analysis)>>,
+    shared: Option<SharedStore>,
+}
+
+impl EvaluationCache {
+    /// A TTL or size of 0 turns the cache off.
+    pub fn from_config(config: &AnalysisConfig, shared: Option<SharedStore>) -> Self {
+        let cache = Self {
+            ttl: Duration::from_secs(config.cache_ttl_secs.unwrap_or(DEFAULT_TTL_SECS)),
+            max_entries: config.cache_max_entries.unwrap_or(DEFAULT_MAX_ENTRIES),
+            entries: Mutex::new(HashMap::new()),
+            shared,
+        };
+        if cache.enabled() {
+            info!("🗃️ Caching evaluations for {}s, up to {}", cache.ttl.as_secs(), cache.max_entries);
+        }
+        cache
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    /// The analysis cached under `key`, without its token usage since a hit spends none. When
+    /// Redis can't be reached the lookup is a miss.
+    pub async fn get(&self, key: &str) -> Option<Analysis> {
+        if let Some(shared) = &self.shared {
+            let analysis = shared.get_json::<Analysis>(&format!("cache:{}", key)).await.unwrap_or_else(|e| {
+                warn!("⚠️ Failed to read the evaluation cache: {:?}", e);
+                None
+            });
+            return analysis.map(|analysis| Analysis { usage: None, ..analysis });
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let (stored, analysis) = entries.get(key)?;
+        if stored.elapsed() >= self.ttl {
+            entries.remove(key);
+            return None;
+        }
+        Some(Analysis { usage: None, ..analysis.clone() })
+    }
+
+    pub async fn insert(&self, key: String, analysis: &Analysis) {
+        if !self.enabled() {
+            return;
+        }
+        if let Some(shared) = &self.shared {
+            if let Err(e) = shared.set_json(&format!("cache:{}", key), analysis, self.ttl).await {
+                warn!("⚠️ Failed to write the evaluation cache: {:?}", e);
+            }
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+            if entries.len() >= self.max_entries {
+                let oldest = entries.iter().min_by_key(|(_, (stored, _))| *stored).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (Instant::now(), analysis.clone()));
+    }
+}
+
+/// What a cached analysis is found by: a hash of the tenant, the provider and model that
+/// would answer, the prompt version (see [`crate::analysis::prompt_version`]) and the text
+/// with runs of whitespace collapsed, so re-indenting a description still hits. Tenants never
+/// share entries.
+pub fn key(tenant: &str, provider: &str, model: &str, prompt_version: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [tenant, provider, model, prompt_version] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            hasher.update(b" ");
+        }
+        hasher.update(word.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
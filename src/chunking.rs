@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use crate::config::AnalysisConfig;
+use crate::diff;
+use crate::RiskItem;
+
+const DEFAULT_MAX_CHARS: usize = 32_000;
+const DEFAULT_OVERLAP_CHARS: usize = 1_600;
+const DEFAULT_CONCURRENCY: usize = 4;
+/// Minimum word overlap for two risks in the same category to be merged. Stricter than
+/// matching across evaluations, since both come from the same text and only the overlap or a
+/// repeated point should make them alike.
+const MERGE_THRESHOLD: f32 = 0.35;
+
+/// How project texts too long for one model call are split up.
+#[derive(Debug, Clone, Copy)]
+pub struct Chunking {
+    /// Longest text sent in one call, in characters; longer ones are split.
+    pub max_chars: usize,
+    /// How much of the end of each chunk the next one repeats, so a risk spelled out across
+    /// a boundary is still seen whole in one of them.
+    pub overlap_chars: usize,
+    /// How many chunks are evaluated at once.
+    pub concurrency: usize,
+}
+
+impl Default for Chunking {
+    fn default() -> Self {
+        Self { max_chars: DEFAULT_MAX_CHARS, overlap_chars: DEFAULT_OVERLAP_CHARS, concurrency: DEFAULT_CONCURRENCY }
+    }
+}
+
+impl Chunking {
+    /// The overlap is kept under half a chunk so every chunk moves the text forward.
+    pub fn from_config(config: &AnalysisConfig) -> Self {
+        let max_chars = config.chunk_max_chars.filter(|&n| n > 0).unwrap_or(DEFAULT_MAX_CHARS);
+        Self {
+            max_chars,
+            overlap_chars: config.chunk_overlap_chars.unwrap_or(DEFAULT_OVERLAP_CHARS).min(max_chars / 2),
+            concurrency: config.chunk_concurrency.filter(|&n| n > 0).unwrap_or(DEFAULT_CONCURRENCY),
+        }
+    }
+
+    /// `text` in chunks of at most `max_chars`, each repeating the last `overlap_chars` or so
+    /// of the one before. Chunks end at a paragraph break where there's one in the second half
+    /// of the chunk, else at a line break, else between words; overlaps start on a word.
+    pub fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        // Byte offset of every character, and the text's end.
+        let bounds: Vec<usize> = text.char_indices().map(|(i, _)| i).chain(std::iter::once(text.len())).collect();
+        let chars = bounds.len() - 1;
+        if chars <= self.max_chars {
+            return vec![text];
+        }
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        loop {
+            if chars - start <= self.max_chars {
+                chunks.push(&text[bounds[start]..]);
+                break;
+            }
+            let limit = start + self.max_chars;
+            let window = &text[bounds[start + self.max_chars / 2]..bounds[limit]];
+            let offset = bounds[start + self.max_chars / 2];
+            let cut = window
+                .rfind("\n\n")
+                .map(|i| i + 2)
+                .or_else(|| window.rfind('\n').map(|i| i + 1))
+                .or_else(|| window.char_indices().rfind(|(_, c)| c.is_whitespace()).map(|(i, c)| i + c.len_utf8()))
+                .map_or(bounds[limit], |i| offset + i);
+            let end = bounds.partition_point(|&b| b < cut);
+            chunks.push(&text[bounds[start]..bounds[end]]);
+
+            // Back up by the overlap, then forward to the start of a word.
+            let back = end.saturating_sub(self.overlap_chars).max(start + 1);
+            let overlap = &text[bounds[back]..bounds[end]];
+            let word = overlap.char_indices().find(|(_, c)| c.is_whitespace()).map_or(0, |(i, c)| i + c.len_utf8());
+            start = bounds.partition_point(|&b| b < bounds[back] + word).min(end);
+        }
+        chunks
+    }
+}
+
+/// Folds the risks found in each chunk into one list, merging those that are the same risk
+/// seen twice: same category and much the same wording. The merged risk takes the worse
+/// severity and ratings, the higher confidence and both risks' evidence.
+#[derive(Default)]
+pub struct RiskMerger {
+    risks: Vec<RiskItem>,
+    words: Vec<HashSet<String>>,
+}
+
+impl RiskMerger {
+    /// Whether `risk` is new, rather than merged into one already added.
+    pub fn add(&mut self, risk: RiskItem) -> bool {
+        let words = diff::risk_words(&risk);
+        let duplicate = self
+            .risks
+            .iter()
+            .zip(&self.words)
+            .enumerate()
+            .filter(|(_, (existing, _))| existing.category.eq_ignore_ascii_case(&risk.category))
+            .map(|(i, (_, existing))| (i, diff::jaccard(existing, &words)))
+            .filter(|&(_, similarity)| similarity >= MERGE_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, _)) = duplicate else {
+            self.risks.push(risk);
+            self.words.push(words);
+            return true;
+        };
+
+        let existing = &mut self.risks[i];
+        self.words[i].extend(words);
+        if risk.severity > existing.severity {
+            existing.severity = risk.severity;
+            existing.score = risk.score;
+            existing.mitigation = risk.mitigation;
+        }
+        existing.likelihood = existing.likelihood.max(risk.likelihood);
+        existing.impact = existing.impact.max(risk.impact);
+        existing.confidence = existing.confidence.max(risk.confidence);
+        for evidence in risk.evidence {
+            if !existing.evidence.iter().any(|e| e.quote == evidence.quote) {
+                existing.evidence.push(evidence);
+            }
+        }
+        false
+    }
+
+    /// The merged risks, most severe first.
+    pub fn into_risks(self) -> Vec<RiskItem> {
+        let mut risks = self.risks;
+        risks.sort_by_key(|risk| std::cmp::Reverse(risk.severity));
+        risks
+    }
+}
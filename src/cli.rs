@@ -0,0 +1,67 @@
+use std::net::{IpAddr, SocketAddr};
+
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{Config, Profile};
+
+/// Launch-time overrides, applied on top of the configuration file and the environment.
+#[derive(Debug, Clone, Parser)]
+#[command(version, about = "HTTP service that evaluates project descriptions for risks using LLMs")]
+pub struct Cli {
+    /// Configuration file to read instead of `CONFIG_FILE` or `./config.toml`.
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<String>,
+    /// `dev`, `staging` or `prod`, instead of `CONFIG_PROFILE`; selects which
+    /// `config.<profile>.toml` is merged over the base file.
+    #[arg(long)]
+    pub profile: Option<Profile>,
+    /// Address to serve the API on instead of the configured ones, with or without a port, e.g.
+    /// `0.0.0.0` or `0.0.0.0:8080`.
+    #[arg(long, value_name = "ADDR", value_parser = parse_bind)]
+    bind: Option<BindAddr>,
+    /// Port to serve the API on, replacing the one in each bind address.
+    #[arg(long)]
+    port: Option<u16>,
+    /// Default LLM provider, e.g. `openai`, `anthropic` or `rules`.
+    #[arg(long, value_name = "NAME")]
+    provider: Option<String>,
+    /// Log filter in `RUST_LOG` syntax, e.g. `debug` or `info,ai_risk_evaluator=trace`.
+    #[arg(long, value_name = "FILTER", value_parser = parse_log_level)]
+    pub log_level: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BindAddr {
+    ip: IpAddr,
+    port: Option<u16>,
+}
+
+impl Cli {
+    pub fn apply(&self, config: &mut Config) {
+        let bind = &mut config.server.bind;
+        if let Some(addr) = self.bind {
+            let port = addr.port.or(bind.first().map(SocketAddr::port)).unwrap_or_default();
+            *bind = vec![SocketAddr::new(addr.ip, port)];
+        }
+        if let Some(port) = self.port {
+            bind.iter_mut().for_each(|addr| addr.set_port(port));
+        }
+        if let Some(provider) = &self.provider {
+            config.providers.default = Some(provider.clone());
+        }
+    }
+}
+
+fn parse_bind(s: &str) -> Result<BindAddr, String> {
+    if let Ok(addr) = s.parse::<SocketAddr>() {
+        return Ok(BindAddr { ip: addr.ip(), port: Some(addr.port()) });
+    }
+    s.parse::<IpAddr>()
+        .map(|ip| BindAddr { ip, port: None })
+        .map_err(|_| format!("'{}' is neither an IP address nor an address with a port", s))
+}
+
+fn parse_log_level(s: &str) -> Result<String, String> {
+    EnvFilter::try_new(s).map(|_| s.to_string()).map_err(|e| e.to_string())
+}
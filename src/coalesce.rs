@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::analysis::Analysis;
+
+type Outcome = Result<Analysis, Arc<anyhow::Error>>;
+
+/// The failure of the evaluation a request was coalesced onto, shared by everyone who waited
+/// for it. [`Coalesced::cause`] is the original error, to map to a status like any other.
+#[derive(Debug)]
+pub struct Coalesced(Arc<anyhow::Error>);
+
+impl Coalesced {
+    pub fn cause(&self) -> &anyhow::Error {
+        &self.0
+    }
+}
+
+impl fmt::Display for Coalesced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Coalesced {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref().as_ref())
+    }
+}
+
+/// Evaluations running right now, by the key that identifies identical requests (see
+/// [`crate::cache::key`]). A request identical to one in flight waits for its result instead
+/// of paying for the same completion twice.
+#[derive(Default)]
+pub struct InFlight {
+    flights: Mutex<HashMap<String, watch::Sender<Option<Outcome>>>>,
+}
+
+impl InFlight {
+    /// Runs `analysis` unless an identical one is already running, in which case its result
+    /// is shared, with `shared` set and without token usage since this request spent none. If
+    /// the one running is cancelled before it finishes, e.g. because its client went away,
+    /// the waiting request runs `analysis` itself.
+    pub async fn run(&self, key: &str, analysis: impl Future<Output = anyhow::Result<Analysis>>) -> anyhow::Result<(Analysis, bool)> {
+        let waiting = {
+            let mut flights = self.flights.lock().unwrap();
+            match flights.get(key) {
+                Some(flight) => Some(flight.subscribe()),
+                None => {
+                    flights.insert(key.to_string(), watch::channel(None).0);
+                    None
+                }
+            }
+        };
+        let Some(mut waiting) = waiting else {
+            let lead = Lead { flights: self, key, finished: false };
+            let result = analysis.await;
+            return lead.finish(result).map(|analysis| (analysis, false));
+        };
+
+        info!("🪢 Waiting for an identical evaluation already in flight");
+        let outcome = waiting.wait_for(Option::is_some).await.ok().and_then(|outcome| outcome.clone());
+        match outcome {
+            Some(Ok(analysis)) => Ok((Analysis { usage: None, ..analysis }, true)),
+            Some(Err(e)) => Err(Coalesced(e).into()),
+            None => analysis.await.map(|analysis| (analysis, false)),
+        }
+    }
+}
+
+/// The running evaluation's place in [`InFlight`], given up when it finishes or is dropped.
+struct Lead<'a> {
+    flights: &'a InFlight,
+    key: &'a str,
+    finished: bool,
+}
+
+impl Lead<'_> {
+    fn finish(mut self, result: anyhow::Result<Analysis>) -> anyhow::Result<Analysis> {
+        self.finished = true;
+        let flight = self.flights.flights.lock().unwrap().remove(self.key);
+        let Some(flight) = flight.filter(|flight| flight.receiver_count() > 0) else {
+            return result;
+        };
+        match result {
+            Ok(analysis) => {
+                let _ = flight.send(Some(Ok(analysis.clone())));
+                Ok(analysis)
+            }
+            Err(e) => {
+                let e = Arc::new(e);
+                let _ = flight.send(Some(Err(e.clone())));
+                Err(Coalesced(e).into())
+            }
+        }
+    }
+}
+
+impl Drop for Lead<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.flights.flights.lock().unwrap().remove(self.key);
+        }
+    }
+}
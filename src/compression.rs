@@ -0,0 +1,28 @@
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tracing::info;
+
+use crate::config::ServerConfig;
+
+const DEFAULT_MIN_BYTES: u16 = 1024;
+
+/// Compresses responses with gzip or brotli, whichever the client's `Accept-Encoding` prefers,
+/// once they reach `compression_min_bytes`. Event streams are left alone so every event goes
+/// out as it happens, and so are PDFs and workbooks, which are compressed already.
+pub fn layer(config: &ServerConfig) -> CompressionLayer<impl Predicate> {
+    let min_bytes = config.compression_min_bytes.unwrap_or(DEFAULT_MIN_BYTES);
+    let predicate = SizeAbove::new(min_bytes)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::const_new("application/pdf"))
+        .and(NotForContentType::const_new("application/vnd.openxmlformats"));
+    let enabled = !config.compression_disabled;
+    if enabled {
+        info!("🗜️ Compressing responses of {} bytes or more", min_bytes);
+    } else {
+        info!("🗜️ Response compression is off");
+    }
+    CompressionLayer::new().gzip(enabled).br(enabled).compress_when(predicate)
+}
@@ -0,0 +1,163 @@
+use std::{
+    fmt,
+    future::Future,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    sync::Arc,
+};
+
+use axum::http::StatusCode;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::LimitsConfig;
+
+const DEFAULT_MAX_CONCURRENCY: usize = 16;
+const DEFAULT_MAX_QUEUED: usize = 64;
+
+/// Caps how many evaluations talk to the LLM providers at once. Each holds one permit for its
+/// whole analysis, fallbacks and re-prompts included, since those calls run one after another.
+pub struct UpstreamLimiter {
+    permits: Arc<Semaphore>,
+    max_concurrency: usize,
+    max_queued: usize,
+    queued: AtomicUsize,
+    /// Requests turned away because the wait queue was full, for `/metrics`.
+    pub rejected: AtomicU64,
+}
+
+impl UpstreamLimiter {
+    /// `llm_max_concurrency` calls may be in flight (default 16) and `llm_max_queued` more may
+    /// wait for a slot (default 64).
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        let max_concurrency = limits.llm_max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY).max(1);
+        let max_queued = limits.llm_max_queued.unwrap_or(DEFAULT_MAX_QUEUED);
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrency)),
+            max_concurrency,
+            max_queued,
+            queued: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// A slot for a request handler: immediately if one is free, after waiting if the queue
+    /// has room, and [`Saturated`] otherwise.
+    pub async fn admit(&self) -> Result<OwnedSemaphorePermit, Saturated> {
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(Saturated);
+        }
+        let _queued = Queued(&self.queued);
+        Ok(self.wait().await)
+    }
+
+    /// A slot for work that was already admitted elsewhere, such as a job worker; never refused.
+    pub async fn wait(&self) -> OwnedSemaphorePermit {
+        self.permits.clone().acquire_owned().await.expect("the semaphore is never closed")
+    }
+
+    /// Runs `analysis` holding a slot, [`admit`](Self::admit)ted or, for work admitted
+    /// elsewhere, [`wait`](Self::wait)ed for. Handed to [`crate::coalesce::InFlight::run`], so
+    /// only the evaluation that calls the provider takes one; those coalesced onto it don't.
+    pub async fn run<T>(&self, admitted: bool, analysis: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+        let _permit = if admitted { self.wait().await } else { self.admit().await? };
+        analysis.await
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.max_concurrency - self.permits.available_permits()
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+}
+
+/// The limiter's queue was full; a 503 for the caller.
+#[derive(Debug, Clone, Copy)]
+pub struct Saturated;
+
+impl fmt::Display for Saturated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Too many evaluations in progress, try again later")
+    }
+}
+
+impl std::error::Error for Saturated {}
+
+impl From<Saturated> for (StatusCode, String) {
+    fn from(saturated: Saturated) -> Self {
+        (StatusCode::SERVICE_UNAVAILABLE, saturated.to_string())
+    }
+}
+
+/// Leaves the wait queue on drop, including when the caller gives up waiting.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+    use crate::analysis::Analysis;
+    use crate::coalesce::InFlight;
+    use crate::extraction::ParsePath;
+
+    fn limiter(max_concurrency: usize, max_queued: usize) -> UpstreamLimiter {
+        UpstreamLimiter::from_config(&LimitsConfig {
+            llm_max_concurrency: Some(max_concurrency),
+            llm_max_queued: Some(max_queued),
+            ..LimitsConfig::default()
+        })
+    }
+
+    fn analysis() -> Analysis {
+        Analysis { risks: Vec::new(), provider: "mock".to_string(), model: "mock".to_string(), parse_path: ParsePath::Direct, usage: None }
+    }
+
+    #[tokio::test]
+    async fn admit_refuses_once_the_queue_is_full() {
+        let limiter = limiter(1, 0);
+        let permit = limiter.admit().await.unwrap();
+        assert!(limiter.admit().await.is_err());
+        assert_eq!(limiter.rejected.load(Ordering::Relaxed), 1);
+        drop(permit);
+        assert!(limiter.admit().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn coalesced_waiters_hold_no_slot() {
+        let (limiter, in_flight) = (limiter(1, 0), InFlight::default());
+        let (release, released) = oneshot::channel::<()>();
+        let leader = in_flight.run("key", limiter.run(false, async {
+            released.await.unwrap();
+            Ok(analysis())
+        }));
+        let waiters = futures::future::join_all(
+            (0..4).map(|_| in_flight.run("key", limiter.run(false, async { panic!("coalesced evaluations don't run") }))),
+        );
+        let check = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            // The leader has the only slot and the waiters took none from the queue.
+            assert_eq!((limiter.in_flight(), limiter.queued()), (1, 0));
+            assert_eq!(limiter.rejected.load(Ordering::Relaxed), 0);
+            release.send(()).unwrap();
+        };
+
+        let (leader, waiters, ()) = tokio::join!(leader, waiters, check);
+        assert!(!leader.unwrap().1);
+        assert!(waiters.into_iter().all(|waiter| waiter.unwrap().1));
+        assert_eq!(limiter.in_flight(), 0);
+    }
+}
@@ -0,0 +1,53 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// A validator for a response rendered from stored data, so a client polling a resource that
+/// hasn't changed gets a 304 instead of the same body again. Weak, since the bytes differ when
+/// the response is compressed (see [`crate::compression`]) while the content doesn't.
+pub struct ETag(HeaderValue);
+
+impl ETag {
+    /// Hashes `value` as it serializes, which `representation` (e.g. `pdf`) was rendered
+    /// from, and this build's version, since a release may render the same data differently.
+    pub fn of<T: Serialize>(value: &T, representation: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update([0]);
+        hasher.update(representation.as_bytes());
+        hasher.update([0]);
+        hasher.update(serde_json::to_vec(value).unwrap_or_default());
+        let tag = format!("W/\"{}\"", &hex::encode(hasher.finalize())[..32]);
+        Self(HeaderValue::from_str(&tag).expect("hex digits are a valid header value"))
+    }
+
+    /// Whether the request's `If-None-Match` lists this tag, or is `*`. Compared weakly, as
+    /// RFC 9110 has it, so the tag matches with or without its `W/`.
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        let Some(tag) = self.0.to_str().ok().map(|tag| tag.trim_start_matches("W/")) else {
+            return false;
+        };
+        headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == tag)
+    }
+
+    /// `304 Not Modified`, without a body.
+    pub fn not_modified(self) -> Response {
+        self.attach(StatusCode::NOT_MODIFIED)
+    }
+
+    /// `response` with the tag in its `ETag` header.
+    pub fn attach(self, response: impl IntoResponse) -> Response {
+        let mut response = response.into_response();
+        response.headers_mut().insert(header::ETAG, self.0);
+        response
+    }
+}
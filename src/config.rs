@@ -0,0 +1,794 @@
+use std::{
+    env, fmt, fs,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::info;
+
+/// Read when `CONFIG_FILE` isn't set, if it exists.
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const DEFAULT_BIND: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3000));
+
+/// Service settings, from `config.toml` (or the file named by `CONFIG_FILE`) merged with the
+/// active profile's file and environment variables layered on top. Every setting can be left out, falling back to the component's
+/// default, and every one has an environment variable that overrides the file; each field
+/// names its variable. Credentials for callers (`API_KEYS`, `JWT_*`, `OAUTH_*`, `HMAC_*`),
+/// webhooks, tenants, pricing and logging are still read from the environment only.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Chosen at launch rather than in a file.
+    #[serde(skip)]
+    pub profile: Profile,
+    /// The files the settings were read from, base first.
+    #[serde(skip)]
+    pub files: Vec<String>,
+    /// The base and profile files, whether or not they exist, for noticing changes.
+    #[serde(skip)]
+    pub sources: Vec<String>,
+    pub server: ServerConfig,
+    pub cors: CorsConfig,
+    pub providers: ProvidersConfig,
+    pub analysis: AnalysisConfig,
+    pub limits: LimitsConfig,
+    pub storage: StorageConfig,
+    pub secrets: SecretsConfig,
+}
+
+/// Which deployment the service runs as. Each has an optional file, `config.<profile>.toml`
+/// next to the base one, whose settings replace the base file's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Dev,
+    Staging,
+    Prod,
+}
+
+impl Profile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Profile::Dev => "dev",
+            Profile::Staging => "staging",
+            Profile::Prod => "prod",
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dev" | "development" => Ok(Profile::Dev),
+            "staging" => Ok(Profile::Staging),
+            "prod" | "production" => Ok(Profile::Prod),
+            other => Err(format!("Unknown profile '{}', expected dev, staging or prod", other)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// `BIND_ADDR`, comma-separated: one address or a list, each served the full API;
+    /// `127.0.0.1:3000` by default. `0.0.0.0:3000` listens on every interface.
+    #[serde(deserialize_with = "one_or_many")]
+    pub bind: Vec<SocketAddr>,
+    /// `ADMIN_BIND_ADDR`: a separate listener for `/metrics`, `/stats`, `/version` and the
+    /// probes, e.g. `127.0.0.1:9090`. Those move off the API listeners when it's set, apart
+    /// from the probes.
+    pub admin_bind: Option<SocketAddr>,
+    /// `UNIX_SOCKET`: also serves the API on a Unix socket at this path, e.g. for a local
+    /// reverse proxy. Set `bind = []` to serve only there.
+    pub unix_socket: Option<String>,
+    /// `UNIX_SOCKET_MODE`, octal: the socket's permissions; `0o660` by default.
+    pub unix_socket_mode: Option<u32>,
+    pub tls: TlsConfig,
+    /// `SHUTDOWN_TIMEOUT_SECS`
+    pub shutdown_timeout_secs: Option<u64>,
+    /// `READY_PROVIDER_CHECK_SECS`
+    pub ready_provider_check_secs: Option<u64>,
+    /// `CONFIG_WATCH_SECS`: how often the config files are checked for changes; 0 turns the
+    /// check off.
+    pub config_watch_secs: Option<u64>,
+    /// `COMPRESSION_MIN_BYTES`: responses smaller than this go out uncompressed; 1024 by
+    /// default.
+    pub compression_min_bytes: Option<u16>,
+    /// `COMPRESSION_DISABLED`: never compresses responses, e.g. behind a proxy that does.
+    pub compression_disabled: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: vec![DEFAULT_BIND],
+            admin_bind: None,
+            unix_socket: None,
+            unix_socket_mode: None,
+            tls: TlsConfig::default(),
+            shutdown_timeout_secs: None,
+            ready_provider_check_secs: None,
+            config_watch_secs: None,
+            compression_min_bytes: None,
+            compression_disabled: false,
+        }
+    }
+}
+
+/// HTTPS on the `bind` listeners; plain HTTP unless both files are set. The admin listener
+/// stays plain HTTP.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// `TLS_CERT_FILE`: PEM certificate chain, leaf first.
+    pub cert_file: Option<String>,
+    /// `TLS_KEY_FILE`: PEM private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_file: Option<String>,
+    /// `TLS_RELOAD_SECS`: how often the two files are checked for a renewed certificate; 0
+    /// turns the check off.
+    pub reload_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// `CORS_ALLOWED_ORIGINS`, comma-separated, e.g. `https://risk.example.com`; `*` allows any.
+    /// Browsers on other origins are refused when empty.
+    pub allowed_origins: Vec<String>,
+    /// `CORS_ALLOWED_METHODS`; the methods the API uses when empty, `*` for any.
+    pub allowed_methods: Vec<String>,
+    /// `CORS_ALLOWED_HEADERS`; the headers the API reads when empty, `*` for any.
+    pub allowed_headers: Vec<String>,
+    /// `CORS_ALLOW_CREDENTIALS`: lets browsers send cookies and HTTP auth.
+    pub allow_credentials: bool,
+    /// `CORS_MAX_AGE_SECS`: how long browsers may cache a preflight answer.
+    pub max_age_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProvidersConfig {
+    /// `LLM_PROVIDER`
+    pub default: Option<String>,
+    /// `LLM_FALLBACK_PROVIDERS`, comma-separated.
+    pub fallbacks: Vec<String>,
+    /// `LLM_TIMEOUT_SECS`
+    pub timeout_secs: Option<u64>,
+    /// `ALLOWED_MODELS`, comma-separated: models callers may request besides each provider's
+    /// default.
+    pub allowed_models: Vec<String>,
+    /// `LLM_MOCK` (any value): registers the mock provider.
+    pub mock: bool,
+    pub http: HttpClientConfig,
+    pub retry: RetryConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub openai: OpenAiConfig,
+    pub anthropic: AnthropicConfig,
+    pub azure: AzureConfig,
+    pub bedrock: BedrockConfig,
+    pub gemini: GeminiConfig,
+    pub ollama: OllamaConfig,
+}
+
+/// How the providers' HTTP clients connect; every provider has its own client built from these.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpClientConfig {
+    /// `HTTP_CONNECT_TIMEOUT_SECS`: 10 by default.
+    pub connect_timeout_secs: Option<u64>,
+    /// `HTTP_REQUEST_TIMEOUT_SECS`: bounds a whole call, reading a streamed answer included,
+    /// so a connection that stops responding can't hold a request forever; 300 by default.
+    pub request_timeout_secs: Option<u64>,
+    /// `HTTP_POOL_MAX_IDLE_PER_HOST`: idle connections kept open per host; unlimited by default.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// `HTTP_POOL_IDLE_TIMEOUT_SECS`: how long an idle connection is kept; 90 by default.
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// `HTTP_VERSION`
+    pub version: HttpVersion,
+    /// `HTTP_TCP_KEEPALIVE_SECS`: 60 by default, 0 turns keepalive probes off.
+    pub tcp_keepalive_secs: Option<u64>,
+    /// `HTTP_USER_AGENT`: `ai-risk-evaluator/<version>` by default.
+    pub user_agent: Option<String>,
+}
+
+/// How provider calls that fail with a 429, a 5xx or a dropped connection are retried before
+/// the next provider in the chain is tried.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    /// `LLM_MAX_RETRIES`: retries of a single call; 2 by default, 0 turns retrying off.
+    pub max_retries: Option<u32>,
+    /// `LLM_RETRY_BASE_DELAY_MS`: the first backoff, doubled on every retry; 500 by default.
+    pub base_delay_ms: Option<u64>,
+    /// `LLM_RETRY_MAX_DELAY_MS`: caps the backoff. A `Retry-After` longer than this isn't
+    /// waited for; 10000 by default.
+    pub max_delay_ms: Option<u64>,
+    /// `LLM_RETRY_BUDGET`: retries one evaluation may spend across its chunks and
+    /// fallbacks; 4 by default.
+    pub budget: Option<u32>,
+}
+
+/// When a provider that keeps failing is skipped, rather than every evaluation waiting for it to
+/// time out.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// `LLM_CIRCUIT_FAILURES`: failed calls in a row that open a provider's circuit; 5 by
+    /// default, 0 never opens it.
+    pub failure_threshold: Option<u32>,
+    /// `LLM_CIRCUIT_OPEN_SECS`: how long an open circuit skips the provider before a call is let
+    /// through to probe it; 30 by default.
+    pub open_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    /// HTTP/1.1, or HTTP/2 where the server offers it.
+    #[default]
+    Auto,
+    /// HTTP/1.1 only.
+    Http1,
+    /// HTTP/2 from the start, without negotiating; for gateways known to speak it.
+    Http2,
+}
+
+impl FromStr for HttpVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(HttpVersion::Auto),
+            "http1" => Ok(HttpVersion::Http1),
+            "http2" => Ok(HttpVersion::Http2),
+            other => Err(format!("Unknown HTTP version '{}', expected auto, http1 or http2", other)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpenAiConfig {
+    /// `OPENAI_API_KEY`
+    pub api_key: Option<String>,
+    /// `OPENAI_BASE_URL`
+    pub base_url: Option<String>,
+    /// `OPENAI_MODEL`
+    pub model: Option<String>,
+    /// `OPENAI_PROXY`
+    pub proxy: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnthropicConfig {
+    /// `ANTHROPIC_API_KEY`
+    pub api_key: Option<String>,
+    /// `ANTHROPIC_MODEL`
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AzureConfig {
+    /// `AZURE_OPENAI_API_KEY`
+    pub api_key: Option<String>,
+    /// `AZURE_OPENAI_RESOURCE`
+    pub resource: Option<String>,
+    /// `AZURE_OPENAI_DEPLOYMENT`
+    pub deployment: Option<String>,
+    /// `AZURE_OPENAI_API_VERSION`
+    pub api_version: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BedrockConfig {
+    /// `AWS_ACCESS_KEY_ID`
+    pub access_key_id: Option<String>,
+    /// `AWS_SECRET_ACCESS_KEY`
+    pub secret_access_key: Option<String>,
+    /// `AWS_SESSION_TOKEN`
+    pub session_token: Option<String>,
+    /// `AWS_REGION`, or `AWS_DEFAULT_REGION`.
+    pub region: Option<String>,
+    /// `BEDROCK_MODEL_ID`
+    pub model_id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeminiConfig {
+    /// `GEMINI_API_KEY`
+    pub api_key: Option<String>,
+    /// `GEMINI_BASE_URL`
+    pub base_url: Option<String>,
+    /// `GEMINI_MODEL`
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OllamaConfig {
+    /// `OLLAMA_BASE_URL`
+    pub base_url: Option<String>,
+    /// `OLLAMA_MODEL`
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnalysisConfig {
+    /// `SYSTEM_PROMPT`: replaces the built-in instructions. `{severity}` is replaced by the
+    /// scale's levels and `{fields}` by the fields each risk must have.
+    pub system_prompt: Option<String>,
+    /// `LLM_TEMPERATURE`
+    pub temperature: Option<f32>,
+    /// `LLM_MAX_TOKENS`
+    pub max_tokens: Option<u32>,
+    /// `EXTRACTION_MODE`: `json_schema`, `tool` or `prompt`.
+    pub extraction_mode: Option<String>,
+    /// `JSON_REPAIR_RETRIES`
+    pub json_repair_retries: Option<u32>,
+    /// `RISK_TAXONOMY_FILE`
+    pub risk_taxonomy_file: Option<String>,
+    /// `SEVERITY_SCALE`: `3`, `4`, `5` or `cvss`.
+    pub severity_scale: Option<String>,
+    /// `SEVERITY_SCORES`
+    pub severity_scores: bool,
+    /// `CHUNK_MAX_CHARS`: project texts longer than this are evaluated in chunks.
+    pub chunk_max_chars: Option<usize>,
+    /// `CHUNK_OVERLAP_CHARS`: how much of each chunk the next one repeats.
+    pub chunk_overlap_chars: Option<usize>,
+    /// `CHUNK_CONCURRENCY`: how many of a text's chunks are evaluated at once.
+    pub chunk_concurrency: Option<usize>,
+    /// `MAX_INPUT_TOKENS`: the most tokens a request's project text may have.
+    pub max_input_tokens: Option<usize>,
+    /// `OVER_TOKEN_LIMIT`: `reject` or `truncate` texts over `max_input_tokens`.
+    pub over_token_limit: Option<String>,
+    /// `CACHE_TTL_SECS`: how long a successful analysis is reused for the same text, prompt and
+    /// model; 3600 by default, 0 turns caching off.
+    pub cache_ttl_secs: Option<u64>,
+    /// `CACHE_MAX_ENTRIES`: analyses kept in the cache; 1000 by default.
+    pub cache_max_entries: Option<usize>,
+    /// `COST_CURRENCY`: ISO 4217 code of request budgets that don't name one; `USD` by default.
+    pub cost_currency: Option<String>,
+}
+
+/// Named after their environment variables, lower-cased.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub rate_limit_per_minute: Option<u32>,
+    pub rate_limit_burst: Option<u32>,
+    pub ip_rate_limit_per_minute: Option<u32>,
+    pub ip_rate_limit_burst: Option<u32>,
+    /// Addresses or CIDR blocks; comma-separated in the environment.
+    pub ip_allowlist: Vec<String>,
+    pub ip_denylist: Vec<String>,
+    pub trust_forwarded_for: bool,
+    /// Proxies in front of the service whose `X-Forwarded-For` entries are believed, for
+    /// chains of more than one; comma-separated in the environment. Only the connecting peer
+    /// when empty.
+    pub trusted_proxies: Vec<String>,
+    pub llm_max_concurrency: Option<usize>,
+    pub llm_max_queued: Option<usize>,
+    pub shed_soft_in_flight: Option<usize>,
+    pub shed_hard_in_flight: Option<usize>,
+    pub shed_latency_ms: Option<u64>,
+    pub shed_retry_after_secs: Option<u64>,
+    pub job_workers: Option<usize>,
+    pub job_queue_capacity: Option<usize>,
+    pub job_interactive_concurrency: Option<usize>,
+    pub job_batch_concurrency: Option<usize>,
+    /// Most rows a `POST /evaluate/batch` CSV may have; 500 by default.
+    pub batch_max_rows: Option<usize>,
+    /// How many of a batch's rows are queued or running at once; 4 by default.
+    pub batch_concurrency: Option<usize>,
+    pub token_budget_per_month: Option<i64>,
+    /// Largest file `POST /evaluate/upload` accepts, and largest page `POST /evaluate/url`
+    /// downloads; 10 MiB by default.
+    pub upload_max_bytes: Option<usize>,
+    /// How long `POST /evaluate/url` waits for a page; 15 seconds by default.
+    pub url_fetch_timeout_secs: Option<u64>,
+    /// Internal addresses or CIDR blocks `POST /evaluate/url` and `POST /evaluate/repo` may
+    /// fetch from anyway, e.g. an intranet wiki; comma-separated in the environment.
+    pub url_fetch_allowed_nets: Vec<String>,
+    /// How long `POST /evaluate/repo` may take to clone a repository; 60 seconds by default.
+    pub repo_clone_timeout_secs: Option<u64>,
+    /// Longest a single file in a repository's corpus gets before it's cut; 64 KiB by default.
+    pub repo_max_file_bytes: Option<usize>,
+    /// Most a repository's checkout, history included, may take up on disk before the clone is
+    /// abandoned; 64 MiB by default.
+    pub repo_max_clone_bytes: Option<u64>,
+    /// Largest corpus a repository is assembled into; 256 KiB by default.
+    pub repo_max_corpus_bytes: Option<usize>,
+    /// How many open issue titles go into the corpus; 100 by default.
+    pub repo_max_issues: Option<usize>,
+    /// How long `POST /evaluate` remembers an `Idempotency-Key` and its response; a day by
+    /// default.
+    pub idempotency_ttl_secs: Option<u64>,
+    /// How many runs `POST /simulate` simulates when the request doesn't say; 10000 by default.
+    pub simulation_iterations: Option<u32>,
+    /// The most runs a `POST /simulate` request may ask for; 100000 by default.
+    pub simulation_max_iterations: Option<u32>,
+}
+
+/// Named after their environment variables, lower-cased.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub database_url: Option<String>,
+    pub database_max_connections: Option<u32>,
+    pub purge_after_days: Option<i64>,
+    pub purge_interval_secs: Option<u64>,
+    /// Shares the evaluation cache, rate limits and job ownership between replicas; they're
+    /// kept in each process's memory when unset.
+    pub redis_url: Option<String>,
+    /// Prepended to every Redis key; `risk-evaluator:` by default.
+    pub redis_key_prefix: Option<String>,
+}
+
+/// Provider credentials fetched from a secrets manager at startup and refreshed while running,
+/// replacing the file's and the environment's.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretsConfig {
+    /// `SECRETS_BACKEND`: `vault` (HashiCorp Vault's KV v2 engine) or `aws` (AWS Secrets
+    /// Manager). Nothing is fetched when unset.
+    pub backend: Option<String>,
+    /// `SECRETS_REFRESH_SECS`: how often the secrets are fetched again, so rotated keys are
+    /// picked up; 0 fetches them at startup only.
+    pub refresh_secs: Option<u64>,
+    pub vault: VaultConfig,
+    pub aws: AwsSecretsConfig,
+    /// Where each credential is kept, as `<path or secret ID>#<field>`.
+    pub keys: SecretKeys,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VaultConfig {
+    /// `VAULT_ADDR`, e.g. `https://vault.example.com:8200`.
+    pub address: Option<String>,
+    /// `VAULT_TOKEN`
+    pub token: Option<String>,
+    /// `VAULT_NAMESPACE`, for Vault Enterprise.
+    pub namespace: Option<String>,
+    /// `VAULT_KV_MOUNT`: where the KV v2 engine is mounted; `secret` by default.
+    pub mount: Option<String>,
+}
+
+/// Signs in with the Bedrock provider's credentials, the standard `AWS_*` variables.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AwsSecretsConfig {
+    /// `SECRETS_AWS_REGION`; the Bedrock provider's region by default.
+    pub region: Option<String>,
+    /// `SECRETS_AWS_ENDPOINT`: replaces `https://secretsmanager.<region>.amazonaws.com`, e.g.
+    /// for LocalStack.
+    pub endpoint: Option<String>,
+}
+
+/// Without `#<field>`, an AWS secret's whole string is used; Vault paths always need one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretKeys {
+    /// `OPENAI_API_KEY_SECRET`
+    pub openai_api_key: Option<String>,
+    /// `ANTHROPIC_API_KEY_SECRET`
+    pub anthropic_api_key: Option<String>,
+    /// `AZURE_OPENAI_API_KEY_SECRET`
+    pub azure_api_key: Option<String>,
+    /// `GEMINI_API_KEY_SECRET`
+    pub gemini_api_key: Option<String>,
+    /// `AWS_ACCESS_KEY_ID_SECRET`, for Bedrock.
+    pub aws_access_key_id: Option<String>,
+    /// `AWS_SECRET_ACCESS_KEY_SECRET`, for Bedrock.
+    pub aws_secret_access_key: Option<String>,
+}
+
+impl Config {
+    /// Reads the base file — `path` (from `--config`), else the one named by `CONFIG_FILE`,
+    /// else `config.toml` if there is one — and merges the profile's file over it, then
+    /// applies the environment overrides. The profile is `profile` (from `--profile`), else
+    /// `CONFIG_PROFILE`, else `dev`. A missing explicit file, a malformed file and unparsable
+    /// override values are all errors rather than silently ignored; a missing profile file is
+    /// not.
+    pub fn load(path: Option<&str>, profile: Option<Profile>) -> Result<Self> {
+        let profile = match profile {
+            Some(profile) => profile,
+            None => parsed("CONFIG_PROFILE")?.unwrap_or_default(),
+        };
+        let explicit = path.map(str::to_string).or_else(|| env::var("CONFIG_FILE").ok());
+        let base = explicit.clone().unwrap_or_else(|| DEFAULT_CONFIG_FILE.to_string());
+        let overlay = profile_path(&base, profile);
+
+        let mut merged = toml::Table::new();
+        let mut loaded = Vec::new();
+        if explicit.is_some() || Path::new(&base).exists() {
+            merged = read_table(&base)?;
+            loaded.push(base.clone());
+        }
+        if Path::new(&overlay).exists() {
+            merge(&mut merged, read_table(&overlay)?);
+            loaded.push(overlay.clone());
+        }
+        let mut config: Self = toml::Value::Table(merged)
+            .try_into()
+            .with_context(|| format!("Invalid configuration in {}", loaded.join(", ")))?;
+        if !loaded.is_empty() {
+            info!("⚙️ Loaded {} configuration from {}", profile.as_str(), loaded.join(", "));
+        }
+        config.profile = profile;
+        config.files = loaded;
+        config.sources = vec![base, overlay];
+        config.apply_env()?;
+        if config.server.bind.is_empty() && config.server.unix_socket.is_none() {
+            anyhow::bail!("server.bind needs at least one address unless server.unix_socket is set");
+        }
+        Ok(config)
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        let server = &mut self.server;
+        if let Ok(value) = env::var("BIND_ADDR") {
+            server.bind = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|addr| addr.parse().map_err(|e| anyhow::anyhow!("Invalid BIND_ADDR '{}': {}", addr, e)))
+                .collect::<Result<_>>()?;
+        }
+        set_parsed(&mut server.admin_bind, "ADMIN_BIND_ADDR")?;
+        set(&mut server.unix_socket, "UNIX_SOCKET");
+        if let Ok(value) = env::var("UNIX_SOCKET_MODE") {
+            let digits = value.trim().trim_start_matches("0o");
+            let mode = u32::from_str_radix(digits, 8)
+                .map_err(|e| anyhow::anyhow!("Invalid UNIX_SOCKET_MODE '{}': {}", value, e))?;
+            server.unix_socket_mode = Some(mode);
+        }
+        let tls = &mut server.tls;
+        set(&mut tls.cert_file, "TLS_CERT_FILE");
+        set(&mut tls.key_file, "TLS_KEY_FILE");
+        set_parsed(&mut tls.reload_secs, "TLS_RELOAD_SECS")?;
+        set_parsed(&mut server.shutdown_timeout_secs, "SHUTDOWN_TIMEOUT_SECS")?;
+        set_parsed(&mut server.ready_provider_check_secs, "READY_PROVIDER_CHECK_SECS")?;
+        set_parsed(&mut server.config_watch_secs, "CONFIG_WATCH_SECS")?;
+        set_parsed(&mut server.compression_min_bytes, "COMPRESSION_MIN_BYTES")?;
+        set_flag(&mut server.compression_disabled, "COMPRESSION_DISABLED");
+
+        let cors = &mut self.cors;
+        set_list(&mut cors.allowed_origins, "CORS_ALLOWED_ORIGINS");
+        set_list(&mut cors.allowed_methods, "CORS_ALLOWED_METHODS");
+        set_list(&mut cors.allowed_headers, "CORS_ALLOWED_HEADERS");
+        set_flag(&mut cors.allow_credentials, "CORS_ALLOW_CREDENTIALS");
+        set_parsed(&mut cors.max_age_secs, "CORS_MAX_AGE_SECS")?;
+
+        let providers = &mut self.providers;
+        set(&mut providers.default, "LLM_PROVIDER");
+        set_list(&mut providers.fallbacks, "LLM_FALLBACK_PROVIDERS");
+        set_parsed(&mut providers.timeout_secs, "LLM_TIMEOUT_SECS")?;
+        set_list(&mut providers.allowed_models, "ALLOWED_MODELS");
+        providers.mock |= env::var("LLM_MOCK").is_ok();
+        let http = &mut providers.http;
+        set_parsed(&mut http.connect_timeout_secs, "HTTP_CONNECT_TIMEOUT_SECS")?;
+        set_parsed(&mut http.request_timeout_secs, "HTTP_REQUEST_TIMEOUT_SECS")?;
+        set_parsed(&mut http.pool_max_idle_per_host, "HTTP_POOL_MAX_IDLE_PER_HOST")?;
+        set_parsed(&mut http.pool_idle_timeout_secs, "HTTP_POOL_IDLE_TIMEOUT_SECS")?;
+        if let Some(version) = parsed("HTTP_VERSION")? {
+            http.version = version;
+        }
+        set_parsed(&mut http.tcp_keepalive_secs, "HTTP_TCP_KEEPALIVE_SECS")?;
+        set(&mut http.user_agent, "HTTP_USER_AGENT");
+        let retry = &mut providers.retry;
+        set_parsed(&mut retry.max_retries, "LLM_MAX_RETRIES")?;
+        set_parsed(&mut retry.base_delay_ms, "LLM_RETRY_BASE_DELAY_MS")?;
+        set_parsed(&mut retry.max_delay_ms, "LLM_RETRY_MAX_DELAY_MS")?;
+        set_parsed(&mut retry.budget, "LLM_RETRY_BUDGET")?;
+        let breaker = &mut providers.circuit_breaker;
+        set_parsed(&mut breaker.failure_threshold, "LLM_CIRCUIT_FAILURES")?;
+        set_parsed(&mut breaker.open_secs, "LLM_CIRCUIT_OPEN_SECS")?;
+        let openai = &mut providers.openai;
+        set(&mut openai.api_key, "OPENAI_API_KEY");
+        set(&mut openai.base_url, "OPENAI_BASE_URL");
+        set(&mut openai.model, "OPENAI_MODEL");
+        set(&mut openai.proxy, "OPENAI_PROXY");
+        let anthropic = &mut providers.anthropic;
+        set(&mut anthropic.api_key, "ANTHROPIC_API_KEY");
+        set(&mut anthropic.model, "ANTHROPIC_MODEL");
+        let azure = &mut providers.azure;
+        set(&mut azure.api_key, "AZURE_OPENAI_API_KEY");
+        set(&mut azure.resource, "AZURE_OPENAI_RESOURCE");
+        set(&mut azure.deployment, "AZURE_OPENAI_DEPLOYMENT");
+        set(&mut azure.api_version, "AZURE_OPENAI_API_VERSION");
+        let bedrock = &mut providers.bedrock;
+        set(&mut bedrock.access_key_id, "AWS_ACCESS_KEY_ID");
+        set(&mut bedrock.secret_access_key, "AWS_SECRET_ACCESS_KEY");
+        set(&mut bedrock.session_token, "AWS_SESSION_TOKEN");
+        set(&mut bedrock.region, "AWS_DEFAULT_REGION");
+        set(&mut bedrock.region, "AWS_REGION");
+        set(&mut bedrock.model_id, "BEDROCK_MODEL_ID");
+        let gemini = &mut providers.gemini;
+        set(&mut gemini.api_key, "GEMINI_API_KEY");
+        set(&mut gemini.base_url, "GEMINI_BASE_URL");
+        set(&mut gemini.model, "GEMINI_MODEL");
+        let ollama = &mut providers.ollama;
+        set(&mut ollama.base_url, "OLLAMA_BASE_URL");
+        set(&mut ollama.model, "OLLAMA_MODEL");
+
+        let analysis = &mut self.analysis;
+        set(&mut analysis.system_prompt, "SYSTEM_PROMPT");
+        set_parsed(&mut analysis.temperature, "LLM_TEMPERATURE")?;
+        set_parsed(&mut analysis.max_tokens, "LLM_MAX_TOKENS")?;
+        set(&mut analysis.extraction_mode, "EXTRACTION_MODE");
+        set_parsed(&mut analysis.json_repair_retries, "JSON_REPAIR_RETRIES")?;
+        set(&mut analysis.risk_taxonomy_file, "RISK_TAXONOMY_FILE");
+        set(&mut analysis.severity_scale, "SEVERITY_SCALE");
+        set_flag(&mut analysis.severity_scores, "SEVERITY_SCORES");
+        set_parsed(&mut analysis.chunk_max_chars, "CHUNK_MAX_CHARS")?;
+        set_parsed(&mut analysis.chunk_overlap_chars, "CHUNK_OVERLAP_CHARS")?;
+        set_parsed(&mut analysis.chunk_concurrency, "CHUNK_CONCURRENCY")?;
+        set_parsed(&mut analysis.max_input_tokens, "MAX_INPUT_TOKENS")?;
+        set(&mut analysis.over_token_limit, "OVER_TOKEN_LIMIT");
+        set_parsed(&mut analysis.cache_ttl_secs, "CACHE_TTL_SECS")?;
+        set_parsed(&mut analysis.cache_max_entries, "CACHE_MAX_ENTRIES")?;
+        set(&mut analysis.cost_currency, "COST_CURRENCY");
+
+        let limits = &mut self.limits;
+        set_parsed(&mut limits.rate_limit_per_minute, "RATE_LIMIT_PER_MINUTE")?;
+        set_parsed(&mut limits.rate_limit_burst, "RATE_LIMIT_BURST")?;
+        set_parsed(&mut limits.ip_rate_limit_per_minute, "IP_RATE_LIMIT_PER_MINUTE")?;
+        set_parsed(&mut limits.ip_rate_limit_burst, "IP_RATE_LIMIT_BURST")?;
+        set_list(&mut limits.ip_allowlist, "IP_ALLOWLIST");
+        set_list(&mut limits.ip_denylist, "IP_DENYLIST");
+        set_flag(&mut limits.trust_forwarded_for, "TRUST_FORWARDED_FOR");
+        set_list(&mut limits.trusted_proxies, "TRUSTED_PROXIES");
+        set_parsed(&mut limits.llm_max_concurrency, "LLM_MAX_CONCURRENCY")?;
+        set_parsed(&mut limits.llm_max_queued, "LLM_MAX_QUEUED")?;
+        set_parsed(&mut limits.shed_soft_in_flight, "SHED_SOFT_IN_FLIGHT")?;
+        set_parsed(&mut limits.shed_hard_in_flight, "SHED_HARD_IN_FLIGHT")?;
+        set_parsed(&mut limits.shed_latency_ms, "SHED_LATENCY_MS")?;
+        set_parsed(&mut limits.shed_retry_after_secs, "SHED_RETRY_AFTER_SECS")?;
+        set_parsed(&mut limits.job_workers, "JOB_WORKERS")?;
+        set_parsed(&mut limits.job_queue_capacity, "JOB_QUEUE_CAPACITY")?;
+        set_parsed(&mut limits.job_interactive_concurrency, "JOB_INTERACTIVE_CONCURRENCY")?;
+        set_parsed(&mut limits.job_batch_concurrency, "JOB_BATCH_CONCURRENCY")?;
+        set_parsed(&mut limits.batch_max_rows, "BATCH_MAX_ROWS")?;
+        set_parsed(&mut limits.batch_concurrency, "BATCH_CONCURRENCY")?;
+        set_parsed(&mut limits.token_budget_per_month, "TOKEN_BUDGET_PER_MONTH")?;
+        set_parsed(&mut limits.upload_max_bytes, "UPLOAD_MAX_BYTES")?;
+        set_parsed(&mut limits.url_fetch_timeout_secs, "URL_FETCH_TIMEOUT_SECS")?;
+        set_list(&mut limits.url_fetch_allowed_nets, "URL_FETCH_ALLOWED_NETS");
+        set_parsed(&mut limits.repo_clone_timeout_secs, "REPO_CLONE_TIMEOUT_SECS")?;
+        set_parsed(&mut limits.repo_max_file_bytes, "REPO_MAX_FILE_BYTES")?;
+        set_parsed(&mut limits.repo_max_clone_bytes, "REPO_MAX_CLONE_BYTES")?;
+        set_parsed(&mut limits.repo_max_corpus_bytes, "REPO_MAX_CORPUS_BYTES")?;
+        set_parsed(&mut limits.repo_max_issues, "REPO_MAX_ISSUES")?;
+        set_parsed(&mut limits.idempotency_ttl_secs, "IDEMPOTENCY_TTL_SECS")?;
+        set_parsed(&mut limits.simulation_iterations, "SIMULATION_ITERATIONS")?;
+        set_parsed(&mut limits.simulation_max_iterations, "SIMULATION_MAX_ITERATIONS")?;
+
+        let storage = &mut self.storage;
+        set(&mut storage.database_url, "DATABASE_URL");
+        set_parsed(&mut storage.database_max_connections, "DATABASE_MAX_CONNECTIONS")?;
+        set_parsed(&mut storage.purge_after_days, "PURGE_AFTER_DAYS")?;
+        set_parsed(&mut storage.purge_interval_secs, "PURGE_INTERVAL_SECS")?;
+        set(&mut storage.redis_url, "REDIS_URL");
+        set(&mut storage.redis_key_prefix, "REDIS_KEY_PREFIX");
+
+        let secrets = &mut self.secrets;
+        set(&mut secrets.backend, "SECRETS_BACKEND");
+        set_parsed(&mut secrets.refresh_secs, "SECRETS_REFRESH_SECS")?;
+        let vault = &mut secrets.vault;
+        set(&mut vault.address, "VAULT_ADDR");
+        set(&mut vault.token, "VAULT_TOKEN");
+        set(&mut vault.namespace, "VAULT_NAMESPACE");
+        set(&mut vault.mount, "VAULT_KV_MOUNT");
+        set(&mut secrets.aws.region, "SECRETS_AWS_REGION");
+        set(&mut secrets.aws.endpoint, "SECRETS_AWS_ENDPOINT");
+        let keys = &mut secrets.keys;
+        set(&mut keys.openai_api_key, "OPENAI_API_KEY_SECRET");
+        set(&mut keys.anthropic_api_key, "ANTHROPIC_API_KEY_SECRET");
+        set(&mut keys.azure_api_key, "AZURE_OPENAI_API_KEY_SECRET");
+        set(&mut keys.gemini_api_key, "GEMINI_API_KEY_SECRET");
+        set(&mut keys.aws_access_key_id, "AWS_ACCESS_KEY_ID_SECRET");
+        set(&mut keys.aws_secret_access_key, "AWS_SECRET_ACCESS_KEY_SECRET");
+        Ok(())
+    }
+}
+
+/// `config.toml` becomes `config.prod.toml`, in the same directory.
+fn profile_path(base: &str, profile: Profile) -> String {
+    let base = Path::new(base);
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match base.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, profile.as_str(), extension.to_string_lossy()),
+        None => format!("{}.{}", stem, profile.as_str()),
+    };
+    base.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn read_table(path: &str) -> Result<toml::Table> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read config file {}", path))?;
+    toml::from_str(&raw).with_context(|| format!("Failed to parse config file {}", path))
+}
+
+/// Tables are merged key by key; anything else in `overlay`, lists included, replaces what
+/// `base` had.
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(overlay)) => merge(existing, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Accepts a single value where a list is expected, e.g. `bind = "0.0.0.0:3000"`.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+fn set(field: &mut Option<String>, name: &str) {
+    if let Ok(value) = env::var(name) {
+        *field = Some(value);
+    }
+}
+
+fn set_parsed<T: FromStr>(field: &mut Option<T>, name: &str) -> Result<()>
+where
+    T::Err: fmt::Display,
+{
+    if let Some(value) = parsed(name)? {
+        *field = Some(value);
+    }
+    Ok(())
+}
+
+fn parsed<T: FromStr>(name: &str) -> Result<Option<T>>
+where
+    T::Err: fmt::Display,
+{
+    match env::var(name) {
+        Ok(value) => {
+            let parsed = value.trim().parse().map_err(|e| anyhow::anyhow!("Invalid {} '{}': {}", name, value, e))?;
+            Ok(Some(parsed))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+fn set_flag(field: &mut bool, name: &str) {
+    if let Ok(value) = env::var(name) {
+        *field = value == "1" || value.eq_ignore_ascii_case("true");
+    }
+}
+
+/// Comma-separated; an empty variable clears the list.
+fn set_list(field: &mut Vec<String>, name: &str) {
+    if let Ok(value) = env::var(name) {
+        *field = value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect();
+    }
+}
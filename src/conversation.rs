@@ -0,0 +1,76 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::analysis::AnalysisOptions;
+use crate::providers::{ChatMessage, Completion, CompletionRequest, OutputFormat, Provider, ProviderRegistry};
+use crate::RiskItem;
+
+const FOLLOW_UP_PROMPT: &str = "You are a risk evaluator assistant. You have already extracted the risks below from a project description. Answer the user's follow-up questions about the project and its risks in plain prose, concisely, and without repeating the whole list.";
+
+/// Context for follow-up questions about one evaluation, kept server-side for the lifetime of
+/// a WebSocket connection. Every question and answer is appended, so later questions can refer
+/// to earlier answers.
+pub struct Conversation {
+    provider: Arc<dyn Provider>,
+    model: Option<String>,
+    messages: Vec<ChatMessage>,
+}
+
+impl Conversation {
+    /// Continues with the provider and model that produced the risks, or the registry's
+    /// default provider if that one is no longer configured.
+    pub fn new(
+        registry: &ProviderRegistry,
+        description: &str,
+        risks: &[RiskItem],
+        provider: &str,
+        model: Option<&str>,
+    ) -> Result<Self> {
+        let (provider, model) = match registry.get(provider) {
+            Some(provider) => (provider, model.map(str::to_string)),
+            None => (registry.default_provider()?, None),
+        };
+        let messages = vec![
+            ChatMessage::system(FOLLOW_UP_PROMPT),
+            ChatMessage::user(format!("Project description:\n\n{}", description)),
+            ChatMessage::assistant(serde_json::to_string(risks)?),
+        ];
+        Ok(Self { provider, model, messages })
+    }
+
+    /// Asks a follow-up question, streaming the answer to `chunks`. A failed or timed-out call
+    /// leaves the context as it was, so the question can simply be asked again.
+    pub async fn ask(
+        &mut self,
+        question: &str,
+        options: &AnalysisOptions,
+        timeout: Duration,
+        chunks: UnboundedSender<String>,
+    ) -> Result<Completion> {
+        self.messages.push(ChatMessage::user(question));
+        let request = CompletionRequest {
+            messages: self.messages.clone(),
+            model: self.model.clone(),
+            max_tokens: options.max_tokens,
+            temperature: options.temperature,
+            output: OutputFormat::Text,
+        };
+
+        let answer = match tokio::time::timeout(timeout, self.provider.complete_streaming(&request, chunks)).await {
+            Ok(answer) => answer,
+            Err(_) => Err(anyhow::anyhow!("Provider '{}' timed out", self.provider.name())),
+        };
+        match answer {
+            Ok(completion) => {
+                self.messages.push(ChatMessage::assistant(completion.content.clone()));
+                Ok(completion)
+            }
+            Err(e) => {
+                self.messages.pop();
+                Err(e)
+            }
+        }
+    }
+}
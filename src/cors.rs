@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tracing::{info, warn};
+
+use crate::config::CorsConfig;
+use crate::idempotency::{IDEMPOTENCY_KEY_HEADER, REPLAYED_HEADER};
+use crate::request_id::REQUEST_ID_HEADER;
+
+/// Methods browsers may use when none are configured: everything the API routes accept.
+const DEFAULT_METHODS: [Method; 5] = [Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE];
+/// Request headers browsers may send when none are configured: the ones the API reads.
+const DEFAULT_HEADERS: [HeaderName; 9] = [
+    header::AUTHORIZATION,
+    header::CONTENT_TYPE,
+    header::IF_NONE_MATCH,
+    HeaderName::from_static("x-api-key"),
+    HeaderName::from_static("x-priority"),
+    REQUEST_ID_HEADER,
+    IDEMPOTENCY_KEY_HEADER,
+    HeaderName::from_static("traceparent"),
+    HeaderName::from_static("tracestate"),
+];
+
+/// Builds the CORS policy. Browsers on other origins are refused unless their origin is listed
+/// (`*` allows any); methods and headers default to the ones the API uses. Credentials can only
+/// be allowed for listed origins, methods and headers, never for `*`.
+pub fn layer(config: &CorsConfig) -> Result<CorsLayer> {
+    let any = |list: &[String]| list.iter().any(|entry| entry == "*");
+    if config.allow_credentials
+        && (any(&config.allowed_origins) || any(&config.allowed_methods) || any(&config.allowed_headers))
+    {
+        anyhow::bail!("CORS credentials can't be allowed together with '*' origins, methods or headers");
+    }
+
+    let origins = if any(&config.allowed_origins) {
+        warn!("⚠️ CORS allows any origin");
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            .map(|origin| HeaderValue::from_str(origin).with_context(|| format!("Invalid CORS origin '{}'", origin)))
+            .collect::<Result<Vec<_>>>()?;
+        if origins.is_empty() {
+            info!("🌐 CORS: no cross-origin browser access");
+        } else {
+            info!("🌐 CORS: allowing origins {}", config.allowed_origins.join(", "));
+        }
+        AllowOrigin::list(origins)
+    };
+
+    let methods = if any(&config.allowed_methods) {
+        AllowMethods::any()
+    } else if config.allowed_methods.is_empty() {
+        AllowMethods::list(DEFAULT_METHODS)
+    } else {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                    .with_context(|| format!("Invalid CORS method '{}'", method))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowMethods::list(methods)
+    };
+
+    let headers = if any(&config.allowed_headers) {
+        AllowHeaders::any()
+    } else if config.allowed_headers.is_empty() {
+        AllowHeaders::list(DEFAULT_HEADERS)
+    } else {
+        let headers = config
+            .allowed_headers
+            .iter()
+            .map(|name| HeaderName::try_from(name.as_str()).with_context(|| format!("Invalid CORS header '{}'", name)))
+            .collect::<Result<Vec<_>>>()?;
+        AllowHeaders::list(headers)
+    };
+
+    let mut cors = CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(config.allow_credentials)
+        .expose_headers([REQUEST_ID_HEADER, REPLAYED_HEADER, header::ETAG, header::RETRY_AFTER]);
+    if let Some(secs) = config.max_age_secs {
+        cors = cors.max_age(Duration::from_secs(secs));
+    }
+    Ok(cors)
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::RiskItem;
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// The figures a caller wants each risk's cost impact estimated against.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProjectBudget {
+    /// The project's total budget, e.g. `250000`.
+    pub total: f64,
+    /// The reserve set aside for risks, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contingency: Option<f64>,
+    /// ISO 4217 code the figures and estimates are in; the configured `cost_currency` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+impl ProjectBudget {
+    /// Checks the figures and fills in `currency`, upper-cased.
+    pub fn resolve(&self, default_currency: &str) -> Result<Self, String> {
+        if !self.total.is_finite() || self.total <= 0.0 {
+            return Err("budget.total must be a positive amount".to_string());
+        }
+        if self.contingency.is_some_and(|contingency| !contingency.is_finite() || contingency < 0.0) {
+            return Err("budget.contingency must not be negative".to_string());
+        }
+        let currency = match &self.currency {
+            Some(currency) => parse_currency(currency).ok_or("budget.currency must be a three-letter ISO 4217 code")?,
+            None => default_currency.to_string(),
+        };
+        Ok(Self { currency: Some(currency), ..self.clone() })
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)
+    }
+
+    /// Asks for a `cost_impact` per risk, in the budget's currency and relative to its figures.
+    pub fn prompt_fragment(&self) -> String {
+        let currency = self.currency();
+        let mut fragment = format!(
+            " Estimate what each risk would cost the project if it occurs as cost_impact, an object with min, likely and max amounts in {}, for a project with a budget of {:.0} {}",
+            currency, self.total, currency
+        );
+        if let Some(contingency) = self.contingency {
+            fragment.push_str(&format!(" and a contingency reserve of {:.0} {}", contingency, currency));
+        }
+        fragment.push('.');
+        fragment
+    }
+}
+
+/// `code` upper-cased, if it looks like an ISO 4217 code.
+pub fn parse_currency(code: &str) -> Option<String> {
+    let code = code.trim();
+    (code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())).then(|| code.to_ascii_uppercase())
+}
+
+/// What a risk would cost if it occurs: a three-point estimate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CostRange {
+    pub min: f64,
+    pub likely: f64,
+    pub max: f64,
+    /// Filled in from the request's budget; models aren't asked for it.
+    #[serde(default)]
+    pub currency: String,
+}
+
+impl CostRange {
+    /// The range in `currency` with its points in order and none below zero, or `None` if the
+    /// model returned something that isn't a number.
+    fn normalized(&self, currency: &str) -> Option<Self> {
+        let mut points = [self.min, self.likely, self.max];
+        if points.iter().any(|point| !point.is_finite()) {
+            return None;
+        }
+        points.sort_by(f64::total_cmp);
+        let [min, likely, max] = points.map(|point| point.max(0.0));
+        Some(Self { min, likely, max, currency: currency.to_string() })
+    }
+}
+
+/// Drops the cost estimates of an evaluation without a budget, and tidies the others.
+pub fn normalize(risks: &mut [RiskItem], budget: Option<&ProjectBudget>) {
+    for risk in risks {
+        risk.cost_impact = match budget {
+            Some(budget) => risk.cost_impact.as_ref().and_then(|range| range.normalized(budget.currency())),
+            None => None,
+        };
+    }
+}
+
+/// The risks' estimates added up.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CostExposure {
+    pub currency: String,
+    pub min: f64,
+    pub likely: f64,
+    pub max: f64,
+    /// `likely` as a fraction of the budget's total.
+    pub likely_share_of_budget: f64,
+    /// Whether `likely` is more than the contingency reserve; absent without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exceeds_contingency: Option<bool>,
+    /// How many of the risks had an estimate.
+    pub estimated_risks: usize,
+}
+
+impl CostExposure {
+    pub fn total(risks: &[RiskItem], budget: &ProjectBudget) -> Self {
+        let ranges: Vec<_> = risks.iter().filter_map(|risk| risk.cost_impact.as_ref()).collect();
+        let likely = ranges.iter().map(|range| range.likely).sum();
+        Self {
+            currency: budget.currency().to_string(),
+            min: ranges.iter().map(|range| range.min).sum(),
+            likely,
+            max: ranges.iter().map(|range| range.max).sum(),
+            likely_share_of_budget: likely / budget.total,
+            exceeds_contingency: budget.contingency.map(|contingency| likely > contingency),
+            estimated_risks: ranges.len(),
+        }
+    }
+}
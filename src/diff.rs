@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::RiskItem;
+
+/// Minimum word overlap for two risks in the same category to count as the same risk.
+const SAME_CATEGORY_THRESHOLD: f32 = 0.2;
+/// Risks that moved category need much closer wording to be matched.
+const CROSS_CATEGORY_THRESHOLD: f32 = 0.6;
+
+/// What changed between two evaluations of the same project.
+#[derive(Debug, Serialize)]
+pub struct RiskDiff {
+    /// Risks in the later evaluation with no counterpart in the earlier one.
+    pub new: Vec<RiskItem>,
+    /// Risks in the earlier evaluation that no longer appear.
+    pub resolved: Vec<RiskItem>,
+    pub severity_changes: Vec<SeverityChange>,
+    /// Matched risks whose severity stayed the same.
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeverityChange {
+    pub before: RiskItem,
+    pub after: RiskItem,
+    /// 0.0–1.0 word overlap the match was based on.
+    pub similarity: f32,
+}
+
+/// Pairs risks across two evaluations by category and wording rather than exact text, since
+/// models rarely phrase the same risk identically twice. Best-scoring pairs are matched first
+/// and each risk is matched at most once.
+pub fn diff_risks(before: &[RiskItem], after: &[RiskItem]) -> RiskDiff {
+    let before_words: Vec<_> = before.iter().map(risk_words).collect();
+    let after_words: Vec<_> = after.iter().map(risk_words).collect();
+
+    let mut candidates = Vec::new();
+    for (i, old) in before.iter().enumerate() {
+        for (j, new) in after.iter().enumerate() {
+            let similarity = jaccard(&before_words[i], &after_words[j]);
+            let threshold = if old.category.eq_ignore_ascii_case(&new.category) {
+                SAME_CATEGORY_THRESHOLD
+            } else {
+                CROSS_CATEGORY_THRESHOLD
+            };
+            if similarity >= threshold {
+                candidates.push((i, j, similarity));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let mut matched_before = vec![false; before.len()];
+    let mut matched_after = vec![false; after.len()];
+    let mut severity_changes = Vec::new();
+    let mut unchanged = 0;
+
+    for (i, j, similarity) in candidates {
+        if matched_before[i] || matched_after[j] {
+            continue;
+        }
+        matched_before[i] = true;
+        matched_after[j] = true;
+        if before[i].severity == after[j].severity {
+            unchanged += 1;
+        } else {
+            severity_changes.push(SeverityChange { before: before[i].clone(), after: after[j].clone(), similarity });
+        }
+    }
+
+    RiskDiff {
+        new: unmatched(after, &matched_after),
+        resolved: unmatched(before, &matched_before),
+        severity_changes,
+        unchanged,
+    }
+}
+
+fn unmatched(risks: &[RiskItem], matched: &[bool]) -> Vec<RiskItem> {
+    risks.iter().zip(matched).filter(|(_, &m)| !m).map(|(risk, _)| risk.clone()).collect()
+}
+
+/// Lower-cased words of a risk's mitigation and evidence.
+pub fn risk_words(risk: &RiskItem) -> HashSet<String> {
+    let evidence = risk.evidence.iter().map(|e| e.quote.as_str());
+    words(std::iter::once(risk.mitigation.as_str()).chain(evidence))
+}
+
+/// Lower-cased words of the given texts, ignoring very short ones.
+pub fn words<'a>(texts: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+    texts
+        .into_iter()
+        .flat_map(|text| text.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+pub fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::RiskItem;
+
+const MAX_DOCUMENTS: usize = 20;
+const MAX_NAME_LEN: usize = 100;
+
+/// One of the documents a request describes the project with, e.g. its charter, budget or
+/// staffing plan.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SourceDocument {
+    pub name: String,
+    pub text: String,
+}
+
+/// Where the description and each document's text sit in the combined project text, in
+/// characters.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    /// Where the description ends; `None` without documents, when the text is all description.
+    description_end: Option<usize>,
+    documents: Vec<DocumentSpan>,
+}
+
+#[derive(Debug, Clone)]
+struct DocumentSpan {
+    name: String,
+    start: usize,
+    end: usize,
+}
+
+/// Names must be present, short and unique, ignoring case, so every risk can be attributed
+/// to exactly one of them.
+pub fn validate(description: &str, documents: &[SourceDocument]) -> Result<(), String> {
+    if documents.is_empty() && description.trim().is_empty() {
+        return Err("Send a description, documents or both".to_string());
+    }
+    if documents.len() > MAX_DOCUMENTS {
+        return Err(format!("At most {} documents are allowed", MAX_DOCUMENTS));
+    }
+    for (i, document) in documents.iter().enumerate() {
+        let name = document.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(format!("Document names must be between 1 and {} characters", MAX_NAME_LEN));
+        }
+        if document.text.trim().is_empty() {
+            return Err(format!("Document '{}' is empty", name));
+        }
+        if documents[..i].iter().any(|other| other.name.trim().eq_ignore_ascii_case(name)) {
+            return Err(format!("Document '{}' is named twice", name));
+        }
+    }
+    Ok(())
+}
+
+/// The project text the model sees: the description, if any, then each document under a
+/// `=== Document: name ===` line, with where each document's text starts and ends. Without
+/// documents it's just the description.
+pub fn combine(description: &str, documents: &[SourceDocument]) -> (String, Sources) {
+    if documents.is_empty() {
+        return (description.to_string(), Sources::default());
+    }
+    let mut text = description.trim_end().to_string();
+    let description_end = text.chars().count();
+    if !text.is_empty() {
+        text.push_str("\n\n");
+    }
+    text.push_str(&format!(
+        "The project is described in {} documents, each starting with a \"=== Document: name ===\" line.",
+        documents.len()
+    ));
+    let mut spans = Vec::new();
+    for document in documents {
+        let name = document.name.trim();
+        text.push_str(&format!("\n\n=== Document: {} ===\n", name));
+        let start = text.chars().count();
+        text.push_str(document.text.trim_end());
+        spans.push(DocumentSpan { name: name.to_string(), start, end: text.chars().count() });
+    }
+    (text, Sources { description_end: Some(description_end), documents: spans })
+}
+
+impl Sources {
+    /// Attributes each risk to the document its first located quote comes from, and re-bases
+    /// the quotes' offsets onto that document's text. Quotes from the description keep their
+    /// offsets into it; ones that only matched the lines between documents lose theirs.
+    /// Without evidence in a document, a risk keeps the document the model named, if there's
+    /// one by that name.
+    pub fn attribute(&self, risks: &mut [RiskItem]) {
+        for risk in risks {
+            let named = risk.document.take();
+            for evidence in &mut risk.evidence {
+                evidence.document = None;
+                let (Some(start), Some(end)) = (evidence.start, evidence.end) else { continue };
+                if let Some(span) = self.documents.iter().find(|span| start >= span.start && end <= span.end) {
+                    evidence.document = Some(span.name.clone());
+                    evidence.start = Some(start - span.start);
+                    evidence.end = Some(end - span.start);
+                } else if self.description_end.is_some_and(|description_end| end > description_end) {
+                    evidence.start = None;
+                    evidence.end = None;
+                }
+            }
+            risk.document = risk.evidence.iter().find_map(|evidence| evidence.document.clone()).or_else(|| {
+                let named = named?;
+                self.documents.iter().find(|span| span.name.eq_ignore_ascii_case(named.trim())).map(|span| span.name.clone())
+            });
+        }
+    }
+}
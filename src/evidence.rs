@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::RiskItem;
+
+/// A passage of the project description that motivated a risk. Models only supply the quote;
+/// the character offsets are computed server-side so reviewers can trust them.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(from = "EvidenceInput")]
+pub struct Evidence {
+    pub quote: String,
+    /// Character offset of the quote in the description, `None` if it could not be found
+    /// (the model paraphrased instead of quoting).
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+    /// The request document the quote is from, for multi-document requests; the offsets are
+    /// then into that document's text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+}
+
+/// Models return either bare quote strings or `{"quote": ...}` objects.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EvidenceInput {
+    Quote(String),
+    Span {
+        #[serde(alias = "text", alias = "sentence")]
+        quote: String,
+        #[serde(default)]
+        start: Option<usize>,
+        #[serde(default)]
+        end: Option<usize>,
+        #[serde(default)]
+        document: Option<String>,
+    },
+}
+
+impl From<EvidenceInput> for Evidence {
+    fn from(input: EvidenceInput) -> Self {
+        match input {
+            EvidenceInput::Quote(quote) => Evidence { quote: quote.trim().to_string(), start: None, end: None, document: None },
+            EvidenceInput::Span { quote, start, end, document } => {
+                Evidence { quote: quote.trim().to_string(), start, end, document }
+            }
+        }
+    }
+}
+
+/// Fills in offsets for every risk's evidence by searching `description`, first exactly and
+/// then ignoring ASCII case.
+pub fn locate_evidence(risks: &mut [RiskItem], description: &str) {
+    let lowered = description.to_ascii_lowercase();
+
+    for evidence in risks.iter_mut().flat_map(|risk| risk.evidence.iter_mut()) {
+        let byte_start = if evidence.quote.is_empty() {
+            None
+        } else {
+            description
+                .find(&evidence.quote)
+                .or_else(|| lowered.find(&evidence.quote.to_ascii_lowercase()))
+        };
+
+        // Offsets claimed by the model are never trusted.
+        evidence.start = byte_start.map(|byte_start| description[..byte_start].chars().count());
+        evidence.end = evidence.start.map(|start| start + evidence.quote.chars().count());
+    }
+}
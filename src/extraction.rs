@@ -0,0 +1,483 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, trace};
+use utoipa::ToSchema;
+
+use crate::severity::Severity;
+use crate::RiskItem;
+
+/// How the pipeline asks the model to return structured risks (`EXTRACTION_MODE`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtractionMode {
+    /// Prompt instructions only; the array is scraped out of free text.
+    Prompt,
+    /// `response_format: json_schema` structured outputs.
+    #[default]
+    JsonSchema,
+    /// A forced `report_risks` tool call whose arguments carry the risks.
+    Tool,
+}
+
+impl FromStr for ExtractionMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "prompt" => Ok(Self::Prompt),
+            "json_schema" | "schema" => Ok(Self::JsonSchema),
+            "tool" | "tools" => Ok(Self::Tool),
+            other => anyhow::bail!("Unknown extraction mode '{}'", other),
+        }
+    }
+}
+
+/// JSON schema for the structured-output envelope and tool arguments, `{"risks": [...]}`.
+/// Strict structured outputs require an object at the root and every property listed as
+/// required. `levels` are the severity labels of the configured scale, `with_score` adds the
+/// 0–10 score, and `categories` restricts the category to a taxonomy when given.
+pub fn risk_report_schema(levels: &[Severity], with_score: bool, with_cost: bool, categories: Option<&[&str]>) -> Value {
+    let mut schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "risks": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "severity": { "type": "string" },
+                        "category": { "type": "string" },
+                        "mitigation": { "type": "string" },
+                        "likelihood": { "type": "integer", "enum": [1, 2, 3, 4, 5] },
+                        "impact": { "type": "integer", "enum": [1, 2, 3, 4, 5] },
+                        "confidence": { "type": "number" },
+                        "evidence": { "type": "array", "items": { "type": "string" } },
+                    },
+                    "required": ["severity", "category", "mitigation", "likelihood", "impact", "confidence", "evidence"],
+                    "additionalProperties": false,
+                },
+            },
+        },
+        "required": ["risks"],
+        "additionalProperties": false,
+    });
+    let item = &mut schema["properties"]["risks"]["items"];
+    let labels: Vec<String> = levels.iter().map(|s| s.as_str().to_lowercase()).collect();
+    item["properties"]["severity"]["enum"] = serde_json::json!(labels);
+    if with_score {
+        item["properties"]["score"] = serde_json::json!({ "type": "number" });
+        item["required"].as_array_mut().expect("required is an array").push(Value::from("score"));
+    }
+    if with_cost {
+        item["properties"]["cost_impact"] = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "min": { "type": "number" },
+                "likely": { "type": "number" },
+                "max": { "type": "number" },
+            },
+            "required": ["min", "likely", "max"],
+            "additionalProperties": false,
+        });
+        item["required"].as_array_mut().expect("required is an array").push(Value::from("cost_impact"));
+    }
+    if let Some(categories) = categories {
+        item["properties"]["category"]["enum"] = serde_json::json!(categories);
+    }
+    schema
+}
+
+/// Which step of the parse pipeline produced the risks, ordered from the cleanest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ParsePath {
+    /// The reply parsed as-is.
+    Direct,
+    /// The reply parsed after [`repair_json`].
+    Repaired,
+    /// The model had to be asked to correct its reply.
+    Reprompted,
+    /// The request's `timeout_ms` passed mid-stream; only the risks completed by then.
+    Partial,
+}
+
+impl ParsePath {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ParsePath::Direct => "direct",
+            ParsePath::Repaired => "repaired",
+            ParsePath::Reprompted => "reprompted",
+            ParsePath::Partial => "partial",
+        }
+    }
+}
+
+impl FromStr for ParsePath {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "direct" => Ok(ParsePath::Direct),
+            "repaired" => Ok(ParsePath::Repaired),
+            "reprompted" => Ok(ParsePath::Reprompted),
+            "partial" => Ok(ParsePath::Partial),
+            other => anyhow::bail!("Unknown parse path '{}'", other),
+        }
+    }
+}
+
+/// Parses risks from a reply, retrying once on a locally repaired copy before giving up.
+pub fn parse_risks(content: &str, relaxed: bool) -> Result<(Vec<RiskItem>, ParsePath)> {
+    let extract = if relaxed { extract_risks_relaxed } else { extract_risks };
+
+    match extract(content) {
+        Ok(risks) => Ok((risks, ParsePath::Direct)),
+        Err(e) => {
+            let repaired = repair_json(content);
+            if repaired == content {
+                return Err(e);
+            }
+            debug!("🩹 Retrying parse on repaired JSON");
+            extract(&repaired).map(|risks| (risks, ParsePath::Repaired))
+        }
+    }
+}
+
+/// Fixes the mistakes models commonly make when writing JSON by hand: Markdown code fences,
+/// typographic quotes and trailing commas before a closing bracket. A reply cut off mid-value
+/// is cut back to the last value that closed, and what it was nested in is closed after it.
+pub fn repair_json(content: &str) -> String {
+    let content = strip_code_fences(content).replace(['\u{201c}', '\u{201d}'], "\"");
+
+    let mut repaired = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    // Open brackets and braces, innermost last, and where the last value closed with the
+    // ones still open around it.
+    let mut containers = Vec::new();
+    let mut closed: Option<(usize, Vec<char>)> = None;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            repaired.push(c);
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '{' => containers.push(c),
+            ']' | '}' => {
+                containers.pop();
+                repaired.push(c);
+                closed = Some((repaired.len(), containers.clone()));
+                continue;
+            }
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if matches!(rest, Some(']') | Some('}')) {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        repaired.push(c);
+    }
+
+    if containers.is_empty() {
+        return repaired;
+    }
+    let Some((end, open)) = closed else {
+        return repaired;
+    };
+    repaired.truncate(end);
+    repaired.extend(open.iter().rev().map(|&c| if c == '[' { ']' } else { '}' }));
+    repaired
+}
+
+#[derive(Deserialize)]
+struct RiskReport {
+    risks: Vec<RiskItem>,
+}
+
+/// Strict path: a schema-shaped `{"risks": [...]}` envelope, or else the first `[` to the last
+/// `]` must parse as an array of risks.
+pub fn extract_risks(content: &str) -> Result<Vec<RiskItem>> {
+    if let Ok(report) = serde_json::from_str::<RiskReport>(content.trim()) {
+        return Ok(report.risks);
+    }
+
+    let start = content.find('[').ok_or_else(|| anyhow::anyhow!("No JSON array found"))?;
+    let end = content.rfind(']').ok_or_else(|| anyhow::anyhow!("No JSON array end found"))?;
+
+    let json_str = &content[start..=end];
+
+    trace!(json = %json_str, "🔍 JSON string to parse");
+
+    let risks: Vec<RiskItem> = serde_json::from_str(json_str)?;
+
+    Ok(risks)
+}
+
+/// Lenient path for less reliable (usually local) models. Tries the strict path first, then
+/// accepts an object wrapping the array (`{"risks": [...]}`) or a lone object, and finally
+/// salvages whichever top-level `{...}` objects in the text parse as risks.
+pub fn extract_risks_relaxed(content: &str) -> Result<Vec<RiskItem>> {
+    let content = strip_code_fences(content);
+
+    if let Ok(risks) = extract_risks(content) {
+        return Ok(risks);
+    }
+
+    if let Ok(value) = serde_json::from_str::<Value>(content.trim()) {
+        if let Some(risks) = risks_from_value(value) {
+            return Ok(risks);
+        }
+    }
+
+    let risks: Vec<RiskItem> = json_objects(content)
+        .filter_map(|obj| serde_json::from_str(obj).ok())
+        .collect();
+
+    if risks.is_empty() {
+        anyhow::bail!("No parseable risks found in AI response");
+    }
+
+    info!(risks = risks.len(), "🩹 Salvaged risks from loosely formatted output");
+
+    Ok(risks)
+}
+
+fn strip_code_fences(content: &str) -> &str {
+    let trimmed = content.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        // A closing fence without the opening one.
+        return trimmed.strip_suffix("```").map_or(trimmed, str::trim_end);
+    };
+    // Drop the info string (e.g. "json") on the opening fence line.
+    let rest = rest.split_once('\n').map_or("", |(_, body)| body);
+    rest.trim_end().strip_suffix("```").unwrap_or(rest).trim()
+}
+
+fn risks_from_value(value: Value) -> Option<Vec<RiskItem>> {
+    match value {
+        Value::Array(_) => serde_json::from_value(value).ok(),
+        Value::Object(map) => {
+            if let Some(array) = map.values().find(|v| v.is_array()) {
+                return serde_json::from_value(array.clone()).ok();
+            }
+            serde_json::from_value(Value::Object(map)).ok().map(|risk| vec![risk])
+        }
+        _ => None,
+    }
+}
+
+/// Yields every balanced top-level `{...}` span in `content`, skipping braces inside strings.
+fn json_objects(content: &str) -> impl Iterator<Item = &str> {
+    let mut spans = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in content.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    start = i;
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    spans.push(&content[start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans.into_iter()
+}
+
+/// Picks complete risks out of a reply while it is still being generated. Each object that
+/// closes directly inside an array (the bare array or the `risks` array of the envelope) is
+/// parsed as a risk; anything that doesn't parse is skipped and left to the final parse of
+/// the whole reply.
+#[derive(Debug, Default)]
+pub struct RiskStream {
+    buffer: String,
+    scanned: usize,
+    /// Open brackets and braces, innermost last.
+    containers: Vec<char>,
+    /// Byte offset of the risk object being read and its nesting depth.
+    object_start: Option<(usize, usize)>,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl RiskStream {
+    /// Appends a chunk of the reply and returns the risks it completed.
+    pub fn feed(&mut self, chunk: &str) -> Vec<RiskItem> {
+        self.buffer.push_str(chunk);
+        let mut risks = Vec::new();
+
+        for (offset, c) in self.buffer[self.scanned..].char_indices() {
+            let i = self.scanned + offset;
+            if self.in_string {
+                match c {
+                    _ if self.escaped => self.escaped = false,
+                    '\\' => self.escaped = true,
+                    '"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => self.in_string = true,
+                '[' => self.containers.push(c),
+                '{' => {
+                    if self.object_start.is_none() && self.containers.last() == Some(&'[') {
+                        self.object_start = Some((i, self.containers.len()));
+                    }
+                    self.containers.push(c);
+                }
+                ']' | '}' => {
+                    self.containers.pop();
+                    if let Some((start, depth)) = self.object_start {
+                        if c == '}' && self.containers.len() == depth {
+                            self.object_start = None;
+                            if let Ok(risk) = serde_json::from_str(&self.buffer[start..=i]) {
+                                risks.push(risk);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.scanned = self.buffer.len();
+
+        risks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RISK: &str = r#"{"severity": "high", "category": "Schedule", "mitigation": "Add a buffer."}"#;
+
+    #[test]
+    fn repair_json_drops_trailing_commas() {
+        assert_eq!(repair_json(r#"[{"a": 1, "b": [1, 2,],},]"#), r#"[{"a": 1, "b": [1, 2]}]"#);
+        // Commas inside strings are left alone.
+        assert_eq!(repair_json(r#"["a,]", "b"]"#), r#"["a,]", "b"]"#);
+    }
+
+    #[test]
+    fn repair_json_strips_stray_fences() {
+        assert_eq!(repair_json("```json\n[1, 2]\n```"), "[1, 2]");
+        assert_eq!(repair_json("```\n[1, 2]"), "[1, 2]");
+        assert_eq!(repair_json("[1, 2]\n```"), "[1, 2]");
+    }
+
+    #[test]
+    fn repair_json_replaces_typographic_quotes() {
+        assert_eq!(repair_json("[\u{201c}a\u{201d}]"), r#"["a"]"#);
+    }
+
+    #[test]
+    fn repair_json_closes_truncated_replies() {
+        let truncated = format!(r#"{{"risks": [{}, {{"severity": "low", "categ"#, RISK);
+        assert_eq!(repair_json(&truncated), format!(r#"{{"risks": [{}]}}"#, RISK));
+
+        let truncated = format!("```json\n[{},\n  {{\"severity\": \"me", RISK);
+        assert_eq!(repair_json(&truncated), format!("[{}]", RISK));
+
+        // Nothing closed before the cut: nothing to keep.
+        assert_eq!(repair_json(r#"[{"severity": "hi"#), r#"[{"severity": "hi"#);
+    }
+
+    #[test]
+    fn parse_risks_falls_back_to_the_repaired_reply() {
+        let (risks, path) = parse_risks(&format!("```json\n[{},]\n```", RISK), false).unwrap();
+        assert_eq!((risks.len(), path), (1, ParsePath::Repaired));
+
+        let (risks, path) = parse_risks(&format!(r#"{{"risks": [{}, {{"sev"#, RISK), false).unwrap();
+        assert_eq!((risks.len(), path), (1, ParsePath::Repaired));
+        assert_eq!(risks[0].category, "Schedule");
+
+        let (_, path) = parse_risks(&format!("[{}]", RISK), false).unwrap();
+        assert_eq!(path, ParsePath::Direct);
+        assert!(parse_risks("no risks here", false).is_err());
+    }
+
+    /// Feeds `reply` in `size`-byte chunks (on char boundaries) and collects the categories of
+    /// the risks each chunk completed.
+    fn stream(reply: &str, size: usize) -> Vec<Vec<String>> {
+        let mut stream = RiskStream::default();
+        let mut emitted = Vec::new();
+        let mut rest = reply;
+        while !rest.is_empty() {
+            let mut end = size.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            let (chunk, tail) = rest.split_at(end);
+            emitted.push(stream.feed(chunk).into_iter().map(|risk| risk.category).collect());
+            rest = tail;
+        }
+        emitted
+    }
+
+    #[test]
+    fn risk_stream_emits_each_risk_once_it_closes() {
+        let reply = format!(r#"{{"risks": [{}, {}]}}"#, RISK, RISK.replace("Schedule", "Budget"));
+        let first_end = reply.find("}, ").unwrap() + 1;
+
+        let mut stream = RiskStream::default();
+        assert!(stream.feed(&reply[..first_end - 1]).is_empty());
+        let risks = stream.feed(&reply[first_end - 1..first_end]);
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].category, "Schedule");
+        let risks = stream.feed(&reply[first_end..]);
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].category, "Budget");
+    }
+
+    #[test]
+    fn risk_stream_is_independent_of_chunk_boundaries() {
+        let tricky = r#"{"severity": "low", "category": "Scope \"}]{\" creep", "mitigation": "Freeze the ”scope”.", "evidence": [{"quote": "[x]"}]}"#;
+        let reply = format!("Here you go:\n```json\n[{}, {}]\n```", RISK, tricky);
+        for size in 1..=reply.len() {
+            let emitted: Vec<String> = stream(&reply, size).into_iter().flatten().collect();
+            assert_eq!(emitted, ["Schedule", r#"Scope "}]{" creep"#], "chunk size {}", size);
+        }
+    }
+
+    #[test]
+    fn risk_stream_skips_objects_that_are_not_risks() {
+        let reply = format!(r#"{{"note": {{"severity": "high"}}, "risks": [{{"severity": "bogus"}}, {}]}}"#, RISK);
+        let emitted: Vec<String> = stream(&reply, 7).into_iter().flatten().collect();
+        assert_eq!(emitted, ["Schedule"]);
+    }
+}
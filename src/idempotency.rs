@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::http::{HeaderMap, HeaderName, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::auth::Identity;
+use crate::config::LimitsConfig;
+use crate::request_id::RequestId;
+use crate::shared::SharedStore;
+use crate::{AppState, RiskRequest, RiskResponse};
+
+pub const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on a response that was stored for an earlier request with the same key.
+pub const REPLAYED_HEADER: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const DEFAULT_TTL_SECS: u64 = 86_400;
+const MAX_KEY_LEN: usize = 255;
+/// How long a request still running holds its key in Redis, in case its replica stops.
+const PENDING_TTL: Duration = Duration::from_secs(15 * 60);
+/// Expired keys are swept once the map grows past this many entries.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+#[derive(Clone, Serialize, Deserialize)]
+enum Record {
+    Pending { fingerprint: String },
+    Done { fingerprint: String, response: RiskResponse },
+}
+
+/// Responses of `POST /evaluate` requests sent with an `Idempotency-Key`, kept for
+/// `idempotency_ttl_secs` so a client retrying after a dropped connection gets the evaluation
+/// it already paid for. In Redis when it's configured, else in memory.
+pub struct IdempotencyKeys {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Record)>>,
+    shared: Option<SharedStore>,
+}
+
+impl IdempotencyKeys {
+    /// `idempotency_ttl_secs` defaults to a day.
+    pub fn from_config(limits: &LimitsConfig, shared: Option<SharedStore>) -> Self {
+        Self {
+            ttl: Duration::from_secs(limits.idempotency_ttl_secs.unwrap_or(DEFAULT_TTL_SECS)),
+            entries: Mutex::new(HashMap::new()),
+            shared,
+        }
+    }
+
+    /// The stored response for `scope`, or `None` once the key is claimed for this request.
+    /// 409 while the original request is still running, 422 if it was a different request.
+    async fn claim(&self, scope: &str, fingerprint: &str) -> Result<Option<RiskResponse>, (StatusCode, String)> {
+        let pending = Record::Pending { fingerprint: fingerprint.to_string() };
+        let record = match &self.shared {
+            Some(shared) => {
+                let name = format!("idempotency:{}", scope);
+                let claimed = shared.set_json_nx(&name, &pending, PENDING_TTL.min(self.ttl)).await;
+                match claimed {
+                    Ok(true) => return Ok(None),
+                    Ok(false) => shared.get_json::<Record>(&name).await,
+                    Err(e) => Err(e),
+                }
+                .unwrap_or_else(|e| {
+                    warn!("⚠️ Failed to check the Idempotency-Key in Redis, evaluating anyway: {:?}", e);
+                    None
+                })
+            }
+            None => {
+                let mut entries = self.entries.lock().unwrap();
+                if entries.len() > SWEEP_THRESHOLD {
+                    entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+                }
+                match entries.get(scope).filter(|(stored, _)| stored.elapsed() < self.ttl) {
+                    Some((_, record)) => Some(record.clone()),
+                    None => {
+                        entries.insert(scope.to_string(), (Instant::now(), pending));
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+        match record {
+            Some(Record::Done { fingerprint: stored, response }) => check(stored == fingerprint, Some(response)),
+            Some(Record::Pending { fingerprint: stored }) => check(stored == fingerprint, None),
+            // Expired between the two commands; evaluating again is what the key would do now.
+            None => Ok(None),
+        }
+    }
+
+    async fn complete(&self, scope: &str, fingerprint: &str, response: &RiskResponse) {
+        let done = Record::Done { fingerprint: fingerprint.to_string(), response: response.clone() };
+        match &self.shared {
+            Some(shared) => {
+                if let Err(e) = shared.set_json(&format!("idempotency:{}", scope), &done, self.ttl).await {
+                    warn!("⚠️ Failed to store the Idempotency-Key's response in Redis: {:?}", e);
+                }
+            }
+            None => {
+                self.entries.lock().unwrap().insert(scope.to_string(), (Instant::now(), done));
+            }
+        }
+    }
+
+    /// Frees the key of a request that failed, so retrying it evaluates again.
+    async fn release(&self, scope: &str) {
+        match &self.shared {
+            Some(shared) => {
+                if let Err(e) = shared.delete(&format!("idempotency:{}", scope)).await {
+                    warn!("⚠️ Failed to release the Idempotency-Key in Redis: {:?}", e);
+                }
+            }
+            None => {
+                self.entries.lock().unwrap().remove(scope);
+            }
+        }
+    }
+}
+
+fn check(fingerprint_matches: bool, replay: Option<RiskResponse>) -> Result<Option<RiskResponse>, (StatusCode, String)> {
+    if !fingerprint_matches {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "The Idempotency-Key was already used for a different request".to_string()));
+    }
+    match replay {
+        Some(response) => Ok(Some(response)),
+        None => Err((StatusCode::CONFLICT, "A request with this Idempotency-Key is still running; retry shortly".to_string())),
+    }
+}
+
+/// The request's `Idempotency-Key`, if it sent one.
+pub fn key(headers: &HeaderMap) -> Result<Option<&str>, (StatusCode, String)> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    match value.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LEN => Ok(Some(key)),
+        _ => Err((StatusCode::BAD_REQUEST, format!("The Idempotency-Key must be 1 to {} visible characters", MAX_KEY_LEN))),
+    }
+}
+
+/// Runs [`crate::evaluate`] once per key and caller: a repeat of a request that succeeded gets
+/// its stored response, with `replayed` set. The evaluation runs detached from the request, so
+/// one whose client went away still finishes and is there for the retry.
+pub async fn evaluate_once(
+    state: Arc<AppState>,
+    identity: Identity,
+    request_id: RequestId,
+    payload: RiskRequest,
+    key: &str,
+) -> Result<(RiskResponse, bool), (StatusCode, String)> {
+    let scope = hash(&[&identity.tenant, &identity.key_id, key]);
+    let fingerprint = hash(&[&serde_json::to_string(&payload).unwrap_or_default()]);
+    if let Some(response) = state.idempotency.claim(&scope, &fingerprint).await? {
+        info!(evaluation_id = %response.id, "♻️ Replaying the response stored for an Idempotency-Key");
+        state.metrics.idempotent_replays.fetch_add(1, Ordering::Relaxed);
+        return Ok((response, true));
+    }
+
+    let evaluation = tokio::spawn({
+        let (state, scope) = (state.clone(), scope.clone());
+        async move {
+            let result = crate::evaluate(&state, &identity, &request_id, &payload).await;
+            match &result {
+                Ok(response) => state.idempotency.complete(&scope, &fingerprint, response).await,
+                Err(_) => state.idempotency.release(&scope).await,
+            }
+            result
+        }
+    });
+    match evaluation.await {
+        Ok(result) => result.map(|response| (response, false)),
+        // A panic skipped the release above; without it every retry would get a 409 until the
+        // key expires.
+        Err(e) => {
+            state.idempotency.release(&scope).await;
+            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("The evaluation failed: {}", e)))
+        }
+    }
+}
+
+fn hash(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
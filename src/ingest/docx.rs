@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use docx_rs::{
+    DocumentChild, InsertChild, Paragraph, ParagraphChild, Run, RunChild, StructuredDataTag, StructuredDataTagChild, Style, Table,
+    TableCellContent, TableChild, TableRowChild,
+};
+
+/// Word only offers nine heading levels.
+const MAX_HEADING_LEVEL: usize = 9;
+
+/// The document body as markdown-flavoured text: headings become `#` lines at their outline
+/// level so the model sees how the plan is organized, list items become `-` lines and table rows
+/// become `|`-separated lines. Tracked deletions, the table of contents and images are left
+/// out. Like the PDF parser this is CPU-bound, so callers run it on the blocking pool.
+pub fn extract_text(bytes: &[u8]) -> Result<String> {
+    let docx = docx_rs::read_docx(bytes).map_err(|e| anyhow::anyhow!("{}", e)).context("Failed to read the DOCX")?;
+    let headings: HashMap<&str, usize> = docx
+        .styles
+        .styles
+        .iter()
+        .filter_map(|style| Some((style.style_id.as_str(), style_level(style)?)))
+        .collect();
+    let mut blocks = Vec::new();
+    for child in &docx.document.children {
+        match child {
+            DocumentChild::Paragraph(paragraph) => push_paragraph(&mut blocks, paragraph, &headings),
+            DocumentChild::Table(table) => push_table(&mut blocks, table),
+            DocumentChild::StructuredDataTag(tag) => push_tag(&mut blocks, tag, &headings),
+            _ => {}
+        }
+    }
+    Ok(super::join_blocks(&blocks))
+}
+
+/// A heading style's level, from its outline level or, for styles that don't set one, the
+/// built-in `Title` and `HeadingN` ids.
+fn style_level(style: &Style) -> Option<usize> {
+    if let Some(outline) = &style.paragraph_property.outline_lvl {
+        return (outline.v < MAX_HEADING_LEVEL).then_some(outline.v + 1);
+    }
+    match style.style_id.as_str() {
+        "Title" => Some(1),
+        id => id.strip_prefix("Heading")?.parse().ok().filter(|level| (1..=MAX_HEADING_LEVEL).contains(level)),
+    }
+}
+
+fn push_paragraph(blocks: &mut Vec<String>, paragraph: &Paragraph, headings: &HashMap<&str, usize>) {
+    let text = paragraph_text(paragraph);
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    let property = &paragraph.property;
+    let level = match &property.outline_lvl {
+        Some(outline) => (outline.v < MAX_HEADING_LEVEL).then_some(outline.v + 1),
+        None => property.style.as_ref().and_then(|style| headings.get(style.val.as_str()).copied()),
+    };
+    let block = match level {
+        Some(level) => format!("{} {}", "#".repeat(level), text),
+        None if property.numbering_property.is_some() => format!("- {}", text),
+        None => text.to_string(),
+    };
+    blocks.push(block);
+}
+
+fn push_table(blocks: &mut Vec<String>, table: &Table) {
+    let rows: Vec<String> = table
+        .rows
+        .iter()
+        .map(|TableChild::TableRow(row)| {
+            row.cells
+                .iter()
+                .map(|TableRowChild::TableCell(cell)| {
+                    let texts: Vec<String> = cell
+                        .children
+                        .iter()
+                        .filter_map(|content| match content {
+                            TableCellContent::Paragraph(paragraph) => Some(paragraph_text(paragraph)),
+                            _ => None,
+                        })
+                        .map(|text| text.trim().to_string())
+                        .filter(|text| !text.is_empty())
+                        .collect();
+                    texts.join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        })
+        .filter(|row| row.chars().any(|c| !c.is_whitespace() && c != '|'))
+        .collect();
+    if !rows.is_empty() {
+        blocks.push(rows.join("\n"));
+    }
+}
+
+/// Content controls, e.g. the fields of a form-like template.
+fn push_tag(blocks: &mut Vec<String>, tag: &StructuredDataTag, headings: &HashMap<&str, usize>) {
+    let mut runs = String::new();
+    for child in &tag.children {
+        match child {
+            StructuredDataTagChild::Run(run) => push_run(&mut runs, run),
+            StructuredDataTagChild::Paragraph(paragraph) => push_paragraph(blocks, paragraph, headings),
+            StructuredDataTagChild::Table(table) => push_table(blocks, table),
+            StructuredDataTagChild::StructuredDataTag(tag) => push_tag(blocks, tag, headings),
+            _ => {}
+        }
+    }
+    if !runs.trim().is_empty() {
+        blocks.push(runs.trim().to_string());
+    }
+}
+
+fn paragraph_text(paragraph: &Paragraph) -> String {
+    let mut text = String::new();
+    push_children(&mut text, &paragraph.children);
+    text
+}
+
+fn push_children(text: &mut String, children: &[ParagraphChild]) {
+    for child in children {
+        match child {
+            ParagraphChild::Run(run) => push_run(text, run),
+            ParagraphChild::Insert(insert) => {
+                for child in &insert.children {
+                    if let InsertChild::Run(run) = child {
+                        push_run(text, run);
+                    }
+                }
+            }
+            ParagraphChild::Hyperlink(link) => push_children(text, &link.children),
+            _ => {}
+        }
+    }
+}
+
+fn push_run(text: &mut String, run: &Run) {
+    for child in &run.children {
+        match child {
+            RunChild::Text(t) => text.push_str(&t.text),
+            RunChild::Tab(_) | RunChild::PTab(_) => text.push('\t'),
+            RunChild::Break(_) | RunChild::CarriageReturn(_) => text.push('\n'),
+            _ => {}
+        }
+    }
+}
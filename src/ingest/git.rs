@@ -0,0 +1,424 @@
+use std::{
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::{header, Url};
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::web::{bad_gateway, UrlFetcher};
+use crate::config::LimitsConfig;
+
+const DEFAULT_CLONE_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_MAX_FILE_BYTES: usize = 64 * 1024;
+const DEFAULT_MAX_CLONE_BYTES: u64 = 64 * 1024 * 1024;
+/// How often a running git command's checkout is measured against `max_clone_bytes`.
+const CLONE_SIZE_INTERVAL: Duration = Duration::from_millis(250);
+const DEFAULT_MAX_CORPUS_BYTES: usize = 256 * 1024;
+const DEFAULT_MAX_ISSUES: usize = 100;
+/// The issue APIs return at most this many per page.
+const MAX_ISSUES_PER_PAGE: usize = 100;
+/// Top-level documents, in the order they go into the corpus.
+const TOP_LEVEL_DOCS: &[&str] = &["README", "ARCHITECTURE", "DESIGN", "ROADMAP", "CONTRIBUTING", "SECURITY", "CHANGELOG"];
+/// Directories whose documents follow the top-level ones.
+const DOC_DIRS: &[&str] = &["docs", "doc"];
+const DOC_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst", "adoc"];
+/// Settings for every git command, given through the environment so the token stays out of
+/// the process list: no protocols but http(s), no redirects, and symlinks checked out as
+/// plain files so none can point outside the clone.
+const GIT_CONFIG: &[(&str, &str)] = &[
+    ("protocol.allow", "never"),
+    ("protocol.https.allow", "always"),
+    ("protocol.http.allow", "always"),
+    ("http.followRedirects", "false"),
+    ("core.symlinks", "false"),
+];
+
+/// Reads what `POST /evaluate/repo` evaluates out of a git repository: its README, top-level
+/// design documents and `docs/`, plus open issue titles on GitHub and GitLab. Repositories are
+/// cloned shallowly and sparsely, without blobs over `max_file_bytes`, to a temporary
+/// directory that's removed afterwards; git is stopped once it has written more than
+/// `max_clone_bytes` there. The host has to pass the
+/// same address checks as `POST /evaluate/url`, and git only connects to the checked addresses.
+pub struct RepoReader {
+    timeout: Duration,
+    max_file_bytes: usize,
+    max_clone_bytes: u64,
+    max_corpus_bytes: usize,
+    max_issues: usize,
+}
+
+/// Deleted when dropped.
+struct Checkout(PathBuf);
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.0) {
+            warn!("⚠️ Failed to remove checkout {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+impl RepoReader {
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        Self {
+            timeout: Duration::from_secs(limits.repo_clone_timeout_secs.unwrap_or(DEFAULT_CLONE_TIMEOUT_SECS)),
+            max_file_bytes: limits.repo_max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES),
+            max_clone_bytes: limits.repo_max_clone_bytes.unwrap_or(DEFAULT_MAX_CLONE_BYTES),
+            max_corpus_bytes: limits.repo_max_corpus_bytes.unwrap_or(DEFAULT_MAX_CORPUS_BYTES),
+            max_issues: limits.repo_max_issues.unwrap_or(DEFAULT_MAX_ISSUES).min(MAX_ISSUES_PER_PAGE),
+        }
+    }
+
+    /// The repository's corpus. `token` authenticates both the clone and the issue listing;
+    /// `reference` is a branch or tag, the default branch when unset. Bad input is a 400,
+    /// internal hosts a 403, checkouts over the byte budget a 413, clones that fail a 502 and
+    /// ones that take too long a 504.
+    pub async fn read(
+        &self,
+        fetcher: &UrlFetcher,
+        repo: &str,
+        token: Option<&str>,
+        reference: Option<&str>,
+    ) -> Result<String, (StatusCode, String)> {
+        let url = Url::parse(repo).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid repo url: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err((StatusCode::BAD_REQUEST, "Only http and https repo urls can be cloned".to_string()));
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err((StatusCode::BAD_REQUEST, "Pass credentials in token, not in the repo url".to_string()));
+        }
+        if let Some(reference) = reference {
+            let valid = !reference.starts_with('-')
+                && reference.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '/' | '-'));
+            if !valid {
+                return Err((StatusCode::BAD_REQUEST, format!("Invalid ref '{}'", reference)));
+            }
+        }
+        let addrs = fetcher.resolve(&url).await?;
+
+        let checkout = Checkout(std::env::temp_dir().join(format!("ai-risk-evaluator-repo-{}", Uuid::new_v4().simple())));
+        let cloned = tokio::time::timeout(self.timeout, self.clone(&url, &addrs, token, reference, &checkout.0)).await;
+        match cloned {
+            Ok(result) => result?,
+            Err(_) => {
+                return Err((
+                    StatusCode::GATEWAY_TIMEOUT,
+                    format!("Cloning {} took longer than {} seconds", url, self.timeout.as_secs()),
+                ))
+            }
+        }
+        let dir = checkout.0.clone();
+        let max_file_bytes = self.max_file_bytes;
+        let documents = tokio::task::spawn_blocking(move || read_documents(&dir, max_file_bytes))
+            .await
+            .map_err(|e| bad_gateway(format!("Failed to read the checkout: {}", e)))?;
+        drop(checkout);
+        let issues = self.issue_titles(fetcher, &url, token).await;
+
+        let mut corpus = format!(
+            "The repository {}: its README, documentation and open issue titles. Evaluate the project's delivery and technical risks.",
+            url
+        );
+        let mut files = 0;
+        for (path, text) in &documents {
+            let section = format!("\n\n## {}\n\n{}", path, text);
+            if corpus.len() + section.len() > self.max_corpus_bytes {
+                break;
+            }
+            corpus.push_str(&section);
+            files += 1;
+        }
+        if !issues.is_empty() {
+            let section = format!("\n\n## Open issues\n\n{}", issues.join("\n"));
+            if corpus.len() + section.len() <= self.max_corpus_bytes {
+                corpus.push_str(&section);
+            }
+        }
+        if files == 0 && issues.is_empty() {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "The repository has no README, documentation or open issues".to_string()));
+        }
+        info!(
+            repo = %url,
+            files,
+            skipped = documents.len() - files,
+            issues = issues.len(),
+            bytes = corpus.len(),
+            "📦 Read repository"
+        );
+        Ok(corpus)
+    }
+
+    async fn clone(
+        &self,
+        url: &Url,
+        addrs: &[SocketAddr],
+        token: Option<&str>,
+        reference: Option<&str>,
+        dir: &Path,
+    ) -> Result<(), (StatusCode, String)> {
+        let mut config: Vec<(String, String)> = GIT_CONFIG.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        let host = url.host_str().unwrap_or_default();
+        if url.domain().is_some() {
+            let addrs: Vec<String> = addrs
+                .iter()
+                .map(|addr| match addr {
+                    SocketAddr::V4(addr) => addr.ip().to_string(),
+                    SocketAddr::V6(addr) => format!("[{}]", addr.ip()),
+                })
+                .collect();
+            let port = url.port_or_known_default().unwrap_or_default();
+            config.push(("http.curloptResolve".to_string(), format!("{}:{}:{}", host, port, addrs.join(","))));
+        }
+        if let Some(token) = token {
+            // GitHub takes a token as the password of any user; GitLab and most others as oauth2's.
+            let user = if host == "github.com" { "x-access-token" } else { "oauth2" };
+            let credentials = STANDARD.encode(format!("{}:{}", user, token));
+            config.push(("http.extraHeader".to_string(), format!("Authorization: Basic {}", credentials)));
+        }
+
+        let filter = format!("--filter=blob:limit={}", self.max_file_bytes);
+        let mut clone = vec!["clone", "--depth", "1", filter.as_str(), "--no-checkout", "--single-branch", "--no-tags"];
+        if let Some(reference) = reference {
+            clone.extend(["--branch", reference]);
+        }
+        let dir_arg = dir.to_string_lossy().to_string();
+        clone.extend(["--", url.as_str(), dir_arg.as_str()]);
+        self.git(&config, None, &clone, dir).await.map_err(|e| e.into_response(url, "clone"))?;
+
+        let patterns = sparse_patterns();
+        let mut sparse = vec!["sparse-checkout", "set", "--no-cone", "--"];
+        sparse.extend(patterns.iter().map(String::as_str));
+        self.git(&config, Some(dir), &sparse, dir).await.map_err(|e| e.into_response(url, "check out"))?;
+        self.git(&config, Some(dir), &["checkout"], dir).await.map_err(|e| e.into_response(url, "check out"))?;
+        Ok(())
+    }
+
+    /// Runs [`git`], stopping it as soon as `checkout` holds more than `max_clone_bytes`.
+    async fn git(&self, config: &[(String, String)], dir: Option<&Path>, args: &[&str], checkout: &Path) -> Result<(), GitError> {
+        let run = git(config, dir, args);
+        tokio::pin!(run);
+        loop {
+            tokio::select! {
+                result = &mut run => {
+                    result.map_err(GitError::Failed)?;
+                    return self.check_size(checkout).await;
+                }
+                // Dropping `run` kills git.
+                _ = tokio::time::sleep(CLONE_SIZE_INTERVAL) => self.check_size(checkout).await?,
+            }
+        }
+    }
+
+    async fn check_size(&self, checkout: &Path) -> Result<(), GitError> {
+        let root = checkout.to_path_buf();
+        let size = tokio::task::spawn_blocking(move || dir_size(&root)).await.unwrap_or_default();
+        if size > self.max_clone_bytes {
+            return Err(GitError::TooLarge(self.max_clone_bytes));
+        }
+        Ok(())
+    }
+
+    /// Open issue titles, newest first, or none when the host isn't GitHub or GitLab or the
+    /// listing fails, e.g. because issues are turned off.
+    async fn issue_titles(&self, fetcher: &UrlFetcher, url: &Url, token: Option<&str>) -> Vec<String> {
+        let host = url.host_str().unwrap_or_default();
+        let path = url.path().trim_matches('/').trim_end_matches(".git");
+        let (api, github) = if host == "github.com" {
+            (format!("https://api.github.com/repos/{}/issues?state=open&per_page={}", path, self.max_issues), true)
+        } else if host.contains("gitlab") {
+            let project = path.replace('/', "%2F");
+            (format!("https://{}/api/v4/projects/{}/issues?state=opened&per_page={}", host, project, self.max_issues), false)
+        } else {
+            return Vec::new();
+        };
+        match self.fetch_issues(fetcher, &api, github, token).await {
+            Ok(issues) => issues,
+            Err(e) => {
+                warn!("⚠️ Failed to list the issues of {}: {}", url, e);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn fetch_issues(&self, fetcher: &UrlFetcher, api: &str, github: bool, token: Option<&str>) -> Result<Vec<String>, String> {
+        let api = Url::parse(api).map_err(|e| e.to_string())?;
+        let client = fetcher.client(&api).await.map_err(|(_, message)| message)?;
+        let mut request = client.get(api).header(header::ACCEPT, "application/json");
+        if let Some(token) = token {
+            request = if github {
+                request.bearer_auth(token)
+            } else {
+                request.header("private-token", token)
+            };
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("the API answered {}", status));
+        }
+        let issues: Vec<Value> = response.json().await.map_err(|e| e.to_string())?;
+        Ok(issues
+            .iter()
+            // GitHub lists pull requests as issues too.
+            .filter(|issue| issue.get("pull_request").is_none())
+            .filter_map(|issue| {
+                let number = if github { &issue["number"] } else { &issue["iid"] };
+                Some(format!("- #{} {}", number.as_u64()?, issue["title"].as_str()?.trim()))
+            })
+            .collect())
+    }
+}
+
+enum GitError {
+    Failed(String),
+    /// The checkout outgrew this many bytes.
+    TooLarge(u64),
+}
+
+impl GitError {
+    fn into_response(self, url: &Url, action: &str) -> (StatusCode, String) {
+        match self {
+            GitError::Failed(e) => bad_gateway(format!("Failed to {} {}: {}", action, url, e)),
+            GitError::TooLarge(max_bytes) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("The checkout of {} is larger than the {} byte limit", url, max_bytes),
+            ),
+        }
+    }
+}
+
+/// Runs git without the system and user configuration, prompts or proxies, returning the last
+/// line it printed when it fails.
+async fn git(config: &[(String, String)], dir: Option<&Path>, args: &[&str]) -> Result<(), String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    command
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_COUNT", config.len().to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for proxy in ["http_proxy", "https_proxy", "all_proxy", "HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+        command.env_remove(proxy);
+    }
+    for (index, (key, value)) in config.iter().enumerate() {
+        command.env(format!("GIT_CONFIG_KEY_{}", index), key).env(format!("GIT_CONFIG_VALUE_{}", index), value);
+    }
+    let output = command.output().await.map_err(|e| format!("failed to run git: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(stderr.lines().map(str::trim).rfind(|line| !line.is_empty()).unwrap_or("git failed").to_string())
+}
+
+/// What the sparse checkout downloads: the top-level documents in upper, lower and title case,
+/// and the documents anywhere under the doc directories.
+fn sparse_patterns() -> Vec<String> {
+    let mut patterns = Vec::new();
+    for name in TOP_LEVEL_DOCS {
+        let lower = name.to_lowercase();
+        let title = format!("{}{}", &name[..1], &lower[1..]);
+        for name in [name.to_string(), lower, title] {
+            patterns.push(format!("/{}", name));
+            patterns.extend(DOC_EXTENSIONS.iter().map(|extension| format!("/{}.{}", name, extension)));
+        }
+    }
+    for dir in DOC_DIRS {
+        patterns.extend(DOC_EXTENSIONS.iter().map(|extension| format!("/{}/**/*.{}", dir, extension)));
+    }
+    patterns
+}
+
+/// The checked-out documents with their paths, in corpus order, each cut to `max_bytes`.
+fn read_documents(root: &Path, max_bytes: usize) -> Vec<(String, String)> {
+    let mut top_level: Vec<(usize, PathBuf)> = fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_uppercase();
+            let rank = TOP_LEVEL_DOCS.iter().position(|name| *name == stem)?;
+            Some((rank, path))
+        })
+        .collect();
+    top_level.sort();
+    let mut paths: Vec<PathBuf> = top_level.into_iter().map(|(_, path)| path).collect();
+    for dir in DOC_DIRS {
+        let mut found = Vec::new();
+        collect_files(&root.join(dir), &mut found);
+        found.sort();
+        paths.extend(found);
+    }
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            // Only regular files; the checkout has no symlinks, but a file can't be trusted to be one.
+            if !fs::symlink_metadata(&path).ok()?.is_file() {
+                return None;
+            }
+            let bytes = fs::read(&path).ok()?;
+            if bytes.contains(&0) {
+                return None;
+            }
+            let mut text = String::from_utf8_lossy(&bytes).trim().to_string();
+            if text.is_empty() {
+                return None;
+            }
+            if text.len() > max_bytes {
+                let mut end = max_bytes;
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                text.truncate(end);
+                text.push_str("\n[…]");
+            }
+            let relative = path.strip_prefix(root).ok()?.to_string_lossy().to_string();
+            Some((relative, text))
+        })
+        .collect()
+}
+
+/// Bytes in the regular files under `dir`; zero before git has created it.
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |metadata| metadata.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+fn collect_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_files(&entry.path(), found);
+        } else if file_type.is_file() {
+            found.push(entry.path());
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use scraper::{ElementRef, Html, Node, Selector};
+
+/// Where the content is on pages that mark it up, most specific first: `main` and `article`
+/// on most sites, `#main-content` on Confluence pages and exports, `#contents` on published
+/// Google Docs.
+const CONTENT_SELECTORS: &[&str] = &["main", "[role=main]", "#main-content", "#contents", "article", "body"];
+/// Site chrome, scripts and controls, which say nothing about the project.
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "nav", "aside", "footer", "form", "button", "select", "svg",
+    "iframe", "canvas", "dialog",
+];
+const SKIPPED_ROLES: &[&str] = &["navigation", "banner", "contentinfo", "search", "complementary", "dialog"];
+/// Tags that start a new line of text.
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "main", "table", "thead", "tbody", "ul", "ol", "dl", "dt", "dd", "blockquote",
+    "pre", "figure", "figcaption", "details", "summary", "hr",
+];
+
+/// The page's readable content as markdown-flavoured text, like a DOCX: headings become `#`
+/// lines, list items `-` lines and table rows `|`-separated lines. Only the main content is kept
+/// when the page marks it up, and navigation, headers, footers and scripts are dropped either
+/// way. Pages whose content has no top-level heading get their `<title>` as one.
+pub fn extract_text(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let root = CONTENT_SELECTORS
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| document.select(&selector).next())
+        .unwrap_or_else(|| document.root_element());
+
+    // A page-wide <header> is the site banner; one inside the content usually holds its title.
+    let mut walker = Walker {
+        blocks: Vec::new(),
+        line: String::new(),
+        prefix: String::new(),
+        skip_header: root.value().name() == "body",
+        in_table: false,
+    };
+    walker.walk(root);
+    walker.flush();
+
+    let has_title = walker.blocks.iter().any(|block| block.starts_with("# "));
+    if !has_title {
+        let title = Selector::parse("title")
+            .ok()
+            .and_then(|selector| document.select(&selector).next())
+            .map(|title| collapse(&title.text().collect::<String>()));
+        if let Some(title) = title.filter(|title| !title.is_empty()) {
+            walker.blocks.insert(0, format!("# {}", title));
+        }
+    }
+    super::join_blocks(&walker.blocks)
+}
+
+struct Walker {
+    blocks: Vec<String>,
+    line: String,
+    /// What the line being collected starts with: `## ` in a heading, `- ` in a list item.
+    prefix: String,
+    skip_header: bool,
+    /// Whether the last block is a table, which the next row joins.
+    in_table: bool,
+}
+
+impl Walker {
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(_) => {
+                    if let Some(element) = ElementRef::wrap(child) {
+                        self.element(element);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let value = element.value();
+        let name = value.name();
+        let hidden = value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true");
+        let role = value.attr("role").unwrap_or_default();
+        if hidden || SKIPPED_TAGS.contains(&name) || SKIPPED_ROLES.contains(&role) || (name == "header" && self.skip_header) {
+            return;
+        }
+        let heading = match name.as_bytes() {
+            [b'h', level @ b'1'..=b'6'] => Some((level - b'0') as usize),
+            _ => None,
+        };
+        if let Some(level) = heading {
+            self.block(element, format!("{} ", "#".repeat(level)));
+        } else if name == "li" {
+            self.block(element, "- ".to_string());
+        } else if name == "br" {
+            self.line.push('\n');
+        } else if name == "tr" {
+            self.flush();
+            self.walk(element);
+            let row = std::mem::take(&mut self.line);
+            let row = collapse(&row);
+            if !row.is_empty() {
+                match self.blocks.last_mut() {
+                    Some(table) if self.in_table => {
+                        table.push('\n');
+                        table.push_str(&row);
+                    }
+                    _ => self.blocks.push(row),
+                }
+                self.in_table = true;
+            }
+        } else if name == "td" || name == "th" {
+            if !self.line.trim().is_empty() {
+                self.line.push_str(" | ");
+            }
+            self.walk(element);
+        } else if BLOCK_TAGS.contains(&name) {
+            self.flush();
+            self.walk(element);
+            self.flush();
+            if name == "table" {
+                self.in_table = false;
+            }
+        } else {
+            self.walk(element);
+        }
+    }
+
+    /// Collects `element` as a line of its own starting with `prefix`.
+    fn block(&mut self, element: ElementRef, prefix: String) {
+        self.flush();
+        self.prefix = prefix;
+        self.walk(element);
+        self.flush();
+        self.prefix.clear();
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let collapsed = collapse(text);
+        if collapsed.is_empty() {
+            // Whitespace between inline elements still separates their words.
+            if !text.is_empty() && !self.line.ends_with([' ', '\n']) && !self.line.is_empty() {
+                self.line.push(' ');
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) && !self.line.is_empty() && !self.line.ends_with([' ', '\n']) {
+            self.line.push(' ');
+        }
+        self.line.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) {
+            self.line.push(' ');
+        }
+    }
+
+    /// Ends the line being collected. The prefix waits for the first line with text, so
+    /// `<li><p>…</p></li>` is still a list item.
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let lines: Vec<&str> = line.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        if !lines.is_empty() {
+            let prefix = std::mem::take(&mut self.prefix);
+            self.blocks.push(format!("{}{}", prefix, lines.join("\n")));
+            self.in_table = false;
+        }
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
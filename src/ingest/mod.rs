@@ -0,0 +1,158 @@
+use axum::http::StatusCode;
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use serde::Serialize;
+use tracing::warn;
+
+mod docx;
+mod git;
+mod html;
+mod pdf;
+mod web;
+
+pub use git::RepoReader;
+pub use web::{AddressGuard, UrlFetcher};
+
+/// Document formats an upload can be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Text,
+    Markdown,
+    Html,
+    Pdf,
+    Docx,
+}
+
+impl Format {
+    /// From the part's content type, else from the file name's extension when the content type
+    /// is missing or generic.
+    fn detect(content_type: Option<&str>, filename: Option<&str>) -> Option<Self> {
+        let mime = content_type.map(|value| value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase());
+        match mime.as_deref() {
+            Some("text/plain") => return Some(Format::Text),
+            Some("text/markdown") | Some("text/x-markdown") => return Some(Format::Markdown),
+            Some("text/html") | Some("application/xhtml+xml") => return Some(Format::Html),
+            Some("application/pdf") => return Some(Format::Pdf),
+            Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document") => return Some(Format::Docx),
+            None | Some("application/octet-stream") => {}
+            Some(_) => return None,
+        }
+        let extension = filename?.rsplit_once('.')?.1.to_ascii_lowercase();
+        match extension.as_str() {
+            "txt" | "text" => Some(Format::Text),
+            "md" | "markdown" => Some(Format::Markdown),
+            "html" | "htm" => Some(Format::Html),
+            "pdf" => Some(Format::Pdf),
+            "docx" => Some(Format::Docx),
+            _ => None,
+        }
+    }
+}
+
+/// An uploaded file's text, ready to be evaluated like a pasted description.
+#[derive(Debug)]
+pub struct Document {
+    pub text: String,
+    pub format: Format,
+    /// The character encoding the text was decoded from, e.g. `UTF-8` or `windows-1252`;
+    /// `None` for formats that carry their own, like PDF and DOCX.
+    pub encoding: Option<&'static str>,
+}
+
+/// Reads the text out of an uploaded file. Unsupported formats are a 415; files that aren't
+/// text, can't be parsed or contain none are a 422.
+pub async fn extract(bytes: Vec<u8>, content_type: Option<&str>, filename: Option<&str>) -> Result<Document, (StatusCode, String)> {
+    let format = Format::detect(content_type, filename).ok_or_else(|| {
+        (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!(
+                "Unsupported file type {}; only plain text, markdown, HTML, PDF and DOCX can be evaluated",
+                content_type.or(filename).unwrap_or("(unknown)")
+            ),
+        )
+    })?;
+    let (text, encoding) = match format {
+        Format::Text | Format::Markdown | Format::Html => {
+            let (text, encoding) = decode(&bytes, content_type.and_then(charset));
+            if text.contains('\0') {
+                return Err((StatusCode::UNPROCESSABLE_ENTITY, "The file doesn't look like text".to_string()));
+            }
+            let text = if format == Format::Html { html::extract_text(&text) } else { text };
+            (text, Some(encoding.name()))
+        }
+        Format::Pdf => (parse(bytes, "PDF", b"%PDF-", pdf::extract_text).await?, None),
+        // A DOCX file is a zip archive.
+        Format::Docx => (parse(bytes, "DOCX", b"PK\x03\x04", docx::extract_text).await?, None),
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "The file contains no text".to_string()));
+    }
+    Ok(Document { text: text.to_string(), format, encoding })
+}
+
+/// Runs a binary format's parser on the blocking pool; a parser panic on a malformed file ends
+/// that task rather than the worker and is reported like any other unreadable file.
+async fn parse(
+    bytes: Vec<u8>,
+    name: &'static str,
+    magic: &'static [u8],
+    parser: fn(&[u8]) -> anyhow::Result<String>,
+) -> Result<String, (StatusCode, String)> {
+    let unreadable = || (StatusCode::UNPROCESSABLE_ENTITY, format!("The file isn't a readable {}", name));
+    if !bytes.starts_with(magic) {
+        return Err(unreadable());
+    }
+    match tokio::task::spawn_blocking(move || parser(&bytes)).await {
+        Ok(Ok(text)) => Ok(text),
+        Ok(Err(e)) => {
+            warn!("⚠️ Failed to extract text from an uploaded {}: {:?}", name, e);
+            Err(unreadable())
+        }
+        Err(e) => {
+            warn!("⚠️ The {} parser failed on an uploaded file: {}", name, e);
+            Err(unreadable())
+        }
+    }
+}
+
+/// A byte order mark wins, then the content type's charset, then UTF-8 if the bytes are valid
+/// UTF-8; anything else is guessed from the bytes, which handles the legacy single-byte and
+/// East Asian encodings older documents tend to be saved in.
+fn decode(bytes: &[u8], charset: Option<&str>) -> (String, &'static Encoding) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return (text.into_owned(), encoding);
+    }
+    let encoding = charset
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .or_else(|| std::str::from_utf8(bytes).is_ok().then_some(UTF_8))
+        .unwrap_or_else(|| {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, true)
+        });
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    (text.into_owned(), encoding)
+}
+
+fn charset(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset").then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Paragraphs, headings and the like with blank lines between them, except within a list.
+fn join_blocks(blocks: &[String]) -> String {
+    let mut text = String::new();
+    for (index, block) in blocks.iter().enumerate() {
+        if index > 0 {
+            let in_list = block.starts_with("- ") && blocks[index - 1].starts_with("- ");
+            text.push_str(if in_list { "\n" } else { "\n\n" });
+        }
+        text.push_str(block);
+    }
+    text
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+
+/// How many lines at the top and bottom of a page can be a running header or footer.
+const MARGIN_LINES: usize = 3;
+/// A margin line repeated on at least this share of the pages is a header or footer.
+const REPEATED_SHARE: f64 = 0.6;
+/// Fewer pages than this are too few to tell a running header from a repeated heading.
+const MIN_PAGES_FOR_REPEATS: usize = 3;
+
+/// The document's text, page by page, with running headers, footers and page numbers removed.
+/// Parsing is CPU-bound and the parser panics on some malformed files, so callers run this on
+/// the blocking pool.
+pub fn extract_text(bytes: &[u8]) -> Result<String> {
+    let pages = pdf_extract::extract_text_from_mem_by_pages(bytes).context("Failed to read the PDF")?;
+    Ok(strip_page_furniture(&pages))
+}
+
+fn strip_page_furniture(pages: &[String]) -> String {
+    let pages: Vec<Vec<&str>> = pages
+        .iter()
+        .map(|page| page.lines().map(str::trim).filter(|line| !line.is_empty()).collect())
+        .collect();
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for lines in &pages {
+        let mut keys: Vec<String> = margin(lines).map(|(index, line)| key(lines, index, line)).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            *seen.entry(key).or_default() += 1;
+        }
+    }
+    let threshold = (pages.len() as f64 * REPEATED_SHARE).ceil() as usize;
+    let repeated = |lines: &[&str], index: usize, line: &str| {
+        pages.len() >= MIN_PAGES_FOR_REPEATS && seen.get(&key(lines, index, line)).is_some_and(|count| *count >= threshold)
+    };
+
+    pages
+        .iter()
+        .map(|lines| {
+            let furniture: Vec<usize> = margin(lines)
+                .filter(|(index, line)| repeated(lines, *index, line) || is_page_number(line))
+                .map(|(index, _)| index)
+                .collect();
+            lines
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !furniture.contains(index))
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .filter(|page| !page.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The first and last few lines of a page, with their positions.
+fn margin<'a>(lines: &'a [&'a str]) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    let bottom = lines.len().saturating_sub(MARGIN_LINES).max(MARGIN_LINES.min(lines.len()));
+    lines
+        .iter()
+        .enumerate()
+        .filter(move |(index, _)| *index < MARGIN_LINES || *index >= bottom)
+        .map(|(index, line)| (index, *line))
+}
+
+/// What a margin line is compared by across pages. The very first and last lines are compared
+/// with their digits masked, since running headers and footers often carry the page number or
+/// date; lines further in have to match exactly, so numbered headings aren't mistaken for them.
+fn key(lines: &[&str], index: usize, line: &str) -> String {
+    if index == 0 || index + 1 == lines.len() {
+        let masked: String = line.chars().map(|c| if c.is_ascii_digit() { '#' } else { c }).collect();
+        format!("edge:{}", masked)
+    } else {
+        format!("line:{}", line)
+    }
+}
+
+/// `7`, `- 7 -`, `7 / 12`, `Page 7`, `Page 7 of 12` and the like.
+fn is_page_number(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    let rest = lower.strip_prefix("page").unwrap_or(&lower);
+    let rest = rest.replace(" of ", "/");
+    rest.chars().any(|c| c.is_ascii_digit())
+        && rest.chars().all(|c| c.is_ascii_digit() || c.is_whitespace() || matches!(c, '-' | '/' | '|' | '.'))
+}
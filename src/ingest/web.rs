@@ -0,0 +1,296 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use reqwest::{header, redirect::Policy, Client, ClientBuilder, Url};
+use tracing::{info, warn};
+
+use super::Document;
+use crate::config::LimitsConfig;
+use crate::ratelimit::{ip_list, IpNet};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 15;
+const MAX_REDIRECTS: usize = 5;
+pub(super) const USER_AGENT: &str = concat!("ai-risk-evaluator/", env!("CARGO_PKG_VERSION"));
+/// The formats upload takes, preferred in this order.
+const ACCEPT: &str = "text/html, application/xhtml+xml, text/markdown, text/plain;q=0.9, application/pdf;q=0.8, \
+                      application/vnd.openxmlformats-officedocument.wordprocessingml.document;q=0.8, */*;q=0.1";
+
+/// Keeps requests to URLs callers chose off the internal network: a host is refused unless
+/// all of its addresses are public, or in `allowed`, and the request then goes to those
+/// checked addresses, so a second DNS answer can't point it somewhere else. Proxies from the
+/// environment are bypassed for the same reason.
+#[derive(Clone)]
+pub struct AddressGuard {
+    allowed: Arc<[IpNet]>,
+}
+
+impl AddressGuard {
+    pub fn new(allowed: Vec<IpNet>) -> Self {
+        Self { allowed: allowed.into() }
+    }
+
+    /// The addresses `url`'s host resolves to, refused unless they're all public or allowed.
+    /// Only http and https urls are taken.
+    pub async fn resolve(&self, url: &Url) -> Result<Vec<SocketAddr>, (StatusCode, String)> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err((StatusCode::BAD_REQUEST, "Only http and https urls can be fetched".to_string()));
+        }
+        let (host, port) = match (url.host_str(), url.port_or_known_default()) {
+            (Some(host), Some(port)) => (host, port),
+            _ => return Err((StatusCode::BAD_REQUEST, format!("{} has no host", url))),
+        };
+        // IPv6 literals keep their brackets in the host.
+        let addrs: Vec<SocketAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(ip) => vec![SocketAddr::new(ip, port)],
+            Err(_) => tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| bad_gateway(format!("Failed to resolve {}: {}", host, e)))?
+                .collect(),
+        };
+        if let Some(addr) = addrs.iter().find(|addr| !self.allowed(addr.ip())) {
+            warn!("⚠️ Refused a request to {}: {} is an internal address", url, addr.ip());
+            return Err((StatusCode::FORBIDDEN, format!("{} resolves to an internal address", host)));
+        }
+        Ok(addrs)
+    }
+
+    /// A client for requests to `url`'s host that only connects to `addrs`, as returned by
+    /// [`AddressGuard::resolve`], and doesn't follow redirects.
+    pub fn pinned(url: &Url, addrs: &[SocketAddr]) -> ClientBuilder {
+        Client::builder()
+            .no_proxy()
+            .redirect(Policy::none())
+            .user_agent(USER_AGENT)
+            .resolve_to_addrs(url.host_str().unwrap_or_default(), addrs)
+    }
+
+    fn allowed(&self, ip: IpAddr) -> bool {
+        is_public(ip) || self.allowed.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Fetches the pages `POST /evaluate/url` evaluates. Every hop, redirects included, goes
+/// through an [`AddressGuard`] that allows `url_fetch_allowed_nets`.
+pub struct UrlFetcher {
+    guard: AddressGuard,
+    timeout: Duration,
+    max_bytes: usize,
+}
+
+impl UrlFetcher {
+    pub fn from_config(limits: &LimitsConfig, max_bytes: usize) -> Self {
+        Self {
+            guard: AddressGuard::new(ip_list("URL_FETCH_ALLOWED_NETS", &limits.url_fetch_allowed_nets)),
+            timeout: Duration::from_secs(limits.url_fetch_timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            max_bytes,
+        }
+    }
+
+    /// The page's text, read like an uploaded file of the type the server says it is. Bad URLs
+    /// are a 400, internal addresses a 403 and pages that can't be fetched a 502.
+    pub async fn fetch(&self, url: &str) -> Result<Document, (StatusCode, String)> {
+        let mut url = Url::parse(url).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid url: {}", e)))?;
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self.get(&url).await?;
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(header::LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or_else(|| bad_gateway(format!("{} redirected without a location", url)))?;
+                url = url.join(location).map_err(|e| bad_gateway(format!("{} redirected to an invalid url: {}", url, e)))?;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(bad_gateway(format!("Fetching {} failed with {}", url, status)));
+            }
+            return self.read(url, response).await;
+        }
+        Err(bad_gateway(format!("Fetching {} took more than {} redirects", url, MAX_REDIRECTS)))
+    }
+
+    async fn get(&self, url: &Url) -> Result<reqwest::Response, (StatusCode, String)> {
+        self.client(url)
+            .await?
+            .get(url.clone())
+            .header(header::ACCEPT, ACCEPT)
+            .send()
+            .await
+            .map_err(|e| bad_gateway(format!("Failed to fetch {}: {}", url, e)))
+    }
+
+    /// A client for requests to `url`'s host, which only connects to its checked addresses and
+    /// doesn't follow redirects.
+    pub(super) async fn client(&self, url: &Url) -> Result<Client, (StatusCode, String)> {
+        let addrs = self.resolve(url).await?;
+        AddressGuard::pinned(url, &addrs)
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| bad_gateway(format!("Failed to set up the request: {}", e)))
+    }
+
+    /// See [`AddressGuard::resolve`].
+    pub(super) async fn resolve(&self, url: &Url) -> Result<Vec<SocketAddr>, (StatusCode, String)> {
+        self.guard.resolve(url).await
+    }
+
+    async fn read(&self, url: Url, mut response: reqwest::Response) -> Result<Document, (StatusCode, String)> {
+        let too_large = || (StatusCode::PAYLOAD_TOO_LARGE, format!("The page is larger than the {} byte limit", self.max_bytes));
+        if response.content_length().is_some_and(|length| length > self.max_bytes as u64) {
+            return Err(too_large());
+        }
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| bad_gateway(format!("Failed to read {}: {}", url, e)))?
+        {
+            if bytes.len() + chunk.len() > self.max_bytes {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let size = bytes.len();
+        let filename = url.path_segments().and_then(|mut segments| segments.next_back()).map(str::to_string);
+        let document = super::extract(bytes, content_type.as_deref(), filename.as_deref()).await?;
+        info!(url = %url, bytes = size, format = ?document.format, "📥 Fetched page");
+        Ok(document)
+    }
+}
+
+pub(super) fn bad_gateway(message: String) -> (StatusCode, String) {
+    (StatusCode::BAD_GATEWAY, message)
+}
+
+/// Whether `ip` is on the public internet: not loopback, private, link-local, shared (CGNAT),
+/// multicast, documentation or otherwise reserved. IPv6 addresses that embed an IPv4 one
+/// are judged by it.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+    let segments = ip.segments();
+    let octets = ip.octets();
+    // IPv4-compatible (::a.b.c.d, deprecated), NAT64 (64:ff9b::/96) and 6to4 (2002::/16)
+    // addresses reach the IPv4 address they carry.
+    if segments[..6] == [0, 0, 0, 0, 0, 0] && (segments[6] != 0 || segments[7] > 1) {
+        return is_public_v4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]));
+    }
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        return is_public_v4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]));
+    }
+    if segments[0] == 0x2002 {
+        return is_public_v4(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5]));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] & 0xffc0) == 0xfec0
+        // Teredo (2001::/32) tunnels to an IPv4 address that can't be checked up front.
+        || (segments[0] == 0x2001 && segments[1] == 0)
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_public_v4_refuses_internal_addresses() {
+        let cases = [
+            ("0.0.0.0", false),
+            ("127.0.0.1", false),
+            ("127.255.255.254", false),
+            ("10.0.0.1", false),
+            ("172.16.0.1", false),
+            ("172.31.255.255", false),
+            ("192.168.1.1", false),
+            ("169.254.169.254", false),
+            ("169.254.0.1", false),
+            ("100.64.0.1", false),
+            ("100.127.255.255", false),
+            ("192.0.0.192", false),
+            ("198.18.0.1", false),
+            ("192.0.2.1", false),
+            ("224.0.0.251", false),
+            ("240.0.0.1", false),
+            ("255.255.255.255", false),
+            ("8.8.8.8", true),
+            ("1.1.1.1", true),
+            ("93.184.216.34", true),
+            ("100.128.0.1", true),
+            ("172.32.0.1", true),
+        ];
+        for (ip, public) in cases {
+            assert_eq!(is_public_v4(ip.parse().unwrap()), public, "{}", ip);
+        }
+    }
+
+    #[test]
+    fn is_public_v6_refuses_internal_addresses() {
+        let cases = [
+            ("::", false),
+            ("::1", false),
+            ("::ffff:127.0.0.1", false),
+            ("::ffff:10.0.0.1", false),
+            ("::ffff:169.254.169.254", false),
+            ("64:ff9b::a9fe:a9fe", false),
+            ("2002:c0a8:0101::1", false),
+            ("fc00::1", false),
+            ("fd00:ec2::254", false),
+            ("fe80::1", false),
+            ("fec0::1", false),
+            ("ff02::1", false),
+            ("2001:db8::1", false),
+            ("::7f00:1", false),
+            ("::a9fe:a9fe", false),
+            ("::10.0.0.1", false),
+            ("::0.0.0.2", false),
+            ("2001:0:4136:e378:8000:63bf:3fff:fdd2", false),
+            ("2001::1", false),
+            ("::808:808", true),
+            ("::ffff:8.8.8.8", true),
+            ("64:ff9b::808:808", true),
+            ("2606:4700:4700::1111", true),
+            ("2001:4860:4860::8888", true),
+        ];
+        for (ip, public) in cases {
+            assert_eq!(is_public_v6(ip.parse().unwrap()), public, "{}", ip);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    sync::Arc,
+    time::Instant,
+};
+
+use chrono::Utc;
+use reqwest::Url;
+use tokio::sync::{mpsc, Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::auth::Identity;
+use crate::config::LimitsConfig;
+use crate::shared;
+use crate::storage::{Job, JobPriority, JobStatus};
+use crate::{analyze_prepared, finish_evaluation, AppState, PreparedEvaluation, RiskRequest};
+
+const DEFAULT_JOB_WORKERS: usize = 2;
+const DEFAULT_JOB_QUEUE_CAPACITY: usize = 100;
+
+/// An accepted evaluation waiting for a worker. Validation, the register lookup and the budget
+/// check already happened when it was submitted.
+pub struct QueuedJob {
+    pub job: Job,
+    pub identity: Identity,
+    pub payload: RiskRequest,
+    pub prepared: PreparedEvaluation,
+    /// Where to POST the result, if the caller asked for a webhook.
+    pub callback_url: Option<Url>,
+    /// Held until the job is done, for submitters that limit how many of their jobs are in
+    /// flight, like batches.
+    pub slot: Option<OwnedSemaphorePermit>,
+}
+
+/// One priority's queue and concurrency cap.
+struct Lane {
+    /// Dropped by [`JobQueue::close`], after which workers drain what is left and stop.
+    sender: std::sync::Mutex<Option<mpsc::Sender<QueuedJob>>>,
+    receiver: Mutex<mpsc::Receiver<QueuedJob>>,
+    queued: AtomicUsize,
+    /// Held by every worker waiting on or running a job from this lane.
+    permits: Arc<Semaphore>,
+    running: AtomicUsize,
+    /// Submissions refused because the lane was full, for `/metrics`.
+    rejected: AtomicU64,
+}
+
+impl Lane {
+    fn new(capacity: usize, concurrency: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity);
+        Self {
+            sender: std::sync::Mutex::new(Some(sender)),
+            receiver: Mutex::new(receiver),
+            queued: AtomicUsize::new(0),
+            permits: Arc::new(Semaphore::new(concurrency)),
+            running: AtomicUsize::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Waits for a slot under the lane's cap, then for a job.
+    async fn next(&self) -> Option<(QueuedJob, OwnedSemaphorePermit)> {
+        let permit = self.permits.clone().acquire_owned().await.ok()?;
+        let queued = self.receiver.lock().await.recv().await?;
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+        Some((queued, permit))
+    }
+}
+
+/// Per-lane figures reported by `/metrics`.
+pub struct LaneStats {
+    pub priority: JobPriority,
+    pub queued: usize,
+    pub running: usize,
+    pub rejected: u64,
+}
+
+/// Bounded in-memory queues, one per [`JobPriority`], in front of the worker pool started by
+/// [`spawn_workers`].
+pub struct JobQueue {
+    interactive: Lane,
+    batch: Lane,
+    workers: usize,
+    handles: std::sync::Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl JobQueue {
+    /// `job_workers` evaluations run at once (default 2) and up to `job_queue_capacity` more
+    /// may wait in each lane (default 100). `job_interactive_concurrency` and
+    /// `job_batch_concurrency` cap how many workers a lane may occupy; batch jobs default to
+    /// all but one, so an interactive job never waits behind a full batch backlog.
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        let setting = |value: Option<usize>, default: usize| value.filter(|&n| n > 0).unwrap_or(default);
+        let workers = setting(limits.job_workers, DEFAULT_JOB_WORKERS);
+        let capacity = setting(limits.job_queue_capacity, DEFAULT_JOB_QUEUE_CAPACITY);
+        let interactive = setting(limits.job_interactive_concurrency, workers);
+        let batch = setting(limits.job_batch_concurrency, workers.saturating_sub(1).max(1));
+        Self {
+            interactive: Lane::new(capacity, interactive),
+            batch: Lane::new(capacity, batch),
+            workers,
+            handles: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    fn lane(&self, priority: JobPriority) -> &Lane {
+        match priority {
+            JobPriority::Interactive => &self.interactive,
+            JobPriority::Batch => &self.batch,
+        }
+    }
+
+    /// Refuses the job, with a message for the caller, when its lane is full or the queue has
+    /// been closed.
+    pub fn enqueue(&self, queued: QueuedJob) -> Result<(), String> {
+        let priority = queued.job.priority;
+        let lane = self.lane(priority);
+        let sender = lane.sender.lock().unwrap();
+        let Some(sender) = sender.as_ref() else {
+            return Err("The service is shutting down, try again later".to_string());
+        };
+        // Counted first so a worker can never pick the job up before it is counted.
+        lane.queued.fetch_add(1, Ordering::Relaxed);
+        if sender.try_send(queued).is_err() {
+            lane.queued.fetch_sub(1, Ordering::Relaxed);
+            lane.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(format!("The {} job queue is full, try again later", priority.as_str()));
+        }
+        Ok(())
+    }
+
+    /// Stops accepting jobs; the workers finish the ones already queued and then exit.
+    pub fn close(&self) {
+        self.interactive.sender.lock().unwrap().take();
+        self.batch.sender.lock().unwrap().take();
+    }
+
+    /// Waits for the workers to exit after [`JobQueue::close`].
+    pub async fn drain(&self) {
+        let handles = std::mem::take(&mut *self.handles.lock().unwrap());
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    pub fn stats(&self) -> Vec<LaneStats> {
+        [JobPriority::Interactive, JobPriority::Batch]
+            .into_iter()
+            .map(|priority| {
+                let lane = self.lane(priority);
+                LaneStats {
+                    priority,
+                    queued: lane.queued.load(Ordering::Relaxed),
+                    running: lane.running.load(Ordering::Relaxed),
+                    rejected: lane.rejected.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Job {
+    pub fn queued(identity: &Identity, priority: JobPriority) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id: identity.tenant.clone(),
+            key_id: identity.key_id.clone(),
+            status: JobStatus::Queued,
+            priority,
+            created_at: now,
+            updated_at: now,
+            result: None,
+            error: None,
+            batch_id: None,
+            batch_row: None,
+            label: None,
+        }
+    }
+}
+
+/// Starts the workers. The queue lives in memory, so jobs a previous run left unfinished are
+/// marked failed first. With Redis, other replicas' jobs may still be running; only those of
+/// replicas that stopped are failed, by [`shared::spawn_heartbeat`].
+pub async fn spawn_workers(state: Arc<AppState>) {
+    match &state.shared {
+        Some(shared) => shared::spawn_heartbeat(shared.clone(), state.storage.clone()),
+        None => match state.storage.fail_unfinished_jobs("Interrupted by a restart; please resubmit").await {
+            Ok(0) => {}
+            Ok(failed) => info!("🧹 Marked {} interrupted jobs as failed", failed),
+            Err(e) => warn!("⚠️ Failed to clean up interrupted jobs: {:?}", e),
+        },
+    }
+
+    let mut handles = state.jobs.handles.lock().unwrap();
+    for _ in 0..state.jobs.workers {
+        let state = state.clone();
+        handles.push(tokio::spawn(async move {
+            loop {
+                // Biased, so a waiting interactive job always wins over a batch one.
+                let (queued, _permit) = tokio::select! {
+                    biased;
+                    Some(next) = state.jobs.interactive.next() => next,
+                    Some(next) = state.jobs.batch.next() => next,
+                    else => break,
+                };
+                let lane = state.jobs.lane(queued.job.priority);
+                lane.running.fetch_add(1, Ordering::Relaxed);
+                run(&state, queued).await;
+                lane.running.fetch_sub(1, Ordering::Relaxed);
+            }
+        }));
+    }
+    info!("👷 Started {} job workers", state.jobs.workers);
+}
+
+async fn run(state: &AppState, queued: QueuedJob) {
+    let QueuedJob { mut job, identity, payload, mut prepared, callback_url, slot: _slot } = queued;
+    prepared.queued = true;
+    update(state, &mut job, JobStatus::Running).await;
+
+    let started = Instant::now();
+    let result = analyze_prepared(state, &identity, &payload, &prepared).await;
+    let response = match finish_evaluation(state, &identity, &payload, prepared, result, started).await {
+        Ok(response) => response,
+        Err((_, message)) => {
+            job.error = Some(message);
+            update(state, &mut job, JobStatus::Failed).await;
+            return;
+        }
+    };
+
+    let body = match serde_json::to_string(&response) {
+        Ok(body) => body,
+        Err(e) => {
+            job.error = Some(format!("Failed to encode the result: {}", e));
+            update(state, &mut job, JobStatus::Failed).await;
+            return;
+        }
+    };
+    // Parsed back from the text rather than built with `to_value`, which would widen the f32
+    // confidences.
+    job.result = serde_json::from_str(&body).ok();
+    update(state, &mut job, JobStatus::Succeeded).await;
+
+    if let (Some(url), Some(webhooks)) = (callback_url, &state.webhooks) {
+        let webhooks = webhooks.clone();
+        tokio::spawn(async move { webhooks.deliver(job.id, url, body).await });
+    }
+}
+
+/// Stores a new job, owned by this replica until it finishes.
+pub async fn insert(state: &AppState, job: &Job) -> anyhow::Result<()> {
+    state.storage.insert_job(job).await?;
+    if let (Some(shared), JobStatus::Queued | JobStatus::Running) = (&state.shared, job.status) {
+        if let Err(e) = shared.claim_job(job.id).await {
+            warn!(job_id = %job.id, error = ?e, "⚠️ Failed to record the job's owner in Redis");
+        }
+    }
+    Ok(())
+}
+
+pub async fn update(state: &AppState, job: &mut Job, status: JobStatus) {
+    job.status = status;
+    job.updated_at = Utc::now();
+    if let Err(e) = state.storage.update_job(job).await {
+        error!(job_id = %job.id, status = job.status.as_str(), error = ?e, "❌ Failed to update job");
+    }
+    if let (Some(shared), JobStatus::Succeeded | JobStatus::Failed) = (&state.shared, status) {
+        if let Err(e) = shared.release_job(job.id).await {
+            warn!(job_id = %job.id, error = ?e, "⚠️ Failed to clear the job's owner in Redis");
+        }
+    }
+}
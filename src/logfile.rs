@@ -0,0 +1,154 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+const DEFAULT_MAX_BYTES: u64 = 100 * 1024 * 1024;
+const DEFAULT_KEEP: usize = 7;
+
+/// When a log file is started afresh regardless of its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+impl Rotation {
+    /// Identifies the period `at` falls in; a change means it's time to rotate.
+    fn period(self, at: DateTime<Utc>) -> String {
+        match self {
+            Rotation::Hourly => at.format("%Y%m%d%H").to_string(),
+            Rotation::Daily => at.format("%Y%m%d").to_string(),
+            Rotation::Never => String::new(),
+        }
+    }
+}
+
+/// A log file that is renamed aside, with a timestamp suffix, once it grows past a size or a
+/// new hour or day begins, keeping only the most recent rotated files.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    period: String,
+    /// `None` rotates on time alone.
+    max_bytes: Option<u64>,
+    rotation: Rotation,
+    keep: usize,
+}
+
+impl RotatingFile {
+    /// Opens the file named by `LOG_FILE`, or returns `None` when it isn't set.
+    /// `LOG_FILE_MAX_BYTES` (default 100 MiB, 0 for no limit) and `LOG_FILE_ROTATION` (`hourly`,
+    /// `daily` — the default — or `never`) decide when it's rotated, and `LOG_FILE_KEEP`
+    /// (default 7) how many rotated files are kept.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(path) = env::var("LOG_FILE").ok().filter(|path| !path.is_empty()) else {
+            return Ok(None);
+        };
+        let max_bytes = match env::var("LOG_FILE_MAX_BYTES") {
+            Ok(s) => s.parse::<u64>().with_context(|| format!("Invalid LOG_FILE_MAX_BYTES '{}'", s))?,
+            Err(_) => DEFAULT_MAX_BYTES,
+        };
+        let rotation = match env::var("LOG_FILE_ROTATION").as_deref() {
+            Ok("hourly") => Rotation::Hourly,
+            Ok("daily") | Err(_) => Rotation::Daily,
+            Ok("never") => Rotation::Never,
+            Ok(other) => anyhow::bail!("Unknown LOG_FILE_ROTATION '{}', expected hourly, daily or never", other),
+        };
+        let keep = match env::var("LOG_FILE_KEEP") {
+            Ok(s) => s.parse().with_context(|| format!("Invalid LOG_FILE_KEEP '{}'", s))?,
+            Err(_) => DEFAULT_KEEP,
+        };
+
+        let path = PathBuf::from(path);
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        }
+        let file = open(&path)?;
+        let metadata = file.metadata()?;
+        // A file left by an earlier run belongs to the period it was last written in.
+        let modified = metadata.modified().map(DateTime::<Utc>::from).unwrap_or_else(|_| Utc::now());
+        Ok(Some(Self {
+            period: rotation.period(modified),
+            written: metadata.len(),
+            path,
+            file,
+            max_bytes: (max_bytes > 0).then_some(max_bytes),
+            rotation,
+            keep,
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let now = Utc::now();
+        let file_name = self.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let mut rotated = self.path.with_file_name(format!("{}.{}", file_name, now.format("%Y%m%d-%H%M%S")));
+        let mut n = 1;
+        while rotated.exists() {
+            rotated = self.path.with_file_name(format!("{}.{}-{}", file_name, now.format("%Y%m%d-%H%M%S"), n));
+            n += 1;
+        }
+        fs::rename(&self.path, &rotated)?;
+        self.file = open(&self.path).map_err(io::Error::other)?;
+        self.written = 0;
+        self.period = self.rotation.period(now);
+        self.prune(&file_name);
+        Ok(())
+    }
+
+    /// Deletes the oldest rotated files beyond the ones to keep. The timestamp suffixes sort in
+    /// the order the files were rotated.
+    fn prune(&self, file_name: &str) {
+        let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let prefix = format!("{}.", file_name);
+        let mut rotated: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .map(|entry| entry.path())
+            .collect();
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.keep);
+        for old in &rotated[..excess] {
+            let _ = fs::remove_file(old);
+        }
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let too_big = self.max_bytes.is_some_and(|max| self.written > 0 && self.written + buf.len() as u64 > max);
+        if too_big || self.rotation.period(Utc::now()) != self.period {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
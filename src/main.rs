@@ -1,154 +1,925 @@
-// --- AI integration code start ---
-use reqwest::Client;
-use anyhow::Result;
+mod analysis;
+mod audit;
+mod auth;
+mod batch;
+mod budget;
+mod cache;
+mod chunking;
+mod cli;
+mod coalesce;
+mod compression;
+mod concurrency;
+mod conditional;
+mod config;
+mod conversation;
+mod cors;
+mod cost;
+mod diff;
+mod documents;
+mod evidence;
+mod extraction;
+mod idempotency;
+mod ingest;
+mod jobs;
+mod logfile;
+mod metrics;
+mod negotiate;
+mod overload;
+mod pricing;
+mod problem;
+mod providers;
+mod ratelimit;
+mod rating;
+mod register;
+mod reload;
+mod report;
+mod reporting;
+mod request_id;
+mod routes;
+mod secrets;
+mod server;
+mod shared;
+mod severity;
+mod shutdown;
+mod simulation;
+mod stats;
+mod storage;
+mod supply_chain;
+mod taxonomy;
+mod telemetry;
+mod tenant;
+mod tls;
+mod tokens;
+mod webhooks;
 
-async fn analyze_risks_ai(api_key: &str, project_text: &str) -> Result<Vec<RiskItem>> {
-    let client = Client::new();
-
-    let system_msg = serde_json::json!({
-        "role": "system",
-        "content": "You are a risk evaluator assistant. Extract project risks with their severity (low, medium, high) and suggested mitigation strategies in JSON format as an array of objects with fields: severity, category, mitigation."
-    });
-
-    let user_msg = serde_json::json!({
-        "role": "user",
-        "content": format!("Analyze the following project description and return risks:\n\n{}", project_text)
-    });
-
-    let request_body = serde_json::json!({
-        "model": "gpt-4o-mini",
-        "messages": [system_msg, user_msg],
-        "max_tokens": 500,
-        "temperature": 0.3,
-    });
-
-    #[cfg(debug_assertions)]
-    println!("📤 Request body: {:?}", request_body);
-
-    let resp = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .bearer_auth(api_key)
-        .json(&request_body)
-        .send()
-        .await?;
-
-    let resp_json = resp.json::<serde_json::Value>().await?;
-
-    println!("📥 OpenAI raw response: {:?}", resp_json);
-
-    let content = resp_json["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("No content in AI response"))?;
-
-    println!("📄 Extracted content: {}", content);
-
-    let start = content.find('[').ok_or_else(|| anyhow::anyhow!("No JSON array found"))?;
-    let end = content.rfind(']').ok_or_else(|| anyhow::anyhow!("No JSON array end found"))?;
-
-    let json_str = &content[start..=end];
-
-    println!("🔍 JSON string to parse: {}", json_str);
-
-    let risks: Vec<RiskItem> = serde_json::from_str(json_str)?;
-
-    Ok(risks)
-}
-// --- AI integration code end ---
+use analysis::{analyze_until, analyze_with_fallback, Analysis, AnalysisOptions, Deadline, DeadlineExceeded, OnTimeout, ProviderSelection, ProviderTimedOut};
+use audit::AuditResource;
+use auth::{AuthConfig, Identity};
+use batch::BatchRunner;
+use budget::TokenBudget;
+use cache::{CacheStatus, EvaluationCache};
+use coalesce::{Coalesced, InFlight};
+use clap::Parser;
+use cli::Cli;
+use concurrency::{Saturated, UpstreamLimiter};
+use config::{Config, Profile};
+use cost::{CostExposure, CostRange, ProjectBudget};
+use documents::{SourceDocument, Sources};
+use evidence::Evidence;
+use extraction::ParsePath;
+use idempotency::IdempotencyKeys;
+use ingest::{RepoReader, UrlFetcher};
+use jobs::JobQueue;
+use metrics::Metrics;
+use negotiate::{Encoded, FormatQuery, Negotiated};
+use overload::LoadShedder;
+use pricing::Pricing;
+use problem::Problem;
+use providers::{CircuitOpen, ProviderRegistry, TokenUsage};
+use ratelimit::{IpPolicy, RateLimiter};
+use rating::Rating;
+use register::RegisterEntry;
+use reload::Reloader;
+use request_id::RequestId;
+use secrets::SecretStore;
+use routes::health::Readiness;
+use severity::Severity;
+use shared::SharedStore;
+use simulation::Simulator;
+use storage::{Evaluation, Storage};
+use tenant::Tenants;
+use webhooks::WebhookSender;
 
 use axum::{
-    extract::State,
-    routing::post,
-    Json, Router,
+    extract::{DefaultBodyLimit, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware,
+    routing::{delete, get, patch, post},
+    Extension, Json, Router,
 };
+use sentry::integrations::tower::{NewSentryLayer, SentryHttpLayer};
+use sentry::SentryFutureExt;
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, sync::Arc};
+use std::{collections::HashSet, future::Future, sync::{atomic::Ordering, Arc, RwLock}, time::{Duration, Instant}};
 use dotenv::dotenv;
-use std::env;
-use tokio::net::TcpListener;
-use tower_http::cors::{Any, CorsLayer};
+use tokio::task::JoinSet;
+use tracing::{error, info, info_span, warn, Instrument};
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct RiskRequest {
+    /// The project brief. Optional when `documents` are sent, as an overview of them.
+    #[serde(default)]
     description: String,
+    /// Named documents analyzed together with the description, e.g. the charter, budget and
+    /// staffing plan; each risk says which one it comes from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    documents: Vec<SourceDocument>,
+    /// Registry name of the provider to try first, e.g. `"anthropic"`.
+    #[serde(default)]
+    provider: Option<String>,
+    /// Model override for that provider; must be on the allow-list.
+    #[serde(default)]
+    model: Option<String>,
+    /// Drops risks the model is less confident about than this (0.0–1.0).
+    #[serde(default)]
+    min_confidence: Option<f32>,
+    /// Upper bound on the number of risks returned; the most severe are kept.
+    #[serde(default)]
+    max_risks: Option<usize>,
+    #[serde(default)]
+    min_severity: Option<Severity>,
+    /// Free-form labels stored with the evaluation, e.g. `"team:payments"` or `"2024-Q3"`.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Files the evaluation under an existing project (`POST /projects`).
+    #[serde(default)]
+    project_id: Option<Uuid>,
+    /// How long the model may take, in milliseconds; when unset only the provider timeout
+    /// applies. Deadline-bound evaluations are streamed and skip correction re-prompts.
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    /// Whether a passed `timeout_ms` returns the risks completed so far or a 504.
+    #[serde(default)]
+    on_timeout: OnTimeout,
+    /// Asks for a cost impact range per risk, estimated against these figures, and their
+    /// totals in `cost_exposure`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    budget: Option<ProjectBudget>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 struct RiskItem {
-    severity: String,
+    severity: Severity,
+    /// 0.0–10.0, present on numeric severity scales.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    score: Option<f32>,
     category: String,
     mitigation: String,
+    /// 1 (rare) to 5 (almost certain).
+    #[serde(default)]
+    likelihood: Option<Rating>,
+    /// 1 (negligible) to 5 (severe).
+    #[serde(default)]
+    impact: Option<Rating>,
+    /// 0.0–1.0; how strongly the description supports this risk.
+    #[serde(default = "rating::default_confidence", deserialize_with = "rating::deserialize_confidence")]
+    confidence: f32,
+    /// Passages of the description that motivated this risk.
+    #[serde(default)]
+    evidence: Vec<Evidence>,
+    /// ID (or title) of the project's register entry that already tracks this risk. Only set
+    /// for evaluations filed under a project with an imported register; unset risks are new.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    covered_by: Option<String>,
+    /// Name of the request document the risk comes from; only set for requests with
+    /// `documents`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    document: Option<String>,
+    /// What the risk would cost if it occurs; only set for requests with a `budget`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cost_impact: Option<CostRange>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 struct RiskResponse {
+    /// Stored evaluation ID, retrievable via `GET /evaluations/{id}`.
+    id: Uuid,
     risks: Vec<RiskItem>,
+    /// Backend that answered.
+    provider: String,
+    model: Option<String>,
+    /// How the risks were recovered from the model output.
+    parse_path: ParsePath,
+    /// Set when `timeout_ms` cut the model off and `risks` is what it had finished by then.
+    partial: bool,
+    /// Set when the project text was over `max_input_tokens` and only its beginning was
+    /// evaluated.
+    truncated: bool,
+    /// Absent when the provider didn't report token counts, or on a cache hit, which spends none.
+    usage: Option<UsageReport>,
+    /// `hit` when the risks are a recent evaluation of the same text, prompt and model,
+    /// `coalesced` when they're those of an identical evaluation that was running already.
+    cache: CacheStatus,
+    /// The risks' `cost_impact` added up; only set for requests with a `budget`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cost_exposure: Option<CostExposure>,
+}
+
+/// Tokens an evaluation spent, and what they cost at list price.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+struct UsageReport {
+    #[serde(flatten)]
+    tokens: TokenUsage,
+    /// In USD; absent for models without a known price.
+    estimated_cost_usd: Option<f64>,
 }
 
-#[derive(Clone)]
 struct AppState {
-    openai_api_key: String,
+    profile: Profile,
+    providers: ProviderRegistry,
+    /// Models callers may request in addition to each provider's default.
+    allowed_models: RwLock<HashSet<String>>,
+    /// Replaced when the configuration is reloaded; see [`AppState::analysis`].
+    analysis: RwLock<AnalysisOptions>,
+    storage: Arc<dyn Storage>,
+    auth: AuthConfig,
+    key_limiter: RateLimiter,
+    ip_policy: IpPolicy,
+    budget: TokenBudget,
+    tenants: Tenants,
+    jobs: JobQueue,
+    batches: BatchRunner,
+    webhooks: Option<WebhookSender>,
+    upstream: UpstreamLimiter,
+    load: LoadShedder,
+    metrics: Metrics,
+    pricing: Pricing,
+    readiness: Readiness,
+    reloader: Reloader,
+    /// Largest file `POST /evaluate/upload` reads, or page `POST /evaluate/url` downloads.
+    upload_max_bytes: usize,
+    url_fetcher: UrlFetcher,
+    repo_reader: RepoReader,
+    graphql: routes::graphql::ApiSchema,
+    cache: EvaluationCache,
+    in_flight: InFlight,
+    idempotency: IdempotencyKeys,
+    simulator: Simulator,
+    /// Redis, when replicas share state; see [`SharedStore`].
+    shared: Option<SharedStore>,
+}
+
+impl AppState {
+    /// Validates a request's provider/model override against the registry and allow-list.
+    fn select_provider(&self, payload: &RiskRequest) -> Result<ProviderSelection, String> {
+        let provider = match &payload.provider {
+            Some(name) => self
+                .providers
+                .get(name)
+                .ok_or_else(|| format!("Unknown or unconfigured provider '{}'", name))?,
+            None => self.providers.default_provider().map_err(|e| e.to_string())?,
+        };
+
+        if let Some(model) = &payload.model {
+            if model != provider.default_model() && !self.allowed_models.read().unwrap().contains(model) {
+                return Err(format!("Model '{}' is not allowed", model));
+            }
+        }
+
+        Ok(ProviderSelection { provider: payload.provider.clone(), model: payload.model.clone() })
+    }
+
+    /// The service-wide analysis settings as they are now, with a retry budget of their own.
+    /// Evaluations take a copy up front, so a reload never changes one halfway through.
+    fn analysis(&self) -> AnalysisOptions {
+        let mut options = self.analysis.read().unwrap().clone();
+        options.retry_budget = self.providers.retry_policy().budget();
+        options
+    }
 }
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
+    let cli = Cli::parse();
+    let telemetry = telemetry::init(cli.log_level.as_deref());
+    let reporting = reporting::init();
+    let mut config = Config::load(cli.config.as_deref(), cli.profile).unwrap_or_else(|e| panic!("❌ {:?}", e));
+    cli.apply(&mut config);
+    let secrets = SecretStore::from_config(&config.secrets, &config.providers)
+        .unwrap_or_else(|e| panic!("❌ {:?}", e))
+        .map(Arc::new);
+    if let Some(secrets) = &secrets {
+        secrets.fetch_initial().await.unwrap_or_else(|e| panic!("❌ {:?}", e));
+        secrets.apply(&mut config.providers);
+    }
 
-    let openai_api_key = env::var("OPENAI_API_KEY")
-    .unwrap_or_else(|_| {
-        if cfg!(debug_assertions) {
-            println!("⚠️ Using fallback API key for dev.");
-            "fake-api-key".to_string()
-        } else {
-            panic!("❌ OPENAI_API_KEY not set in production!");
-        }
-    });
+    info!("🏷️ Running with the {} profile", config.profile.as_str());
+    let providers = ProviderRegistry::from_config(&config.providers);
+    let provider = providers
+        .default_provider()
+        .unwrap_or_else(|e| panic!("❌ {}", e));
+    info!("🔐 Provider '{}' ready (model {}).", provider.name(), provider.default_model());
+    let chain: Vec<_> = providers.chain(None).iter().map(|p| p.name()).collect();
+    info!("🔗 Provider chain: {}", chain.join(" → "));
 
-    let state = Arc::new(AppState { openai_api_key });
+    let storage = storage::connect(&config.storage)
+        .await
+        .unwrap_or_else(|e| panic!("❌ {:?}", e));
+    info!("🗄️ Storage ready.");
+    storage::spawn_purge_job(storage.clone(), &config.storage);
+    let shared = SharedStore::connect(&config.storage)
+        .await
+        .unwrap_or_else(|e| panic!("❌ {:?}", e));
 
-    println!("🔐 API Key loaded from environment.");
+    let upload_max_bytes = config.limits.upload_max_bytes.unwrap_or(routes::upload::DEFAULT_MAX_BYTES);
+    let state = Arc::new(AppState {
+        profile: config.profile,
+        providers,
+        allowed_models: RwLock::new(config.providers.allowed_models.iter().cloned().collect()),
+        analysis: RwLock::new(AnalysisOptions::from_config(&config.analysis)),
+        storage,
+        auth: AuthConfig::from_env(),
+        key_limiter: RateLimiter::per_key(&config.limits, shared.clone()),
+        ip_policy: IpPolicy::from_config(&config.limits, shared.clone()),
+        budget: TokenBudget::from_config(&config.limits),
+        tenants: Tenants::from_env(),
+        jobs: JobQueue::from_config(&config.limits),
+        batches: BatchRunner::from_config(&config.limits),
+        webhooks: WebhookSender::from_env(),
+        upstream: UpstreamLimiter::from_config(&config.limits),
+        load: LoadShedder::from_config(&config.limits),
+        metrics: Metrics::default(),
+        pricing: Pricing::from_env(),
+        readiness: Readiness::from_config(&config.server),
+        reloader: Reloader::new(cli, &config, secrets.clone()),
+        upload_max_bytes,
+        url_fetcher: UrlFetcher::from_config(&config.limits, upload_max_bytes),
+        repo_reader: RepoReader::from_config(&config.limits),
+        graphql: routes::graphql::schema(),
+        cache: EvaluationCache::from_config(&config.analysis, shared.clone()),
+        in_flight: InFlight::default(),
+        idempotency: IdempotencyKeys::from_config(&config.limits, shared.clone()),
+        simulator: Simulator::from_config(&config.limits),
+        shared,
+    });
+    jobs::spawn_workers(state.clone()).await;
+    reload::spawn_watcher(state.clone());
+    if let Some(secrets) = secrets {
+        secrets::spawn_refresher(secrets, state.clone());
+    }
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = cors::layer(&config.cors).unwrap_or_else(|e| panic!("❌ {:?}", e));
+    let compression = compression::layer(&config.server);
 
-    let app = Router::new()
+    let admin_bind = config.server.admin_bind;
+    let mut app = Router::new()
         .route("/evaluate", post(evaluate_risks))
+        .route("/evaluate/stream", post(routes::stream::evaluate_stream))
+        .route(
+            "/evaluate/upload",
+            post(routes::upload::evaluate_upload)
+                .layer(DefaultBodyLimit::max(upload_max_bytes + routes::upload::MULTIPART_OVERHEAD_BYTES)),
+        )
+        .route(
+            "/evaluate/manifest",
+            post(routes::manifest::evaluate_manifest)
+                .layer(DefaultBodyLimit::max(upload_max_bytes + routes::upload::MULTIPART_OVERHEAD_BYTES)),
+        )
+        .route("/evaluate/url", post(routes::url::evaluate_url))
+        .route("/evaluate/repo", post(routes::repo::evaluate_repo))
+        .route("/evaluate/async", post(routes::jobs::submit_evaluation))
+        .route("/jobs/:id", get(routes::jobs::get_job))
+        .route(
+            "/evaluate/batch",
+            post(routes::batches::submit_batch)
+                .layer(DefaultBodyLimit::max(upload_max_bytes + routes::upload::MULTIPART_OVERHEAD_BYTES)),
+        )
+        .route("/batches/:id", get(routes::batches::get_batch))
+        .route("/batches/:id/results", get(routes::batches::batch_results))
+        .route("/evaluations", get(routes::evaluations::list_evaluations))
+        .route(
+            "/evaluations/:id",
+            get(routes::evaluations::get_evaluation).delete(routes::evaluations::delete_evaluation),
+        )
+        .route("/evaluations/:id/export", get(routes::evaluations::export_evaluation))
+        .route("/evaluations/:id/report.pdf", get(routes::evaluations::report_pdf))
+        .route("/evaluations/:id/report.md", get(routes::evaluations::report_markdown))
+        .route("/evaluations/:id/report.html", get(routes::evaluations::report_html))
+        .route("/evaluations/:id/report.xlsx", get(routes::evaluations::report_xlsx))
+        .route("/evaluations/:id/matrix.svg", get(routes::evaluations::matrix_svg))
+        .route("/evaluations/:id/archive", post(routes::evaluations::archive_evaluation))
+        .route("/evaluations/:id/unarchive", post(routes::evaluations::unarchive_evaluation))
+        .route("/evaluations/:id/tags", patch(routes::evaluations::update_tags))
+        .route("/simulate", post(routes::simulate::simulate))
+        .route(
+            "/projects",
+            get(routes::projects::list_projects).post(routes::projects::create_project),
+        )
+        .route("/projects/:id", get(routes::projects::get_project))
+        .route("/projects/:id/diff", get(routes::projects::diff_evaluations))
+        .route("/projects/:id/trends", get(routes::projects::project_trends))
+        .route(
+            "/projects/:id/register",
+            get(routes::projects::get_register).put(routes::projects::import_register),
+        )
+        .route(
+            "/api-keys",
+            get(routes::api_keys::list_api_keys).post(routes::api_keys::create_api_key),
+        )
+        .route("/api-keys/:id", delete(routes::api_keys::revoke_api_key))
+        .route("/usage", get(routes::usage::get_usage))
+        .route("/audit", get(routes::audit::export_audit_log))
+        .route("/config/reload", post(routes::config::reload_config))
+        .route("/ws", get(routes::ws::evaluation_socket))
+        .route("/graphql", post(routes::graphql::graphql))
+        .route_layer(middleware::from_fn_with_state(state.clone(), ratelimit::limit_per_key))
+        .route_layer(middleware::from_fn_with_state(state.clone(), overload::shed))
+        .route_layer(middleware::from_fn(auth::authorize))
+        .route_layer(middleware::from_fn_with_state(state.clone(), audit::record))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_auth))
+        .route("/oauth/token", post(routes::oauth::token))
+        .route("/openapi.json", get(routes::docs::openapi))
+        .route("/docs", get(routes::docs::swagger_ui))
+        .route_layer(middleware::from_fn_with_state(state.clone(), metrics::track))
+        .route_layer(middleware::from_fn(telemetry::record_route));
+    if admin_bind.is_none() {
+        app = app
+            .route("/metrics", get(routes::metrics::metrics))
+            .route("/version", get(routes::version::version))
+            .route("/stats", get(routes::stats::stats));
+    }
+    let app = app
+        .layer(middleware::from_fn_with_state(state.clone(), ratelimit::limit_per_ip))
+        .layer(middleware::from_fn_with_state(state.clone(), overload::track))
+        // Probes skip the IP rules and load shedding, so a busy instance isn't restarted.
+        .route("/healthz", get(routes::health::healthz))
+        .route("/readyz", get(routes::health::readyz))
         .layer(cors)
-        .with_state(state);
+        .layer(middleware::from_fn(problem::render))
+        .layer(compression)
+        .layer(middleware::from_fn(telemetry::trace_request))
+        .layer(middleware::from_fn(request_id::assign))
+        .layer(SentryHttpLayer::new())
+        .layer(NewSentryLayer::<Request>::new_from_top())
+        .with_state(state.clone());
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    let listener = TcpListener::bind(addr).await.unwrap();
-    println!("✅ Listening on http://{}", addr);
+    let certificates = tls::Certificates::from_config(&config.server.tls).unwrap_or_else(|e| panic!("❌ {:?}", e));
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(());
+    let mut servers = JoinSet::new();
+    for &addr in &config.server.bind {
+        let listener = server::listen(addr).await;
+        match &certificates {
+            Some(certificates) => {
+                info!("✅ Listening on https://{}", addr);
+                servers.spawn(server::serve_tls(listener, app.clone(), certificates.acceptor(), stop_rx.clone()));
+            }
+            None => {
+                info!("✅ Listening on http://{}", addr);
+                servers.spawn(server::serve(listener, app.clone(), stop_rx.clone()));
+            }
+        }
+    }
+    if let Some(path) = &config.server.unix_socket {
+        let socket = server::listen_unix(path, config.server.unix_socket_mode)
+            .await
+            .unwrap_or_else(|e| panic!("❌ {:?}", e));
+        info!("✅ Listening on unix:{}", socket.path().display());
+        servers.spawn(server::serve_unix(socket, app.clone(), stop_rx.clone()));
+    }
+    if let Some(certificates) = certificates {
+        tls::spawn_watcher(certificates);
+    }
+    if let Some(addr) = admin_bind {
+        let listener = server::listen(addr).await;
+        info!("🔒 Serving metrics, stats, version and probes on http://{}", addr);
+        servers.spawn(server::serve(listener, admin_router(state.clone()), stop_rx));
+    }
+    tokio::select! {
+        _ = shutdown::signal() => {}
+        result = servers.join_next() => panic!("❌ Server stopped unexpectedly: {:?}", result),
+    }
 
-    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
-        .await
-        .unwrap();
+    // New connections and jobs are refused from here on; what was already accepted gets until
+    // the deadline to finish.
+    let deadline = shutdown::deadline(&config.server);
+    info!("🛑 Shutting down, draining requests and jobs for up to {}s", deadline.as_secs());
+    let _ = stop_tx.send(());
+    state.jobs.close();
+    let drained = tokio::time::timeout(deadline, async {
+        while servers.join_next().await.is_some() {}
+        state.jobs.drain().await;
+    })
+    .await;
+    if drained.is_err() {
+        warn!("⚠️ Shutdown deadline passed; abandoning unfinished requests and jobs");
+    }
+
+    state.storage.close().await;
+    info!("👋 Stopped");
+    telemetry.shutdown();
+    drop(reporting);
+}
+
+/// The endpoints served on `admin_bind`, without authentication, IP rules or load shedding:
+/// the address is meant to be reachable only from inside the deployment.
+fn admin_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/metrics", get(routes::metrics::metrics))
+        .route("/version", get(routes::version::version))
+        .route("/stats", get(routes::stats::stats))
+        .route("/healthz", get(routes::health::healthz))
+        .route("/readyz", get(routes::health::readyz))
+        .layer(middleware::from_fn(telemetry::trace_request))
+        .layer(middleware::from_fn(request_id::assign))
+        .with_state(state)
 }
 
+/// `POST /evaluate`: evaluates a project description and stores the result.
+#[utoipa::path(
+    post,
+    path = "/evaluate",
+    tag = "evaluations",
+    params(
+        FormatQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Makes retries safe: a repeat of a request that succeeded gets the stored response, marked `Idempotent-Replayed: true`, instead of a second evaluation"),
+    ),
+    request_body = RiskRequest,
+    responses(
+        (status = 200, description = "The stored evaluation's risks", content(
+            (RiskResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "text/markdown"),
+        )),
+        (status = 400, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "A request with the same Idempotency-Key is still running", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The Idempotency-Key was used for a different request", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "Project text over the input limit", body = Problem, content_type = "application/problem+json"),
+        (status = 502, description = "No provider could evaluate the project", body = Problem, content_type = "application/problem+json"),
+        (status = 504, description = "The provider or the request's deadline timed out", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 async fn evaluate_risks(
     State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Negotiated(format): Negotiated,
+    headers: HeaderMap,
     Json(payload): Json<RiskRequest>,
-) -> Json<RiskResponse> {
-    println!("📨 Received: {}", payload.description);
+) -> Result<(Extension<AuditResource>, HeaderMap, Encoded<RiskResponse>), (StatusCode, String)> {
+    let mut response_headers = HeaderMap::new();
+    let response = match idempotency::key(&headers)? {
+        Some(key) => {
+            let (response, replayed) = idempotency::evaluate_once(state, identity, request_id, payload, key).await?;
+            if replayed {
+                response_headers.insert(idempotency::REPLAYED_HEADER, HeaderValue::from_static("true"));
+            }
+            response
+        }
+        None => evaluate(&state, &identity, &request_id, &payload).await?,
+    };
+    Ok((Extension(AuditResource(response.id.to_string())), response_headers, Encoded(format, response)))
+}
+
+/// Runs and stores one evaluation, answering once it's complete; shared by `POST /evaluate`
+/// and the uploads.
+async fn evaluate(
+    state: &AppState,
+    identity: &Identity,
+    request_id: &RequestId,
+    payload: &RiskRequest,
+) -> Result<RiskResponse, (StatusCode, String)> {
+    let prepared = prepare_evaluation(state, identity, request_id, payload).await?;
+
+    let started = Instant::now();
+    let abandoned = Abandoned::new(prepared.id);
+    let result = analyze_prepared(state, identity, payload, &prepared).await;
+    abandoned.disarm();
+    finish_evaluation(state, identity, payload, prepared, result, started).await
+}
+
+/// Logs an evaluation whose future was dropped before its analysis finished, which is how a
+/// client disconnect shows up: hyper drops the handler, and dropping the analysis aborts the
+/// in-flight provider request so no more tokens are spent on it.
+struct Abandoned {
+    id: Uuid,
+    armed: bool,
+}
+
+impl Abandoned {
+    fn new(id: Uuid) -> Self {
+        Self { id, armed: true }
+    }
+
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for Abandoned {
+    fn drop(&mut self) {
+        if self.armed {
+            info!(evaluation_id = %self.id, "🔌 Client went away; cancelled evaluation");
+        }
+    }
+}
+
+/// Everything about a request that is settled before the model is called.
+struct PreparedEvaluation {
+    id: Uuid,
+    request_id: String,
+    /// The description and documents combined, as the model sees them.
+    text: String,
+    sources: Sources,
+    /// Whether `text` was cut to the input token limit.
+    truncated: bool,
+    selection: ProviderSelection,
+    options: AnalysisOptions,
+    tags: Vec<String>,
+    register: Vec<RegisterEntry>,
+    deadline: Option<Deadline>,
+    /// Identifies identical requests: the cache key, and what concurrent ones are coalesced on.
+    key: String,
+    /// A recent analysis under `key`, used instead of calling a provider.
+    cached: Option<Analysis>,
+    /// Already admitted to the job queue, so it waits for an upstream slot rather than being
+    /// refused one.
+    queued: bool,
+}
 
-    match analyze_risks_ai(&state.openai_api_key, &payload.description).await {
-        Ok(risks) => Json(RiskResponse { risks }),
+impl PreparedEvaluation {
+    /// Tags the analysis' provider logs with the evaluation and request they belong to; job
+    /// workers run outside the request's own span.
+    fn span(&self) -> tracing::Span {
+        info_span!("evaluation", id = %self.id, request_id = %self.request_id)
+    }
+}
+
+/// Runs the analysis for a prepared request without streaming, under its deadline if it set one.
+async fn analyze_prepared(
+    state: &AppState,
+    identity: &Identity,
+    payload: &RiskRequest,
+    prepared: &PreparedEvaluation,
+) -> anyhow::Result<(Analysis, CacheStatus)> {
+    let (selection, options, text) = (&prepared.selection, &prepared.options, &prepared.text);
+    let analysis = async {
+        match prepared.deadline {
+            Some(deadline) => {
+                let (risks, _) = tokio::sync::mpsc::unbounded_channel();
+                analyze_until(&state.providers, selection, options, text, deadline, risks).await
+            }
+            None => analyze_with_fallback(&state.providers, selection, options, text).await,
+        }
+    };
+    let analysis = analysis.instrument(prepared.span()).bind_hub(reporting::evaluation_hub(identity, payload, prepared));
+    shared_analysis(state, prepared, analysis).await
+}
+
+/// The cached analysis, or that of an identical evaluation already in flight, or else the
+/// result of running `analysis` with an upstream slot. Deadline-bound evaluations aren't
+/// coalesced, since what they return depends on the deadline.
+async fn shared_analysis(
+    state: &AppState,
+    prepared: &PreparedEvaluation,
+    analysis: impl Future<Output = anyhow::Result<Analysis>>,
+) -> anyhow::Result<(Analysis, CacheStatus)> {
+    if let Some(cached) = &prepared.cached {
+        return Ok((cached.clone(), CacheStatus::Hit));
+    }
+    let analysis = state.upstream.run(prepared.queued, analysis);
+    if prepared.deadline.is_some() {
+        return Ok((analysis.await?, CacheStatus::Miss));
+    }
+    let (analysis, shared) = state.in_flight.run(&prepared.key, analysis).await?;
+    if !shared {
+        return Ok((analysis, CacheStatus::Miss));
+    }
+    state.metrics.coalesced_evaluations.fetch_add(1, Ordering::Relaxed);
+    Ok((analysis, CacheStatus::Coalesced))
+}
+
+/// The error for an analysis that produced no risks: a 504 when the request's deadline passed
+/// and it asked for an error or when the last provider timed out, else a 502.
+fn analysis_failed(e: &anyhow::Error) -> (StatusCode, String) {
+    let e = e.downcast_ref::<Coalesced>().map_or(e, Coalesced::cause);
+    if let Some(&saturated) = e.downcast_ref::<Saturated>() {
+        return saturated.into();
+    }
+    if let Some(exceeded) = e.downcast_ref::<DeadlineExceeded>() {
+        return (StatusCode::GATEWAY_TIMEOUT, exceeded.to_string());
+    }
+    let status = if e.is::<ProviderTimedOut>() {
+        StatusCode::GATEWAY_TIMEOUT
+    } else if e.is::<CircuitOpen>() {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::BAD_GATEWAY
+    };
+    (status, format!("No provider could evaluate the project: {}", e))
+}
+
+/// Validates the request, loads the project's register and checks the caller's budget.
+async fn prepare_evaluation(
+    state: &AppState,
+    identity: &Identity,
+    request_id: &RequestId,
+    payload: &RiskRequest,
+) -> Result<PreparedEvaluation, (StatusCode, String)> {
+    let id = Uuid::new_v4();
+    let (text, sources) = documents::combine(&payload.description, &payload.documents);
+    info!(
+        evaluation_id = %id,
+        client = %identity.name,
+        key_id = %identity.key_id,
+        chars = text.len(),
+        documents = payload.documents.len(),
+        "📨 Received evaluation request"
+    );
+
+    let selection = state
+        .select_provider(payload)
+        .map_err(|msg| (StatusCode::BAD_REQUEST, msg))?;
+
+    documents::validate(&payload.description, &payload.documents).map_err(|msg| (StatusCode::BAD_REQUEST, msg))?;
+    validate_filters(payload).map_err(|msg| (StatusCode::BAD_REQUEST, msg.to_string()))?;
+    let tags = storage::normalize_tags(&payload.tags).map_err(|msg| (StatusCode::BAD_REQUEST, msg))?;
+    let mut register = Vec::new();
+    if let Some(project_id) = payload.project_id {
+        match state.storage.get_register(&identity.tenant, project_id).await {
+            Ok(Some(entries)) => register = entries,
+            Ok(None) => return Err((StatusCode::BAD_REQUEST, format!("Unknown project '{}'", project_id))),
+            Err(e) => {
+                error!("❌ Failed to load project {}: {:?}", project_id, e);
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load project".to_string()));
+            }
+        }
+    }
+    let deadline = match payload.timeout_ms {
+        Some(0) => return Err((StatusCode::BAD_REQUEST, "timeout_ms must be positive".to_string())),
+        Some(ms) => Some(Deadline { limit: Duration::from_millis(ms), on_timeout: payload.on_timeout }),
+        None => None,
+    };
+    budget::check(state, identity).await?;
+
+    let mut options = AnalysisOptions {
+        max_risks: payload.max_risks,
+        min_severity: payload.min_severity,
+        ..state.analysis()
+    };
+    if let Some(budget) = &payload.budget {
+        options.budget = Some(budget.resolve(&options.cost_currency).map_err(|msg| (StatusCode::BAD_REQUEST, msg))?);
+    }
+    state.tenants.apply(&identity.tenant, &mut options);
+    let (text, truncated) = options.input_limit.apply(text).await.map_err(|msg| (StatusCode::PAYLOAD_TOO_LARGE, msg))?;
+    let key = evaluation_key(state, identity, &selection, &options, &text);
+    let cached = if state.cache.enabled() { state.cache.get(&key).await } else { None };
+    if state.cache.enabled() {
+        let status = if cached.is_some() { CacheStatus::Hit } else { CacheStatus::Miss };
+        state.metrics.cache.inc(&[status.as_str()]);
+    }
+
+    Ok(PreparedEvaluation {
+        id,
+        request_id: request_id.0.clone(),
+        text,
+        sources,
+        truncated,
+        selection,
+        options,
+        tags,
+        register,
+        deadline,
+        key,
+        cached,
+        queued: false,
+    })
+}
+
+/// The [`cache::key`] for `text` as the chain's first provider would evaluate it.
+fn evaluation_key(
+    state: &AppState,
+    identity: &Identity,
+    selection: &ProviderSelection,
+    options: &AnalysisOptions,
+    text: &str,
+) -> String {
+    let primary = state.providers.chain(selection.provider.as_deref()).into_iter().next();
+    let provider = primary.as_ref().map_or("", |p| p.name());
+    let model = selection.model.as_deref().or(primary.as_ref().map(|p| p.default_model())).unwrap_or_default();
+    cache::key(&identity.tenant, provider, model, &analysis::prompt_version(options), text)
+}
+
+/// Turns the analysis into the response, then stores it and records the caller's token usage.
+/// A failed analysis is an error for the caller, see [`analysis_failed`]; nothing is stored.
+async fn finish_evaluation(
+    state: &AppState,
+    identity: &Identity,
+    payload: &RiskRequest,
+    prepared: PreparedEvaluation,
+    result: anyhow::Result<(Analysis, CacheStatus)>,
+    started: Instant,
+) -> Result<RiskResponse, (StatusCode, String)> {
+    let id = prepared.id;
+    // Turned away before any provider was called: neither a failure nor a latency sample.
+    if let Err(e) = &result {
+        if let Some(&saturated) = e.downcast_ref::<Coalesced>().map_or(e, Coalesced::cause).downcast_ref::<Saturated>() {
+            return Err(saturated.into());
+        }
+    }
+    if !matches!(result, Ok((_, CacheStatus::Hit))) {
+        state.load.record_latency(started.elapsed());
+    }
+    let (analysis, cache) = match result {
+        Ok(outcome) => outcome,
         Err(e) => {
-            eprintln!("❌ AI call error: {:?}", e);
-            let fallback = vec![
-                RiskItem {
-                    severity: "High".to_string(),
-                    category: "Timeline".to_string(),
-                    mitigation: "Add buffer time and revalidate milestones.".to_string(),
-                },
-                RiskItem {
-                    severity: "Medium".to_string(),
-                    category: "Dependencies".to_string(),
-                    mitigation: "Check for alternatives and establish SLAs.".to_string(),
-                },
-            ];
-            Json(RiskResponse { risks: fallback })
+            error!(evaluation_id = %id, error = ?e, "❌ Evaluation failed");
+            state.metrics.failed_evaluations.fetch_add(1, Ordering::Relaxed);
+            return Err(analysis_failed(&e));
         }
+    };
+    // Partial analyses depend on the deadline, not just the text.
+    if cache == CacheStatus::Miss && analysis.parse_path != ParsePath::Partial {
+        state.cache.insert(prepared.key.clone(), &analysis).await;
+    }
+    let usage = analysis.usage;
+    let cost = usage.and_then(|usage| state.pricing.estimate(&analysis.model, usage));
+    let mut response = RiskResponse {
+        id,
+        risks: analysis.risks,
+        provider: analysis.provider,
+        model: Some(analysis.model),
+        parse_path: analysis.parse_path,
+        partial: analysis.parse_path == ParsePath::Partial,
+        truncated: prepared.truncated,
+        usage: usage.map(|tokens| UsageReport { tokens, estimated_cost_usd: cost }),
+        cache,
+        cost_exposure: None,
+    };
+
+    apply_filters(&mut response.risks, payload);
+    prepared.sources.attribute(&mut response.risks);
+    if !prepared.register.is_empty() {
+        register::mark_covered(&mut response.risks, &prepared.register);
+    }
+    if let Some(budget) = &prepared.options.budget {
+        response.cost_exposure = Some(CostExposure::total(&response.risks, budget));
+    }
+
+    let evaluation = Evaluation {
+        id,
+        created_at: chrono::Utc::now(),
+        description: prepared.text,
+        request: serde_json::to_value(payload).unwrap_or_default(),
+        risks: response.risks.clone(),
+        provider: response.provider.clone(),
+        model: response.model.clone(),
+        parse_path: Some(response.parse_path),
+        latency_ms: started.elapsed().as_millis() as i64,
+        usage,
+        archived: false,
+        tags: prepared.tags,
+        project_id: payload.project_id,
+        request_id: Some(prepared.request_id),
+        tenant_id: identity.tenant.clone(),
+    };
+    // A storage outage shouldn't cost the caller their result.
+    if let Err(e) = state.storage.insert_evaluation(&evaluation).await {
+        error!(evaluation_id = %evaluation.id, error = ?e, "❌ Failed to store evaluation");
     }
+    let today = chrono::Utc::now().date_naive();
+    if let Err(e) = state.storage.record_usage(&identity.tenant, &identity.key_id, today, usage, cost).await {
+        error!(key_id = %identity.key_id, error = ?e, "❌ Failed to record usage");
+    }
+    state.metrics.evaluations.inc(&[&response.provider, response.parse_path.as_str()]);
+    if let Some(usage) = usage {
+        state.metrics.tokens.add(&[&response.provider, "prompt"], usage.prompt_tokens.into());
+        state.metrics.tokens.add(&[&response.provider, "completion"], usage.completion_tokens.into());
+    }
+    if let Some(cost) = cost {
+        state.metrics.cost.add(&[&response.provider], cost);
+    }
+    info!(
+        evaluation_id = %id,
+        provider = %response.provider,
+        model = %response.model.as_deref().unwrap_or("none"),
+        risks = response.risks.len(),
+        latency_ms = evaluation.latency_ms,
+        prompt_tokens = usage.map(|u| u.prompt_tokens),
+        completion_tokens = usage.map(|u| u.completion_tokens),
+        estimated_cost_usd = cost,
+        cache = cache.as_str(),
+        "✅ Evaluation finished"
+    );
+
+    Ok(response)
+}
+
+const MAX_RISKS_LIMIT: usize = 50;
+
+fn validate_filters(payload: &RiskRequest) -> Result<(), &'static str> {
+    if payload.min_confidence.is_some_and(|min| !(0.0..=1.0).contains(&min)) {
+        return Err("min_confidence must be between 0 and 1");
+    }
+    if payload.max_risks.is_some_and(|max| max == 0 || max > MAX_RISKS_LIMIT) {
+        return Err("max_risks must be between 1 and 50");
+    }
+    Ok(())
+}
+
+/// Post-filters the parsed list: the model doesn't always honor the limits in the prompt.
+fn apply_filters(risks: &mut Vec<RiskItem>, payload: &RiskRequest) {
+    risks.retain(|risk| passes_filters(risk, payload));
+    if let Some(max) = payload.max_risks {
+        risks.sort_by_key(|risk| std::cmp::Reverse(risk.severity));
+        risks.truncate(max);
+    }
+}
+
+/// The per-risk part of [`apply_filters`]: minimum severity and confidence.
+fn passes_filters(risk: &RiskItem, payload: &RiskRequest) -> bool {
+    payload.min_severity.is_none_or(|min| risk.severity >= min)
+        && payload.min_confidence.is_none_or(|min| risk.confidence >= min)
 }
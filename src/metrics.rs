@@ -0,0 +1,237 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::stats::RollingStats;
+use crate::AppState;
+
+/// Upper bounds, in seconds, of the latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 11] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0];
+
+/// A counter with one series per combination of label values.
+pub struct CounterVec {
+    labels: &'static [&'static str],
+    series: Mutex<BTreeMap<Vec<String>, f64>>,
+}
+
+impl CounterVec {
+    pub fn new(labels: &'static [&'static str]) -> Self {
+        Self { labels, series: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// `values` line up with the label names given to [`CounterVec::new`].
+    pub fn add(&self, values: &[&str], n: f64) {
+        let key = values.iter().map(|v| v.to_string()).collect();
+        *self.series.lock().unwrap().entry(key).or_default() += n;
+    }
+
+    pub fn inc(&self, values: &[&str]) {
+        self.add(values, 1.0);
+    }
+
+    pub fn render(&self, body: &mut String, name: &str, help: &str) {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} counter", name);
+        for (values, count) in self.series.lock().unwrap().iter() {
+            let _ = writeln!(body, "{}{{{}}} {}", name, label_set(self.labels, values), count);
+        }
+    }
+}
+
+/// A latency histogram with one series per combination of label values.
+pub struct HistogramVec {
+    labels: &'static [&'static str],
+    series: Mutex<BTreeMap<Vec<String>, Histogram>>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl HistogramVec {
+    pub fn new(labels: &'static [&'static str]) -> Self {
+        Self { labels, series: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn observe(&self, values: &[&str], elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let key = values.iter().map(|v| v.to_string()).collect();
+        let mut series = self.series.lock().unwrap();
+        let histogram = series.entry(key).or_default();
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+        histogram.sum += secs;
+        histogram.count += 1;
+    }
+
+    pub fn render(&self, body: &mut String, name: &str, help: &str) {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} histogram", name);
+        for (values, histogram) in self.series.lock().unwrap().iter() {
+            let labels = label_set(self.labels, values);
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(body, "{}_bucket{{{},le=\"{}\"}} {}", name, labels, bound, count);
+            }
+            let _ = writeln!(body, "{}_bucket{{{},le=\"+Inf\"}} {}", name, labels, histogram.count);
+            let _ = writeln!(body, "{}_sum{{{}}} {}", name, labels, histogram.sum);
+            let _ = writeln!(body, "{}_count{{{}}} {}", name, labels, histogram.count);
+        }
+    }
+}
+
+fn label_set(names: &[&str], values: &[String]) -> String {
+    names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Request- and evaluation-level figures for `/metrics`; per-call LLM figures live in
+/// [`CallMetrics`] on the provider registry.
+pub struct Metrics {
+    pub requests: CounterVec,
+    pub request_duration: HistogramVec,
+    /// Recent requests by method and route, for `/stats`; server errors count as failures.
+    pub route_stats: RollingStats,
+    /// Finished evaluations by the provider that answered and how its reply was parsed.
+    pub evaluations: CounterVec,
+    /// Evaluations that failed because no provider answered in time or parseably.
+    pub failed_evaluations: AtomicU64,
+    pub tokens: CounterVec,
+    /// Estimated spend in USD, by provider.
+    pub cost: CounterVec,
+    /// Evaluation cache lookups by `result`, `hit` or `miss`.
+    pub cache: CounterVec,
+    /// Evaluations that shared the result of an identical one already in flight.
+    pub coalesced_evaluations: AtomicU64,
+    /// `POST /evaluate` retries answered with the response stored for their `Idempotency-Key`.
+    pub idempotent_replays: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            requests: CounterVec::new(&["method", "route", "status"]),
+            request_duration: HistogramVec::new(&["method", "route"]),
+            route_stats: RollingStats::default(),
+            evaluations: CounterVec::new(&["provider", "parse_path"]),
+            failed_evaluations: AtomicU64::new(0),
+            tokens: CounterVec::new(&["provider", "kind"]),
+            cost: CounterVec::new(&["provider"]),
+            cache: CounterVec::new(&["result"]),
+            coalesced_evaluations: AtomicU64::new(0),
+            idempotent_replays: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn render(&self, body: &mut String) {
+        self.requests.render(body, "risk_evaluator_http_requests_total", "HTTP requests by route and status.");
+        self.request_duration
+            .render(body, "risk_evaluator_http_request_duration_seconds", "Time to answer HTTP requests.");
+        self.evaluations
+            .render(body, "risk_evaluator_evaluations_total", "Finished evaluations by provider and parse path.");
+        body.push_str("# HELP risk_evaluator_failed_evaluations_total Evaluations that failed on every provider.\n");
+        body.push_str("# TYPE risk_evaluator_failed_evaluations_total counter\n");
+        let _ = writeln!(body, "risk_evaluator_failed_evaluations_total {}", self.failed_evaluations.load(Ordering::Relaxed));
+        self.tokens.render(body, "risk_evaluator_tokens_total", "Tokens spent on evaluations, by provider.");
+        self.cost
+            .render(body, "risk_evaluator_estimated_cost_usd_total", "Estimated evaluation spend in USD, by provider.");
+        self.cache.render(body, "risk_evaluator_evaluation_cache_total", "Evaluation cache lookups by result.");
+        body.push_str("# HELP risk_evaluator_coalesced_evaluations_total Evaluations answered by an identical one in flight.\n");
+        body.push_str("# TYPE risk_evaluator_coalesced_evaluations_total counter\n");
+        let _ = writeln!(body, "risk_evaluator_coalesced_evaluations_total {}", self.coalesced_evaluations.load(Ordering::Relaxed));
+        body.push_str("# HELP risk_evaluator_idempotent_replays_total Evaluations replayed for a repeated Idempotency-Key.\n");
+        body.push_str("# TYPE risk_evaluator_idempotent_replays_total counter\n");
+        let _ = writeln!(body, "risk_evaluator_idempotent_replays_total {}", self.idempotent_replays.load(Ordering::Relaxed));
+    }
+}
+
+/// Figures for individual provider calls, recorded by the analysis pipeline.
+pub struct CallMetrics {
+    /// Per attempt, re-prompts included; `outcome` is `ok`, `error` or `timeout`.
+    pub latency: HistogramVec,
+    /// Replies that could not be parsed into risks, before any repair re-prompt.
+    pub parse_failures: CounterVec,
+    /// Calls retried after a transient failure; `reason` is `rate_limited`, `server_error` or
+    /// `connection`.
+    pub retries: CounterVec,
+    /// Transient failures that weren't retried because the evaluation's retry budget was spent.
+    pub retries_exhausted: CounterVec,
+    /// Calls not made because the provider's circuit was open.
+    pub short_circuited: CounterVec,
+    /// Recent attempts by provider and model, for `/stats`.
+    pub model_stats: RollingStats,
+}
+
+impl Default for CallMetrics {
+    fn default() -> Self {
+        Self {
+            latency: HistogramVec::new(&["provider", "outcome"]),
+            parse_failures: CounterVec::new(&["provider"]),
+            retries: CounterVec::new(&["provider", "reason"]),
+            retries_exhausted: CounterVec::new(&["provider"]),
+            short_circuited: CounterVec::new(&["provider"]),
+            model_stats: RollingStats::default(),
+        }
+    }
+}
+
+impl CallMetrics {
+    pub fn render(&self, body: &mut String) {
+        self.latency
+            .render(body, "risk_evaluator_llm_request_duration_seconds", "Time spent on each LLM provider attempt.");
+        self.parse_failures
+            .render(body, "risk_evaluator_llm_parse_failures_total", "LLM replies that did not parse into risks.");
+        self.retries.render(body, "risk_evaluator_llm_retries_total", "LLM calls retried after a transient failure.");
+        self.retries_exhausted.render(
+            body,
+            "risk_evaluator_llm_retry_budget_exhausted_total",
+            "Transient LLM failures not retried because the evaluation's retry budget was spent.",
+        );
+        self.short_circuited.render(
+            body,
+            "risk_evaluator_llm_short_circuited_total",
+            "LLM calls skipped because the provider's circuit breaker was open.",
+        );
+    }
+}
+
+/// Counts and times every routed request under its route template, e.g. `/jobs/:id`.
+pub async fn track(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path().to_string(), |path| path.as_str().to_string());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+    let elapsed = started.elapsed();
+    state.metrics.requests.inc(&[&method, &route, response.status().as_str()]);
+    state.metrics.request_duration.observe(&[&method, &route], elapsed);
+    state.metrics.route_stats.record(&[&method, &route], elapsed, response.status().is_server_error());
+    response
+}
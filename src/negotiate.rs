@@ -0,0 +1,308 @@
+use std::{borrow::Cow, str::FromStr};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::{header, request::Parts, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::cache::CacheStatus;
+use crate::rating::Rating;
+use crate::report;
+use crate::severity::Severity;
+use crate::storage::Evaluation;
+use crate::{RiskItem, RiskResponse};
+
+/// What an evaluation endpoint answers in: the JSON document, a CSV with a line per risk for
+/// pasting into a spreadsheet, or a Markdown report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ResponseFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// The best supported type the `Accept` header allows, by quality and then by how specific
+    /// the range is, so `text/csv, */*` is a CSV. `None` when it allows none of them.
+    fn from_accept(accept: &str) -> Option<Self> {
+        let mut best: Option<((f32, bool), Self)> = None;
+        for range in accept.split(',') {
+            let mut params = range.split(';');
+            let media = params.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let (format, specific) = match media.as_str() {
+                "application/json" => (Self::Json, true),
+                "text/csv" => (Self::Csv, true),
+                "text/markdown" | "text/x-markdown" => (Self::Markdown, true),
+                "application/*" | "*/*" => (Self::Json, false),
+                _ => continue,
+            };
+            let rank = (quality, specific);
+            if quality > 0.0 && best.is_none_or(|(best_rank, _)| rank > best_rank) {
+                best = Some((rank, format));
+            }
+        }
+        best.map(|(_, format)| format)
+    }
+}
+
+impl FromStr for ResponseFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "md" | "markdown" => Ok(Self::Markdown),
+            other => Err(format!("Unknown format '{}'; expected json, csv or md", other)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FormatQuery {
+    /// `json`, `csv` or `md`; takes precedence over `Accept`.
+    format: Option<String>,
+}
+
+/// The format the caller asked for: `?format=` when given, else the `Accept` header, else
+/// JSON. An unknown `format` is a 400; an `Accept` header listing only types we can't produce
+/// gets JSON, like one listing none.
+#[derive(Debug, Clone, Copy)]
+pub struct Negotiated(pub ResponseFormat);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Negotiated {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = Query::<FormatQuery>::try_from_uri(&parts.uri).map_err(|e| (StatusCode::BAD_REQUEST, e.body_text()))?;
+        if let Some(format) = &query.format {
+            return format.parse().map(Self).map_err(|msg| (StatusCode::BAD_REQUEST, msg));
+        }
+        let format = parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .and_then(ResponseFormat::from_accept)
+            .unwrap_or_default();
+        Ok(Self(format))
+    }
+}
+
+/// A result that can be answered in every [`ResponseFormat`].
+pub trait Encode: Serialize {
+    fn id(&self) -> Uuid;
+    fn risks(&self) -> &[RiskItem];
+    fn markdown(&self) -> String;
+}
+
+impl Encode for Evaluation {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn risks(&self) -> &[RiskItem] {
+        &self.risks
+    }
+
+    fn markdown(&self) -> String {
+        report::markdown::render(self)
+    }
+}
+
+impl Encode for RiskResponse {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn risks(&self) -> &[RiskItem] {
+        &self.risks
+    }
+
+    fn markdown(&self) -> String {
+        let mut byline = format!("Evaluation {}", self.id);
+        match &self.model {
+            Some(model) => byline.push_str(&format!(" · {} ({})", self.provider, model)),
+            None => byline.push_str(&format!(" · {}", self.provider)),
+        }
+        let mut notes = Vec::new();
+        let exposure = self.cost_exposure.as_ref().map(|exposure| {
+            format!(
+                "Estimated cost exposure: {:.0} {} likely, {:.0} to {:.0}, across {} risks ({:.0}% of the budget).",
+                exposure.likely,
+                exposure.currency,
+                exposure.min,
+                exposure.max,
+                exposure.estimated_risks,
+                exposure.likely_share_of_budget * 100.0
+            )
+        });
+        if self.partial {
+            notes.push("The deadline cut the model off; these are the risks it had finished.");
+        }
+        if self.truncated {
+            notes.push("The project text was over the token limit; only its beginning was evaluated.");
+        }
+        match self.cache {
+            CacheStatus::Hit => notes.push("Served from the cache: a recent evaluation of the same text, prompt and model."),
+            CacheStatus::Coalesced => notes.push("Shared with an identical evaluation that was already running."),
+            CacheStatus::Miss => {}
+        }
+        if let Some(exposure) = &exposure {
+            notes.push(exposure);
+        }
+        report::markdown::render_risks(&byline, &notes, &self.risks)
+    }
+}
+
+/// Answers with `value` in `format`. CSV comes as a download, Markdown inline; all three
+/// vary on `Accept` so caches keep them apart.
+pub struct Encoded<T>(pub ResponseFormat, pub T);
+
+impl<T: Encode> IntoResponse for Encoded<T> {
+    fn into_response(self) -> Response {
+        let Encoded(format, value) = self;
+        let mut response = match format {
+            ResponseFormat::Json => Json(value).into_response(),
+            ResponseFormat::Csv => match risks_csv(value.risks()) {
+                Ok(body) => {
+                    let disposition = format!("attachment; filename=\"evaluation-{}.csv\"", value.id());
+                    ([(header::CONTENT_TYPE, "text/csv".to_string()), (header::CONTENT_DISPOSITION, disposition)], body).into_response()
+                }
+                Err(e) => {
+                    error!("❌ Failed to write CSV for evaluation {}: {:?}", value.id(), e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to export evaluation").into_response()
+                }
+            },
+            ResponseFormat::Markdown => {
+                let disposition = format!("inline; filename=\"evaluation-{}.md\"", value.id());
+                (
+                    [(header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+                    value.markdown(),
+                )
+                    .into_response()
+            }
+        };
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+/// A line of the CSV, one per risk.
+#[derive(Serialize)]
+struct RiskLine<'a> {
+    severity: Severity,
+    category: Cow<'a, str>,
+    likelihood: Option<Rating>,
+    impact: Option<Rating>,
+    confidence: f32,
+    mitigation: Cow<'a, str>,
+    document: Option<Cow<'a, str>>,
+    covered_by: Option<Cow<'a, str>>,
+    cost_min: Option<f64>,
+    cost_likely: Option<f64>,
+    cost_max: Option<f64>,
+    currency: Option<&'a str>,
+}
+
+/// `value` as a CSV cell that a spreadsheet shows as text: one starting with `=`, `+`, `-`,
+/// `@`, a tab or a carriage return would be run as a formula, so it gets a leading `'`. For
+/// every cell that holds model output or caller text.
+pub fn csv_cell(value: &str) -> Cow<'_, str> {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+fn risks_csv(risks: &[RiskItem]) -> anyhow::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for risk in risks {
+        writer.serialize(RiskLine {
+            severity: risk.severity,
+            category: csv_cell(&risk.category),
+            likelihood: risk.likelihood,
+            impact: risk.impact,
+            confidence: risk.confidence,
+            mitigation: csv_cell(&risk.mitigation),
+            document: risk.document.as_deref().map(csv_cell),
+            covered_by: risk.covered_by.as_deref().map(csv_cell),
+            cost_min: risk.cost_impact.as_ref().map(|cost| cost.min),
+            cost_likely: risk.cost_impact.as_ref().map(|cost| cost.likely),
+            cost_max: risk.cost_impact.as_ref().map(|cost| cost.max),
+            currency: risk.cost_impact.as_ref().map(|cost| cost.currency.as_str()),
+        })?;
+    }
+    // Without risks, serialize writes nothing, not even the header.
+    if risks.is_empty() {
+        writer.write_record([
+            "severity",
+            "category",
+            "likelihood",
+            "impact",
+            "confidence",
+            "mitigation",
+            "document",
+            "covered_by",
+            "cost_min",
+            "cost_likely",
+            "cost_max",
+            "currency",
+        ])?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn csv_cell_neutralizes_formulas() {
+        for formula in ["=HYPERLINK(\"http://x\")", "+1+1", "-2+3", "@SUM(A1)", "\tx", "\rx"] {
+            assert_eq!(csv_cell(formula), format!("'{}", formula));
+        }
+        assert!(matches!(csv_cell("Add a buffer = 2 weeks"), Cow::Borrowed("Add a buffer = 2 weeks")));
+        assert_eq!(csv_cell(""), "");
+    }
+
+    #[test]
+    fn risks_csv_escapes_text_cells() {
+        let risk: RiskItem = serde_json::from_value(json!({
+            "severity": "High",
+            "category": "=cmd|' /C calc'!A0",
+            "mitigation": "@SUM(1+1)*cmd|' /C calc'!A0",
+            "document": "-notes.md",
+            "covered_by": "+R-12",
+        }))
+        .unwrap();
+        let csv = String::from_utf8(risks_csv(&[risk]).unwrap()).unwrap();
+        let line = csv.lines().nth(1).unwrap();
+        assert!(line.starts_with("High,'=cmd|' /C calc'!A0,"), "{}", line);
+        assert!(line.contains(",'@SUM(1+1)*cmd|' /C calc'!A0,'-notes.md,'+R-12,"), "{}", line);
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::info;
+
+use crate::auth::{Identity, Role};
+use crate::config::LimitsConfig;
+use crate::AppState;
+
+const PRIORITY_HEADER: &str = "x-priority";
+const DEFAULT_SOFT_IN_FLIGHT: usize = 64;
+const DEFAULT_HARD_IN_FLIGHT: usize = 128;
+const DEFAULT_LATENCY_MS: u64 = 20_000;
+const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+/// Weight of the newest sample in the upstream latency average.
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// How quickly the average falls back towards zero without new samples, so shedding
+/// low-priority traffic (which then records none) doesn't last forever.
+const LATENCY_HALF_LIFE: Duration = Duration::from_secs(30);
+
+/// How much a request matters when the service is struggling. Callers can lower theirs with
+/// `X-Priority: low`; only admins can raise it with `X-Priority: high`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Bulk or background traffic; shed as soon as the service is under pressure.
+    Low,
+    Normal,
+    /// Never shed.
+    High,
+}
+
+impl Priority {
+    fn of(identity: Option<&Identity>, header: Option<&str>) -> Self {
+        match header.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("low") => Priority::Low,
+            Some("high") if identity.is_some_and(|identity| identity.role == Role::Admin) => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
+}
+
+/// A moving average that decays by [`LATENCY_HALF_LIFE`] from its last sample.
+struct LatencyAverage {
+    ms: f64,
+    updated: Instant,
+}
+
+impl LatencyAverage {
+    fn now(&self) -> f64 {
+        self.ms * 0.5f64.powf(self.updated.elapsed().as_secs_f64() / LATENCY_HALF_LIFE.as_secs_f64())
+    }
+}
+
+/// Turns traffic away early when the service is overloaded, rather than letting every request
+/// queue up and time out. Under pressure — too many requests in flight, or upstream calls
+/// getting slow — low-priority requests are refused; past the hard limit, normal ones are too.
+pub struct LoadShedder {
+    in_flight: AtomicUsize,
+    soft_in_flight: usize,
+    hard_in_flight: usize,
+    /// Moving average of evaluation latency, in milliseconds.
+    latency: Mutex<LatencyAverage>,
+    latency_threshold_ms: u64,
+    retry_after_secs: u64,
+    /// Requests refused so far, for `/metrics`.
+    pub shed: AtomicU64,
+}
+
+impl LoadShedder {
+    /// `shed_soft_in_flight` (default 64) and `shed_hard_in_flight` (default 128) bound
+    /// concurrent requests, `shed_latency_ms` (default 20000) the average upstream latency, and
+    /// `shed_retry_after_secs` (default 5) is what refused callers are told to wait.
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            soft_in_flight: limits.shed_soft_in_flight.unwrap_or(DEFAULT_SOFT_IN_FLIGHT),
+            hard_in_flight: limits.shed_hard_in_flight.unwrap_or(DEFAULT_HARD_IN_FLIGHT),
+            latency: Mutex::new(LatencyAverage { ms: 0.0, updated: Instant::now() }),
+            latency_threshold_ms: limits.shed_latency_ms.unwrap_or(DEFAULT_LATENCY_MS),
+            retry_after_secs: limits.shed_retry_after_secs.unwrap_or(DEFAULT_RETRY_AFTER_SECS),
+            shed: AtomicU64::new(0),
+        }
+    }
+
+    /// Folds one evaluation's upstream latency into the moving average; evaluations served
+    /// from the cache shouldn't be recorded.
+    pub fn record_latency(&self, latency: Duration) {
+        let sample = latency.as_millis() as f64;
+        let mut average = self.latency.lock().unwrap();
+        let current = average.now();
+        average.ms = if current < 1.0 { sample } else { current + LATENCY_SMOOTHING * (sample - current) };
+        average.updated = Instant::now();
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    pub fn latency_ms(&self) -> u64 {
+        self.latency.lock().unwrap().now() as u64
+    }
+
+    /// Whether a request of this priority should be refused right now; `in_flight` includes it.
+    fn should_shed(&self, priority: Priority, in_flight: usize) -> bool {
+        match priority {
+            Priority::High => false,
+            Priority::Normal => in_flight > self.hard_in_flight,
+            Priority::Low => in_flight > self.soft_in_flight || self.latency_ms() > self.latency_threshold_ms,
+        }
+    }
+
+    fn overloaded(&self) -> Response {
+        self.shed.fetch_add(1, Ordering::Relaxed);
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, self.retry_after_secs.to_string())],
+            "Service is overloaded, try again later",
+        )
+            .into_response()
+    }
+}
+
+/// Counts the request as in flight until its response is ready; see [`shed`].
+pub async fn track(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let _in_flight = InFlight::enter(&state.load.in_flight);
+    next.run(request).await
+}
+
+/// Sheds the request if the service is overloaded for its priority. Runs after
+/// authentication, since the caller's role decides whether it may ask for `high`; the
+/// in-flight count it goes by is kept by [`track`], which sees all traffic.
+pub async fn shed(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let shedder = &state.load;
+    let priority = Priority::of(
+        request.extensions().get::<Identity>(),
+        request.headers().get(PRIORITY_HEADER).and_then(|v| v.to_str().ok()),
+    );
+    if shedder.should_shed(priority, shedder.in_flight()) {
+        info!(?priority, path = request.uri().path(), "🪫 Shed request");
+        return shedder.overloaded();
+    }
+    next.run(request).await
+}
+
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,85 @@
+use std::{collections::HashMap, env, fs};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::providers::TokenUsage;
+
+/// List prices in USD per million prompt and completion tokens, matched against the model a
+/// provider reports. The longest matching name wins, so `gpt-4o-mini-2024-07-18` is priced as
+/// `gpt-4o-mini` and a Bedrock `anthropic.claude-3-5-haiku-…` ID as `claude-3-5-haiku`.
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("keyword-rules", 0.0, 0.0),
+    ("mock", 0.0, 0.0),
+];
+
+/// Prices loaded from a TOML file, which replace or add to the built-in ones:
+///
+/// ```toml
+/// [models."gpt-4o-mini"]
+/// prompt = 0.15
+/// completion = 0.60
+/// ```
+#[derive(Debug, Deserialize)]
+struct PricingFile {
+    models: HashMap<String, ModelPrice>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct ModelPrice {
+    prompt: f64,
+    completion: f64,
+}
+
+/// Turns token counts into an estimated cost. Models without a price (local Ollama models,
+/// say) get no estimate rather than a made-up one.
+pub struct Pricing {
+    /// Longest name first, so the most specific match is found first.
+    prices: Vec<(String, ModelPrice)>,
+}
+
+impl Pricing {
+    /// The built-in prices, overridden by the file named by `PRICING_FILE` if set.
+    pub fn from_env() -> Self {
+        let mut prices: HashMap<String, ModelPrice> = DEFAULT_PRICES
+            .iter()
+            .map(|&(model, prompt, completion)| (model.to_string(), ModelPrice { prompt, completion }))
+            .collect();
+        if let Ok(path) = env::var("PRICING_FILE") {
+            match load(&path) {
+                Ok(file) => {
+                    info!("💲 Loaded prices for {} models from {}", file.models.len(), path);
+                    prices.extend(file.models);
+                }
+                Err(e) => warn!("⚠️ {:?}, using the built-in prices.", e),
+            }
+        }
+
+        let mut prices: Vec<_> = prices.into_iter().collect();
+        prices.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        Self { prices }
+    }
+
+    /// Estimated cost of `usage` on `model`, in USD.
+    pub fn estimate(&self, model: &str, usage: TokenUsage) -> Option<f64> {
+        let (_, price) = self.prices.iter().find(|(name, _)| model.contains(name.as_str()))?;
+        let cost = (usage.prompt_tokens as f64 * price.prompt + usage.completion_tokens as f64 * price.completion) / 1_000_000.0;
+        Some(cost)
+    }
+}
+
+fn load(path: &str) -> Result<PricingFile> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read pricing file {}", path))?;
+    toml::from_str(&raw).with_context(|| format!("Failed to parse pricing file {}", path))
+}
@@ -0,0 +1,111 @@
+use axum::{
+    body::to_bytes,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::request_id::RequestId;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+/// Error bodies are short messages; anything longer is cut rather than buffered.
+const MAX_DETAIL_BYTES: usize = 16 * 1024;
+
+/// An RFC 7807 problem detail: what every error response carries, so callers can tell a
+/// failure from a result by more than the status and match on `code` rather than wording.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Problem {
+    /// Always `about:blank`: the status and `code` say what went wrong.
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    /// Stable, machine-readable, e.g. `invalid_request` or `provider_error`.
+    code: &'static str,
+    /// `X-Request-Id` of the failed call, to quote when reporting it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, detail: impl Into<String>, request_id: Option<&RequestId>) -> Self {
+        let detail = detail.into();
+        let title = status.canonical_reason().unwrap_or("Error");
+        Self {
+            kind: "about:blank",
+            title,
+            status: status.as_u16(),
+            detail: if detail.trim().is_empty() { title.to_string() } else { detail },
+            code: code(status),
+            request_id: request_id.map(|id| id.0.clone()),
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
+/// The `code` for an error status. Handlers pick the status to match the failure, so the two
+/// always agree: a provider that failed is a 502, one that timed out a 504.
+pub fn code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "invalid_request",
+        StatusCode::UNAUTHORIZED => "unauthenticated",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::BAD_GATEWAY => "provider_error",
+        StatusCode::SERVICE_UNAVAILABLE => "unavailable",
+        StatusCode::GATEWAY_TIMEOUT => "timeout",
+        status if status.is_client_error() => "client_error",
+        _ => "internal_error",
+    }
+}
+
+/// Rewrites error responses with a plain-text or empty body, which is what handlers' `(StatusCode,
+/// String)` errors and axum's own rejections produce, as `application/problem+json`. Headers
+/// such as `Retry-After` are kept. Errors that already have a JSON body, like the OAuth token
+/// endpoint's, are left alone.
+pub async fn render(request: Request, next: Next) -> Response {
+    let request_id = request.extensions().get::<RequestId>().cloned();
+    let response = next.run(request).await;
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+    let plain = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|content_type| content_type.starts_with("text/plain"));
+    if !plain {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let detail = match to_bytes(body, MAX_DETAIL_BYTES).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(_) => String::new(),
+    };
+    let problem = Problem::new(status, detail, request_id.as_ref()).into_response();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let (problem_parts, body) = problem.into_parts();
+    parts.headers.extend(problem_parts.headers);
+    Response::from_parts(parts, body)
+}
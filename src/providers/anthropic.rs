@@ -0,0 +1,217 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+use tracing::trace;
+
+use super::{Completion, CompletionRequest, OutputFormat, Provider, TokenUsage};
+use crate::config::{AnthropicConfig, HttpClientConfig};
+
+const DEFAULT_MODEL: &str = "claude-3-5-haiku-latest";
+const API_VERSION: &str = "2023-06-01";
+
+pub struct AnthropicProvider {
+    client: Client,
+    api_key: String,
+    model: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(client: Client, api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self { client, api_key: api_key.into(), model: model.into() }
+    }
+
+    /// `None` when no API key is configured.
+    pub fn from_config(config: &AnthropicConfig, http: &HttpClientConfig) -> Option<Self> {
+        let api_key = config.api_key.clone()?;
+        let model = config.model.as_deref().unwrap_or(DEFAULT_MODEL);
+        Some(Self::new(super::http::client(http), api_key, model))
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    async fn check(&self) -> Result<()> {
+        self.client
+            .get("https://api.anthropic.com/v1/models")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let model = request.model.as_deref().unwrap_or(&self.model);
+        let request_body = request_body(request, model);
+
+        #[cfg(debug_assertions)]
+        trace!(body = ?request_body, "📤 Request body");
+
+        let resp = self
+            .client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&request_body)
+            .send()
+            .await?;
+        let resp = super::http::check_status("Anthropic", resp).await?;
+
+        let resp_json = resp.json::<serde_json::Value>().await?;
+
+        trace!(response = ?resp_json, "📥 Anthropic raw response");
+
+        parse_response(&resp_json, model)
+    }
+}
+
+/// The Messages API body for `request`.
+fn request_body(request: &CompletionRequest, model: &str) -> Value {
+    // The Messages API takes the system prompt as a top-level field, not as a message.
+    let system: Vec<&str> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let messages: Vec<_> = request.messages.iter().filter(|m| m.role != "system").collect();
+
+    let mut request_body = serde_json::json!({
+        "model": model,
+        "system": system.join("\n\n"),
+        "messages": messages,
+        "max_tokens": request.max_tokens,
+        "temperature": request.temperature,
+    });
+    if let OutputFormat::ToolCall(tool) = &request.output {
+        request_body["tools"] = serde_json::json!([{
+            "name": tool.name,
+            "description": tool.description,
+            "input_schema": tool.parameters,
+        }]);
+        request_body["tool_choice"] = serde_json::json!({ "type": "tool", "name": tool.name });
+    }
+    request_body
+}
+
+/// The forced tool call's input if there is one, else the text blocks joined.
+fn parse_response(resp_json: &Value, model: &str) -> Result<Completion> {
+    let usage = TokenUsage::from_json(&resp_json["usage"], "input_tokens", "output_tokens");
+
+    let blocks = resp_json["content"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("No content in AI response"))?;
+
+    if let Some(tool_use) = blocks.iter().find(|block| block["type"] == "tool_use") {
+        let content = serde_json::to_string(&tool_use["input"])?;
+        return Ok(Completion { content, model: model.to_string(), usage });
+    }
+
+    let content: String = blocks
+        .iter()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect();
+
+    if content.is_empty() {
+        anyhow::bail!("No text content in AI response");
+    }
+
+    Ok(Completion { content, model: model.to_string(), usage })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::analysis::{completion_request, AnalysisOptions};
+    use crate::extraction::{self, ExtractionMode};
+    use crate::severity::Severity;
+
+    const DESCRIPTION: &str = "Migrate the billing system to a new vendor before the contract ends in March.";
+
+    #[test]
+    fn request_body_lifts_the_system_prompt_out_of_the_messages() {
+        let options = AnalysisOptions { max_tokens: 1234, temperature: 0.2, ..AnalysisOptions::default() };
+        let body = request_body(&completion_request(None, &options, DESCRIPTION), DEFAULT_MODEL);
+
+        assert_eq!(body["model"], DEFAULT_MODEL);
+        assert_eq!(body["max_tokens"], 1234);
+        assert!((body["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+        assert!(body["system"].as_str().is_some_and(|system| system.contains("severity")));
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["role"], "user");
+        assert!(messages[0]["content"].as_str().unwrap().ends_with(DESCRIPTION));
+        assert!(body.get("tools").is_none());
+    }
+
+    #[test]
+    fn request_body_forces_the_report_tool() {
+        let options = AnalysisOptions { extraction: ExtractionMode::Tool, ..AnalysisOptions::default() };
+        let body = request_body(&completion_request(Some("claude-test"), &options, DESCRIPTION), "claude-test");
+
+        assert_eq!(body["model"], "claude-test");
+        assert_eq!(body["tools"][0]["name"], "report_risks");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["tool_choice"], json!({ "type": "tool", "name": "report_risks" }));
+    }
+
+    #[test]
+    fn parse_response_joins_text_blocks_into_risks() {
+        let resp = json!({
+            "content": [
+                { "type": "text", "text": "[{\"severity\": \"high\", \"category\": \"Schedule\", " },
+                { "type": "text", "text": "\"mitigation\": \"Negotiate an extension.\"}]" },
+            ],
+            "usage": { "input_tokens": 120, "output_tokens": 45 },
+        });
+        let completion = parse_response(&resp, DEFAULT_MODEL).unwrap();
+
+        assert_eq!(completion.model, DEFAULT_MODEL);
+        let usage = completion.usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (120, 45));
+
+        let (risks, _) = extraction::parse_risks(&completion.content, false).unwrap();
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].severity, Severity::High);
+        assert_eq!(risks[0].category, "Schedule");
+    }
+
+    #[test]
+    fn parse_response_prefers_the_tool_input() {
+        let resp = json!({
+            "content": [
+                { "type": "text", "text": "Here are the risks." },
+                { "type": "tool_use", "name": "report_risks", "input": { "risks": [
+                    { "severity": "critical", "category": "Vendor", "mitigation": "Keep the old system running." },
+                ] } },
+            ],
+            "usage": { "input_tokens": 10, "output_tokens": 20 },
+        });
+        let completion = parse_response(&resp, DEFAULT_MODEL).unwrap();
+
+        let (risks, _) = extraction::parse_risks(&completion.content, false).unwrap();
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].severity, Severity::Critical);
+        assert_eq!(risks[0].mitigation, "Keep the old system running.");
+    }
+
+    #[test]
+    fn parse_response_rejects_replies_without_text() {
+        assert!(parse_response(&json!({ "content": [] }), DEFAULT_MODEL).is_err());
+        assert!(parse_response(&json!({ "error": "overloaded" }), DEFAULT_MODEL).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use tracing::trace;
+
+use super::openai::{chat_request_body, chat_response_content};
+use super::{Completion, CompletionRequest, Provider, TokenUsage};
+use crate::config::{AzureConfig, HttpClientConfig};
+
+const DEFAULT_API_VERSION: &str = "2024-06-01";
+
+/// Azure-hosted OpenAI. Requests are routed by deployment rather than by model name, so the
+/// per-request `model` is treated as a deployment ID.
+pub struct AzureOpenAiProvider {
+    client: Client,
+    api_key: String,
+    resource: String,
+    deployment: String,
+    api_version: String,
+}
+
+impl AzureOpenAiProvider {
+    pub fn new(
+        client: Client,
+        api_key: impl Into<String>,
+        resource: impl Into<String>,
+        deployment: impl Into<String>,
+        api_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            api_key: api_key.into(),
+            resource: resource.into(),
+            deployment: deployment.into(),
+            api_version: api_version.into(),
+        }
+    }
+
+    /// `None` unless the API key, resource and deployment are all set.
+    pub fn from_config(config: &AzureConfig, http: &HttpClientConfig) -> Option<Self> {
+        let api_key = config.api_key.clone()?;
+        let resource = config.resource.clone()?;
+        let deployment = config.deployment.clone()?;
+        let api_version = config.api_version.as_deref().unwrap_or(DEFAULT_API_VERSION);
+        Some(Self::new(super::http::client(http), api_key, resource, deployment, api_version))
+    }
+
+    fn endpoint(&self, deployment: &str) -> String {
+        format!(
+            "https://{}.openai.azure.com/openai/deployments/{}/chat/completions",
+            self.resource, deployment
+        )
+    }
+}
+
+#[async_trait]
+impl Provider for AzureOpenAiProvider {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.deployment
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let deployment = request.model.as_deref().unwrap_or(&self.deployment);
+
+        let request_body = chat_request_body(None, request);
+
+        #[cfg(debug_assertions)]
+        trace!(body = ?request_body, "📤 Request body");
+
+        let resp = self
+            .client
+            .post(self.endpoint(deployment))
+            .query(&[("api-version", &self.api_version)])
+            .header("api-key", &self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+        let resp = super::http::check_status("Azure OpenAI", resp).await?;
+
+        let resp_json = resp.json::<serde_json::Value>().await?;
+
+        trace!(response = ?resp_json, "📥 Azure OpenAI raw response");
+
+        let content = chat_response_content(&resp_json)?;
+
+        let usage = TokenUsage::from_json(&resp_json["usage"], "prompt_tokens", "completion_tokens");
+
+        Ok(Completion { content, model: deployment.to_string(), usage })
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+use tracing::trace;
+
+use super::sigv4::{self, Credentials};
+use super::{Completion, CompletionRequest, Provider, TokenUsage};
+use crate::config::{BedrockConfig, HttpClientConfig};
+
+const DEFAULT_REGION: &str = "us-east-1";
+const DEFAULT_MODEL: &str = "anthropic.claude-3-haiku-20240307-v1:0";
+const SERVICE: &str = "bedrock";
+
+/// AWS Bedrock via the model-agnostic Converse API, so Claude and Titan models share one
+/// request/response mapping.
+pub struct BedrockProvider {
+    client: Client,
+    credentials: Credentials,
+    region: String,
+    model: String,
+}
+
+impl BedrockProvider {
+    pub fn new(client: Client, credentials: Credentials, region: impl Into<String>, model: impl Into<String>) -> Self {
+        Self { client, credentials, region: region.into(), model: model.into() }
+    }
+
+    /// `None` unless an access key ID and secret are configured; these, the session token and
+    /// the region usually come from the standard `AWS_*` environment variables.
+    pub fn from_config(config: &BedrockConfig, http: &HttpClientConfig) -> Option<Self> {
+        let credentials = Credentials {
+            access_key_id: config.access_key_id.clone()?,
+            secret_access_key: config.secret_access_key.clone()?,
+            session_token: config.session_token.clone(),
+        };
+        let region = config.region.as_deref().unwrap_or(DEFAULT_REGION);
+        let model = config.model_id.as_deref().unwrap_or(DEFAULT_MODEL);
+        Some(Self::new(super::http::client(http), credentials, region, model))
+    }
+
+    fn host(&self) -> String {
+        format!("bedrock-runtime.{}.amazonaws.com", self.region)
+    }
+}
+
+fn request_body(request: &CompletionRequest) -> Value {
+    let system: Vec<Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| serde_json::json!({ "text": m.content }))
+        .collect();
+
+    let messages: Vec<Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| serde_json::json!({ "role": m.role, "content": [{ "text": m.content }] }))
+        .collect();
+
+    serde_json::json!({
+        "system": system,
+        "messages": messages,
+        "inferenceConfig": {
+            "maxTokens": request.max_tokens,
+            "temperature": request.temperature,
+        },
+    })
+}
+
+fn response_content(resp_json: &Value) -> Result<String> {
+    let content: String = resp_json["output"]["message"]["content"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("No content in AI response"))?
+        .iter()
+        .filter_map(|block| block["text"].as_str())
+        .collect();
+
+    if content.is_empty() {
+        anyhow::bail!("No text content in AI response");
+    }
+
+    Ok(content)
+}
+
+#[async_trait]
+impl Provider for BedrockProvider {
+    fn name(&self) -> &'static str {
+        "bedrock"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let model = request.model.as_deref().unwrap_or(&self.model);
+
+        let request_body = request_body(request);
+
+        #[cfg(debug_assertions)]
+        trace!(body = ?request_body, "📤 Request body");
+
+        let body = serde_json::to_vec(&request_body)?;
+        let host = self.host();
+        let path = format!("/model/{}/converse", sigv4::uri_encode(model));
+        let content_type = "application/json";
+        let signed = sigv4::sign_post(&self.credentials, &self.region, SERVICE, &host, &path, content_type, &body);
+
+        let mut builder = self
+            .client
+            .post(format!("https://{}{}", host, path))
+            .header("content-type", content_type)
+            .header("x-amz-date", &signed.amz_date)
+            .header("authorization", &signed.authorization);
+        if let Some(token) = &signed.security_token {
+            builder = builder.header("x-amz-security-token", token);
+        }
+
+        let resp = builder.body(body).send().await?;
+        let resp = super::http::check_status("Bedrock", resp).await?;
+
+        let resp_json = resp.json::<Value>().await?;
+
+        trace!(response = ?resp_json, "📥 Bedrock raw response");
+
+        let content = response_content(&resp_json)?;
+
+        let usage = TokenUsage::from_json(&resp_json["usage"], "inputTokens", "outputTokens");
+
+        Ok(Completion { content, model: model.to_string(), usage })
+    }
+}
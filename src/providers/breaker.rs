@@ -0,0 +1,174 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::config::CircuitBreakerConfig;
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_OPEN_SECS: u64 = 30;
+
+/// When a provider's circuit opens: after `failure_threshold` failed calls in a row, for
+/// `open_for`. A threshold of 0 never opens it.
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerPolicy {
+    failure_threshold: u32,
+    open_for: Duration,
+}
+
+impl BreakerPolicy {
+    pub fn from_config(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            failure_threshold: config.failure_threshold.unwrap_or(DEFAULT_FAILURE_THRESHOLD),
+            open_for: Duration::from_secs(config.open_secs.unwrap_or(DEFAULT_OPEN_SECS)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// The open period is over; `probing` while the one call let through to test the provider
+    /// is still running.
+    HalfOpen { probing: bool },
+}
+
+impl Circuit {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed { .. } => "closed",
+            Self::Open { .. } => "open",
+            Self::HalfOpen { .. } => "half_open",
+        }
+    }
+}
+
+/// A provider skipped because its circuit is open.
+#[derive(Debug)]
+pub struct CircuitOpen {
+    pub provider: String,
+    /// Until the next probe may go through; zero while one is running.
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "provider '{}' is unavailable after repeated failures", self.provider)?;
+        if !self.retry_in.is_zero() {
+            write!(f, "; it will be tried again in {}s", self.retry_in.as_millis().div_ceil(1000))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+/// A circuit per provider name. They outlive configuration reloads, so reloading doesn't
+/// close the circuit of a provider that is still down.
+#[derive(Default)]
+pub struct CircuitBreakers {
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreakers {
+    /// Lets a call to `provider` go ahead unless its circuit is open. Once the open period is
+    /// over a single call goes through as a probe; the others keep failing fast until it
+    /// settles whether the circuit closes again.
+    pub fn admit(&self, provider: &str, policy: BreakerPolicy) -> Result<Admission<'_>, CircuitOpen> {
+        if policy.failure_threshold == 0 {
+            return Ok(Admission { breakers: self, provider: provider.to_string(), policy, probe: false, settled: false });
+        }
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(provider.to_string()).or_insert(Circuit::Closed { failures: 0 });
+        let probe = match *circuit {
+            Circuit::Closed { .. } => false,
+            Circuit::Open { until } if Instant::now() < until => {
+                return Err(CircuitOpen { provider: provider.to_string(), retry_in: until - Instant::now() });
+            }
+            Circuit::Open { .. } | Circuit::HalfOpen { probing: false } => {
+                info!(provider, "🩺 Probing provider with an open circuit");
+                *circuit = Circuit::HalfOpen { probing: true };
+                true
+            }
+            Circuit::HalfOpen { probing: true } => {
+                return Err(CircuitOpen { provider: provider.to_string(), retry_in: Duration::ZERO });
+            }
+        };
+        Ok(Admission { breakers: self, provider: provider.to_string(), policy, probe, settled: false })
+    }
+
+    fn record(&self, provider: &str, policy: BreakerPolicy, healthy: bool) {
+        if policy.failure_threshold == 0 {
+            return;
+        }
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(provider.to_string()).or_insert(Circuit::Closed { failures: 0 });
+        *circuit = match (*circuit, healthy) {
+            (Circuit::Closed { .. }, true) => Circuit::Closed { failures: 0 },
+            (_, true) => {
+                info!(provider, "✅ Provider recovered, circuit closed");
+                Circuit::Closed { failures: 0 }
+            }
+            (Circuit::Closed { failures }, false) if failures + 1 < policy.failure_threshold => {
+                Circuit::Closed { failures: failures + 1 }
+            }
+            (Circuit::Closed { .. } | Circuit::HalfOpen { .. }, false) => {
+                warn!(provider, open_for = ?policy.open_for, "🚧 Circuit opened after repeated provider failures");
+                Circuit::Open { until: Instant::now() + policy.open_for }
+            }
+            // A call let through before the circuit opened; the open period stands.
+            (open @ Circuit::Open { .. }, false) => open,
+        };
+    }
+
+    /// Each provider's circuit state as a gauge, for `/metrics`.
+    pub fn render(&self, body: &mut String) {
+        let circuits = self.circuits.lock().unwrap();
+        let mut providers: Vec<_> = circuits.iter().collect();
+        providers.sort_by_key(|(provider, _)| provider.as_str());
+        body.push_str("# HELP risk_evaluator_llm_circuit_state Provider circuit breakers, 1 for the current state.\n");
+        body.push_str("# TYPE risk_evaluator_llm_circuit_state gauge\n");
+        for (provider, circuit) in providers {
+            for state in ["closed", "open", "half_open"] {
+                let value = u8::from(circuit.as_str() == state);
+                let _ = writeln!(body, "risk_evaluator_llm_circuit_state{{provider=\"{}\",state=\"{}\"}} {}", provider, state, value);
+            }
+        }
+    }
+}
+
+/// A call let through by [`CircuitBreakers::admit`]. Its outcome is recorded with
+/// [`Admission::record`]; a probe dropped before that, e.g. because the client went away,
+/// lets the next call probe instead.
+pub struct Admission<'a> {
+    breakers: &'a CircuitBreakers,
+    provider: String,
+    policy: BreakerPolicy,
+    probe: bool,
+    settled: bool,
+}
+
+impl Admission<'_> {
+    /// `healthy` unless the provider failed in a way that says it's down: a timeout, a 429, a
+    /// 5xx or a dropped connection. A reply that doesn't parse still shows it's up.
+    pub fn record(mut self, healthy: bool) {
+        self.settled = true;
+        self.breakers.record(&self.provider, self.policy, healthy);
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        if self.probe && !self.settled {
+            let mut circuits = self.breakers.circuits.lock().unwrap();
+            if let Some(circuit @ Circuit::HalfOpen { .. }) = circuits.get_mut(&self.provider) {
+                *circuit = Circuit::HalfOpen { probing: false };
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+use tracing::trace;
+
+use super::{Completion, CompletionRequest, Provider, TokenUsage};
+use crate::config::{GeminiConfig, HttpClientConfig};
+
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const DEFAULT_MODEL: &str = "gemini-1.5-flash";
+
+/// Google's Generative Language API (`models/{model}:generateContent`).
+pub struct GeminiProvider {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl GeminiProvider {
+    pub fn new(client: Client, api_key: impl Into<String>, base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            api_key: api_key.into(),
+            base_url: base_url.into(),
+            model: model.into(),
+        }
+    }
+
+    /// `None` when no API key is configured.
+    pub fn from_config(config: &GeminiConfig, http: &HttpClientConfig) -> Option<Self> {
+        let api_key = config.api_key.clone()?;
+        let base_url = config.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        let model = config.model.as_deref().unwrap_or(DEFAULT_MODEL);
+        Some(Self::new(super::http::client(http), api_key, base_url, model))
+    }
+}
+
+/// Maps chat messages onto Gemini's `systemInstruction` + `contents` shape. Gemini calls the
+/// assistant role `model`.
+fn request_body(request: &CompletionRequest) -> Value {
+    let system: Vec<Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| serde_json::json!({ "text": m.content }))
+        .collect();
+
+    let contents: Vec<Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| {
+            let role = if m.role == "assistant" { "model" } else { "user" };
+            serde_json::json!({ "role": role, "parts": [{ "text": m.content }] })
+        })
+        .collect();
+
+    let mut body = serde_json::json!({
+        "contents": contents,
+        "generationConfig": {
+            "temperature": request.temperature,
+            "maxOutputTokens": request.max_tokens,
+        },
+    });
+    if !system.is_empty() {
+        body["systemInstruction"] = serde_json::json!({ "parts": system });
+    }
+    body
+}
+
+fn response_content(resp_json: &Value) -> Result<String> {
+    let content: String = resp_json["candidates"][0]["content"]["parts"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("No content in AI response"))?
+        .iter()
+        .filter_map(|part| part["text"].as_str())
+        .collect();
+
+    if content.is_empty() {
+        anyhow::bail!("No text content in AI response");
+    }
+
+    Ok(content)
+}
+
+#[async_trait]
+impl Provider for GeminiProvider {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    async fn check(&self) -> Result<()> {
+        self.client
+            .get(format!("{}/models", self.base_url.trim_end_matches('/')))
+            .header("x-goog-api-key", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let model = request.model.as_deref().unwrap_or(&self.model);
+
+        let request_body = request_body(request);
+
+        #[cfg(debug_assertions)]
+        trace!(body = ?request_body, "📤 Request body");
+
+        let resp = self
+            .client
+            .post(format!(
+                "{}/models/{}:generateContent",
+                self.base_url.trim_end_matches('/'),
+                model
+            ))
+            .header("x-goog-api-key", &self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+        let resp = super::http::check_status("Gemini", resp).await?;
+
+        let resp_json = resp.json::<Value>().await?;
+
+        trace!(response = ?resp_json, "📥 Gemini raw response");
+
+        let content = response_content(&resp_json)?;
+
+        let usage = TokenUsage::from_json(&resp_json["usageMetadata"], "promptTokenCount", "candidatesTokenCount");
+
+        Ok(Completion { content, model: model.to_string(), usage })
+    }
+}
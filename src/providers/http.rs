@@ -0,0 +1,100 @@
+use std::{fmt, time::Duration};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{self, HeaderValue},
+    Client, ClientBuilder, Response, StatusCode,
+};
+use tracing::warn;
+
+use crate::config::{HttpClientConfig, HttpVersion};
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 300;
+const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+const DEFAULT_TCP_KEEPALIVE_SECS: u64 = 60;
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// A client builder with the configured timeouts, pooling, HTTP version and user agent, for
+/// providers that add settings of their own.
+pub fn builder(config: &HttpClientConfig) -> ClientBuilder {
+    let keepalive = config.tcp_keepalive_secs.unwrap_or(DEFAULT_TCP_KEEPALIVE_SECS);
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS)))
+        .timeout(Duration::from_secs(config.request_timeout_secs.unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS)))
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs.unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT_SECS)))
+        .tcp_keepalive((keepalive > 0).then(|| Duration::from_secs(keepalive)));
+    builder = match config.user_agent.as_deref().map(HeaderValue::from_str) {
+        Some(Ok(user_agent)) => builder.user_agent(user_agent),
+        Some(Err(_)) => {
+            warn!("⚠️ Ignoring invalid HTTP_USER_AGENT {:?}", config.user_agent);
+            builder.user_agent(DEFAULT_USER_AGENT)
+        }
+        None => builder.user_agent(DEFAULT_USER_AGENT),
+    };
+    if let Some(max_idle) = config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    match config.version {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+    }
+}
+
+pub fn build(builder: ClientBuilder) -> Client {
+    builder.build().unwrap_or_else(|e| {
+        warn!("⚠️ Falling back to default HTTP client: {}", e);
+        Client::new()
+    })
+}
+
+pub fn client(config: &HttpClientConfig) -> Client {
+    build(builder(config))
+}
+
+/// Error bodies are quoted in the error; an HTML error page is cut short.
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+/// A provider answering with an error status rather than a completion. Kept as its own type so
+/// retries can tell a 429 or a 503 from a request the provider will never accept.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub provider: &'static str,
+    pub status: StatusCode,
+    /// The provider's `Retry-After`, as a delay from now.
+    pub retry_after: Option<Duration>,
+    body: String,
+}
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} returned {}: {}", self.provider, self.status, self.body)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// `response` if its status is a success, else an [`HttpStatusError`] with its body. Providers
+/// call this before reading a completion, so an error page isn't mistaken for malformed JSON.
+pub async fn check_status(provider: &'static str, response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let retry_after =
+        response.headers().get(header::RETRY_AFTER).and_then(|value| value.to_str().ok()).and_then(parse_retry_after);
+    let body: String = response.text().await.unwrap_or_default().trim().chars().take(MAX_ERROR_BODY_CHARS).collect();
+    Err(HttpStatusError { provider, status, retry_after, body }.into())
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date; a date in the past means now.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
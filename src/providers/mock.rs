@@ -0,0 +1,81 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use super::{Completion, CompletionRequest, Provider};
+
+const MODEL: &str = "mock-deterministic";
+const MAX_RISKS: usize = 3;
+
+const SEVERITIES: &[&str] = &["Low", "Medium", "High"];
+const CATEGORIES: &[(&str, &str)] = &[
+    ("Timeline", "Add buffer time and revalidate milestones."),
+    ("Dependencies", "Check for alternatives and establish SLAs."),
+    ("Scope", "Freeze scope for the current phase and route changes through review."),
+    ("Team", "Identify key-person dependencies and plan for cross-training."),
+    ("Budget", "Track spend against budget and agree on contingency reserves."),
+];
+
+/// Returns risks derived from a hash of each sentence of the description, so the same input
+/// always produces the same output without any network access or API key. Cost impacts are
+/// fractions of the budget when the prompt gives one.
+pub struct MockProvider;
+
+#[async_trait]
+impl Provider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn default_model(&self) -> &str {
+        MODEL
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let text = request
+            .messages
+            .iter()
+            .filter(|m| m.role == "user")
+            // Skip the instruction line the pipeline puts in front of the description.
+            .map(|m| m.content.split_once("\n\n").map_or(m.content.as_str(), |(_, description)| description))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let budget = requested_budget(request);
+
+        let risks: Vec<_> = text
+            .split(['.', '!', '?', '\n'])
+            .map(str::trim)
+            .filter(|sentence| !sentence.is_empty())
+            .take(MAX_RISKS)
+            .map(|sentence| {
+                let digest = Sha256::digest(sentence.as_bytes());
+                let (category, mitigation) = CATEGORIES[digest[0] as usize % CATEGORIES.len()];
+                let severity = SEVERITIES[digest[1] as usize % SEVERITIES.len()];
+                let mut risk = serde_json::json!({
+                    "severity": severity,
+                    "category": category,
+                    "mitigation": mitigation,
+                    "likelihood": digest[2] % 5 + 1,
+                    "impact": digest[3] % 5 + 1,
+                    "confidence": f32::from(digest[4]) / 255.0,
+                    "evidence": [sentence],
+                });
+                if let Some(budget) = budget {
+                    // 1–10% of the budget, give or take half.
+                    let likely = budget * (f64::from(digest[5] % 10) + 1.0) / 100.0;
+                    risk["cost_impact"] = serde_json::json!({ "min": likely / 2.0, "likely": likely, "max": likely * 1.5 });
+                }
+                risk
+            })
+            .collect();
+
+        Ok(Completion { content: serde_json::to_string(&risks)?, model: MODEL.to_string(), usage: None })
+    }
+}
+
+/// The budget the system prompt asks cost impacts to be estimated against, if it does.
+fn requested_budget(request: &CompletionRequest) -> Option<f64> {
+    let prompt = request.messages.iter().find(|m| m.role == "system")?;
+    let (_, rest) = prompt.content.split_once("a budget of ")?;
+    rest.split_whitespace().next()?.parse().ok()
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::config::ProvidersConfig;
+use crate::metrics::CallMetrics;
+
+mod anthropic;
+mod azure;
+mod bedrock;
+mod breaker;
+mod gemini;
+mod http;
+mod mock;
+mod ollama;
+mod openai;
+pub mod retry;
+mod rules;
+pub mod sigv4;
+
+pub use anthropic::AnthropicProvider;
+pub use azure::AzureOpenAiProvider;
+pub use bedrock::BedrockProvider;
+pub use breaker::{Admission, BreakerPolicy, CircuitBreakers, CircuitOpen};
+pub use gemini::GeminiProvider;
+pub use mock::MockProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+pub use retry::{RetryBudget, RetryPolicy};
+pub use rules::RulesProvider;
+
+/// A single message in a chat-completion conversation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: "system".to_string(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: "user".to_string(), content: content.into() }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self { role: "assistant".to_string(), content: content.into() }
+    }
+}
+
+/// Backend-agnostic chat-completion request built by the evaluation pipeline.
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub messages: Vec<ChatMessage>,
+    /// Overrides the provider's default model when set.
+    pub model: Option<String>,
+    pub max_tokens: u32,
+    pub temperature: f32,
+    pub output: OutputFormat,
+}
+
+/// How the reply should be shaped. Backends without support for a format fall back to plain
+/// text and rely on the prompt alone.
+#[derive(Debug, Clone, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// Reply content must conform to this JSON schema.
+    JsonSchema(Value),
+    /// Reply is a forced call to this tool; its JSON arguments become the completion content.
+    ToolCall(ToolSpec),
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the tool arguments.
+    pub parameters: Value,
+}
+
+/// What a provider hands back: the raw assistant text (or tool-call arguments), the model
+/// that produced it and, when the backend reports it, token usage.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub content: String,
+    pub model: String,
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+impl TokenUsage {
+    /// Reads a usage block whose counters live under backend-specific key names.
+    pub fn from_json(usage: &Value, prompt_key: &str, completion_key: &str) -> Option<Self> {
+        Some(Self {
+            prompt_tokens: usage.get(prompt_key)?.as_u64()? as u32,
+            completion_tokens: usage.get(completion_key)?.as_u64()? as u32,
+        })
+    }
+}
+
+impl std::ops::Add for TokenUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            prompt_tokens: self.prompt_tokens + other.prompt_tokens,
+            completion_tokens: self.completion_tokens + other.completion_tokens,
+        }
+    }
+}
+
+/// A chat-completion backend the risk evaluator can run against.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    /// Registry key, e.g. `"openai"` or `"anthropic"`.
+    fn name(&self) -> &'static str;
+
+    fn default_model(&self) -> &str;
+
+    /// Whether output from this backend should go through the lenient JSON extraction path.
+    fn relaxed_json(&self) -> bool {
+        false
+    }
+
+    /// Confirms the backend is reachable and accepts our credentials, without spending tokens.
+    /// Backends with no cheap way to tell are assumed fine.
+    async fn check(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion>;
+
+    /// Like [`Provider::complete`], but sends the reply content to `chunks` piece by piece as
+    /// it is generated. Backends that can't stream send it in one piece at the end.
+    async fn complete_streaming(&self, request: &CompletionRequest, chunks: UnboundedSender<String>) -> Result<Completion> {
+        let completion = self.complete(request).await?;
+        let _ = chunks.send(completion.content.clone());
+        Ok(completion)
+    }
+}
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Named set of configured providers plus the one used when a caller doesn't pick, and the
+/// ordered fallbacks tried when it fails. Everything but the call metrics is replaced as a whole
+/// when the configuration is reloaded; evaluations already running keep the provider they got.
+pub struct ProviderRegistry {
+    routing: RwLock<Routing>,
+    /// Latency and parse failures of individual calls, for `/metrics`.
+    pub calls: CallMetrics,
+    /// Kept across reloads, like the metrics.
+    pub breakers: CircuitBreakers,
+}
+
+struct Routing {
+    providers: HashMap<String, Arc<dyn Provider>>,
+    default: String,
+    fallbacks: Vec<String>,
+    timeout: Duration,
+    retry: RetryPolicy,
+    breaker: BreakerPolicy,
+}
+
+impl Routing {
+    /// The default provider is `openai` if unset (or `mock` when built with the `mock`
+    /// feature), the fallbacks are tried in order when it fails, and the timeout bounds each
+    /// attempt, retries included.
+    fn from_config(config: &ProvidersConfig) -> Self {
+        let fallback_default = if cfg!(feature = "mock") { "mock" } else { "openai" };
+        let mut routing = Self {
+            providers: HashMap::new(),
+            default: config.default.as_deref().unwrap_or(fallback_default).to_string(),
+            fallbacks: config.fallbacks.clone(),
+            timeout: Duration::from_secs(config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            retry: RetryPolicy::from_config(&config.retry),
+            breaker: BreakerPolicy::from_config(&config.circuit_breaker),
+        };
+
+        routing.register(Arc::new(RulesProvider));
+        if cfg!(feature = "mock") || routing.default == "mock" || config.mock {
+            routing.register(Arc::new(MockProvider));
+        }
+
+        if let Some(openai) = OpenAiProvider::from_config(&config.openai, &config.http) {
+            routing.register(Arc::new(openai));
+        }
+        if let Some(anthropic) = AnthropicProvider::from_config(&config.anthropic, &config.http) {
+            routing.register(Arc::new(anthropic));
+        }
+        if let Some(azure) = AzureOpenAiProvider::from_config(&config.azure, &config.http) {
+            routing.register(Arc::new(azure));
+        }
+        if let Some(bedrock) = BedrockProvider::from_config(&config.bedrock, &config.http) {
+            routing.register(Arc::new(bedrock));
+        }
+        if let Some(gemini) = GeminiProvider::from_config(&config.gemini, &config.http) {
+            routing.register(Arc::new(gemini));
+        }
+        if let Some(ollama) = OllamaProvider::from_config(&config.ollama, &config.http) {
+            routing.register(Arc::new(ollama));
+        }
+
+        routing
+    }
+
+    fn register(&mut self, provider: Arc<dyn Provider>) {
+        self.providers.insert(provider.name().to_string(), provider);
+    }
+
+    fn default_provider(&self) -> Result<Arc<dyn Provider>> {
+        self.providers
+            .get(&self.default)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("LLM provider '{}' is unknown or not configured", self.default))
+    }
+}
+
+impl ProviderRegistry {
+    /// Builds the registry from the `[providers]` configuration.
+    pub fn from_config(config: &ProvidersConfig) -> Self {
+        Self {
+            routing: RwLock::new(Routing::from_config(config)),
+            calls: CallMetrics::default(),
+            breakers: CircuitBreakers::default(),
+        }
+    }
+
+    /// Swaps in providers built from `config`, unless its default provider isn't usable, in
+    /// which case the current ones are kept.
+    pub fn reload(&self, config: &ProvidersConfig) -> Result<()> {
+        let routing = Routing::from_config(config);
+        routing.default_provider()?;
+        *self.routing.write().unwrap() = routing;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Provider>> {
+        self.routing.read().unwrap().providers.get(name).cloned()
+    }
+
+    /// Every registered provider, by name.
+    pub fn all(&self) -> Vec<Arc<dyn Provider>> {
+        let mut providers: Vec<_> = self.routing.read().unwrap().providers.values().cloned().collect();
+        providers.sort_by_key(|provider| provider.name());
+        providers
+    }
+
+    pub fn default_provider(&self) -> Result<Arc<dyn Provider>> {
+        self.routing.read().unwrap().default_provider()
+    }
+
+    /// `primary` (or the default provider) followed by the configured fallbacks, without
+    /// duplicates. Fallbacks that aren't registered are skipped with a warning.
+    pub fn chain(&self, primary: Option<&str>) -> Vec<Arc<dyn Provider>> {
+        let routing = self.routing.read().unwrap();
+        let primary = primary.unwrap_or(&routing.default);
+        std::iter::once(primary)
+            .chain(routing.fallbacks.iter().map(String::as_str).filter(|name| *name != primary))
+            .filter_map(|name| {
+                let provider = routing.providers.get(name).cloned();
+                if provider.is_none() {
+                    warn!("⚠️ Provider '{}' in fallback chain is not configured, skipping.", name);
+                }
+                provider
+            })
+            .collect()
+    }
+
+    /// Upper bound on a single provider attempt before moving on to the next one.
+    pub fn timeout(&self) -> Duration {
+        self.routing.read().unwrap().timeout
+    }
+
+    /// How calls failing with a 429, a 5xx or a dropped connection are retried.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.routing.read().unwrap().retry
+    }
+
+    /// Lets a call to `provider` through unless its circuit is open; see [`CircuitBreakers`].
+    pub fn admit(&self, provider: &dyn Provider) -> Result<Admission<'_>, CircuitOpen> {
+        let policy = self.routing.read().unwrap().breaker;
+        self.breakers
+            .admit(provider.name(), policy)
+            .inspect_err(|_| self.calls.short_circuited.inc(&[provider.name()]))
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use tracing::trace;
+
+use super::{ChatMessage, Completion, CompletionRequest, Provider, TokenUsage};
+use crate::config::{HttpClientConfig, OllamaConfig};
+
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "llama3.1";
+
+/// Extra instructions appended to the system prompt. Small local models tend to wrap the
+/// array in prose or invent field names unless they are shown the exact shape.
+const SMALL_MODEL_HINT: &str = "Respond with ONLY a JSON array and no other text. Example:\n\
+[{\"severity\": \"high\", \"category\": \"Timeline\", \"mitigation\": \"Add buffer time.\", \"likelihood\": 4, \"impact\": 3, \"confidence\": 0.8}]";
+
+/// Local models served by Ollama's `/api/chat`, for air-gapped deployments.
+pub struct OllamaProvider {
+    client: Client,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn new(client: Client, base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self { client, base_url: base_url.into(), model: model.into() }
+    }
+
+    /// Enabled when the base URL or the model is set, defaulting the other.
+    pub fn from_config(config: &OllamaConfig, http: &HttpClientConfig) -> Option<Self> {
+        if config.base_url.is_none() && config.model.is_none() {
+            return None;
+        }
+        Some(Self::new(
+            super::http::client(http),
+            config.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL),
+            config.model.as_deref().unwrap_or(DEFAULT_MODEL),
+        ))
+    }
+
+    fn adapt_messages(messages: &[ChatMessage]) -> Vec<ChatMessage> {
+        messages
+            .iter()
+            .map(|m| match m.role.as_str() {
+                "system" => ChatMessage::system(format!("{}\n\n{}", m.content, SMALL_MODEL_HINT)),
+                _ => m.clone(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    async fn check(&self) -> Result<()> {
+        self.client
+            .get(format!("{}/api/tags", self.base_url.trim_end_matches('/')))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn relaxed_json(&self) -> bool {
+        true
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let model = request.model.as_deref().unwrap_or(&self.model);
+
+        let request_body = serde_json::json!({
+            "model": model,
+            "messages": Self::adapt_messages(&request.messages),
+            "stream": false,
+            "options": {
+                "temperature": request.temperature,
+                "num_predict": request.max_tokens,
+            },
+        });
+
+        #[cfg(debug_assertions)]
+        trace!(body = ?request_body, "📤 Request body");
+
+        let resp = self
+            .client
+            .post(format!("{}/api/chat", self.base_url.trim_end_matches('/')))
+            .json(&request_body)
+            .send()
+            .await?;
+        let resp = super::http::check_status("Ollama", resp).await?;
+
+        let resp_json = resp.json::<serde_json::Value>().await?;
+
+        trace!(response = ?resp_json, "📥 Ollama raw response");
+
+        let content = resp_json["message"]["content"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("No content in AI response"))?;
+
+        let usage = TokenUsage::from_json(&resp_json, "prompt_eval_count", "eval_count");
+
+        Ok(Completion { content: content.to_string(), model: model.to_string(), usage })
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, trace, warn};
+
+use super::{Completion, CompletionRequest, OutputFormat, Provider, TokenUsage};
+use crate::config::{HttpClientConfig, OpenAiConfig};
+
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// OpenAI chat completions, or any OpenAI-compatible gateway (LiteLLM, vLLM, ...) via the
+/// base URL.
+pub struct OpenAiProvider {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(client: Client, api_key: impl Into<String>, base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self { client, api_key: api_key.into(), base_url: base_url.into(), model: model.into() }
+    }
+
+    /// `None` when no API key is configured. `base_url` points at a compatible gateway and `proxy` routes requests through an
+    /// HTTP(S) proxy (the standard `HTTPS_PROXY` variables are honored as well).
+    pub fn from_config(config: &OpenAiConfig, http: &HttpClientConfig) -> Option<Self> {
+        let api_key = match config.api_key.clone() {
+            Some(key) => key,
+            None => {
+                warn!("⚠️ No OpenAI API key (OPENAI_API_KEY) configured, OpenAI provider disabled.");
+                return None;
+            }
+        };
+
+        let base_url = config.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        let model = config.model.as_deref().unwrap_or(DEFAULT_MODEL);
+
+        let mut builder = super::http::builder(http);
+        if let Some(proxy_url) = &config.proxy {
+            match reqwest::Proxy::all(proxy_url) {
+                Ok(proxy) => builder = builder.proxy(proxy),
+                Err(e) => warn!("⚠️ Ignoring invalid OPENAI_PROXY '{}': {}", proxy_url, e),
+            }
+        }
+        Some(Self::new(super::http::build(builder), api_key, base_url, model))
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    async fn check(&self) -> Result<()> {
+        self.client
+            .get(format!("{}/models", self.base_url.trim_end_matches('/')))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let model = request.model.as_deref().unwrap_or(&self.model);
+
+        let request_body = chat_request_body(Some(model), request);
+
+        #[cfg(debug_assertions)]
+        trace!(body = ?request_body, "📤 Request body");
+
+        let resp = self
+            .client
+            .post(self.endpoint())
+            .bearer_auth(&self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+        let resp = super::http::check_status("OpenAI", resp).await?;
+
+        let resp_json = resp.json::<serde_json::Value>().await?;
+
+        trace!(response = ?resp_json, "📥 OpenAI raw response");
+
+        let content = chat_response_content(&resp_json)?;
+
+        let usage = TokenUsage::from_json(&resp_json["usage"], "prompt_tokens", "completion_tokens");
+
+        Ok(Completion { content, model: model.to_string(), usage })
+    }
+
+    /// Reads the server-sent events of a `"stream": true` request; usage arrives in the last
+    /// event thanks to `stream_options.include_usage`.
+    async fn complete_streaming(&self, request: &CompletionRequest, chunks: UnboundedSender<String>) -> Result<Completion> {
+        let model = request.model.as_deref().unwrap_or(&self.model);
+
+        let mut request_body = chat_request_body(Some(model), request);
+        request_body["stream"] = Value::Bool(true);
+        request_body["stream_options"] = serde_json::json!({ "include_usage": true });
+
+        let resp = self
+            .client
+            .post(self.endpoint())
+            .bearer_auth(&self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+        let mut resp = super::http::check_status("OpenAI", resp).await?;
+
+        let mut content = String::new();
+        let mut usage = None;
+        let mut pending: Vec<u8> = Vec::new();
+        while let Some(bytes) = resp.chunk().await? {
+            pending.extend_from_slice(&bytes);
+            while let Some(newline) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    continue;
+                }
+                let event: Value = serde_json::from_str(data)?;
+                if let Some(delta) = stream_delta(&event) {
+                    content.push_str(delta);
+                    let _ = chunks.send(delta.to_string());
+                }
+                if let Some(reported) = TokenUsage::from_json(&event["usage"], "prompt_tokens", "completion_tokens") {
+                    usage = Some(reported);
+                }
+            }
+        }
+
+        debug!(chars = content.len(), "📥 OpenAI streamed reply");
+        if content.is_empty() {
+            anyhow::bail!("No content in AI response");
+        }
+
+        Ok(Completion { content, model: model.to_string(), usage })
+    }
+}
+
+/// The text (or tool-call arguments) added by one streamed chunk.
+fn stream_delta(event: &Value) -> Option<&str> {
+    let delta = &event["choices"][0]["delta"];
+    delta["tool_calls"][0]["function"]["arguments"].as_str().or_else(|| delta["content"].as_str())
+}
+
+/// Chat-completions request body, shared with OpenAI-compatible backends such as Azure.
+/// `model` is omitted for backends that route by deployment instead.
+pub(super) fn chat_request_body(model: Option<&str>, request: &CompletionRequest) -> Value {
+    let mut body = serde_json::json!({
+        "messages": request.messages,
+        "max_tokens": request.max_tokens,
+        "temperature": request.temperature,
+    });
+    if let Some(model) = model {
+        body["model"] = Value::from(model);
+    }
+    match &request.output {
+        OutputFormat::Text => {}
+        OutputFormat::JsonSchema(schema) => {
+            body["response_format"] = serde_json::json!({
+                "type": "json_schema",
+                "json_schema": { "name": "risk_report", "strict": true, "schema": schema },
+            });
+        }
+        OutputFormat::ToolCall(tool) => {
+            body["tools"] = serde_json::json!([{
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                    "strict": true,
+                },
+            }]);
+            body["tool_choice"] = serde_json::json!({
+                "type": "function",
+                "function": { "name": tool.name },
+            });
+        }
+    }
+    body
+}
+
+/// Assistant text, or the arguments of the first tool call when the model answered with one.
+pub(super) fn chat_response_content(resp_json: &Value) -> Result<String> {
+    let message = &resp_json["choices"][0]["message"];
+    if let Some(arguments) = message["tool_calls"][0]["function"]["arguments"].as_str() {
+        return Ok(arguments.to_string());
+    }
+    message["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("No content in AI response"))
+}
@@ -0,0 +1,111 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rand::Rng;
+use reqwest::StatusCode;
+
+use super::http::HttpStatusError;
+use crate::config::RetryConfig;
+
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const DEFAULT_BASE_DELAY_MS: u64 = 500;
+const DEFAULT_MAX_DELAY_MS: u64 = 10_000;
+const DEFAULT_BUDGET: u32 = 4;
+
+/// How a provider call that failed for a reason that may pass is retried: up to `max_retries`
+/// times, after a backoff that doubles from `base_delay` up to `max_delay`, or after the
+/// provider's `Retry-After`.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    budget: u32,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &RetryConfig) -> Self {
+        Self {
+            max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            base_delay: Duration::from_millis(config.base_delay_ms.unwrap_or(DEFAULT_BASE_DELAY_MS)),
+            max_delay: Duration::from_millis(config.max_delay_ms.unwrap_or(DEFAULT_MAX_DELAY_MS)),
+            budget: config.budget.unwrap_or(DEFAULT_BUDGET),
+        }
+    }
+
+    /// A fresh budget for one evaluation.
+    pub fn budget(&self) -> RetryBudget {
+        RetryBudget::new(self.budget)
+    }
+
+    /// How long to wait before retry number `attempt`, counting from 1: the provider's
+    /// `Retry-After` when it sent one, else the capped exponential backoff with equal jitter,
+    /// so concurrent evaluations that failed together don't come back together. `None` when
+    /// the provider asked for longer than `max_delay`; it's better to move on to the next one.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let half = backoff / 2;
+        Some(half + half.mul_f64(rand::thread_rng().gen::<f64>()))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_config(&RetryConfig::default())
+    }
+}
+
+/// Retries an evaluation may still spend, shared by its chunks and the providers it falls back
+/// to, so a provider that's down can't multiply one request into dozens of calls.
+#[derive(Debug, Clone)]
+pub struct RetryBudget(Arc<AtomicU32>);
+
+impl RetryBudget {
+    pub fn new(retries: u32) -> Self {
+        Self(Arc::new(AtomicU32::new(retries)))
+    }
+
+    /// Spends one retry; false once they're all spent.
+    pub fn take(&self) -> bool {
+        self.0.fetch_update(Ordering::AcqRel, Ordering::Acquire, |left| left.checked_sub(1)).is_ok()
+    }
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUDGET)
+    }
+}
+
+/// A failure worth retrying.
+#[derive(Debug, Clone, Copy)]
+pub struct Transient {
+    /// `rate_limited`, `server_error` or `connection`, the `reason` label of the retry metric.
+    pub reason: &'static str,
+    pub retry_after: Option<Duration>,
+}
+
+/// Whether `error` may pass on its own: a 429, a 5xx, or a connection that couldn't be made or
+/// was reset. Timeouts aren't retried, since another attempt would likely wait as long.
+pub fn transient(error: &anyhow::Error) -> Option<Transient> {
+    if let Some(status_error) = error.downcast_ref::<HttpStatusError>() {
+        let reason = match status_error.status {
+            StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+            status if status.is_server_error() => "server_error",
+            _ => return None,
+        };
+        return Some(Transient { reason, retry_after: status_error.retry_after });
+    }
+    let http_error = error.downcast_ref::<reqwest::Error>()?;
+    let dropped = !http_error.is_timeout() && (http_error.is_connect() || http_error.is_request() || http_error.is_body());
+    dropped.then_some(Transient { reason: "connection", retry_after: None })
+}
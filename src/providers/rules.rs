@@ -0,0 +1,121 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{Completion, CompletionRequest, Provider};
+
+const MODEL: &str = "keyword-rules";
+
+struct Rule {
+    keywords: &'static [&'static str],
+    severity: &'static str,
+    category: &'static str,
+    mitigation: &'static str,
+    likelihood: u8,
+    impact: u8,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        keywords: &["deadline", "timeline", "schedule", "launch", "milestone", "weeks"],
+        severity: "High",
+        category: "Timeline",
+        mitigation: "Add buffer time and revalidate milestones.",
+        likelihood: 4,
+        impact: 3,
+    },
+    Rule {
+        keywords: &["vendor", "third-party", "third party", "integration", "dependency", "dependencies", "api"],
+        severity: "Medium",
+        category: "Dependencies",
+        mitigation: "Check for alternatives and establish SLAs.",
+        likelihood: 3,
+        impact: 3,
+    },
+    Rule {
+        keywords: &["budget", "cost", "funding", "spend"],
+        severity: "Medium",
+        category: "Budget",
+        mitigation: "Track spend against budget and agree on contingency reserves.",
+        likelihood: 3,
+        impact: 3,
+    },
+    Rule {
+        keywords: &["team", "hire", "hiring", "staff", "contractor", "headcount"],
+        severity: "Medium",
+        category: "Team",
+        mitigation: "Identify key-person dependencies and plan for cross-training.",
+        likelihood: 3,
+        impact: 3,
+    },
+    Rule {
+        keywords: &["security", "privacy", "personal data", "compliance", "auth", "gdpr"],
+        severity: "High",
+        category: "Security",
+        mitigation: "Schedule a security review and threat model before launch.",
+        likelihood: 2,
+        impact: 5,
+    },
+    Rule {
+        keywords: &["scope", "requirements", "feature", "features", "stakeholder"],
+        severity: "Medium",
+        category: "Scope",
+        mitigation: "Freeze scope for the current phase and route changes through review.",
+        likelihood: 4,
+        impact: 2,
+    },
+];
+
+/// Offline keyword heuristics, used as the last link of a fallback chain so that an outage of
+/// every remote provider still yields risks derived from the actual description.
+pub struct RulesProvider;
+
+#[async_trait]
+impl Provider for RulesProvider {
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+
+    fn default_model(&self) -> &str {
+        MODEL
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion> {
+        let text = request
+            .messages
+            .iter()
+            .filter(|m| m.role == "user")
+            .map(|m| m.content.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut risks: Vec<_> = RULES
+            .iter()
+            .filter_map(|rule| Some((rule, rule.keywords.iter().find(|k| text.contains(*k))?)))
+            .map(|(rule, keyword)| {
+                serde_json::json!({
+                    "severity": rule.severity,
+                    "category": rule.category,
+                    "mitigation": rule.mitigation,
+                    "likelihood": rule.likelihood,
+                    "impact": rule.impact,
+                    // Keyword hits are weak evidence.
+                    "confidence": 0.4,
+                    "evidence": [keyword],
+                })
+            })
+            .collect();
+
+        if risks.is_empty() {
+            risks.push(serde_json::json!({
+                "severity": "Low",
+                "category": "Scope",
+                "mitigation": "Clarify goals, owners and success criteria in the project description.",
+                "likelihood": 2,
+                "impact": 2,
+                "confidence": 0.2,
+            }));
+        }
+
+        Ok(Completion { content: serde_json::to_string(&risks)?, model: MODEL.to_string(), usage: None })
+    }
+}
@@ -0,0 +1,178 @@
+//! Minimal AWS Signature Version 4 signing for JSON POST requests, shared by Bedrock and
+//! Secrets Manager.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Headers to attach to the signed request, in addition to the signed `content-type`.
+pub struct SignedHeaders {
+    pub authorization: String,
+    pub amz_date: String,
+    pub security_token: Option<String>,
+}
+
+/// Signs a `POST host/path` request with a JSON `content_type` for `service` in `region`.
+/// `path` must already be URI-encoded exactly as it will be sent on the wire.
+pub fn sign_post(
+    credentials: &Credentials,
+    region: &str,
+    service: &str,
+    host: &str,
+    path: &str,
+    content_type: &str,
+    body: &[u8],
+) -> SignedHeaders {
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let (canonical_request, signed_headers) =
+        canonical_request(host, path, content_type, body, &amz_date, credentials.session_token.as_deref());
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let signature = signature(&credentials.secret_access_key, &scope, &amz_date, &canonical_request);
+
+    SignedHeaders {
+        authorization: format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        ),
+        amz_date,
+        security_token: credentials.session_token.clone(),
+    }
+}
+
+/// The canonical form of the request and its signed header names.
+fn canonical_request(
+    host: &str,
+    path: &str,
+    content_type: &str,
+    body: &[u8],
+    amz_date: &str,
+    session_token: Option<&str>,
+) -> (String, String) {
+    let mut headers = vec![("content-type", content_type), ("host", host), ("x-amz-date", amz_date)];
+    if let Some(token) = session_token {
+        headers.push(("x-amz-security-token", token));
+    }
+
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v.trim())).collect();
+    let signed_headers = headers.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(";");
+
+    // Non-S3 services sign the path with every segment encoded a second time.
+    let canonical_uri = path.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+
+    let canonical_request = format!(
+        "POST\n{}\n\n{}\n{}\n{}",
+        canonical_uri,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body))
+    );
+    (canonical_request, signed_headers)
+}
+
+/// Hex signature of `canonical_request` for the credential `scope` (`date/region/service/aws4_request`).
+fn signature(secret_access_key: &str, scope: &str, amz_date: &str, canonical_request: &str) -> String {
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = format!("AWS4{}", secret_access_key).into_bytes();
+    for part in scope.split('/') {
+        key = hmac(&key, part.as_bytes());
+    }
+    hex::encode(hmac(&key, string_to_sign.as_bytes()))
+}
+
+/// RFC 3986 percent-encoding, leaving only unreserved characters as-is.
+pub fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `post-x-www-form-urlencoded` from the AWS Signature Version 4 test suite.
+    const SECRET_ACCESS_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+    const SCOPE: &str = "20150830/us-east-1/service/aws4_request";
+    const AMZ_DATE: &str = "20150830T123600Z";
+
+    fn example() -> (String, String) {
+        canonical_request(
+            "example.amazonaws.com",
+            "/",
+            "application/x-www-form-urlencoded",
+            b"Param1=value1",
+            AMZ_DATE,
+            None,
+        )
+    }
+
+    #[test]
+    fn canonical_request_matches_the_published_example() {
+        let (canonical_request, signed_headers) = example();
+        assert_eq!(
+            canonical_request,
+            "POST\n/\n\n\
+             content-type:application/x-www-form-urlencoded\n\
+             host:example.amazonaws.com\n\
+             x-amz-date:20150830T123600Z\n\n\
+             content-type;host;x-amz-date\n\
+             9095672bbd1f56dfc5b65f3e153adc8731a4a654192329106275f4c7b24d0b6e"
+        );
+        assert_eq!(signed_headers, "content-type;host;x-amz-date");
+        assert_eq!(
+            hex::encode(Sha256::digest(canonical_request.as_bytes())),
+            "42a5e5bb34198acb3e84da4f085bb7927f2bc277ca766e6d19c73c2154021281"
+        );
+    }
+
+    #[test]
+    fn signature_matches_the_published_example() {
+        let (canonical_request, _) = example();
+        assert_eq!(
+            signature(SECRET_ACCESS_KEY, SCOPE, AMZ_DATE, &canonical_request),
+            "ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+        );
+    }
+
+    #[test]
+    fn session_token_is_signed() {
+        let (canonical_request, signed_headers) = canonical_request(
+            "bedrock-runtime.us-east-1.amazonaws.com",
+            "/model/a%3Ab/converse",
+            "application/json",
+            b"{}",
+            AMZ_DATE,
+            Some("token"),
+        );
+        assert_eq!(signed_headers, "content-type;host;x-amz-date;x-amz-security-token");
+        assert!(canonical_request.starts_with("POST\n/model/a%253Ab/converse\n"));
+        assert!(canonical_request.contains("x-amz-security-token:token\n"));
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::{info, warn};
+
+use crate::auth::Identity;
+use crate::config::LimitsConfig;
+use crate::shared::SharedStore;
+use crate::AppState;
+
+/// Idle buckets are swept once the map grows past this many entries.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets keyed by caller: each holds up to `burst` requests and refills at `per_minute`.
+/// The limit can be changed, or lifted, while running. With Redis the buckets are shared by
+/// all replicas, so the limit is the deployment's rather than each instance's.
+pub struct RateLimiter {
+    /// What the limit applies to in logs: `key` or `IP`.
+    scope: &'static str,
+    state: Mutex<LimiterState>,
+    shared: Option<SharedStore>,
+    /// Requests turned away so far, for `/metrics`.
+    pub rejected: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limit {
+    burst: f64,
+    per_sec: f64,
+}
+
+struct LimiterState {
+    /// `None` lets everything through.
+    limit: Option<Limit>,
+    buckets: HashMap<String, Bucket>,
+}
+
+impl RateLimiter {
+    fn new(scope: &'static str, shared: Option<SharedStore>) -> Self {
+        Self {
+            scope,
+            state: Mutex::new(LimiterState { limit: None, buckets: HashMap::new() }),
+            shared,
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// `rate_limit_per_minute` requests per authenticated key (unset or 0 disables the limit),
+    /// with bursts of up to `rate_limit_burst` (defaults to the per-minute figure).
+    pub fn per_key(limits: &LimitsConfig, shared: Option<SharedStore>) -> Self {
+        let limiter = Self::new("key", shared);
+        limiter.configure(limits.rate_limit_per_minute, limits.rate_limit_burst);
+        limiter
+    }
+
+    /// Changes the limit; `per_minute` of `None` or 0 lifts it. Callers keep the tokens they
+    /// have, up to the new burst.
+    pub fn configure(&self, per_minute: Option<u32>, burst: Option<u32>) {
+        let limit = per_minute.filter(|&n| n > 0).map(|per_minute| (per_minute, burst.unwrap_or(per_minute)));
+        let mut state = self.state.lock().unwrap();
+        let previous = state.limit;
+        state.limit = limit.map(|(per_minute, burst)| Limit { burst: burst.max(1) as f64, per_sec: per_minute as f64 / 60.0 });
+        if state.limit == previous {
+            return;
+        }
+        match limit {
+            Some((per_minute, burst)) => {
+                info!("🚦 Rate limiting to {} requests/minute per {} (burst {})", per_minute, self.scope, burst)
+            }
+            None => {
+                state.buckets.clear();
+                info!("🚦 No rate limit per {}", self.scope);
+            }
+        }
+    }
+
+    /// Takes one token from `key`'s bucket, or returns how long until one is available. When
+    /// Redis can't be reached this instance's own bucket is used meanwhile.
+    pub async fn check(&self, key: &str) -> Result<(), Duration> {
+        if let Some(shared) = &self.shared {
+            let Some(limit) = self.state.lock().unwrap().limit else {
+                return Ok(());
+            };
+            match shared.take_token(&format!("ratelimit:{}:{}", self.scope, key), limit.burst, limit.per_sec).await {
+                Ok(Ok(())) => return Ok(()),
+                Ok(Err(retry_after)) => {
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(retry_after);
+                }
+                Err(e) => warn!("⚠️ Failed to check the shared rate limit, using this instance's: {:?}", e),
+            }
+        }
+        self.check_local(key)
+    }
+
+    fn check_local(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let Some(limit) = state.limit else {
+            return Ok(());
+        };
+        if state.buckets.len() > SWEEP_THRESHOLD {
+            sweep(&mut state.buckets, limit, now);
+        }
+
+        let bucket = state.buckets.entry(key.to_string()).or_insert(Bucket { tokens: limit.burst, updated: now });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * limit.per_sec).min(limit.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / limit.per_sec))
+        }
+    }
+}
+
+/// Drops buckets that would be full by now; recreating them later is equivalent.
+fn sweep(buckets: &mut HashMap<String, Bucket>, limit: Limit, now: Instant) {
+    buckets.retain(|_, bucket| bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * limit.per_sec < limit.burst);
+}
+
+/// 429 with `Retry-After` in whole seconds, rounded up.
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, secs.to_string())],
+        "Rate limit exceeded",
+    )
+        .into_response()
+}
+
+/// Applies the per-key limit to authenticated requests; must run inside the auth middleware.
+/// Nothing is limited here when authentication is disabled.
+pub async fn limit_per_key(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let identity = request.extensions().get::<Identity>().filter(|_| !state.auth.disabled);
+    if let Some(identity) = identity {
+        if let Err(retry_after) = state.key_limiter.check(&identity.key_id).await {
+            info!("🚦 Rate limited '{}'", identity.name);
+            return too_many_requests(retry_after);
+        }
+    }
+    next.run(request).await
+}
+
+/// An address or CIDR block from `IP_ALLOWLIST` / `IP_DENYLIST` or `URL_FETCH_ALLOWED_NETS`.
+#[derive(Debug, Clone, Copy)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    fn parse(s: &str) -> Option<Self> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr.parse().ok()?, Some(prefix.parse().ok()?)),
+            None => (s.parse().ok()?, None),
+        };
+        let max = if matches!(addr, IpAddr::V4(_)) { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max);
+        (prefix <= max).then_some(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        fn masked(bits: u128, prefix: u8, width: u8) -> u128 {
+            if prefix == 0 { 0 } else { bits >> (width - prefix) }
+        }
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                masked(u32::from(net) as u128, self.prefix, 32) == masked(u32::from(ip) as u128, self.prefix, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                masked(u128::from(net), self.prefix, 128) == masked(u128::from(ip), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+pub fn ip_list(name: &str, entries: &[String]) -> Vec<IpNet> {
+    entries
+        .iter()
+        .filter_map(|s| {
+            let net = IpNet::parse(s);
+            if net.is_none() {
+                warn!("⚠️ Ignoring invalid {} entry '{}'", name, s);
+            }
+            net
+        })
+        .collect()
+}
+
+/// Client-address rules for deployments that can't rely on authentication: denied addresses
+/// are refused outright, allowed ones skip the limit, everyone else shares a bucket per IP.
+pub struct IpPolicy {
+    limiter: RateLimiter,
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+    /// Take the client address from `X-Forwarded-For` when running behind a proxy.
+    trust_forwarded: bool,
+    /// Proxies whose `X-Forwarded-For` entries are believed; only the connecting peer when empty.
+    trusted_proxies: Vec<IpNet>,
+    pub denied: AtomicU64,
+}
+
+impl IpPolicy {
+    /// Uses `ip_rate_limit_per_minute` and `ip_rate_limit_burst`, `ip_allowlist` and
+    /// `ip_denylist` (addresses or CIDR blocks), and `trust_forwarded_for` with `trusted_proxies`.
+    pub fn from_config(limits: &LimitsConfig, shared: Option<SharedStore>) -> Self {
+        let limiter = RateLimiter::new("IP", shared);
+        limiter.configure(limits.ip_rate_limit_per_minute, limits.ip_rate_limit_burst);
+        Self {
+            limiter,
+            allow: ip_list("IP_ALLOWLIST", &limits.ip_allowlist),
+            deny: ip_list("IP_DENYLIST", &limits.ip_denylist),
+            trust_forwarded: limits.trust_forwarded_for,
+            trusted_proxies: ip_list("TRUSTED_PROXIES", &limits.trusted_proxies),
+            denied: AtomicU64::new(0),
+        }
+    }
+
+    /// Applies changed `ip_rate_limit_per_minute` and `ip_rate_limit_burst` settings.
+    pub fn reconfigure(&self, limits: &LimitsConfig) {
+        self.limiter.configure(limits.ip_rate_limit_per_minute, limits.ip_rate_limit_burst);
+    }
+
+    pub fn rate_limited(&self) -> u64 {
+        self.limiter.rejected.load(Ordering::Relaxed)
+    }
+
+    /// The peer, or with `trust_forwarded_for` the rightmost `X-Forwarded-For` entry that
+    /// wasn't added by a trusted proxy. Entries further left are whatever the client sent, so
+    /// they're never believed.
+    fn client_ip(&self, headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+        if !self.trust_forwarded {
+            return peer;
+        }
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+        let mut client = peer;
+        for (hop, entry) in forwarded.iter().rev().enumerate() {
+            if !self.trusted_proxy(hop, client) {
+                break;
+            }
+            match entry.trim().parse() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client
+    }
+
+    /// Whether `ip`, `hop` entries from the right of the chain (the peer being 0), is a proxy
+    /// whose entry to its left is believed. Without `trusted_proxies`, only the peer is.
+    fn trusted_proxy(&self, hop: usize, ip: IpAddr) -> bool {
+        if self.trusted_proxies.is_empty() {
+            return hop == 0;
+        }
+        self.trusted_proxies.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Enforces the [`IpPolicy`] on every request, authenticated or not.
+pub async fn limit_per_ip(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let policy = &state.ip_policy;
+    let ip = policy.client_ip(request.headers(), peer.ip());
+
+    if policy.deny.iter().any(|net| net.contains(ip)) {
+        policy.denied.fetch_add(1, Ordering::Relaxed);
+        info!("🚫 Refused request from denied address {}", ip);
+        return (StatusCode::FORBIDDEN, "Forbidden").into_response();
+    }
+    if !policy.allow.iter().any(|net| net.contains(ip)) {
+        if let Err(retry_after) = policy.limiter.check(&ip.to_string()).await {
+            info!("🚦 Rate limited {}", ip);
+            return too_many_requests(retry_after);
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(trust_forwarded: bool, trusted_proxies: &[&str]) -> IpPolicy {
+        IpPolicy {
+            limiter: RateLimiter::new("IP", None),
+            allow: Vec::new(),
+            deny: Vec::new(),
+            trust_forwarded,
+            trusted_proxies: trusted_proxies.iter().filter_map(|net| IpNet::parse(net)).collect(),
+            denied: AtomicU64::new(0),
+        }
+    }
+
+    /// `trust_forwarded_for`, `trusted_proxies`, the peer, the header lines, the client.
+    type Case<'a> = (bool, &'a [&'a str], &'a str, &'a [&'a str], &'a str);
+
+    fn headers(lines: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for line in lines {
+            headers.append("x-forwarded-for", line.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn client_ip_walks_x_forwarded_for_from_the_right() {
+        let proxies = &["10.0.0.0/8", "192.168.1.1"];
+        let cases: &[Case] = &[
+            // Off: the header is ignored.
+            (false, &[], "10.0.0.1", &["203.0.113.7"], "10.0.0.1"),
+            (false, proxies, "10.0.0.1", &["203.0.113.7"], "10.0.0.1"),
+            // No header: the peer.
+            (true, &[], "10.0.0.1", &[], "10.0.0.1"),
+            // Only the peer is trusted: a spoofed leftmost entry loses to the one it appended.
+            (true, &[], "10.0.0.1", &["1.1.1.1, 203.0.113.7"], "203.0.113.7"),
+            (true, &[], "10.0.0.1", &["203.0.113.7"], "203.0.113.7"),
+            // An entry naming the peer itself doesn't extend the trust.
+            (true, &[], "10.0.0.1", &["1.1.1.1, 10.0.0.1"], "10.0.0.1"),
+            // A chain of trusted proxies is walked to the first address outside them.
+            (true, proxies, "10.0.0.1", &["1.1.1.1, 203.0.113.7, 192.168.1.1, 10.2.3.4"], "203.0.113.7"),
+            (true, proxies, "10.0.0.1", &["10.9.9.9, 10.2.3.4"], "10.9.9.9"),
+            // An untrusted peer's header isn't believed at all.
+            (true, proxies, "198.51.100.9", &["203.0.113.7"], "198.51.100.9"),
+            // Garbage or empty entries stop the walk at the last trusted hop.
+            (true, proxies, "10.0.0.1", &["203.0.113.7, garbage"], "10.0.0.1"),
+            (true, proxies, "10.0.0.1", &["203.0.113.7, , 10.2.3.4"], "10.2.3.4"),
+            (true, proxies, "10.0.0.1", &[""], "10.0.0.1"),
+            (true, &[], "10.0.0.1", &["203.0.113.7:4711"], "10.0.0.1"),
+            // Header lines are one list, the last line rightmost.
+            (true, proxies, "10.0.0.1", &["1.1.1.1, 203.0.113.7", "10.2.3.4"], "203.0.113.7"),
+            (true, &[], "10.0.0.1", &["1.1.1.1", "203.0.113.7"], "203.0.113.7"),
+            (true, &[], "10.0.0.1", &["2001:db8::1"], "2001:db8::1"),
+        ];
+        for (trust_forwarded, trusted, peer, lines, expected) in cases {
+            let ip = policy(*trust_forwarded, trusted).client_ip(&headers(lines), peer.parse().unwrap());
+            assert_eq!(ip, expected.parse::<IpAddr>().unwrap(), "peer {} with {:?}", peer, lines);
+        }
+    }
+}
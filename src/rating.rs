@@ -0,0 +1,134 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::openapi::{schema::Type, ObjectBuilder, RefOr, Schema};
+use utoipa::{PartialSchema, ToSchema};
+
+/// A 1–5 rating used for both likelihood (1 = rare … 5 = almost certain) and impact
+/// (1 = negligible … 5 = severe), so risks can be placed on a 5×5 probability×impact matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Rating(u8);
+
+impl Rating {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 5;
+
+    pub fn new(value: u8) -> Option<Self> {
+        (Self::MIN..=Self::MAX).contains(&value).then_some(Self(value))
+    }
+
+    pub fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Maps the words models use for likelihood or impact onto the scale.
+    fn from_label(label: &str) -> Option<Self> {
+        let value = match label.trim().to_ascii_lowercase().as_str() {
+            "rare" | "very unlikely" | "negligible" | "insignificant" | "very low" => 1,
+            "unlikely" | "minor" | "low" => 2,
+            "possible" | "moderate" | "medium" => 3,
+            "likely" | "major" | "high" => 4,
+            "almost certain" | "very likely" | "certain" | "severe" | "catastrophic" | "very high" => 5,
+            other => return other.parse().ok().and_then(Self::new),
+        };
+        Some(Self(value))
+    }
+}
+
+impl<'de> Deserialize<'de> for Rating {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f64),
+            Text(String),
+        }
+
+        let rating = match Raw::deserialize(deserializer)? {
+            Raw::Number(n) => Self::new(n.round().clamp(0.0, u8::MAX as f64) as u8),
+            Raw::Text(s) => Self::from_label(&s),
+        };
+        rating.ok_or_else(|| serde::de::Error::custom("rating must be between 1 and 5"))
+    }
+}
+
+/// Documented as the integer it serializes to; labels like "likely" are only accepted from
+/// models, not advertised.
+impl PartialSchema for Rating {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::Integer)
+            .minimum(Some(Self::MIN))
+            .maximum(Some(Self::MAX))
+            .into()
+    }
+}
+
+impl ToSchema for Rating {}
+
+/// Confidence assumed when a model omits it: neither trusted nor discarded by default.
+pub fn default_confidence() -> f32 {
+    0.5
+}
+
+/// Accepts a 0–1 fraction, a 0–100 percentage, or either as a string ("80%"), clamped to 0–1.
+/// NaN and infinities, which a string can spell, are refused.
+pub fn deserialize_confidence<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f32),
+        Text(String),
+    }
+
+    let value = match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => n,
+        Raw::Text(s) => s
+            .trim()
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid confidence '{}'", s)))?,
+    };
+    if !value.is_finite() {
+        return Err(serde::de::Error::custom(format!("invalid confidence '{}'", value)));
+    }
+    let value = if value > 1.0 { value / 100.0 } else { value };
+    Ok(value.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::value::{Error, F32Deserializer, StrDeserializer};
+    use serde::de::IntoDeserializer;
+
+    use super::*;
+
+    fn from_str(s: &str) -> Result<f32, Error> {
+        let deserializer: StrDeserializer<Error> = s.into_deserializer();
+        deserialize_confidence(deserializer)
+    }
+
+    fn from_f32(n: f32) -> Result<f32, Error> {
+        let deserializer: F32Deserializer<Error> = n.into_deserializer();
+        deserialize_confidence(deserializer)
+    }
+
+    #[test]
+    fn confidence_accepts_fractions_and_percentages() {
+        assert_eq!(from_f32(0.8).unwrap(), 0.8);
+        assert_eq!(from_f32(80.0).unwrap(), 0.8);
+        assert_eq!(from_f32(-3.0).unwrap(), 0.0);
+        assert_eq!(from_f32(250.0).unwrap(), 1.0);
+        assert_eq!(from_str("80%").unwrap(), 0.8);
+        assert_eq!(from_str(" 0.25 ").unwrap(), 0.25);
+    }
+
+    #[test]
+    fn confidence_refuses_non_finite_values() {
+        for s in ["NaN", "nan", "inf", "-infinity", "NaN%"] {
+            assert!(from_str(s).is_err(), "{}", s);
+        }
+        assert!(from_f32(f32::NAN).is_err());
+        assert!(from_f32(f32::INFINITY).is_err());
+        assert!(from_str("high").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::diff;
+use crate::severity::Severity;
+use crate::RiskItem;
+
+const MAX_ENTRIES: usize = 1000;
+
+/// Minimum share of the shorter word set found in the other for a risk to count as covered by
+/// an entry in the same category.
+const SAME_CATEGORY_THRESHOLD: f32 = 0.3;
+/// Entries in another (or no) category need closer wording.
+const CROSS_CATEGORY_THRESHOLD: f32 = 0.5;
+
+/// A risk the organization already tracks in its own register.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterEntry {
+    /// The register's own reference, e.g. `"R-042"`, if it has one.
+    pub id: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub severity: Option<Severity>,
+    pub mitigation: Option<String>,
+}
+
+impl RegisterEntry {
+    /// How a covered risk refers back to this entry.
+    fn reference(&self) -> String {
+        self.id.clone().unwrap_or_else(|| self.title.clone())
+    }
+
+    fn words(&self) -> HashSet<String> {
+        diff::words(
+            [Some(self.title.as_str()), self.description.as_deref(), self.mitigation.as_deref()]
+                .into_iter()
+                .flatten(),
+        )
+    }
+}
+
+/// Column names accepted for each field, compared case-insensitively. Registers exported from
+/// spreadsheets rarely agree on headers.
+const ID_COLUMNS: &[&str] = &["id", "ref", "reference", "risk id"];
+const TITLE_COLUMNS: &[&str] = &["title", "risk", "name", "risk title", "summary"];
+const DESCRIPTION_COLUMNS: &[&str] = &["description", "details", "risk description"];
+const CATEGORY_COLUMNS: &[&str] = &["category", "type", "risk category"];
+const SEVERITY_COLUMNS: &[&str] = &["severity", "rating", "level", "risk level"];
+const MITIGATION_COLUMNS: &[&str] = &["mitigation", "response", "treatment", "mitigation plan"];
+
+/// Parses a register CSV with a header row. Rows need a title or a description; an unknown
+/// severity is dropped rather than rejecting the file.
+pub fn parse_csv(data: &str) -> Result<Vec<RegisterEntry>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(data.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_lowercase()).collect();
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+
+    let (title, description) = (column(TITLE_COLUMNS), column(DESCRIPTION_COLUMNS));
+    if title.is_none() && description.is_none() {
+        anyhow::bail!("Register needs a title or description column");
+    }
+    let (id, category, severity, mitigation) =
+        (column(ID_COLUMNS), column(CATEGORY_COLUMNS), column(SEVERITY_COLUMNS), column(MITIGATION_COLUMNS));
+
+    let mut entries = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("Invalid register row {}", i + 2))?;
+        let field = |index: Option<usize>| {
+            index.and_then(|index| record.get(index)).filter(|value| !value.is_empty()).map(str::to_string)
+        };
+
+        let description = field(description);
+        let Some(title) = field(title).or_else(|| description.clone()) else { continue };
+        entries.push(RegisterEntry {
+            id: field(id),
+            title,
+            description,
+            category: field(category),
+            severity: field(severity).and_then(|s| s.parse().ok()),
+            mitigation: field(mitigation),
+        });
+        if entries.len() > MAX_ENTRIES {
+            anyhow::bail!("Register has more than {} entries", MAX_ENTRIES);
+        }
+    }
+    Ok(entries)
+}
+
+/// Sets `covered_by` on each risk that matches a register entry and clears it on the rest, so
+/// the unmarked risks are the ones the register doesn't track yet.
+pub fn mark_covered(risks: &mut [RiskItem], entries: &[RegisterEntry]) {
+    let entry_words: Vec<_> = entries.iter().map(RegisterEntry::words).collect();
+
+    for risk in risks {
+        let mut words = diff::risk_words(risk);
+        words.extend(diff::words([risk.category.as_str()]));
+
+        risk.covered_by = entries
+            .iter()
+            .zip(&entry_words)
+            .filter_map(|(entry, entry_words)| {
+                let similarity = overlap(&words, entry_words);
+                let same_category =
+                    entry.category.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(&risk.category));
+                let threshold = if same_category { SAME_CATEGORY_THRESHOLD } else { CROSS_CATEGORY_THRESHOLD };
+                (similarity >= threshold).then_some((entry, similarity))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entry, _)| entry.reference());
+    }
+}
+
+/// Overlap coefficient: a short register title fully contained in a longer risk still matches.
+fn overlap(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let smaller = a.len().min(b.len());
+    if smaller == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / smaller as f32
+}
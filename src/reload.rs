@@ -0,0 +1,113 @@
+use std::{
+    collections::HashSet,
+    fs,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use crate::analysis::AnalysisOptions;
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::secrets::SecretStore;
+use crate::AppState;
+
+const DEFAULT_WATCH_SECS: u64 = 5;
+
+/// Re-reads the configuration the way it was read at startup — same files, profile and
+/// command-line overrides, current environment, last fetched secrets — and applies the settings that can change
+/// while running: the system prompt and other analysis settings including the taxonomy, the
+/// providers with their models, the allowed models and the rate limits. Everything else needs
+/// a restart. Evaluations and jobs already running finish with the settings they started with.
+pub struct Reloader {
+    cli: Cli,
+    secrets: Option<Arc<SecretStore>>,
+    /// The config files' modification times as of the last reload, `None` for missing ones.
+    /// Held while reloading, so reloads don't interleave.
+    seen: Mutex<Vec<(String, Option<SystemTime>)>>,
+    interval: Option<Duration>,
+}
+
+impl Reloader {
+    pub fn new(cli: Cli, config: &Config, secrets: Option<Arc<SecretStore>>) -> Self {
+        let secs = config.server.config_watch_secs.unwrap_or(DEFAULT_WATCH_SECS);
+        Self {
+            cli,
+            secrets,
+            seen: Mutex::new(modification_times(&config.sources)),
+            interval: (secs > 0).then(|| Duration::from_secs(secs)),
+        }
+    }
+
+    /// Reloads and applies the configuration, keeping the current one if the new one doesn't
+    /// load or its default provider isn't usable. Returns the new configuration.
+    pub async fn reload(&self, state: &AppState) -> Result<Config> {
+        let mut seen = self.seen.lock().await;
+        let loaded = self.load_and_apply(state);
+        // Remembered even when the reload fails, so a broken file is reported once rather than
+        // on every check.
+        if let Ok(config) = &loaded {
+            *seen = modification_times(&config.sources);
+        } else {
+            let paths: Vec<String> = seen.iter().map(|(path, _)| path.clone()).collect();
+            *seen = modification_times(&paths);
+        }
+        loaded
+    }
+
+    fn load_and_apply(&self, state: &AppState) -> Result<Config> {
+        let mut config = Config::load(self.cli.config.as_deref(), self.cli.profile)?;
+        self.cli.apply(&mut config);
+        if let Some(secrets) = &self.secrets {
+            secrets.apply(&mut config.providers);
+        }
+
+        state.providers.reload(&config.providers)?;
+        let allowed_models: HashSet<String> = config.providers.allowed_models.iter().cloned().collect();
+        *state.allowed_models.write().unwrap() = allowed_models;
+        *state.analysis.write().unwrap() = AnalysisOptions::from_config(&config.analysis);
+        state.key_limiter.configure(config.limits.rate_limit_per_minute, config.limits.rate_limit_burst);
+        state.ip_policy.reconfigure(&config.limits);
+
+        let chain: Vec<_> = state.providers.chain(None).iter().map(|p| p.name()).collect();
+        info!("🔄 Reloaded configuration; provider chain: {}", chain.join(" → "));
+        Ok(config)
+    }
+
+    async fn changed(&self) -> bool {
+        let seen = self.seen.lock().await;
+        let paths: Vec<String> = seen.iter().map(|(path, _)| path.clone()).collect();
+        modification_times(&paths) != *seen
+    }
+}
+
+fn modification_times(paths: &[String]) -> Vec<(String, Option<SystemTime>)> {
+    paths
+        .iter()
+        .map(|path| (path.clone(), fs::metadata(path).and_then(|metadata| metadata.modified()).ok()))
+        .collect()
+}
+
+/// Reloads whenever one of the config files is created, changed or removed, checking every
+/// `config_watch_secs` (default 5).
+pub fn spawn_watcher(state: Arc<AppState>) {
+    let Some(interval) = state.reloader.interval else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !state.reloader.changed().await {
+                continue;
+            }
+            if let Err(e) = state.reloader.reload(&state).await {
+                error!("❌ Failed to reload configuration, keeping the current one: {:?}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,190 @@
+use super::{byline, escape, excerpt, rated, severity_color, severity_counts, zone_color, Matrix, IMPACT_LABELS, LIKELIHOOD_LABELS};
+use crate::severity::SeverityScale;
+use crate::storage::Evaluation;
+
+const STYLE: &str = r#"
+body { font: 14px/1.45 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #222; max-width: 960px; margin: 2em auto; padding: 0 1em; }
+h1 { margin-bottom: 0.1em; }
+.byline { color: #666; font-size: 12px; }
+blockquote { margin: 0; padding: 0.5em 1em; border-left: 4px solid #ddd; color: #444; white-space: pre-wrap; }
+.charts { display: flex; flex-wrap: wrap; gap: 2em; align-items: flex-start; }
+table { border-collapse: collapse; }
+.matrix td, .matrix th { width: 5.5em; height: 2.4em; text-align: center; font-size: 12px; }
+.matrix td { border: 2px solid #fff; font-weight: bold; font-size: 16px; }
+.matrix th.side { text-align: right; padding-right: 0.6em; width: 8em; }
+.risks { width: 100%; margin-top: 0.5em; }
+.risks th, .risks td { border-bottom: 1px solid #e4e4e4; padding: 0.45em 0.6em; text-align: left; vertical-align: top; }
+.risks th { cursor: pointer; user-select: none; white-space: nowrap; background: #f6f6f6; }
+.risks th[data-order="asc"]::after { content: " ▲"; }
+.risks th[data-order="desc"]::after { content: " ▼"; }
+.severity { display: inline-block; padding: 0 0.5em; border-radius: 3px; white-space: nowrap; }
+.evidence { color: #555; font-size: 12px; margin: 0.3em 0 0; }
+"#;
+
+/// Sorts the risk table by the clicked column, on each cell's `data-sort` value where it has
+/// one: numbers numerically, text alphabetically; clicking again reverses the order.
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.risks th").forEach(function (th, column) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table"), body = table.tBodies[0];
+    var order = th.dataset.order === "asc" ? "desc" : "asc";
+    table.querySelectorAll("th").forEach(function (other) { delete other.dataset.order; });
+    th.dataset.order = order;
+    var key = function (row) {
+      var cell = row.cells[column], value = cell.dataset.sort !== undefined ? cell.dataset.sort : cell.textContent.trim();
+      return value !== "" && !isNaN(value) ? Number(value) : value.toLowerCase();
+    };
+    Array.from(body.rows)
+      .sort(function (a, b) {
+        var x = key(a), y = key(b), sign = order === "asc" ? 1 : -1;
+        return (x < y ? -1 : x > y ? 1 : 0) * sign;
+      })
+      .forEach(function (row) { body.appendChild(row); });
+  });
+});
+"#;
+
+/// The evaluation as one HTML page with its styles and script inline, so it can be emailed
+/// or opened from disk: the summary, a bar chart of risks by severity, the likelihood ×
+/// impact matrix and a sortable table of the risks. Needs no network and shows everything
+/// but sorting with scripts off.
+pub fn render(evaluation: &Evaluation) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str(&format!("<title>Risk report {}</title>\n<style>{}</style>\n</head>\n<body>\n", evaluation.id, STYLE));
+    out.push_str("<h1>Project risk report</h1>\n");
+    out.push_str(&format!("<p class=\"byline\">{}</p>\n", escape(&byline(evaluation))));
+
+    out.push_str("<h2>Summary</h2>\n");
+    out.push_str(&format!("<blockquote>{}</blockquote>\n", escape(&excerpt(&evaluation.description))));
+    out.push_str(&format!("<p><strong>{}</strong></p>\n", escape(&severity_counts(&evaluation.risks))));
+    if !evaluation.tags.is_empty() {
+        let tags: Vec<String> = evaluation.tags.iter().map(|tag| format!("<code>{}</code>", escape(tag))).collect();
+        out.push_str(&format!("<p>Tags: {}</p>\n", tags.join(", ")));
+    }
+
+    out.push_str("<div class=\"charts\">\n<div>\n<h2>By severity</h2>\n");
+    severity_chart(&mut out, evaluation);
+    out.push_str("</div>\n<div>\n<h2>Risk matrix</h2>\n");
+    matrix_table(&mut out, evaluation);
+    out.push_str("</div>\n</div>\n");
+
+    out.push_str("<h2>Risks</h2>\n");
+    if evaluation.risks.is_empty() {
+        out.push_str("<p>No risks were found.</p>\n");
+    } else {
+        risk_table(&mut out, evaluation);
+    }
+    out.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    out
+}
+
+/// A horizontal bar per severity, drawn as inline SVG so it needs no charting library.
+fn severity_chart(out: &mut String, evaluation: &Evaluation) {
+    const LABEL_WIDTH: usize = 90;
+    const BAR_WIDTH: usize = 240;
+    const ROW_HEIGHT: usize = 28;
+    let levels = SeverityScale::Five.levels();
+    let counts: Vec<_> = levels
+        .iter()
+        .rev()
+        .map(|&severity| (severity, evaluation.risks.iter().filter(|risk| risk.severity == severity).count()))
+        .collect();
+    let most = counts.iter().map(|&(_, count)| count).max().unwrap_or(0).max(1);
+
+    out.push_str(&format!(
+        "<svg role=\"img\" aria-label=\"Risks by severity\" width=\"{}\" height=\"{}\" font-size=\"13\">\n",
+        LABEL_WIDTH + BAR_WIDTH + 40,
+        counts.len() * ROW_HEIGHT
+    ));
+    for (row, (severity, count)) in counts.iter().enumerate() {
+        let y = row * ROW_HEIGHT;
+        let width = count * BAR_WIDTH / most;
+        out.push_str(&format!(
+            "<text x=\"0\" y=\"{}\">{}</text><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:06X}\"/><text x=\"{}\" y=\"{}\">{}</text>\n",
+            y + 18,
+            severity,
+            LABEL_WIDTH,
+            y + 4,
+            width,
+            ROW_HEIGHT - 8,
+            severity_color(*severity),
+            LABEL_WIDTH + width + 6,
+            y + 18,
+            count
+        ));
+    }
+    out.push_str("</svg>\n");
+}
+
+fn matrix_table(out: &mut String, evaluation: &Evaluation) {
+    let matrix = Matrix::of(&evaluation.risks);
+    out.push_str("<table class=\"matrix\">\n<tr><th class=\"side\">Likelihood \\ Impact</th>");
+    for (impact, label) in IMPACT_LABELS.iter().enumerate() {
+        out.push_str(&format!("<th>{} {}</th>", impact + 1, label));
+    }
+    out.push_str("</tr>\n");
+    for likelihood in (0..5).rev() {
+        out.push_str(&format!("<tr><th class=\"side\">{} {}</th>", likelihood + 1, LIKELIHOOD_LABELS[likelihood]));
+        for (impact, &count) in matrix.counts[likelihood].iter().enumerate() {
+            let count = if count > 0 { count.to_string() } else { String::new() };
+            out.push_str(&format!(
+                "<td style=\"background:#{:06X}\">{}</td>",
+                zone_color(likelihood + 1, impact + 1),
+                count
+            ));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    if matrix.unrated > 0 {
+        out.push_str(&format!(
+            "<p class=\"byline\">{} without a likelihood or impact rating.</p>\n",
+            matrix.unrated
+        ));
+    }
+}
+
+/// Severity and the ratings sort on their rank rather than their label.
+fn risk_table(out: &mut String, evaluation: &Evaluation) {
+    out.push_str("<table class=\"risks\">\n<thead><tr>");
+    for title in ["#", "Severity", "Category", "Likelihood", "Impact", "Confidence", "Mitigation"] {
+        out.push_str(&format!("<th>{}</th>", title));
+    }
+    out.push_str("</tr></thead>\n<tbody>\n");
+    for (i, risk) in evaluation.risks.iter().enumerate() {
+        out.push_str("<tr>");
+        out.push_str(&format!("<td>{}</td>", i + 1));
+        out.push_str(&format!(
+            "<td data-sort=\"{}\"><span class=\"severity\" style=\"background:#{:06X}\">{}</span></td>",
+            risk.severity as u8,
+            severity_color(risk.severity),
+            risk.severity
+        ));
+        out.push_str(&format!("<td>{}</td>", escape(&risk.category)));
+        for (rating, labels) in [(risk.likelihood, &LIKELIHOOD_LABELS), (risk.impact, &IMPACT_LABELS)] {
+            match rating {
+                Some(rating) => out.push_str(&format!("<td data-sort=\"{}\">{}</td>", rating.value(), rated(rating, labels))),
+                None => out.push_str("<td data-sort=\"0\">–</td>"),
+            }
+        }
+        out.push_str(&format!(
+            "<td data-sort=\"{:.2}\">{:.0}%</td>",
+            risk.confidence,
+            risk.confidence * 100.0
+        ));
+        out.push_str(&format!("<td>{}", escape(&risk.mitigation)));
+        for evidence in risk.evidence.iter().take(super::MAX_QUOTES) {
+            out.push_str(&format!("<p class=\"evidence\">“{}”</p>", escape(evidence.quote.trim())));
+        }
+        if let Some(document) = &risk.document {
+            out.push_str(&format!("<p class=\"evidence\">From {}</p>", escape(document)));
+        }
+        if let Some(covered_by) = &risk.covered_by {
+            out.push_str(&format!("<p class=\"evidence\">Tracked as {}</p>", escape(covered_by)));
+        }
+        out.push_str("</td></tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+}
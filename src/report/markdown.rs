@@ -0,0 +1,137 @@
+use super::{byline, excerpt, rated, severity_counts, IMPACT_LABELS, LIKELIHOOD_LABELS};
+use crate::storage::Evaluation;
+use crate::RiskItem;
+
+/// The evaluation as Markdown for pasting into a wiki page, issue or pull request: the
+/// summary, a table of the risks, then a section per risk with its ratings, mitigation and
+/// evidence. Sticks to what GitHub and Confluence both render.
+pub fn render(evaluation: &Evaluation) -> String {
+    let mut out = String::new();
+    out.push_str("# Project risk report\n\n");
+    out.push_str(&format!("_{}_\n\n", inline(&byline(evaluation))));
+
+    out.push_str("## Summary\n\n");
+    for line in excerpt(&evaluation.description).lines() {
+        out.push_str(&format!("> {}\n", inline(line)));
+    }
+    out.push_str(&format!("\n**{}**\n", severity_counts(&evaluation.risks)));
+    if !evaluation.tags.is_empty() {
+        let tags: Vec<String> = evaluation.tags.iter().map(|tag| code(tag)).collect();
+        out.push_str(&format!("\nTags: {}\n", tags.join(", ")));
+    }
+
+    risk_tables(&mut out, &evaluation.risks);
+    out
+}
+
+/// The risks alone under a one-line byline, for results that come without the description,
+/// such as the response to `POST /evaluate`.
+pub fn render_risks(byline: &str, notes: &[&str], risks: &[RiskItem]) -> String {
+    let mut out = String::new();
+    out.push_str("# Project risk report\n\n");
+    out.push_str(&format!("_{}_\n\n", inline(byline)));
+    for note in notes {
+        out.push_str(&format!("**Note:** {}\n\n", inline(note)));
+    }
+    out.push_str(&format!("**{}**\n", severity_counts(risks)));
+    risk_tables(&mut out, risks);
+    out
+}
+
+/// The summary table, then a section per risk.
+fn risk_tables(out: &mut String, risks: &[RiskItem]) {
+    if !risks.is_empty() {
+        out.push_str("\n| # | Severity | Category | Likelihood | Impact | Confidence | Mitigation |\n");
+        out.push_str("|---|---|---|---|---|---|---|\n");
+        for (i, risk) in risks.iter().enumerate() {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {:.0}% | {} |\n",
+                i + 1,
+                risk.severity,
+                cell(&risk.category),
+                risk.likelihood.map_or("–".to_string(), |likelihood| rated(likelihood, &LIKELIHOOD_LABELS)),
+                risk.impact.map_or("–".to_string(), |impact| rated(impact, &IMPACT_LABELS)),
+                risk.confidence * 100.0,
+                cell(&risk.mitigation),
+            ));
+        }
+
+        out.push_str("\n## Risks\n");
+        for (i, risk) in risks.iter().enumerate() {
+            risk_section(out, i + 1, risk);
+        }
+    }
+}
+
+fn risk_section(out: &mut String, number: usize, risk: &RiskItem) {
+    out.push_str(&format!("\n### {}. {} · {}\n\n", number, risk.severity, inline(&risk.category)));
+    if let Some(likelihood) = risk.likelihood {
+        out.push_str(&format!("- **Likelihood:** {}\n", rated(likelihood, &LIKELIHOOD_LABELS)));
+    }
+    if let Some(impact) = risk.impact {
+        out.push_str(&format!("- **Impact:** {}\n", rated(impact, &IMPACT_LABELS)));
+    }
+    out.push_str(&format!("- **Confidence:** {:.0}%\n", risk.confidence * 100.0));
+    if let Some(score) = risk.score {
+        out.push_str(&format!("- **Score:** {:.1}\n", score));
+    }
+    if let Some(document) = &risk.document {
+        out.push_str(&format!("- **Document:** {}\n", inline(document)));
+    }
+    if let Some(covered_by) = &risk.covered_by {
+        out.push_str(&format!("- **Tracked as:** {}\n", inline(covered_by)));
+    }
+    if let Some(cost) = &risk.cost_impact {
+        out.push_str(&format!(
+            "- **Cost impact:** {:.0} {} likely, {:.0} to {:.0}\n",
+            cost.likely,
+            inline(&cost.currency),
+            cost.min,
+            cost.max
+        ));
+    }
+    out.push_str(&format!("\n**Mitigation:** {}\n", inline(&risk.mitigation)));
+
+    let quotes: Vec<String> =
+        risk.evidence.iter().take(super::MAX_QUOTES).map(|e| format!("> {}\n", inline(e.quote.trim()))).collect();
+    if !quotes.is_empty() {
+        out.push_str("\n**Evidence:**\n\n");
+        out.push_str(&quotes.join(">\n"));
+    }
+}
+
+/// `text` on one line with the characters Markdown would treat as formatting escaped.
+fn inline(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' | '\r' => escaped.push(' '),
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Table cells additionally can't be empty without collapsing the column in some renderers.
+fn cell(text: &str) -> String {
+    let text = inline(text.trim());
+    if text.is_empty() {
+        "–".to_string()
+    } else {
+        text
+    }
+}
+
+/// `text` as inline code, fenced with enough backticks to hold any it contains.
+fn code(text: &str) -> String {
+    if !text.contains('`') {
+        return format!("`{}`", text);
+    }
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    format!("{} {} {}", fence, text, fence)
+}
@@ -0,0 +1,119 @@
+use crate::rating::Rating;
+use crate::severity::{Severity, SeverityScale};
+use crate::storage::Evaluation;
+use crate::RiskItem;
+
+pub mod html;
+pub mod markdown;
+pub mod pdf;
+pub mod svg;
+pub mod xlsx;
+
+/// How much of the description the summary quotes.
+const SUMMARY_CHARS: usize = 1_200;
+/// Evidence quotes shown per risk.
+const MAX_QUOTES: usize = 3;
+const LIKELIHOOD_LABELS: [&str; 5] = ["Rare", "Unlikely", "Possible", "Likely", "Almost certain"];
+const IMPACT_LABELS: [&str; 5] = ["Negligible", "Minor", "Moderate", "Major", "Severe"];
+
+/// E.g. "Evaluation … · 2026-01-31 09:15 UTC · openai (gpt-4o)".
+fn byline(evaluation: &Evaluation) -> String {
+    let mut byline = format!("Evaluation {} · {}", evaluation.id, evaluation.created_at.format("%Y-%m-%d %H:%M UTC"));
+    match &evaluation.model {
+        Some(model) => byline.push_str(&format!(" · {} ({})", evaluation.provider, model)),
+        None => byline.push_str(&format!(" · {}", evaluation.provider)),
+    }
+    byline
+}
+
+/// The start of the description, cut at a word.
+fn excerpt(description: &str) -> String {
+    let description = description.trim();
+    if description.chars().count() <= SUMMARY_CHARS {
+        return description.to_string();
+    }
+    let cut: String = description.chars().take(SUMMARY_CHARS).collect();
+    let cut = cut.rfind(char::is_whitespace).map_or(cut.as_str(), |i| &cut[..i]);
+    format!("{} …", cut.trim_end())
+}
+
+/// E.g. "4 risks: 1 High, 3 Medium".
+fn severity_counts(risks: &[RiskItem]) -> String {
+    let counts: Vec<String> = SeverityScale::Five
+        .levels()
+        .iter()
+        .rev()
+        .filter_map(|&severity| {
+            let count = risks.iter().filter(|risk| risk.severity == severity).count();
+            (count > 0).then(|| format!("{} {}", count, severity))
+        })
+        .collect();
+    match risks.len() {
+        0 => "No risks found".to_string(),
+        1 => format!("1 risk: {}", counts.join(", ")),
+        n => format!("{} risks: {}", n, counts.join(", ")),
+    }
+}
+
+/// E.g. "4/5 (Likely)".
+fn rated(rating: Rating, labels: &[&str; 5]) -> String {
+    format!("{}/5 ({})", rating.value(), labels[rating.value() as usize - 1])
+}
+
+/// How many risks sit in each cell of the 5×5 matrix, by likelihood then impact, lowest
+/// first; risks missing either rating aren't on it.
+struct Matrix {
+    counts: [[usize; 5]; 5],
+    unrated: usize,
+}
+
+impl Matrix {
+    fn of(risks: &[RiskItem]) -> Self {
+        let mut matrix = Self { counts: [[0; 5]; 5], unrated: 0 };
+        for risk in risks {
+            match (risk.likelihood, risk.impact) {
+                (Some(likelihood), Some(impact)) => {
+                    matrix.counts[likelihood.value() as usize - 1][impact.value() as usize - 1] += 1;
+                }
+                _ => matrix.unrated += 1,
+            }
+        }
+        matrix
+    }
+}
+
+/// Green, amber or red, as `0xRRGGBB`, by likelihood × impact, split where risk matrices
+/// usually are.
+fn zone_color(likelihood: usize, impact: usize) -> u32 {
+    match likelihood * impact {
+        15.. => 0xE8736B,
+        8.. => 0xFACC73,
+        _ => 0xA6D999,
+    }
+}
+
+fn severity_color(severity: Severity) -> u32 {
+    match severity {
+        Severity::Critical => 0xB31A1A,
+        Severity::High => 0xE8736B,
+        Severity::Medium => 0xFACC73,
+        Severity::Low => 0xA6D999,
+        Severity::Negligible => 0xCCCCCC,
+    }
+}
+
+/// `text` safe to put in HTML or SVG, in text or a quoted attribute.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
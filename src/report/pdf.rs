@@ -0,0 +1,317 @@
+use anyhow::{anyhow, Result};
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Rect, Rgb};
+
+use super::{
+    byline, excerpt, rated, severity_color, severity_counts, zone_color, Matrix, IMPACT_LABELS, LIKELIHOOD_LABELS,
+};
+use crate::storage::Evaluation;
+use crate::RiskItem;
+
+/// Where things go on the page, in millimetres, and how big the type is, in points. A4
+/// portrait, one column.
+struct Layout {
+    page_width: f32,
+    page_height: f32,
+    margin: f32,
+    title_size: f32,
+    heading_size: f32,
+    body_size: f32,
+    small_size: f32,
+    /// Line height as a multiple of the type size.
+    leading: f32,
+    /// Space before a section heading and after a block of text.
+    section_gap: f32,
+    block_gap: f32,
+    matrix_label_width: f32,
+    matrix_cell_width: f32,
+    matrix_cell_height: f32,
+}
+
+const LAYOUT: Layout = Layout {
+    page_width: 210.0,
+    page_height: 297.0,
+    margin: 18.0,
+    title_size: 20.0,
+    heading_size: 14.0,
+    body_size: 10.0,
+    small_size: 8.0,
+    leading: 1.35,
+    section_gap: 6.0,
+    block_gap: 3.0,
+    matrix_label_width: 30.0,
+    matrix_cell_width: 24.0,
+    matrix_cell_height: 11.0,
+};
+
+const MM_PER_PT: f32 = 25.4 / 72.0;
+
+/// The evaluation as a PDF report: a summary of the project, a likelihood × impact matrix
+/// counting its risks, then each risk with its ratings, mitigation and evidence.
+pub fn render(evaluation: &Evaluation) -> Result<Vec<u8>> {
+    let mut pages = Pages::new(evaluation)?;
+
+    pages.text("Project risk report", LAYOUT.title_size, true);
+    pages.text(&byline(evaluation), LAYOUT.small_size, false);
+
+    pages.heading("Project summary");
+    pages.text(&excerpt(&evaluation.description), LAYOUT.body_size, false);
+    pages.gap(LAYOUT.block_gap);
+    pages.text(&severity_counts(&evaluation.risks), LAYOUT.body_size, true);
+    if !evaluation.tags.is_empty() {
+        pages.text(&format!("Tags: {}", evaluation.tags.join(", ")), LAYOUT.small_size, false);
+    }
+
+    pages.heading("Risk matrix");
+    pages.matrix(&evaluation.risks);
+
+    pages.heading("Risks");
+    if evaluation.risks.is_empty() {
+        pages.text("No risks were found.", LAYOUT.body_size, false);
+    }
+    for (i, risk) in evaluation.risks.iter().enumerate() {
+        pages.risk(i + 1, risk);
+    }
+
+    pages.doc.save_to_bytes().map_err(|e| anyhow!("Failed to write the PDF: {}", e))
+}
+
+/// The document being written and where the next line goes.
+struct Pages {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    footer: String,
+    page: usize,
+    /// Top of the next line, from the bottom of the page.
+    y: f32,
+}
+
+impl Pages {
+    fn new(evaluation: &Evaluation) -> Result<Self> {
+        let title = format!("Risk report {}", evaluation.id);
+        let (doc, page, layer) = PdfDocument::new(&title, Mm(LAYOUT.page_width), Mm(LAYOUT.page_height), "Report");
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| anyhow!("{}", e))?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| anyhow!("{}", e))?;
+        let layer = doc.get_page(page).get_layer(layer);
+        let pages = Self {
+            doc,
+            layer,
+            regular,
+            bold,
+            footer: format!("Risk report · evaluation {}", evaluation.id),
+            page: 1,
+            y: LAYOUT.page_height - LAYOUT.margin,
+        };
+        pages.write_footer();
+        Ok(pages)
+    }
+
+    fn write_footer(&self) {
+        let footer = format!("{} · page {}", self.footer, self.page);
+        self.layer.use_text(printable(&footer), LAYOUT.small_size, Mm(LAYOUT.margin), Mm(LAYOUT.margin / 2.0), &self.regular);
+    }
+
+    /// Starts a new page unless `height` more fits on this one.
+    fn ensure(&mut self, height: f32) {
+        if self.y - height >= LAYOUT.margin {
+            return;
+        }
+        let (page, layer) = self.doc.add_page(Mm(LAYOUT.page_width), Mm(LAYOUT.page_height), "Report");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.page += 1;
+        self.y = LAYOUT.page_height - LAYOUT.margin;
+        self.write_footer();
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Writes `text` wrapped to the page width, one paragraph per line of it.
+    fn text(&mut self, text: &str, size: f32, bold: bool) {
+        self.text_at(text, size, bold, 0.0);
+    }
+
+    fn text_at(&mut self, text: &str, size: f32, bold: bool, indent: f32) {
+        let height = size * LAYOUT.leading * MM_PER_PT;
+        let width = LAYOUT.page_width - 2.0 * LAYOUT.margin - indent;
+        for line in wrap(text, size, width) {
+            self.ensure(height);
+            self.y -= height;
+            let font = if bold { &self.bold } else { &self.regular };
+            self.layer.use_text(printable(&line), size, Mm(LAYOUT.margin + indent), Mm(self.y), font);
+        }
+    }
+
+    /// A section heading, kept on the same page as at least a few lines of its section.
+    fn heading(&mut self, title: &str) {
+        self.gap(LAYOUT.section_gap);
+        self.ensure(LAYOUT.heading_size * MM_PER_PT * 6.0);
+        self.text(title, LAYOUT.heading_size, true);
+        self.gap(LAYOUT.block_gap);
+    }
+
+    /// The 5×5 matrix, likelihood down the side from almost certain to rare and impact
+    /// along the top, each cell counting the risks rated there and shaded by how serious
+    /// that combination is.
+    fn matrix(&mut self, risks: &[RiskItem]) {
+        let matrix = Matrix::of(risks);
+        let (label_width, cell_width, cell_height) =
+            (LAYOUT.matrix_label_width, LAYOUT.matrix_cell_width, LAYOUT.matrix_cell_height);
+        self.ensure(cell_height * 6.0 + 12.0);
+        self.text_at("Impact", LAYOUT.small_size, true, label_width);
+        self.y -= cell_height;
+        for (column, label) in IMPACT_LABELS.iter().enumerate() {
+            let x = LAYOUT.margin + label_width + column as f32 * cell_width;
+            self.label(&format!("{} {}", column + 1, label), x + 1.5, self.y + cell_height / 3.0);
+        }
+        for likelihood in (0..5).rev() {
+            self.y -= cell_height;
+            self.label(
+                &format!("{} {}", likelihood + 1, LIKELIHOOD_LABELS[likelihood]),
+                LAYOUT.margin,
+                self.y + cell_height / 3.0,
+            );
+            for (impact, &count) in matrix.counts[likelihood].iter().enumerate() {
+                let x = LAYOUT.margin + label_width + impact as f32 * cell_width;
+                self.layer.set_fill_color(rgb(zone_color(likelihood + 1, impact + 1)));
+                self.layer.set_outline_color(rgb(0xFFFFFF));
+                self.layer.set_outline_thickness(1.0);
+                self.layer.add_rect(
+                    Rect::new(Mm(x), Mm(self.y), Mm(x + cell_width), Mm(self.y + cell_height))
+                        .with_mode(PaintMode::FillStroke),
+                );
+                self.layer.set_fill_color(rgb(0x000000));
+                if count > 0 {
+                    self.layer.use_text(
+                        count.to_string(),
+                        LAYOUT.heading_size,
+                        Mm(x + cell_width / 2.0 - 2.0),
+                        Mm(self.y + cell_height / 3.0),
+                        &self.bold,
+                    );
+                }
+            }
+        }
+        self.text("Likelihood down the side, impact along the top.", LAYOUT.small_size, false);
+        if matrix.unrated > 0 {
+            let noun = if matrix.unrated == 1 { "risk isn't" } else { "risks aren't" };
+            self.text(
+                &format!("{} {} on the matrix, missing a likelihood or impact rating.", matrix.unrated, noun),
+                LAYOUT.small_size,
+                false,
+            );
+        }
+    }
+
+    fn label(&self, text: &str, x: f32, y: f32) {
+        self.layer.use_text(printable(text), LAYOUT.small_size, Mm(x), Mm(y), &self.regular);
+    }
+
+    /// One risk: a heading marked in its severity's colour, its ratings, the mitigation and
+    /// the first few passages quoted as evidence.
+    fn risk(&mut self, number: usize, risk: &RiskItem) {
+        let heading_height = LAYOUT.body_size * LAYOUT.leading * MM_PER_PT;
+        self.gap(LAYOUT.block_gap);
+        self.ensure(heading_height * 4.0);
+        self.layer.set_fill_color(rgb(severity_color(risk.severity)));
+        self.layer.add_rect(Rect::new(
+            Mm(LAYOUT.margin),
+            Mm(self.y - heading_height),
+            Mm(LAYOUT.margin + 2.0),
+            Mm(self.y),
+        ));
+        self.layer.set_fill_color(rgb(0x000000));
+        self.text_at(&format!("{}. {} · {}", number, risk.severity, risk.category), LAYOUT.body_size, true, 4.0);
+
+        let mut ratings = Vec::new();
+        if let Some(likelihood) = risk.likelihood {
+            ratings.push(format!("Likelihood {}", rated(likelihood, &LIKELIHOOD_LABELS)));
+        }
+        if let Some(impact) = risk.impact {
+            ratings.push(format!("Impact {}", rated(impact, &IMPACT_LABELS)));
+        }
+        ratings.push(format!("Confidence {:.0}%", risk.confidence * 100.0));
+        if let Some(score) = risk.score {
+            ratings.push(format!("Score {:.1}", score));
+        }
+        if let Some(document) = &risk.document {
+            ratings.push(format!("From {}", document));
+        }
+        if let Some(covered_by) = &risk.covered_by {
+            ratings.push(format!("Tracked as {}", covered_by));
+        }
+        self.text_at(&ratings.join(" · "), LAYOUT.small_size, false, 4.0);
+        self.text_at(&format!("Mitigation: {}", risk.mitigation), LAYOUT.body_size, false, 4.0);
+        for evidence in risk.evidence.iter().take(super::MAX_QUOTES) {
+            self.text_at(&format!("“{}”", evidence.quote.trim()), LAYOUT.small_size, false, 8.0);
+        }
+    }
+}
+
+/// A `0xRRGGBB` colour as the PDF wants it.
+fn rgb(hex: u32) -> Color {
+    let channel = |shift: u32| ((hex >> shift) & 0xFF) as f32 / 255.0;
+    Color::Rgb(Rgb::new(channel(16), channel(8), channel(0), None))
+}
+
+/// `text` split into lines that fit `width` millimetres at `size` points, breaking between
+/// words where it can.
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let fits = |line: &str| text_width(line, size) <= width;
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if fits(&candidate) {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // A word wider than the page on its own is broken wherever it has to be.
+            for c in word.chars() {
+                line.push(c);
+                if !fits(&line) {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Approximate width of `text` in Helvetica, in millimetres: close enough to wrap lines
+/// without measuring each glyph.
+fn text_width(text: &str, size: f32) -> f32 {
+    let ems: f32 = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' | ' ' => 0.28,
+            'f' | 't' | 'r' | 'I' | '(' | ')' | '-' => 0.35,
+            'm' | 'w' | 'M' | 'W' => 0.85,
+            c if c.is_uppercase() => 0.68,
+            _ => 0.56,
+        })
+        .sum();
+    ems * size * MM_PER_PT
+}
+
+/// The built-in fonts only have the Windows-1252 characters; anything else would be dropped,
+/// so it's shown as `?` instead.
+fn printable(text: &str) -> String {
+    const EXTRA: &str = "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ";
+    text.chars()
+        .map(|c| match c {
+            '\t' => ' ',
+            c if c.is_ascii_graphic() || c == ' ' || ('\u{a0}'..='\u{ff}').contains(&c) || EXTRA.contains(c) => c,
+            _ => '?',
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+use super::{escape, severity_color, zone_color, IMPACT_LABELS, LIKELIHOOD_LABELS};
+use crate::storage::Evaluation;
+use crate::RiskItem;
+
+const CELL_WIDTH: usize = 96;
+const CELL_HEIGHT: usize = 64;
+/// Room for the likelihood labels on the left and the impact labels underneath.
+const LEFT: usize = 130;
+const TOP: usize = 36;
+const BOTTOM: usize = 52;
+const RIGHT: usize = 12;
+const MARKER_RADIUS: usize = 10;
+/// Markers per row within a cell, and rows of them before the rest are summed up as "+n".
+const MARKERS_PER_ROW: usize = 4;
+const MARKER_ROWS: usize = 2;
+
+/// The evaluation's risks plotted on the 5×5 likelihood × impact grid as a standalone SVG:
+/// each risk a dot in its severity's colour, numbered as in the reports and with its
+/// category as a tooltip, on cells shaded green to red. Risks missing a rating are counted
+/// in a note instead.
+pub fn render(evaluation: &Evaluation) -> String {
+    let mut cells: Vec<Vec<Vec<(usize, &RiskItem)>>> = vec![vec![Vec::new(); 5]; 5];
+    let mut unrated = 0;
+    for (i, risk) in evaluation.risks.iter().enumerate() {
+        match (risk.likelihood, risk.impact) {
+            (Some(likelihood), Some(impact)) => {
+                cells[likelihood.value() as usize - 1][impact.value() as usize - 1].push((i + 1, risk));
+            }
+            _ => unrated += 1,
+        }
+    }
+
+    let width = LEFT + 5 * CELL_WIDTH + RIGHT;
+    let height = TOP + 5 * CELL_HEIGHT + BOTTOM + if unrated > 0 { 20 } else { 0 };
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"Helvetica, Arial, sans-serif\" font-size=\"12\" role=\"img\" \
+         aria-label=\"Risk matrix for evaluation {id}\">\n",
+        w = width,
+        h = height,
+        id = evaluation.id
+    );
+    out.push_str(&format!(
+        "<text x=\"{}\" y=\"22\" font-size=\"15\" font-weight=\"bold\">Risk matrix</text>\n",
+        LEFT
+    ));
+
+    for likelihood in 0..5 {
+        // Almost certain at the top.
+        let y = TOP + (4 - likelihood) * CELL_HEIGHT;
+        out.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{} {}</text>\n",
+            LEFT - 8,
+            y + CELL_HEIGHT / 2 + 4,
+            likelihood + 1,
+            LIKELIHOOD_LABELS[likelihood]
+        ));
+        for (impact, risks) in cells[likelihood].iter().enumerate() {
+            let x = LEFT + impact * CELL_WIDTH;
+            out.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:06X}\" stroke=\"#FFFFFF\" stroke-width=\"2\"/>\n",
+                x,
+                y,
+                CELL_WIDTH,
+                CELL_HEIGHT,
+                zone_color(likelihood + 1, impact + 1)
+            ));
+            markers(&mut out, x, y, risks);
+        }
+    }
+
+    let axis_y = TOP + 5 * CELL_HEIGHT;
+    for (impact, label) in IMPACT_LABELS.iter().enumerate() {
+        out.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{} {}</text>\n",
+            LEFT + impact * CELL_WIDTH + CELL_WIDTH / 2,
+            axis_y + 18,
+            impact + 1,
+            label
+        ));
+    }
+    out.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-weight=\"bold\">Impact</text>\n",
+        LEFT + 5 * CELL_WIDTH / 2,
+        axis_y + 40
+    ));
+    out.push_str(&format!(
+        "<text x=\"14\" y=\"{}\" font-weight=\"bold\" transform=\"rotate(-90 14 {})\" text-anchor=\"middle\">Likelihood</text>\n",
+        TOP + 5 * CELL_HEIGHT / 2,
+        TOP + 5 * CELL_HEIGHT / 2
+    ));
+    if unrated > 0 {
+        let noun = if unrated == 1 { "risk isn't" } else { "risks aren't" };
+        out.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"#666666\">{} {} plotted, missing a likelihood or impact rating.</text>\n",
+            LEFT,
+            axis_y + BOTTOM + 12,
+            unrated,
+            noun
+        ));
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// The cell's risks as numbered dots in rows, with any that don't fit summed up as "+n".
+fn markers(out: &mut String, x: usize, y: usize, risks: &[(usize, &RiskItem)]) {
+    let capacity = MARKERS_PER_ROW * MARKER_ROWS;
+    let shown = if risks.len() > capacity { capacity - 1 } else { risks.len() };
+    let spacing = CELL_WIDTH / MARKERS_PER_ROW;
+    let row_height = CELL_HEIGHT / MARKER_ROWS;
+    for (slot, (number, risk)) in risks.iter().take(shown).enumerate() {
+        let cx = x + spacing / 2 + (slot % MARKERS_PER_ROW) * spacing;
+        let cy = y + row_height / 2 + (slot / MARKERS_PER_ROW) * row_height;
+        out.push_str(&format!(
+            "<g><title>#{} {} · {}</title><circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#{:06X}\" stroke=\"#333333\"/>\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\">{}</text></g>\n",
+            number,
+            risk.severity,
+            escape(&risk.category),
+            cx,
+            cy,
+            MARKER_RADIUS,
+            severity_color(risk.severity),
+            cx,
+            cy + 4,
+            number
+        ));
+    }
+    if shown < risks.len() {
+        let slot = shown;
+        out.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"11\" font-weight=\"bold\">+{}</text>\n",
+            x + spacing / 2 + (slot % MARKERS_PER_ROW) * spacing,
+            y + row_height / 2 + (slot / MARKERS_PER_ROW) * row_height + 4,
+            risks.len() - shown
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, Workbook, Worksheet};
+
+use super::{byline, severity_color, zone_color, Matrix, IMPACT_LABELS, LIKELIHOOD_LABELS};
+use crate::severity::SeverityScale;
+use crate::storage::Evaluation;
+use crate::RiskItem;
+
+const SUMMARY_SHEET: &str = "Summary";
+/// Excel's limit on sheet name length.
+const MAX_SHEET_NAME: usize = 31;
+const RISK_COLUMNS: [(&str, f64); 9] = [
+    ("#", 5.0),
+    ("Severity", 11.0),
+    ("Likelihood", 11.0),
+    ("Impact", 9.0),
+    ("Confidence", 11.0),
+    ("Mitigation", 60.0),
+    ("Evidence", 50.0),
+    ("Document", 20.0),
+    ("Tracked as", 16.0),
+];
+
+/// The evaluation as an Excel workbook: a summary sheet with the severity counts and the
+/// likelihood × impact matrix, then a sheet per category listing its risks. Risks are
+/// numbered as in the other reports, so the sheets can be read side by side with them.
+pub fn render(evaluation: &Evaluation) -> Result<Vec<u8>> {
+    let groups = by_category(&evaluation.risks);
+    let sheet_names = sheet_names(groups.iter().map(|(category, _)| category.as_str()));
+
+    let mut workbook = Workbook::new();
+    workbook.push_worksheet(summary_sheet(evaluation, &groups, &sheet_names)?);
+    for ((_, risks), name) in groups.iter().zip(&sheet_names) {
+        workbook.push_worksheet(category_sheet(name, risks)?);
+    }
+    Ok(workbook.save_to_buffer()?)
+}
+
+fn summary_sheet(evaluation: &Evaluation, groups: &[(String, Vec<(usize, &RiskItem)>)], names: &[String]) -> Result<Worksheet> {
+    let title = Format::new().set_bold().set_font_size(14);
+    let bold = Format::new().set_bold();
+    let header = Format::new().set_bold().set_border_bottom(FormatBorder::Thin);
+
+    let mut sheet = Worksheet::new();
+    sheet.set_name(SUMMARY_SHEET)?;
+    sheet.set_column_width(0, 22)?;
+    sheet.set_column_width(1, 14)?;
+    for column in 2..=5 {
+        sheet.set_column_width(column, 14)?;
+    }
+    sheet.write_string_with_format(0, 0, "Project risk report", &title)?;
+    sheet.write_string(1, 0, byline(evaluation))?;
+
+    sheet.write_string_with_format(3, 0, "Severity", &header)?;
+    sheet.write_string_with_format(3, 1, "Risks", &header)?;
+    let mut row = 4;
+    for &severity in SeverityScale::Five.levels().iter().rev() {
+        let count = evaluation.risks.iter().filter(|risk| risk.severity == severity).count();
+        let fill = Format::new().set_background_color(Color::RGB(severity_color(severity)));
+        sheet.write_string_with_format(row, 0, severity.as_str(), &fill)?;
+        sheet.write_number(row, 1, count as f64)?;
+        row += 1;
+    }
+    sheet.write_string_with_format(row, 0, "Total", &bold)?;
+    sheet.write_number_with_format(row, 1, evaluation.risks.len() as f64, &bold)?;
+
+    row += 2;
+    sheet.write_string_with_format(row, 0, "Risk matrix", &bold)?;
+    row += 1;
+    sheet.write_string_with_format(row, 0, "Likelihood \\ Impact", &header)?;
+    for (column, label) in IMPACT_LABELS.iter().enumerate() {
+        sheet.write_string_with_format(row, column as u16 + 1, format!("{} {}", column + 1, label), &header)?;
+    }
+    let matrix = Matrix::of(&evaluation.risks);
+    for likelihood in (0..5).rev() {
+        row += 1;
+        sheet.write_string(row, 0, format!("{} {}", likelihood + 1, LIKELIHOOD_LABELS[likelihood]))?;
+        for (impact, &count) in matrix.counts[likelihood].iter().enumerate() {
+            let cell = Format::new()
+                .set_background_color(Color::RGB(zone_color(likelihood + 1, impact + 1)))
+                .set_align(FormatAlign::Center)
+                .set_border(FormatBorder::Thin)
+                .set_border_color(Color::White);
+            if count > 0 {
+                sheet.write_number_with_format(row, impact as u16 + 1, count as f64, &cell)?;
+            } else {
+                sheet.write_blank(row, impact as u16 + 1, &cell)?;
+            }
+        }
+    }
+    if matrix.unrated > 0 {
+        row += 1;
+        sheet.write_string(row, 0, format!("{} without a likelihood or impact rating", matrix.unrated))?;
+    }
+
+    row += 2;
+    sheet.write_string_with_format(row, 0, "Category", &header)?;
+    sheet.write_string_with_format(row, 1, "Risks", &header)?;
+    sheet.write_string_with_format(row, 2, "Sheet", &header)?;
+    for ((category, risks), name) in groups.iter().zip(names) {
+        row += 1;
+        sheet.write_string(row, 0, category)?;
+        sheet.write_number(row, 1, risks.len() as f64)?;
+        sheet.write_url_with_text(row, 2, format!("internal:'{}'!A1", name.replace('\'', "''")).as_str(), name)?;
+    }
+    Ok(sheet)
+}
+
+fn category_sheet(name: &str, risks: &[(usize, &RiskItem)]) -> Result<Worksheet> {
+    let header = Format::new().set_bold().set_border_bottom(FormatBorder::Thin);
+    let wrapped = Format::new().set_text_wrap().set_align(FormatAlign::Top);
+    let top = Format::new().set_align(FormatAlign::Top);
+    let percent = Format::new().set_num_format("0%").set_align(FormatAlign::Top);
+
+    let mut sheet = Worksheet::new();
+    sheet.set_name(name)?;
+    for (column, (title, width)) in RISK_COLUMNS.iter().enumerate() {
+        sheet.set_column_width(column as u16, *width)?;
+        sheet.write_string_with_format(0, column as u16, *title, &header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    for (row, (number, risk)) in risks.iter().enumerate() {
+        let row = row as u32 + 1;
+        let severity = Format::new()
+            .set_background_color(Color::RGB(severity_color(risk.severity)))
+            .set_align(FormatAlign::Top);
+        sheet.write_number_with_format(row, 0, *number as f64, &top)?;
+        sheet.write_string_with_format(row, 1, risk.severity.as_str(), &severity)?;
+        if let Some(likelihood) = risk.likelihood {
+            sheet.write_number_with_format(row, 2, likelihood.value() as f64, &top)?;
+        }
+        if let Some(impact) = risk.impact {
+            sheet.write_number_with_format(row, 3, impact.value() as f64, &top)?;
+        }
+        // Rounded so the cell doesn't show f32 noise like 0.4000000059604645.
+        sheet.write_number_with_format(row, 4, (risk.confidence as f64 * 100.0).round() / 100.0, &percent)?;
+        sheet.write_string_with_format(row, 5, &risk.mitigation, &wrapped)?;
+        let quotes: Vec<&str> = risk.evidence.iter().map(|evidence| evidence.quote.trim()).collect();
+        sheet.write_string_with_format(row, 6, quotes.join("\n"), &wrapped)?;
+        if let Some(document) = &risk.document {
+            sheet.write_string_with_format(row, 7, document, &top)?;
+        }
+        if let Some(covered_by) = &risk.covered_by {
+            sheet.write_string_with_format(row, 8, covered_by, &top)?;
+        }
+    }
+    sheet.autofilter(0, 0, risks.len() as u32, RISK_COLUMNS.len() as u16 - 1)?;
+    Ok(sheet)
+}
+
+/// The risks by category, ignoring case, in the order the categories first appear, each with
+/// its number in the evaluation.
+fn by_category(risks: &[RiskItem]) -> Vec<(String, Vec<(usize, &RiskItem)>)> {
+    let mut groups: Vec<(String, Vec<(usize, &RiskItem)>)> = Vec::new();
+    for (i, risk) in risks.iter().enumerate() {
+        let category = risk.category.trim();
+        let category = if category.is_empty() { "Uncategorized" } else { category };
+        match groups.iter_mut().find(|(name, _)| name.eq_ignore_ascii_case(category)) {
+            Some((_, members)) => members.push((i + 1, risk)),
+            None => groups.push((category.to_string(), vec![(i + 1, risk)])),
+        }
+    }
+    groups
+}
+
+/// A valid, distinct sheet name for each category: Excel forbids some characters, names
+/// over 31 characters and two names differing only in case, and keeps "History" for itself.
+fn sheet_names<'a>(categories: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut taken: Vec<String> = vec![SUMMARY_SHEET.to_lowercase(), "history".to_string()];
+    let mut names = Vec::new();
+    for category in categories {
+        let cleaned: String = category.chars().filter(|c| !"[]:*?/\\".contains(*c)).collect();
+        let cleaned = cleaned.trim().trim_matches('\'');
+        let base: String = if cleaned.is_empty() { "Risks".to_string() } else { cleaned.chars().take(MAX_SHEET_NAME).collect() };
+        let mut name = base.clone();
+        let mut n = 2;
+        while taken.contains(&name.to_lowercase()) {
+            let suffix = format!(" ({})", n);
+            let kept: String = base.chars().take(MAX_SHEET_NAME - suffix.len()).collect();
+            name = format!("{}{}", kept.trim_end(), suffix);
+            n += 1;
+        }
+        taken.push(name.to_lowercase());
+        names.push(name);
+    }
+    names
+}
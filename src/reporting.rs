@@ -0,0 +1,65 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use sentry::{integrations::anyhow::capture_anyhow, protocol::Context, ClientInitGuard, ClientOptions, Hub};
+use tracing::info;
+
+use crate::auth::Identity;
+use crate::{PreparedEvaluation, RiskRequest};
+
+/// Starts reporting errors and panics to Sentry when `SENTRY_DSN` is set; `SENTRY_ENVIRONMENT`
+/// and `SENTRY_RELEASE` are honoured too. Pending events are sent when the guard is dropped.
+pub fn init() -> ClientInitGuard {
+    // `send_default_pii` stays off, which keeps API keys and forwarded IPs out of request data.
+    let mut options = ClientOptions::default();
+    options.release = sentry::release_name!();
+    let guard = sentry::init(options);
+    if guard.is_enabled() {
+        info!("🚨 Reporting errors to Sentry");
+    }
+    guard
+}
+
+/// A hub for one evaluation, so anything it reports says who asked, in which request and with
+/// which settings. The project text itself is never attached.
+pub fn evaluation_hub(identity: &Identity, payload: &RiskRequest, prepared: &PreparedEvaluation) -> Arc<Hub> {
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    hub.configure_scope(|scope| {
+        scope.set_tag("evaluation_id", prepared.id);
+        scope.set_tag("request_id", &prepared.request_id);
+        scope.set_tag("tenant", &identity.tenant);
+        scope.set_tag("key_id", &identity.key_id);
+
+        let mut evaluation = BTreeMap::new();
+        evaluation.insert("chars".to_string(), prepared.text.chars().count().into());
+        evaluation.insert("provider".to_string(), prepared.selection.provider.clone().into());
+        evaluation.insert("model".to_string(), prepared.selection.model.clone().into());
+        evaluation.insert("max_risks".to_string(), prepared.options.max_risks.into());
+        evaluation.insert("timeout_ms".to_string(), payload.timeout_ms.into());
+        evaluation.insert("project_id".to_string(), payload.project_id.map(|id| id.to_string()).into());
+        scope.set_context("evaluation", Context::Other(evaluation));
+    });
+    hub
+}
+
+/// A provider call that errored or timed out.
+pub fn call_failed(provider: &str, model: Option<&str>, error: &anyhow::Error) {
+    capture(provider, model, "call", error);
+}
+
+/// A reply that still didn't parse into risks once any correction re-prompts were spent.
+pub fn parse_failed(provider: &str, model: &str, error: &anyhow::Error) {
+    capture(provider, Some(model), "parse", error);
+}
+
+fn capture(provider: &str, model: Option<&str>, stage: &str, error: &anyhow::Error) {
+    sentry::with_scope(
+        |scope| {
+            scope.set_tag("provider", provider);
+            scope.set_tag("stage", stage);
+            if let Some(model) = model {
+                scope.set_tag("model", model);
+            }
+        },
+        || capture_anyhow(error),
+    );
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::audit::AuditResource;
+use crate::auth::{self, Identity, Role};
+use crate::storage::ApiKey;
+use crate::AppState;
+
+/// Body of `POST /api-keys`.
+#[derive(Debug, Deserialize)]
+pub struct NewApiKey {
+    name: String,
+    #[serde(default = "default_role")]
+    role: Role,
+    /// Tokens per calendar month; the service default applies when unset.
+    #[serde(default)]
+    monthly_token_budget: Option<i64>,
+}
+
+fn default_role() -> Role {
+    Role::Evaluator
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    record: ApiKey,
+    /// The plaintext key. It is not stored and cannot be retrieved again.
+    key: String,
+}
+
+/// `POST /api-keys`: issues a database-managed key in the caller's tenant, with the evaluator
+/// role unless another is given.
+pub async fn create_api_key(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Json(payload): Json<NewApiKey>,
+) -> Result<(StatusCode, Extension<AuditResource>, Json<CreatedApiKey>), (StatusCode, String)> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Key name must not be empty".to_string()));
+    }
+
+    let key = auth::generate_key();
+    let record = ApiKey {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        key_hash: auth::hash_key(&key),
+        role: payload.role,
+        tenant_id: identity.tenant,
+        monthly_token_budget: payload.monthly_token_budget,
+        created_at: chrono::Utc::now(),
+        revoked_at: None,
+    };
+    state.storage.insert_api_key(&record).await.map_err(|e| {
+        error!("❌ Failed to store API key: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to store API key".to_string())
+    })?;
+
+    Ok((StatusCode::CREATED, Extension(AuditResource(record.id.to_string())), Json(CreatedApiKey { record, key })))
+}
+
+/// `GET /api-keys`: metadata of the tenant's keys, including revoked ones.
+pub async fn list_api_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+) -> Result<Json<Vec<ApiKey>>, (StatusCode, String)> {
+    state.storage.list_api_keys(&identity.tenant).await.map(Json).map_err(|e| {
+        error!("❌ Failed to list API keys: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to list API keys".to_string())
+    })
+}
+
+/// `DELETE /api-keys/{id}`: revokes the key; it stops authenticating immediately.
+pub async fn revoke_api_key(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state.storage.revoke_api_key(&identity.tenant, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("API key {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to revoke API key {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to revoke API key".to_string()))
+        }
+    }
+}
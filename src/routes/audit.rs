@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::auth::Identity;
+use crate::storage::AuditFilter;
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    key_id: Option<String>,
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// `GET /audit`: exports the tenant's audit log, oldest first, as JSON or (`format=csv`) a CSV download.
+pub async fn export_audit_log(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let filter = AuditFilter { from: query.from, to: query.to, key_id: query.key_id };
+    let entries = state.storage.list_audit_entries(&identity.tenant, &filter).await.map_err(|e| {
+        error!("❌ Failed to export audit log: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to export audit log".to_string())
+    })?;
+
+    match query.format {
+        ExportFormat::Json => Ok(Json(entries).into_response()),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for entry in &entries {
+                writer.serialize(entry).map_err(|e| {
+                    error!("❌ Failed to write audit CSV: {:?}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to export audit log".to_string())
+                })?;
+            }
+            let body = writer.into_inner().map_err(|e| {
+                error!("❌ Failed to write audit CSV: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to export audit log".to_string())
+            })?;
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"audit-log.csv\""),
+                ],
+                body,
+            )
+                .into_response())
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Serialize;
+use serde_json::Map;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::upload::{multipart_error, read_bytes};
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::batch::{self, RowResult};
+use crate::jobs;
+use crate::request_id::RequestId;
+use crate::storage::{Job, JobStatus};
+use crate::AppState;
+
+/// Where a batch is, row by row.
+#[derive(Debug, Serialize)]
+pub struct BatchStatus {
+    id: Uuid,
+    /// Whether every row has succeeded or failed, so the results file is complete.
+    finished: bool,
+    total: usize,
+    queued: usize,
+    running: usize,
+    succeeded: usize,
+    failed: usize,
+    rows: Vec<RowStatus>,
+}
+
+#[derive(Debug, Serialize)]
+struct RowStatus {
+    /// Line number in the CSV, counting the header.
+    row: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    job_id: Uuid,
+    status: JobStatus,
+    /// The stored evaluation, once the row has succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    evaluation_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    risks: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl BatchStatus {
+    fn new(id: Uuid, jobs: Vec<Job>) -> Self {
+        let count = |status: JobStatus| jobs.iter().filter(|job| job.status == status).count();
+        let (queued, running, succeeded, failed) =
+            (count(JobStatus::Queued), count(JobStatus::Running), count(JobStatus::Succeeded), count(JobStatus::Failed));
+        let rows = jobs
+            .into_iter()
+            .map(|job| {
+                let result = RowResult::of(&job);
+                RowStatus {
+                    row: job.batch_row,
+                    name: job.label,
+                    job_id: job.id,
+                    status: job.status,
+                    evaluation_id: result.as_ref().map(|result| result.id),
+                    risks: result.map(|result| result.risks.len()),
+                    error: job.error,
+                }
+            })
+            .collect::<Vec<_>>();
+        Self { id, finished: queued + running == 0, total: rows.len(), queued, running, succeeded, failed, rows }
+    }
+}
+
+/// `POST /evaluate/batch`: evaluates every row of a CSV uploaded as the `file` part, one
+/// project description per row in a `description` column, with an optional `name` column to
+/// tell the results apart. Columns named after other `POST /evaluate` fields set them per row,
+/// e.g. `provider` or `tags` (`;`-separated), and an optional `options` part sets them for all
+/// rows as for `POST /evaluate/upload`. The rows run as batch-priority jobs, a few at a time;
+/// answers `202 Accepted` with the batch right away. Poll `GET /batches/{id}` for each row's
+/// status and download `GET /batches/{id}/results` once it's finished.
+pub async fn submit_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Extension<AuditResource>, Json<BatchStatus>), (StatusCode, String)> {
+    let max_bytes = state.upload_max_bytes;
+    let mut csv = None;
+    let mut options = Map::new();
+    while let Some(field) = multipart.next_field().await.map_err(|e| multipart_error(e, max_bytes))? {
+        match field.name() {
+            Some("file") => {
+                let bytes = read_bytes(field, max_bytes).await?;
+                let text = String::from_utf8(bytes)
+                    .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, "The CSV isn't UTF-8".to_string()))?;
+                // Spreadsheets often save UTF-8 with a byte order mark.
+                csv = Some(text.trim_start_matches('\u{feff}').to_string());
+            }
+            Some("options") => {
+                let text = field.text().await.map_err(|e| multipart_error(e, max_bytes))?;
+                options = serde_json::from_str(&text)
+                    .map_err(|e| (StatusCode::BAD_REQUEST, format!("The options part isn't a JSON object: {}", e)))?;
+            }
+            other => return Err((StatusCode::BAD_REQUEST, format!("Unexpected part '{}'", other.unwrap_or_default()))),
+        }
+    }
+    let csv = csv.ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing the file part".to_string()))?;
+    if options.contains_key("description") {
+        return Err((StatusCode::BAD_REQUEST, "The descriptions come from the CSV; leave it out".to_string()));
+    }
+    let rows = state.batches.parse(&csv, &options).map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+
+    let batch_id = Uuid::new_v4();
+    let mut jobs = Vec::new();
+    let mut runnable = Vec::new();
+    for row in rows {
+        let job = batch::row_job(&identity, batch_id, &row);
+        jobs::insert(&state, &job).await.map_err(|e| {
+            error!("❌ Failed to store batch job: {:?}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to queue the batch".to_string())
+        })?;
+        if let Ok(payload) = row.request {
+            runnable.push((job.clone(), payload));
+        }
+        jobs.push(job);
+    }
+    info!(%batch_id, rows = jobs.len(), invalid = jobs.len() - runnable.len(), "📥 Queued batch");
+    state.batches.spawn(state.clone(), identity, request_id, runnable);
+
+    Ok((StatusCode::ACCEPTED, Extension(AuditResource(batch_id.to_string())), Json(BatchStatus::new(batch_id, jobs))))
+}
+
+/// `GET /batches/{id}`: the batch's progress and each row's status.
+pub async fn get_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BatchStatus>, (StatusCode, String)> {
+    let jobs = load_batch(&state, &identity, id).await?;
+    Ok(Json(BatchStatus::new(id, jobs)))
+}
+
+/// `GET /batches/{id}/results`: a CSV download with a line per risk found, in row order.
+/// Rows still running, failed or without risks get a line with their status, so it can be
+/// downloaded before the batch has finished too.
+pub async fn batch_results(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, (StatusCode, String)> {
+    let jobs = load_batch(&state, &identity, id).await?;
+    let body = batch::results_csv(&jobs).map_err(|e| {
+        error!("❌ Failed to write batch results CSV: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to export the batch results".to_string())
+    })?;
+    let disposition = format!("attachment; filename=\"batch-{}.csv\"", id);
+    Ok(([(header::CONTENT_TYPE, "text/csv".to_string()), (header::CONTENT_DISPOSITION, disposition)], body).into_response())
+}
+
+async fn load_batch(state: &AppState, identity: &Identity, id: Uuid) -> Result<Vec<Job>, (StatusCode, String)> {
+    match state.storage.list_batch_jobs(&identity.tenant, id).await {
+        Ok(jobs) if jobs.is_empty() => Err((StatusCode::NOT_FOUND, format!("Batch {} not found", id))),
+        Ok(jobs) => Ok(jobs),
+        Err(e) => {
+            error!("❌ Failed to load batch {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load the batch".to_string()))
+        }
+    }
+}
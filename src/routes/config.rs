@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use tracing::error;
+
+use crate::config::Profile;
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct ReloadReport {
+    profile: Profile,
+    /// The files the settings were read from, base first.
+    files: Vec<String>,
+    /// Tried in order for requests that don't pick a provider.
+    provider_chain: Vec<&'static str>,
+}
+
+/// `POST /config/reload`: applies changes to the config files without a restart, the same as
+/// when the file watcher notices them. A configuration that doesn't load is a 422, and the
+/// current one stays in place.
+pub async fn reload_config(State(state): State<Arc<AppState>>) -> Result<Json<ReloadReport>, (StatusCode, String)> {
+    let config = state.reloader.reload(&state).await.map_err(|e| {
+        error!("❌ Failed to reload configuration, keeping the current one: {:?}", e);
+        (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", e))
+    })?;
+    Ok(Json(ReloadReport {
+        profile: config.profile,
+        files: config.files,
+        provider_chain: state.providers.chain(None).iter().map(|provider| provider.name()).collect(),
+    }))
+}
@@ -0,0 +1,117 @@
+use axum::{response::Html, Json};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::analysis::OnTimeout;
+use crate::cache::CacheStatus;
+use crate::cost::{CostExposure, CostRange, ProjectBudget};
+use crate::documents::SourceDocument;
+use crate::evidence::Evidence;
+use crate::extraction::ParsePath;
+use crate::problem::Problem;
+use crate::providers::TokenUsage;
+use crate::rating::Rating;
+use crate::severity::Severity;
+use crate::simulation::{HistogramBin, Simulation};
+use crate::storage::{Evaluation, Job, JobPriority, JobStatus};
+use crate::{RiskItem, RiskRequest, RiskResponse, UsageReport};
+
+/// The evaluation API as an OpenAPI 3 document, generated from the handlers' `#[utoipa::path]`
+/// annotations and the request and response types, so clients can be generated from it.
+/// Administrative endpoints (API keys, usage, audit, configuration) aren't described.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "AI Risk Evaluator", description = "Evaluates project descriptions for delivery, technical and business risks."),
+    paths(
+        crate::evaluate_risks,
+        super::stream::evaluate_stream,
+        super::upload::evaluate_upload,
+        super::url::evaluate_url,
+        super::repo::evaluate_repo,
+        super::jobs::submit_evaluation,
+        super::jobs::get_job,
+        super::evaluations::list_evaluations,
+        super::evaluations::get_evaluation,
+        super::evaluations::export_evaluation,
+        super::evaluations::delete_evaluation,
+        super::simulate::simulate,
+    ),
+    components(schemas(
+        RiskRequest,
+        RiskResponse,
+        RiskItem,
+        UsageReport,
+        TokenUsage,
+        ProjectBudget,
+        CostRange,
+        CostExposure,
+        Evidence,
+        SourceDocument,
+        Severity,
+        Rating,
+        ParsePath,
+        OnTimeout,
+        CacheStatus,
+        Evaluation,
+        super::evaluations::EvaluationPage,
+        super::simulate::SimulationRequest,
+        Simulation,
+        HistogramBin,
+        super::jobs::AsyncRequest,
+        Job,
+        JobStatus,
+        JobPriority,
+        Problem,
+    )),
+    modifiers(&Credentials),
+    security(("api_key" = []), ("bearer" = [])),
+    tags(
+        (name = "evaluations", description = "Evaluate projects and read stored evaluations"),
+        (name = "jobs", description = "Evaluations queued to run in the background"),
+    )
+)]
+struct ApiDoc;
+
+/// Either an `X-Api-Key` or an `Authorization: Bearer` token: an access token from
+/// `POST /oauth/token` or a JWT from the configured identity provider.
+struct Credentials;
+
+impl Modify for Credentials {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("api_key", SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))));
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+/// Swagger UI, loaded from unpkg, pointed at the spec next to it.
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>AI Risk Evaluator API</title>
+<link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+<script>
+window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+</script>
+</body>
+</html>
+"##;
+
+/// `GET /openapi.json`
+pub async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// `GET /docs`: Swagger UI for `/openapi.json`, to read the API and try it out with a key.
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI)
+}
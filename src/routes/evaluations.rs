@@ -0,0 +1,417 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::auth::Identity;
+use crate::conditional::ETag;
+use crate::negotiate::{Encoded, FormatQuery, Negotiated};
+use crate::problem::Problem;
+use crate::report;
+use crate::severity::Severity;
+use crate::storage::{self, Cursor, Evaluation, EvaluationFilter};
+use crate::AppState;
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Query string of `GET /evaluations`.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Page size, 1–100 (default 20).
+    limit: Option<u32>,
+    /// `next_cursor` from the previous page.
+    cursor: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    severity: Option<Severity>,
+    category: Option<String>,
+    tag: Option<String>,
+    project_id: Option<Uuid>,
+    /// Include archived evaluations, which are hidden by default.
+    #[serde(default)]
+    include_archived: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EvaluationPage {
+    pub(super) evaluations: Vec<Evaluation>,
+    /// Pass back as `cursor` to fetch the next page; absent on the last one.
+    pub(super) next_cursor: Option<String>,
+}
+
+/// `GET /evaluations`: stored evaluations, newest first, one page at a time.
+#[utoipa::path(
+    get,
+    path = "/evaluations",
+    tag = "evaluations",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "A page of evaluations", body = EvaluationPage),
+        (status = 400, description = "Invalid limit or cursor", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_evaluations(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<EvaluationPage>, (StatusCode, String)> {
+    let filter = EvaluationFilter {
+        from: query.from,
+        to: query.to,
+        severity: query.severity,
+        category: query.category,
+        tag: query.tag,
+        project_id: query.project_id,
+        include_archived: query.include_archived,
+    };
+    evaluation_page(&state, &identity, &filter, query.cursor.as_deref(), query.limit).await.map(Json)
+}
+
+/// A page of the tenant's evaluations matching `filter`, of `limit` (1–100, default 20) after
+/// `cursor`; shared with the GraphQL API.
+pub(super) async fn evaluation_page(
+    state: &AppState,
+    identity: &Identity,
+    filter: &EvaluationFilter,
+    cursor: Option<&str>,
+    limit: Option<u32>,
+) -> Result<EvaluationPage, (StatusCode, String)> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err((StatusCode::BAD_REQUEST, format!("limit must be between 1 and {}", MAX_PAGE_SIZE)));
+    }
+    let after = cursor
+        .map(Cursor::decode)
+        .transpose()
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))?;
+
+    // One extra row tells us whether another page follows.
+    let mut evaluations = state
+        .storage
+        .list_evaluations(&identity.tenant, filter, after.as_ref(), limit + 1)
+        .await
+        .map_err(|e| {
+            error!("❌ Failed to list evaluations: {:?}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to list evaluations".to_string())
+        })?;
+
+    let next_cursor = if evaluations.len() > limit as usize {
+        evaluations.truncate(limit as usize);
+        evaluations.last().map(|last| Cursor::after(last).encode())
+    } else {
+        None
+    };
+
+    Ok(EvaluationPage { evaluations, next_cursor })
+}
+
+/// `GET /evaluations/{id}`: the stored description, risks and run metadata; or, asked for
+/// CSV or Markdown with `Accept` or `?format=`, the risks as a CSV download or a report.
+/// Answers 304 when `If-None-Match` has the current `ETag`, which changes with the tags.
+#[utoipa::path(
+    get,
+    path = "/evaluations/{id}",
+    tag = "evaluations",
+    params(
+        ("id" = Uuid, Path, description = "Evaluation ID"),
+        FormatQuery,
+        ("If-None-Match" = Option<String>, Header, description = "The `ETag` of a copy already held"),
+    ),
+    responses(
+        (status = 200, description = "The evaluation", content(
+            (Evaluation = "application/json"),
+            (String = "text/csv"),
+            (String = "text/markdown"),
+        )),
+        (status = 304, description = "Unchanged since the `ETag` sent in `If-None-Match`"),
+        (status = 404, description = "No such evaluation", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_evaluation(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    Negotiated(format): Negotiated,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, format.extension());
+    if etag.matches(&headers) {
+        return Ok(negotiated_not_modified(etag));
+    }
+    Ok(etag.attach(Encoded(format, evaluation)))
+}
+
+/// `GET /evaluations/{id}/export`: downloads the evaluation, as JSON, (`format=csv`) a CSV
+/// with a line per risk for pasting into a spreadsheet, or (`format=md`) Markdown.
+#[utoipa::path(
+    get,
+    path = "/evaluations/{id}/export",
+    tag = "evaluations",
+    params(
+        ("id" = Uuid, Path, description = "Evaluation ID"),
+        FormatQuery,
+        ("If-None-Match" = Option<String>, Header, description = "The `ETag` of a copy already held"),
+    ),
+    responses(
+        (status = 200, description = "The evaluation as a download", content(
+            (Evaluation = "application/json"),
+            (String = "text/csv"),
+            (String = "text/markdown"),
+        )),
+        (status = 304, description = "Unchanged since the `ETag` sent in `If-None-Match`"),
+        (status = 404, description = "No such evaluation", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn export_evaluation(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    Negotiated(format): Negotiated,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, format.extension());
+    if etag.matches(&headers) {
+        return Ok(negotiated_not_modified(etag));
+    }
+    let disposition = format!("attachment; filename=\"evaluation-{}.{}\"", id, format.extension());
+    Ok(etag.attach(([(header::CONTENT_DISPOSITION, disposition)], Encoded(format, evaluation))))
+}
+
+/// `GET /evaluations/{id}/report.pdf`: the evaluation as a printable report, with a summary
+/// of the project, a likelihood × impact matrix and each risk's details and mitigation.
+pub async fn report_pdf(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, "pdf");
+    if etag.matches(&headers) {
+        return Ok(etag.not_modified());
+    }
+    let rendered = tokio::task::spawn_blocking(move || report::pdf::render(&evaluation))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|rendered| rendered);
+    let body = rendered.map_err(|e| {
+        error!("❌ Failed to render report for evaluation {}: {:?}", id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to render the report".to_string())
+    })?;
+    let disposition = format!("inline; filename=\"risk-report-{}.pdf\"", id);
+    Ok(etag.attach(([(header::CONTENT_TYPE, "application/pdf".to_string()), (header::CONTENT_DISPOSITION, disposition)], body)))
+}
+
+/// `GET /evaluations/{id}/report.md`: the evaluation as Markdown, a summary table and a
+/// section per risk, ready to paste into Confluence, an issue or a pull request.
+pub async fn report_markdown(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, "report.md");
+    if etag.matches(&headers) {
+        return Ok(etag.not_modified());
+    }
+    let disposition = format!("inline; filename=\"risk-report-{}.md\"", id);
+    Ok(etag.attach((
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        report::markdown::render(&evaluation),
+    )))
+}
+
+/// `GET /evaluations/{id}/report.html`: the evaluation as a standalone web page, styles and
+/// script inline, with a severity chart and a sortable risk table; made to be emailed.
+pub async fn report_html(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, "html");
+    if etag.matches(&headers) {
+        return Ok(etag.not_modified());
+    }
+    let disposition = format!("inline; filename=\"risk-report-{}.html\"", id);
+    Ok(etag.attach((
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        report::html::render(&evaluation),
+    )))
+}
+
+/// `GET /evaluations/{id}/matrix.svg`: the risks plotted on the likelihood × impact grid, for
+/// dashboards and documents to embed as an image.
+pub async fn matrix_svg(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, "svg");
+    if etag.matches(&headers) {
+        return Ok(etag.not_modified());
+    }
+    Ok(etag.attach(([(header::CONTENT_TYPE, "image/svg+xml")], report::svg::render(&evaluation))))
+}
+
+/// `GET /evaluations/{id}/report.xlsx`: the evaluation as an Excel workbook, a summary
+/// sheet with severity counts and the risk matrix, then a sheet per category.
+pub async fn report_xlsx(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let evaluation = load_evaluation(&state, &identity, id).await?;
+    let etag = ETag::of(&evaluation, "xlsx");
+    if etag.matches(&headers) {
+        return Ok(etag.not_modified());
+    }
+    let rendered = tokio::task::spawn_blocking(move || report::xlsx::render(&evaluation))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|rendered| rendered);
+    let body = rendered.map_err(|e| {
+        error!("❌ Failed to render workbook for evaluation {}: {:?}", id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to render the workbook".to_string())
+    })?;
+    let disposition = format!("attachment; filename=\"risk-report-{}.xlsx\"", id);
+    Ok(etag.attach((
+        [
+            (header::CONTENT_TYPE, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )))
+}
+
+/// A 304 for a representation chosen by `Accept`, which caches need to know it varies by.
+fn negotiated_not_modified(etag: ETag) -> Response {
+    let mut response = etag.not_modified();
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+pub(super) async fn load_evaluation(state: &AppState, identity: &Identity, id: Uuid) -> Result<Evaluation, (StatusCode, String)> {
+    match state.storage.get_evaluation(&identity.tenant, id).await {
+        Ok(Some(evaluation)) => Ok(evaluation),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("Evaluation {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to load evaluation {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load evaluation".to_string()))
+        }
+    }
+}
+
+/// `DELETE /evaluations/{id}`: soft delete; the row is hard-deleted later by the purge job.
+#[utoipa::path(
+    delete,
+    path = "/evaluations/{id}",
+    tag = "evaluations",
+    params(("id" = Uuid, Path, description = "Evaluation ID")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such evaluation", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_evaluation(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state.storage.delete_evaluation(&identity.tenant, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("Evaluation {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to delete evaluation {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete evaluation".to_string()))
+        }
+    }
+}
+
+/// `POST /evaluations/{id}/archive`: hides the evaluation from listings.
+pub async fn archive_evaluation(
+    state: State<Arc<AppState>>,
+    identity: Extension<Identity>,
+    id: Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    set_archived(state, identity, id, true).await
+}
+
+/// `POST /evaluations/{id}/unarchive`
+pub async fn unarchive_evaluation(
+    state: State<Arc<AppState>>,
+    identity: Extension<Identity>,
+    id: Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    set_archived(state, identity, id, false).await
+}
+
+async fn set_archived(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    archived: bool,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state.storage.set_archived(&identity.tenant, id, archived).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("Evaluation {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to update evaluation {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to update evaluation".to_string()))
+        }
+    }
+}
+
+/// Body of `PATCH /evaluations/{id}/tags`; additions are applied before removals.
+#[derive(Debug, Deserialize)]
+pub struct TagsPatch {
+    #[serde(default)]
+    add: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagsResponse {
+    tags: Vec<String>,
+}
+
+/// `PATCH /evaluations/{id}/tags`
+pub async fn update_tags(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<TagsPatch>,
+) -> Result<Json<TagsResponse>, (StatusCode, String)> {
+    let internal = |e: anyhow::Error| {
+        error!("❌ Failed to update tags of evaluation {}: {:?}", id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update tags".to_string())
+    };
+    let not_found = || (StatusCode::NOT_FOUND, format!("Evaluation {} not found", id));
+
+    let evaluation = state.storage.get_evaluation(&identity.tenant, id).await.map_err(internal)?.ok_or_else(not_found)?;
+    let tags = storage::edit_tags(evaluation.tags, &patch.add, &patch.remove).map_err(|msg| (StatusCode::BAD_REQUEST, msg))?;
+
+    if !state.storage.set_tags(&identity.tenant, id, &tags).await.map_err(internal)? {
+        return Err(not_found());
+    }
+    Ok(Json(TagsResponse { tags }))
+}
@@ -0,0 +1,574 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    Context, EmptySubscription, Enum, ErrorExtensions, InputObject, Object, Schema, SimpleObject,
+};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Utc};
+use tracing::error;
+use uuid::Uuid;
+
+use super::evaluations::evaluation_page;
+use crate::auth::{self, Identity, Scope};
+use crate::cost::{CostExposure, CostRange, ProjectBudget};
+use crate::documents::SourceDocument;
+use crate::problem;
+use crate::request_id::RequestId;
+use crate::storage::{Evaluation, EvaluationFilter, Project};
+use crate::{evaluate, AppState, RiskItem, RiskRequest, RiskResponse};
+
+/// Deep enough for project → evaluations → risks → evidence with room to spare, shallow
+/// enough that nobody walks project → evaluation → project → … to load the whole tenant.
+const MAX_DEPTH: usize = 10;
+const MAX_COMPLEXITY: usize = 1_000;
+
+pub type ApiSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn schema() -> ApiSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// Who is asking, handed to every resolver.
+struct Session {
+    state: Arc<AppState>,
+    identity: Identity,
+    request_id: RequestId,
+}
+
+/// `POST /graphql`: queries over the caller's stored evaluations, projects and their risks,
+/// and an `evaluate` mutation. Reads need `evaluations:read`, the mutation `evaluate:write`.
+/// Errors are reported in the response's `errors`, each with the `code` and `status` the
+/// REST endpoints would have answered with.
+pub async fn graphql(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let schema = state.graphql.clone();
+    let request = request.data(Session { state, identity, request_id });
+    Json(schema.execute(request).await)
+}
+
+fn session<'a>(ctx: &Context<'a>) -> &'a Session {
+    ctx.data_unchecked::<Session>()
+}
+
+/// A REST-style error as a GraphQL one.
+fn failure((status, message): (StatusCode, String)) -> async_graphql::Error {
+    async_graphql::Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", problem::code(status));
+        extensions.set("status", status.as_u16());
+    })
+}
+
+fn internal(what: &str) -> async_graphql::Error {
+    failure((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load {}", what)))
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "Severity", remote = "crate::severity::Severity")]
+enum SeverityLevel {
+    Negligible,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "ParsePath", remote = "crate::extraction::ParsePath")]
+enum ParseStep {
+    Direct,
+    Repaired,
+    Reprompted,
+    Partial,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "OnTimeout", remote = "crate::analysis::OnTimeout")]
+enum TimeoutMode {
+    Partial,
+    Error,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "CacheStatus", remote = "crate::cache::CacheStatus")]
+enum CacheResult {
+    Hit,
+    Miss,
+    Coalesced,
+}
+
+/// Narrows a listing like the query string of `GET /evaluations`.
+#[derive(InputObject, Default)]
+struct EvaluationFilterInput {
+    /// Inclusive lower bound on `createdAt`.
+    from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `createdAt`.
+    to: Option<DateTime<Utc>>,
+    /// Only evaluations with at least one risk of this severity.
+    severity: Option<SeverityLevel>,
+    /// Only evaluations with at least one risk in this category, ignoring case.
+    category: Option<String>,
+    tag: Option<String>,
+    project_id: Option<Uuid>,
+    #[graphql(default)]
+    include_archived: bool,
+}
+
+impl EvaluationFilterInput {
+    fn into_filter(self) -> EvaluationFilter {
+        EvaluationFilter {
+            from: self.from,
+            to: self.to,
+            severity: self.severity.map(Into::into),
+            category: self.category,
+            tag: self.tag,
+            project_id: self.project_id,
+            include_archived: self.include_archived,
+        }
+    }
+}
+
+async fn list(ctx: &Context<'_>, filter: EvaluationFilter, first: Option<u32>, after: Option<String>) -> async_graphql::Result<EvaluationPage> {
+    let session = session(ctx);
+    let page = evaluation_page(&session.state, &session.identity, &filter, after.as_deref(), first).await.map_err(failure)?;
+    Ok(EvaluationPage {
+        nodes: page.evaluations.into_iter().map(EvaluationNode).collect(),
+        next_cursor: page.next_cursor,
+    })
+}
+
+async fn load_project(ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<ProjectNode>> {
+    let session = session(ctx);
+    match session.state.storage.get_project(&session.identity.tenant, id).await {
+        Ok(project) => Ok(project.map(ProjectNode)),
+        Err(e) => {
+            error!("❌ Failed to load project {}: {:?}", id, e);
+            Err(internal("project"))
+        }
+    }
+}
+
+/// The risks at or above `min_severity` and, if given, in `category` (ignoring case).
+fn risks(risks: &[RiskItem], min_severity: Option<SeverityLevel>, category: Option<&str>) -> Vec<RiskNode> {
+    let min_severity = min_severity.map(crate::severity::Severity::from);
+    risks
+        .iter()
+        .filter(|risk| min_severity.is_none_or(|min| risk.severity >= min))
+        .filter(|risk| category.is_none_or(|category| risk.category.eq_ignore_ascii_case(category.trim())))
+        .cloned()
+        .map(RiskNode)
+        .collect()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A stored evaluation; null if there is none with this ID.
+    async fn evaluation(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<EvaluationNode>> {
+        let session = session(ctx);
+        match session.state.storage.get_evaluation(&session.identity.tenant, id).await {
+            Ok(evaluation) => Ok(evaluation.map(EvaluationNode)),
+            Err(e) => {
+                error!("❌ Failed to load evaluation {}: {:?}", id, e);
+                Err(internal("evaluation"))
+            }
+        }
+    }
+
+    /// Stored evaluations, newest first, `first` (1–100, default 20) at a time; pass
+    /// `nextCursor` as `after` for the next page.
+    async fn evaluations(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: EvaluationFilterInput,
+        first: Option<u32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<EvaluationPage> {
+        list(ctx, filter.into_filter(), first, after).await
+    }
+
+    async fn project(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<ProjectNode>> {
+        load_project(ctx, id).await
+    }
+
+    async fn projects(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<ProjectNode>> {
+        let session = session(ctx);
+        match session.state.storage.list_projects(&session.identity.tenant).await {
+            Ok(projects) => Ok(projects.into_iter().map(ProjectNode).collect()),
+            Err(e) => {
+                error!("❌ Failed to list projects: {:?}", e);
+                Err(internal("projects"))
+            }
+        }
+    }
+}
+
+#[derive(InputObject)]
+#[graphql(name = "DocumentInput")]
+struct DocumentInput {
+    name: String,
+    text: String,
+}
+
+#[derive(InputObject)]
+struct BudgetInput {
+    total: f64,
+    contingency: Option<f64>,
+    /// ISO 4217 code; the configured default when unset.
+    currency: Option<String>,
+}
+
+/// The fields of a `POST /evaluate` request.
+#[derive(InputObject)]
+struct EvaluateInput {
+    /// The project brief. Optional when `documents` are sent, as an overview of them.
+    #[graphql(default)]
+    description: String,
+    #[graphql(default)]
+    documents: Vec<DocumentInput>,
+    provider: Option<String>,
+    model: Option<String>,
+    min_confidence: Option<f32>,
+    max_risks: Option<usize>,
+    min_severity: Option<SeverityLevel>,
+    #[graphql(default)]
+    tags: Vec<String>,
+    project_id: Option<Uuid>,
+    timeout_ms: Option<u64>,
+    on_timeout: Option<TimeoutMode>,
+    /// Asks for a cost impact range per risk, estimated against these figures.
+    budget: Option<BudgetInput>,
+}
+
+impl EvaluateInput {
+    fn into_request(self) -> RiskRequest {
+        RiskRequest {
+            description: self.description,
+            documents: self.documents.into_iter().map(|document| SourceDocument { name: document.name, text: document.text }).collect(),
+            provider: self.provider,
+            model: self.model,
+            min_confidence: self.min_confidence,
+            max_risks: self.max_risks,
+            min_severity: self.min_severity.map(Into::into),
+            tags: self.tags,
+            project_id: self.project_id,
+            timeout_ms: self.timeout_ms,
+            on_timeout: self.on_timeout.map(Into::into).unwrap_or_default(),
+            budget: self.budget.map(|budget| ProjectBudget {
+                total: budget.total,
+                contingency: budget.contingency,
+                currency: budget.currency,
+            }),
+        }
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Runs and stores an evaluation like `POST /evaluate`, answering once it's complete.
+    async fn evaluate(&self, ctx: &Context<'_>, input: EvaluateInput) -> async_graphql::Result<EvaluationRun> {
+        let session = session(ctx);
+        auth::check(&session.identity, Scope::EvaluateWrite).map_err(failure)?;
+        let payload = input.into_request();
+        let response = evaluate(&session.state, &session.identity, &session.request_id, &payload).await.map_err(failure)?;
+        Ok(EvaluationRun(response))
+    }
+}
+
+#[derive(SimpleObject)]
+struct EvaluationPage {
+    nodes: Vec<EvaluationNode>,
+    /// Pass as `after` to fetch the next page; null on the last one.
+    next_cursor: Option<String>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "TokenUsage")]
+struct Usage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+struct EvaluationNode(Evaluation);
+
+#[Object(name = "Evaluation")]
+impl EvaluationNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    /// The risks, optionally only those at or above `minSeverity` or in `category`.
+    async fn risks(&self, min_severity: Option<SeverityLevel>, category: Option<String>) -> Vec<RiskNode> {
+        risks(&self.0.risks, min_severity, category.as_deref())
+    }
+
+    async fn provider(&self) -> &str {
+        &self.0.provider
+    }
+
+    async fn model(&self) -> Option<&str> {
+        self.0.model.as_deref()
+    }
+
+    async fn parse_path(&self) -> Option<ParseStep> {
+        self.0.parse_path.map(Into::into)
+    }
+
+    async fn latency_ms(&self) -> i64 {
+        self.0.latency_ms
+    }
+
+    async fn usage(&self) -> Option<Usage> {
+        self.0.usage.map(|usage| Usage { prompt_tokens: usage.prompt_tokens, completion_tokens: usage.completion_tokens })
+    }
+
+    async fn archived(&self) -> bool {
+        self.0.archived
+    }
+
+    async fn tags(&self) -> &[String] {
+        &self.0.tags
+    }
+
+    /// `X-Request-Id` of the call that produced it.
+    async fn request_id(&self) -> Option<&str> {
+        self.0.request_id.as_deref()
+    }
+
+    async fn project(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<ProjectNode>> {
+        match self.0.project_id {
+            Some(id) => load_project(ctx, id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// What the `evaluate` mutation answers: the run's risks and how they were produced, and the
+/// evaluation as stored.
+struct EvaluationRun(RiskResponse);
+
+#[Object]
+impl EvaluationRun {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn risks(&self, min_severity: Option<SeverityLevel>, category: Option<String>) -> Vec<RiskNode> {
+        risks(&self.0.risks, min_severity, category.as_deref())
+    }
+
+    async fn provider(&self) -> &str {
+        &self.0.provider
+    }
+
+    async fn model(&self) -> Option<&str> {
+        self.0.model.as_deref()
+    }
+
+    async fn parse_path(&self) -> ParseStep {
+        self.0.parse_path.into()
+    }
+
+    /// Set when `timeoutMs` cut the model off and `risks` is what it had finished by then.
+    async fn partial(&self) -> bool {
+        self.0.partial
+    }
+
+    /// Set when the project text was over the input token limit and only its beginning was
+    /// evaluated.
+    async fn truncated(&self) -> bool {
+        self.0.truncated
+    }
+
+    async fn usage(&self) -> Option<Usage> {
+        self.0.usage.map(|usage| Usage { prompt_tokens: usage.tokens.prompt_tokens, completion_tokens: usage.tokens.completion_tokens })
+    }
+
+    /// In USD at list price; null for models without a known price.
+    async fn estimated_cost_usd(&self) -> Option<f64> {
+        self.0.usage.and_then(|usage| usage.estimated_cost_usd)
+    }
+
+    /// `HIT` when the risks are a recent evaluation of the same text, prompt and model,
+    /// `COALESCED` when they're those of an identical evaluation that was running already.
+    async fn cache(&self) -> CacheResult {
+        self.0.cache.into()
+    }
+
+    /// The risks' cost impact added up; null without a `budget`.
+    async fn cost_exposure(&self) -> Option<Exposure> {
+        self.0.cost_exposure.clone().map(Into::into)
+    }
+
+    /// The stored evaluation, for its description, tags and project.
+    async fn evaluation(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<EvaluationNode>> {
+        QueryRoot.evaluation(ctx, self.0.id).await
+    }
+}
+
+struct RiskNode(RiskItem);
+
+#[Object(name = "Risk")]
+impl RiskNode {
+    async fn severity(&self) -> SeverityLevel {
+        self.0.severity.into()
+    }
+
+    /// 0.0–10.0, present on numeric severity scales.
+    async fn score(&self) -> Option<f32> {
+        self.0.score
+    }
+
+    async fn category(&self) -> &str {
+        &self.0.category
+    }
+
+    async fn mitigation(&self) -> &str {
+        &self.0.mitigation
+    }
+
+    /// 1 (rare) to 5 (almost certain).
+    async fn likelihood(&self) -> Option<u8> {
+        self.0.likelihood.map(|rating| rating.value())
+    }
+
+    /// 1 (negligible) to 5 (severe).
+    async fn impact(&self) -> Option<u8> {
+        self.0.impact.map(|rating| rating.value())
+    }
+
+    /// 0.0–1.0; how strongly the description supports this risk.
+    async fn confidence(&self) -> f32 {
+        self.0.confidence
+    }
+
+    async fn evidence(&self) -> Vec<EvidenceNode> {
+        self.0
+            .evidence
+            .iter()
+            .map(|evidence| EvidenceNode {
+                quote: evidence.quote.clone(),
+                start: evidence.start,
+                end: evidence.end,
+                document: evidence.document.clone(),
+            })
+            .collect()
+    }
+
+    /// The project register entry that already tracks this risk.
+    async fn covered_by(&self) -> Option<&str> {
+        self.0.covered_by.as_deref()
+    }
+
+    /// The request document the risk comes from.
+    async fn document(&self) -> Option<&str> {
+        self.0.document.as_deref()
+    }
+
+    /// What the risk would cost if it occurs; null without a `budget`.
+    async fn cost_impact(&self) -> Option<CostImpact> {
+        self.0.cost_impact.clone().map(Into::into)
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "CostRange")]
+struct CostImpact {
+    min: f64,
+    likely: f64,
+    max: f64,
+    currency: String,
+}
+
+impl From<CostRange> for CostImpact {
+    fn from(range: CostRange) -> Self {
+        Self { min: range.min, likely: range.likely, max: range.max, currency: range.currency }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "CostExposure")]
+struct Exposure {
+    currency: String,
+    min: f64,
+    likely: f64,
+    max: f64,
+    likely_share_of_budget: f64,
+    exceeds_contingency: Option<bool>,
+    estimated_risks: usize,
+}
+
+impl From<CostExposure> for Exposure {
+    fn from(exposure: CostExposure) -> Self {
+        Self {
+            currency: exposure.currency,
+            min: exposure.min,
+            likely: exposure.likely,
+            max: exposure.max,
+            likely_share_of_budget: exposure.likely_share_of_budget,
+            exceeds_contingency: exposure.exceeds_contingency,
+            estimated_risks: exposure.estimated_risks,
+        }
+    }
+}
+
+/// A passage of the description that motivated a risk, with its character offsets when it
+/// was found verbatim.
+#[derive(SimpleObject)]
+#[graphql(name = "Evidence")]
+struct EvidenceNode {
+    quote: String,
+    start: Option<usize>,
+    end: Option<usize>,
+    document: Option<String>,
+}
+
+struct ProjectNode(Project);
+
+#[Object(name = "Project")]
+impl ProjectNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// The project's evaluations, paged like `Query.evaluations`.
+    async fn evaluations(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: EvaluationFilterInput,
+        first: Option<u32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<EvaluationPage> {
+        let filter = EvaluationFilter { project_id: Some(self.0.id), ..filter.into_filter() };
+        list(ctx, filter, first, after).await
+    }
+}
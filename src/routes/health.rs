@@ -0,0 +1,97 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::config::ServerConfig;
+use crate::AppState;
+
+const DEFAULT_PROVIDER_CHECK_SECS: u64 = 60;
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Remembers the last provider check, so frequent probes don't turn into a stream of calls to
+/// every provider's API.
+pub struct Readiness {
+    providers: Mutex<Option<(Instant, BTreeMap<&'static str, String>)>>,
+    provider_ttl: Duration,
+}
+
+impl Readiness {
+    /// `ready_provider_check_secs` (default 60) is how long a provider check is reused.
+    pub fn from_config(server: &ServerConfig) -> Self {
+        let secs = server.ready_provider_check_secs.unwrap_or(DEFAULT_PROVIDER_CHECK_SECS);
+        Self { providers: Mutex::new(None), provider_ttl: Duration::from_secs(secs) }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    ready: bool,
+    /// `ok`, or why the dependency isn't usable.
+    storage: String,
+    /// Only present when Redis is configured. Doesn't affect `ready`: while it's down the cache
+    /// misses and each replica applies the rate limits on its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redis: Option<String>,
+    /// Each provider in the default chain, in the same form.
+    providers: BTreeMap<&'static str, String>,
+}
+
+/// `GET /healthz`: answers as long as the process is serving requests.
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// `GET /readyz`: 200 once configuration is loaded, storage answers and at least one provider
+/// in the default chain accepts its credentials; 503 with the failing checks otherwise.
+pub async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, Json<ReadinessReport>) {
+    let storage = match tokio::time::timeout(CHECK_TIMEOUT, state.storage.ping()).await {
+        Ok(Ok(())) => "ok".to_string(),
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => "timed out".to_string(),
+    };
+    let redis = match &state.shared {
+        Some(shared) => Some(match tokio::time::timeout(CHECK_TIMEOUT, shared.ping()).await {
+            Ok(Ok(())) => "ok".to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "timed out".to_string(),
+        }),
+        None => None,
+    };
+    let providers = check_providers(&state).await;
+
+    let ready = storage == "ok" && providers.values().any(|status| status == "ok");
+    if !ready {
+        warn!(storage = %storage, redis = ?redis, providers = ?providers, "⚠️ Not ready");
+    }
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(ReadinessReport { ready, storage, redis, providers }))
+}
+
+async fn check_providers(state: &AppState) -> BTreeMap<&'static str, String> {
+    // Held across the check, so concurrent probes wait for one result instead of each calling out.
+    let mut cached = state.readiness.providers.lock().await;
+    if let Some((checked, results)) = cached.as_ref() {
+        if checked.elapsed() < state.readiness.provider_ttl {
+            return results.clone();
+        }
+    }
+
+    let mut results = BTreeMap::new();
+    for provider in state.providers.chain(None) {
+        let status = match tokio::time::timeout(CHECK_TIMEOUT, provider.check()).await {
+            Ok(Ok(())) => "ok".to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "timed out".to_string(),
+        };
+        results.insert(provider.name(), status);
+    }
+    *cached = Some((Instant::now(), results.clone()));
+    results
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+use tracing::{error, info};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::jobs::{self, QueuedJob};
+use crate::problem::Problem;
+use crate::request_id::RequestId;
+use crate::storage::{Job, JobPriority, JobStatus};
+use crate::{prepare_evaluation, AppState, RiskRequest};
+
+/// Body of `POST /evaluate/async`: an `/evaluate` request plus an optional webhook.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AsyncRequest {
+    #[serde(flatten)]
+    request: RiskRequest,
+    /// Receives the `RiskResponse` as a signed POST once the job succeeds.
+    callback_url: Option<String>,
+    /// `interactive` (the default) or `batch`; batch jobs wait while interactive ones run.
+    #[serde(default)]
+    priority: JobPriority,
+}
+
+/// `POST /evaluate/async`: validates the request like `POST /evaluate`, queues it and answers
+/// `202 Accepted` with the job right away. Poll `GET /jobs/{id}` for the result, or pass a
+/// `callback_url` to have it delivered.
+#[utoipa::path(
+    post,
+    path = "/evaluate/async",
+    tag = "jobs",
+    request_body = AsyncRequest,
+    responses(
+        (status = 202, description = "The queued job", body = Job),
+        (status = 400, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "The `callback_url` resolves to an internal address", body = Problem, content_type = "application/problem+json"),
+        (status = 503, description = "The queue is full", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn submit_evaluation(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Json(AsyncRequest { request: payload, callback_url, priority }): Json<AsyncRequest>,
+) -> Result<(StatusCode, Extension<AuditResource>, Json<Job>), (StatusCode, String)> {
+    let callback_url = match (callback_url, &state.webhooks) {
+        (Some(_), None) => {
+            return Err((StatusCode::BAD_REQUEST, "Webhooks are not enabled on this server".to_string()));
+        }
+        (Some(url), Some(webhooks)) => Some(webhooks.check_url(&url).await?),
+        (None, _) => None,
+    };
+    let prepared = prepare_evaluation(&state, &identity, &request_id, &payload).await?;
+    let evaluation_id = prepared.id;
+
+    let mut job = Job::queued(&identity, priority);
+    jobs::insert(&state, &job).await.map_err(|e| {
+        error!("❌ Failed to store job: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to queue evaluation".to_string())
+    })?;
+
+    let queued = QueuedJob { job: job.clone(), identity, payload, prepared, callback_url, slot: None };
+    if let Err(message) = state.jobs.enqueue(queued) {
+        job.error = Some(message.clone());
+        jobs::update(&state, &mut job, JobStatus::Failed).await;
+        return Err((StatusCode::SERVICE_UNAVAILABLE, message));
+    }
+    info!(job_id = %job.id, %evaluation_id, priority = priority.as_str(), "📥 Queued job");
+
+    Ok((StatusCode::ACCEPTED, Extension(AuditResource(job.id.to_string())), Json(job)))
+}
+
+/// `GET /jobs/{id}`: the job's status, with the `RiskResponse` once it has succeeded.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = Uuid, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "The job, with its result once it has succeeded", body = Job),
+        (status = 404, description = "No such job", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, (StatusCode, String)> {
+    match state.storage.get_job(&identity.tenant, id).await {
+        Ok(Some(job)) => Ok(Json(job)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("Job {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to load job {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load job".to_string()))
+        }
+    }
+}
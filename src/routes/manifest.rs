@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Multipart, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Serialize;
+use serde_json::Map;
+use tracing::info;
+
+use super::upload::{multipart_error, read_bytes, with_description};
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::request_id::RequestId;
+use crate::supply_chain::{self, Ecosystem, Finding, ManifestFile};
+use crate::{evaluate, AppState, RiskResponse};
+
+#[derive(Debug, Serialize)]
+pub struct ManifestResponse {
+    #[serde(flatten)]
+    evaluation: RiskResponse,
+    ecosystem: Ecosystem,
+    dependencies: usize,
+    /// How many packages the lockfile resolves; absent without one.
+    locked_packages: Option<usize>,
+    /// What static analysis of the manifest found, most severe first. The model is given
+    /// these too, so `risks` builds on them with mitigations.
+    findings: Vec<Finding>,
+}
+
+/// `POST /evaluate/manifest`: evaluates the supply-chain risks of a dependency manifest,
+/// `Cargo.toml`, `package.json` or `requirements.txt`, uploaded as a `file` part named after
+/// it. A second `file` part can hold the lockfile (`Cargo.lock`, `package-lock.json` or
+/// `poetry.lock`) so the size of the whole tree is known. Unmaintained packages, pre-1.0 and
+/// unbounded requirements, git and URL sources and heavy trees are flagged statically; the
+/// model then evaluates the manifest with those findings. An optional `options` part holds
+/// the other `POST /evaluate` fields, as for `POST /evaluate/upload`.
+pub async fn evaluate_manifest(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    mut multipart: Multipart,
+) -> Result<(Extension<AuditResource>, Json<ManifestResponse>), (StatusCode, String)> {
+    let max_bytes = state.upload_max_bytes;
+    let mut manifest = None;
+    let mut lockfile = None;
+    let mut options = Map::new();
+    while let Some(field) = multipart.next_field().await.map_err(|e| multipart_error(e, max_bytes))? {
+        match field.name() {
+            Some("file") => {
+                let filename = field
+                    .file_name()
+                    .map(str::to_string)
+                    .ok_or_else(|| (StatusCode::BAD_REQUEST, "Name the file part after the manifest, e.g. Cargo.toml".to_string()))?;
+                let bytes = read_bytes(field, max_bytes).await?;
+                let text = String::from_utf8(bytes)
+                    .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, format!("{} isn't UTF-8", filename)))?;
+                let parsed = supply_chain::parse(&filename, &text).ok_or_else(|| {
+                    (
+                        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        format!("Unsupported manifest {}; upload Cargo.toml, package.json or requirements.txt", filename),
+                    )
+                })?;
+                match parsed.map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", e)))? {
+                    ManifestFile::Manifest(parsed) if manifest.is_none() => manifest = Some(parsed),
+                    ManifestFile::Lockfile(parsed) if lockfile.is_none() => lockfile = Some(parsed),
+                    _ => return Err((StatusCode::BAD_REQUEST, "Upload one manifest and at most one lockfile".to_string())),
+                }
+            }
+            Some("options") => {
+                let text = field.text().await.map_err(|e| multipart_error(e, max_bytes))?;
+                options = serde_json::from_str(&text)
+                    .map_err(|e| (StatusCode::BAD_REQUEST, format!("The options part isn't a JSON object: {}", e)))?;
+            }
+            other => return Err((StatusCode::BAD_REQUEST, format!("Unexpected part '{}'", other.unwrap_or_default()))),
+        }
+    }
+    let manifest = manifest.ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing the manifest file part".to_string()))?;
+    let findings = supply_chain::analyze(&manifest, lockfile.as_ref());
+    info!(
+        file = manifest.file,
+        dependencies = manifest.dependencies.len(),
+        findings = findings.len(),
+        "📦 Analyzed dependency manifest"
+    );
+    let description = supply_chain::describe(&manifest, lockfile.as_ref(), &findings);
+    let payload = with_description(options, description, "the manifest")?;
+
+    let evaluation = evaluate(&state, &identity, &request_id, &payload).await?;
+    let resource = AuditResource(evaluation.id.to_string());
+    Ok((
+        Extension(resource),
+        Json(ManifestResponse {
+            evaluation,
+            ecosystem: manifest.ecosystem,
+            dependencies: manifest.dependencies.len(),
+            locked_packages: lockfile.map(|lockfile| lockfile.packages),
+            findings,
+        }),
+    ))
+}
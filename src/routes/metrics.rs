@@ -0,0 +1,60 @@
+use std::{fmt::Write, sync::atomic::Ordering, sync::Arc};
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::AppState;
+
+/// `GET /metrics`: Prometheus text exposition of the service's counters.
+pub async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let key_limited = state.key_limiter.rejected.load(Ordering::Relaxed);
+    let rejected = [
+        ("key_rate_limited", key_limited),
+        ("ip_rate_limited", state.ip_policy.rate_limited()),
+        ("ip_denied", state.ip_policy.denied.load(Ordering::Relaxed)),
+        ("upstream_saturated", state.upstream.rejected.load(Ordering::Relaxed)),
+        ("load_shed", state.load.shed.load(Ordering::Relaxed)),
+    ];
+
+    let mut body = String::new();
+    body.push_str("# HELP risk_evaluator_rejected_requests_total Requests turned away by rate limits and IP rules.\n");
+    body.push_str("# TYPE risk_evaluator_rejected_requests_total counter\n");
+    for (reason, count) in rejected {
+        let _ = writeln!(body, "risk_evaluator_rejected_requests_total{{reason=\"{}\"}} {}", reason, count);
+    }
+
+    body.push_str("# HELP risk_evaluator_requests_in_flight Requests currently being handled.\n");
+    body.push_str("# TYPE risk_evaluator_requests_in_flight gauge\n");
+    let _ = writeln!(body, "risk_evaluator_requests_in_flight {}", state.load.in_flight());
+    body.push_str("# HELP risk_evaluator_upstream_latency_ms Moving average of evaluation latency.\n");
+    body.push_str("# TYPE risk_evaluator_upstream_latency_ms gauge\n");
+    let _ = writeln!(body, "risk_evaluator_upstream_latency_ms {}", state.load.latency_ms());
+    body.push_str("# HELP risk_evaluator_upstream_in_flight Evaluations currently calling LLM providers.\n");
+    body.push_str("# TYPE risk_evaluator_upstream_in_flight gauge\n");
+    let _ = writeln!(body, "risk_evaluator_upstream_in_flight {}", state.upstream.in_flight());
+    body.push_str("# HELP risk_evaluator_upstream_queued Requests waiting for an LLM slot.\n");
+    body.push_str("# TYPE risk_evaluator_upstream_queued gauge\n");
+    let _ = writeln!(body, "risk_evaluator_upstream_queued {}", state.upstream.queued());
+
+    let lanes = state.jobs.stats();
+    body.push_str("# HELP risk_evaluator_jobs_queued Async jobs waiting for a worker.\n");
+    body.push_str("# TYPE risk_evaluator_jobs_queued gauge\n");
+    for lane in &lanes {
+        let _ = writeln!(body, "risk_evaluator_jobs_queued{{priority=\"{}\"}} {}", lane.priority.as_str(), lane.queued);
+    }
+    body.push_str("# HELP risk_evaluator_jobs_running Async jobs being evaluated.\n");
+    body.push_str("# TYPE risk_evaluator_jobs_running gauge\n");
+    for lane in &lanes {
+        let _ = writeln!(body, "risk_evaluator_jobs_running{{priority=\"{}\"}} {}", lane.priority.as_str(), lane.running);
+    }
+    body.push_str("# HELP risk_evaluator_jobs_rejected_total Async jobs refused because their queue was full.\n");
+    body.push_str("# TYPE risk_evaluator_jobs_rejected_total counter\n");
+    for lane in &lanes {
+        let _ = writeln!(body, "risk_evaluator_jobs_rejected_total{{priority=\"{}\"}} {}", lane.priority.as_str(), lane.rejected);
+    }
+
+    state.metrics.render(&mut body);
+    state.providers.calls.render(&mut body);
+    state.providers.breakers.render(&mut body);
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
@@ -0,0 +1,22 @@
+pub mod api_keys;
+pub mod audit;
+pub mod batches;
+pub mod config;
+pub mod docs;
+pub mod evaluations;
+pub mod graphql;
+pub mod health;
+pub mod jobs;
+pub mod manifest;
+pub mod metrics;
+pub mod oauth;
+pub mod projects;
+pub mod repo;
+pub mod simulate;
+pub mod stats;
+pub mod stream;
+pub mod upload;
+pub mod url;
+pub mod usage;
+pub mod version;
+pub mod ws;
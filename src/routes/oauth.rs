@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::auth::OAuthError;
+use crate::AppState;
+
+/// Form body of `POST /oauth/token` (RFC 6749 §4.4). Client credentials may be sent here or
+/// via HTTP Basic auth.
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    grant_type: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    scope: Option<String>,
+}
+
+/// `POST /oauth/token`: exchanges client credentials for a short-lived bearer token.
+pub async fn token(State(state): State<Arc<AppState>>, headers: HeaderMap, Form(form): Form<TokenRequest>) -> Response {
+    let Some(oauth) = &state.auth.oauth else {
+        return (StatusCode::NOT_FOUND, "Token endpoint is not enabled").into_response();
+    };
+    if form.grant_type != "client_credentials" {
+        return token_error(StatusCode::BAD_REQUEST, "unsupported_grant_type");
+    }
+
+    let credentials = basic_credentials(&headers).or_else(|| Some((form.client_id?, form.client_secret?)));
+    let Some((client_id, client_secret)) = credentials else {
+        return token_error(StatusCode::UNAUTHORIZED, OAuthError::InvalidClient.as_str());
+    };
+
+    match oauth.issue(&client_id, &client_secret, form.scope.as_deref()) {
+        Ok(issued) => {
+            info!("🔐 Issued token to client '{}' ({})", client_id, issued.scope);
+            let body = json!({
+                "access_token": issued.access_token,
+                "token_type": "Bearer",
+                "expires_in": issued.expires_in,
+                "scope": issued.scope,
+            });
+            ([(header::CACHE_CONTROL, "no-store")], Json(body)).into_response()
+        }
+        Err(e) => {
+            let status = match e {
+                OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+                OAuthError::InvalidScope => StatusCode::BAD_REQUEST,
+                OAuthError::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            token_error(status, e.as_str())
+        }
+    }
+}
+
+fn token_error(status: StatusCode, error: &str) -> Response {
+    (status, Json(json!({ "error": error }))).into_response()
+}
+
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers.get(header::AUTHORIZATION)?.to_str().ok()?.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded).ok()?).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    Some((id.to_string(), secret.to_string()))
+}
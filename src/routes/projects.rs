@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::diff::{self, RiskDiff};
+use crate::register::{self, RegisterEntry};
+use crate::severity::Severity;
+use crate::storage::{Cursor, Evaluation, EvaluationFilter, Project};
+use crate::AppState;
+
+/// Body of `POST /projects`.
+#[derive(Debug, Deserialize)]
+pub struct NewProject {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// `POST /projects`: registers an initiative that evaluations can be filed under via
+/// `project_id`.
+pub async fn create_project(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Json(payload): Json<NewProject>,
+) -> Result<(StatusCode, Extension<AuditResource>, Json<Project>), (StatusCode, String)> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Project name must not be empty".to_string()));
+    }
+
+    let project = Project {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        description: payload.description,
+        created_at: chrono::Utc::now(),
+        tenant_id: identity.tenant,
+    };
+    state.storage.insert_project(&project).await.map_err(|e| {
+        error!("❌ Failed to store project: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to store project".to_string())
+    })?;
+
+    Ok((StatusCode::CREATED, Extension(AuditResource(project.id.to_string())), Json(project)))
+}
+
+/// `GET /projects`
+pub async fn list_projects(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+) -> Result<Json<Vec<Project>>, (StatusCode, String)> {
+    state.storage.list_projects(&identity.tenant).await.map(Json).map_err(|e| {
+        error!("❌ Failed to list projects: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to list projects".to_string())
+    })
+}
+
+/// `GET /projects/{id}`; its evaluations are listed by `GET /evaluations?project_id={id}`.
+pub async fn get_project(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Project>, (StatusCode, String)> {
+    match state.storage.get_project(&identity.tenant, id).await {
+        Ok(Some(project)) => Ok(Json(project)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("Project {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to load project {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load project".to_string()))
+        }
+    }
+}
+
+/// Query string of `GET /projects/{id}/diff`: the two evaluation IDs to compare.
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    from: Uuid,
+    to: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffResponse {
+    from: Uuid,
+    to: Uuid,
+    #[serde(flatten)]
+    diff: RiskDiff,
+}
+
+/// `GET /projects/{id}/diff?from=&to=`: new, resolved and re-rated risks between two of the
+/// project's evaluations.
+pub async fn diff_evaluations(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<DiffResponse>, (StatusCode, String)> {
+    let before = project_evaluation(&state, &identity.tenant, id, query.from).await?;
+    let after = project_evaluation(&state, &identity.tenant, id, query.to).await?;
+
+    Ok(Json(DiffResponse {
+        from: query.from,
+        to: query.to,
+        diff: diff::diff_risks(&before.risks, &after.risks),
+    }))
+}
+
+/// Loads an evaluation, treating one filed under another project as missing.
+async fn project_evaluation(
+    state: &AppState,
+    tenant: &str,
+    project_id: Uuid,
+    evaluation_id: Uuid,
+) -> Result<Evaluation, (StatusCode, String)> {
+    match state.storage.get_evaluation(tenant, evaluation_id).await {
+        Ok(Some(evaluation)) if evaluation.project_id == Some(project_id) => Ok(evaluation),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Evaluation {} not found in project {}", evaluation_id, project_id),
+        )),
+        Err(e) => {
+            error!("❌ Failed to load evaluation {}: {:?}", evaluation_id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load evaluation".to_string()))
+        }
+    }
+}
+
+const TRENDS_PAGE_SIZE: u32 = 100;
+
+/// Query string of `GET /projects/{id}/trends`.
+#[derive(Debug, Deserialize)]
+pub struct TrendsQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+/// One evaluation's risk profile.
+#[derive(Debug, Serialize)]
+pub struct TrendPoint {
+    evaluation_id: Uuid,
+    created_at: DateTime<Utc>,
+    /// Risks per severity, with every level of the configured scale present.
+    counts: BTreeMap<Severity, usize>,
+    total: usize,
+}
+
+/// `GET /projects/{id}/trends`: risk counts by severity for each of the project's
+/// evaluations, oldest first, ready to chart.
+pub async fn project_trends(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<TrendsQuery>,
+) -> Result<Json<Vec<TrendPoint>>, (StatusCode, String)> {
+    let internal = |e: anyhow::Error| {
+        error!("❌ Failed to load trends of project {}: {:?}", id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load trends".to_string())
+    };
+    if state.storage.get_project(&identity.tenant, id).await.map_err(internal)?.is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Project {} not found", id)));
+    }
+
+    let filter = EvaluationFilter { project_id: Some(id), from: query.from, to: query.to, ..Default::default() };
+    let mut points = Vec::new();
+    let mut after: Option<Cursor> = None;
+    loop {
+        let page = state
+            .storage
+            .list_evaluations(&identity.tenant, &filter, after.as_ref(), TRENDS_PAGE_SIZE)
+            .await
+            .map_err(internal)?;
+        let Some(last) = page.last() else { break };
+        after = Some(Cursor::after(last));
+
+        points.extend(page.iter().map(|evaluation| {
+            let mut counts: BTreeMap<Severity, usize> =
+                state.analysis().severity_scale.levels().iter().map(|&level| (level, 0)).collect();
+            for risk in &evaluation.risks {
+                *counts.entry(risk.severity).or_default() += 1;
+            }
+            TrendPoint {
+                evaluation_id: evaluation.id,
+                created_at: evaluation.created_at,
+                counts,
+                total: evaluation.risks.len(),
+            }
+        }));
+        if page.len() < TRENDS_PAGE_SIZE as usize {
+            break;
+        }
+    }
+    points.reverse();
+
+    Ok(Json(points))
+}
+
+/// `PUT /projects/{id}/register`: replaces the project's risk register with the CSV body.
+/// Later evaluations filed under the project mark which risks it already covers.
+pub async fn import_register(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+    body: String,
+) -> Result<Json<Vec<RegisterEntry>>, (StatusCode, String)> {
+    let entries = register::parse_csv(&body).map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+
+    match state.storage.set_register(&identity.tenant, id, &entries).await {
+        Ok(true) => {
+            info!("📋 Imported {} register entries into project {}", entries.len(), id);
+            Ok(Json(entries))
+        }
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("Project {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to store register of project {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to store register".to_string()))
+        }
+    }
+}
+
+/// `GET /projects/{id}/register`
+pub async fn get_register(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<RegisterEntry>>, (StatusCode, String)> {
+    match state.storage.get_register(&identity.tenant, id).await {
+        Ok(Some(entries)) => Ok(Json(entries)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("Project {} not found", id))),
+        Err(e) => {
+            error!("❌ Failed to load register of project {}: {:?}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to load register".to_string()))
+        }
+    }
+}
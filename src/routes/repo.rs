@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde_json::{Map, Value};
+
+use super::upload::with_description;
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::negotiate::{Encoded, FormatQuery, Negotiated};
+use crate::problem::Problem;
+use crate::request_id::RequestId;
+use crate::{evaluate, AppState, RiskResponse};
+
+/// `POST /evaluate/repo`: evaluates a git repository's delivery and technical risks from its
+/// README, documentation and open issue titles. The body is a `POST /evaluate` request with
+/// `repo`, an http(s) clone url, in place of `description`, plus an optional `token` for
+/// private repositories and `ref`, a branch or tag. The token is only used for the clone
+/// and the issue listing. Answers like `POST /evaluate`.
+#[utoipa::path(
+    post,
+    path = "/evaluate/repo",
+    tag = "evaluations",
+    params(FormatQuery),
+    request_body(
+        content = Object,
+        description = "A `RiskRequest` with `repo` in place of `description`, and optionally `token` and `ref`"
+    ),
+    responses(
+        (status = 200, description = "The stored evaluation's risks", content(
+            (RiskResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "text/markdown"),
+        )),
+        (status = 400, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "Project text over the input limit, or a checkout over `repo_max_clone_bytes`", body = Problem, content_type = "application/problem+json"),
+        (status = 502, description = "No provider could evaluate the project", body = Problem, content_type = "application/problem+json"),
+        (status = 504, description = "The provider or the request's deadline timed out", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn evaluate_repo(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Negotiated(format): Negotiated,
+    Json(mut body): Json<Map<String, Value>>,
+) -> Result<(Extension<AuditResource>, Encoded<RiskResponse>), (StatusCode, String)> {
+    let repo = string_field(&mut body, "repo")?.ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing repo".to_string()))?;
+    let token = string_field(&mut body, "token")?;
+    let reference = string_field(&mut body, "ref")?;
+    let corpus = state
+        .repo_reader
+        .read(&state.url_fetcher, &repo, token.as_deref(), reference.as_deref())
+        .await?;
+    let payload = with_description(body, corpus, "the repository")?;
+
+    let response = evaluate(&state, &identity, &request_id, &payload).await?;
+    Ok((Extension(AuditResource(response.id.to_string())), Encoded(format, response)))
+}
+
+fn string_field(body: &mut Map<String, Value>, name: &str) -> Result<Option<String>, (StatusCode, String)> {
+    match body.remove(name) {
+        Some(Value::String(value)) => Ok(Some(value)),
+        None | Some(Value::Null) => Ok(None),
+        Some(_) => Err((StatusCode::BAD_REQUEST, format!("{} must be a string", name))),
+    }
+}
@@ -0,0 +1,78 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use tracing::info;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::evaluations::load_evaluation;
+use crate::auth::Identity;
+use crate::problem::Problem;
+use crate::simulation::{self, Simulation};
+use crate::AppState;
+
+/// Body of `POST /simulate`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SimulationRequest {
+    /// The stored evaluation whose risks are simulated.
+    evaluation_id: Uuid,
+    /// How many runs to simulate; the configured `simulation_iterations` when unset.
+    #[serde(default)]
+    iterations: Option<u32>,
+    /// How many bins the histogram has, 1 to 100; 20 when unset.
+    #[serde(default)]
+    bins: Option<usize>,
+    /// Makes the result reproducible; a random one when unset.
+    #[serde(default)]
+    seed: Option<u64>,
+}
+
+/// `POST /simulate`: Monte Carlo simulation of an evaluation's aggregate exposure. Each run,
+/// every risk occurs with the chance its likelihood gives and then costs somewhere in its
+/// `cost_impact` range, or around its impact rating for evaluations without a budget.
+#[utoipa::path(
+    post,
+    path = "/simulate",
+    tag = "evaluations",
+    request_body = SimulationRequest,
+    responses(
+        (status = 200, description = "Exposure percentiles and histogram", body = Simulation),
+        (status = 400, description = "Invalid iterations or bins", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such evaluation", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The evaluation has no risks", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn simulate(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Json(payload): Json<SimulationRequest>,
+) -> Result<Json<Simulation>, (StatusCode, String)> {
+    let iterations = state.simulator.iterations(payload.iterations).map_err(|msg| (StatusCode::BAD_REQUEST, msg))?;
+    let bins = payload.bins.unwrap_or(simulation::DEFAULT_BINS);
+    if bins == 0 || bins > simulation::MAX_BINS {
+        return Err((StatusCode::BAD_REQUEST, format!("bins must be between 1 and {}", simulation::MAX_BINS)));
+    }
+    let evaluation = load_evaluation(&state, &identity, payload.evaluation_id).await?;
+    if evaluation.risks.is_empty() {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Evaluation {} has no risks to simulate", evaluation.id),
+        ));
+    }
+
+    let seed = payload.seed.unwrap_or_else(rand::random);
+    let started = Instant::now();
+    let result = tokio::task::spawn_blocking(move || simulation::run(evaluation.id, &evaluation.risks, iterations, bins, seed))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("The simulation failed: {}", e)))?;
+    info!(
+        evaluation_id = %result.evaluation_id,
+        iterations,
+        unit = %result.unit,
+        p80 = result.p80,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "🎲 Simulated evaluation exposure"
+    );
+    Ok(Json(result))
+}
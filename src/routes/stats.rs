@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+use crate::stats::WindowStats;
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct StatsResponse {
+    /// Provider attempts, re-prompts included; errors and timeouts count as failures.
+    providers: Vec<ModelStats>,
+    /// Requests by route; only 5xx responses count as failures.
+    routes: Vec<RouteStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelStats {
+    provider: String,
+    model: String,
+    /// Shortest window first.
+    windows: Vec<WindowStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteStats {
+    method: String,
+    route: String,
+    windows: Vec<WindowStats>,
+}
+
+/// `GET /stats`: latency percentiles and error rates over rolling windows, per provider and
+/// model and per route, for comparing backends and checking SLOs.
+pub async fn stats(State(state): State<Arc<AppState>>) -> Json<StatsResponse> {
+    let providers = state
+        .providers
+        .calls
+        .model_stats
+        .snapshot()
+        .into_iter()
+        .map(|(mut labels, windows)| {
+            let model = labels.pop().unwrap_or_default();
+            let provider = labels.pop().unwrap_or_default();
+            ModelStats { provider, model, windows }
+        })
+        .collect();
+    let routes = state
+        .metrics
+        .route_stats
+        .snapshot()
+        .into_iter()
+        .map(|(mut labels, windows)| {
+            let route = labels.pop().unwrap_or_default();
+            let method = labels.pop().unwrap_or_default();
+            RouteStats { method, route, windows }
+        })
+        .collect();
+
+    Json(StatsResponse { providers, routes })
+}
@@ -0,0 +1,140 @@
+use std::{convert::Infallible, sync::Arc, time::Instant};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Extension, Json,
+};
+use sentry::SentryFutureExt;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream, StreamExt};
+use tracing::{error, Instrument};
+
+use crate::analysis::{analyze_streaming, analyze_until};
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::problem::Problem;
+use crate::register;
+use crate::reporting;
+use crate::request_id::RequestId;
+use crate::cache::CacheStatus;
+use crate::{
+    finish_evaluation, passes_filters, prepare_evaluation, shared_analysis, Abandoned, AppState, PreparedEvaluation, RiskItem, RiskRequest,
+    RiskResponse,
+};
+
+/// `POST /evaluate/stream`: the same request as `POST /evaluate`, answered with server-sent
+/// events. Each `risk` event carries one risk as soon as the model has finished writing it;
+/// the closing `done` event carries the full stored response, or an `error` event carries a
+/// problem detail like the other endpoints' error responses if every provider failed or
+/// `timeout_ms` passed and the request asked for an error. Streamed risks are a preview —
+/// severity sorting and a fallback provider can change the final list, so `done` is
+/// authoritative.
+#[utoipa::path(
+    post,
+    path = "/evaluate/stream",
+    tag = "evaluations",
+    request_body = RiskRequest,
+    responses(
+        (status = 200, description = "`risk` events, then a `done` event with the `RiskResponse` or an `error` event with a `Problem`", content_type = "text/event-stream"),
+        (status = 400, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn evaluate_stream(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Json(payload): Json<RiskRequest>,
+) -> Result<(Extension<AuditResource>, Sse<impl Stream<Item = Result<Event, Infallible>>>), (StatusCode, String)> {
+    let prepared = prepare_evaluation(&state, &identity, &request_id, &payload).await?;
+    let id = prepared.id;
+    let (events, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let evaluation = stream_evaluation(&state, &identity, &payload, prepared, |risk| {
+            let _ = events.send(event("risk", risk));
+        });
+        // The response stream is dropped when the client disconnects; stop paying for the
+        // evaluation then rather than finishing it for nobody.
+        let outcome = tokio::select! {
+            outcome = evaluation => outcome,
+            () = events.closed() => return,
+        };
+        match outcome {
+            Ok(response) => {
+                let _ = events.send(event("done", &response));
+            }
+            Err((status, message)) => {
+                let _ = events.send(event("error", &Problem::new(status, message, Some(&request_id))));
+            }
+        }
+    });
+
+    let stream = UnboundedReceiverStream::new(rx).map(Ok::<_, Infallible>);
+    Ok((Extension(AuditResource(id.to_string())), Sse::new(stream).keep_alive(KeepAlive::default())))
+}
+
+/// Runs a prepared evaluation with [`analyze_streaming`], handing each streamed risk that
+/// passes the request's filters to `on_risk` before the evaluation is stored. Shared by the
+/// SSE and WebSocket endpoints. Dropping the future cancels the evaluation; it fails when no
+/// provider could evaluate the project or a deadline passed and the request asked for an error.
+pub async fn stream_evaluation(
+    state: &AppState,
+    identity: &Identity,
+    payload: &RiskRequest,
+    prepared: PreparedEvaluation,
+    mut on_risk: impl FnMut(&RiskItem) + Send,
+) -> Result<RiskResponse, (StatusCode, String)> {
+    let started = Instant::now();
+    let (risk_tx, mut risk_rx) = mpsc::unbounded_channel();
+    let (selection, options, text) = (&prepared.selection, &prepared.options, &prepared.text);
+    let shared_tx = risk_tx.clone();
+    let streamed = async {
+        match prepared.deadline {
+            Some(deadline) => analyze_until(&state.providers, selection, options, text, deadline, risk_tx).await,
+            None => analyze_streaming(&state.providers, selection, options, text, risk_tx).await,
+        }
+    }
+    .instrument(prepared.span())
+    .bind_hub(reporting::evaluation_hub(identity, payload, &prepared));
+    // Risks from the cache or an identical evaluation weren't streamed; they're sent at once.
+    // Moving the sender in closes the channel once the analysis is done.
+    let shared = &prepared;
+    let analysis = async move {
+        let outcome = shared_analysis(state, shared, streamed).await;
+        if let Ok((analysis, CacheStatus::Hit | CacheStatus::Coalesced)) = &outcome {
+            for risk in &analysis.risks {
+                let _ = shared_tx.send(risk.clone());
+            }
+        }
+        outcome
+    };
+
+    let forward = async {
+        let mut sent = 0;
+        while let Some(risk) = risk_rx.recv().await {
+            if !passes_filters(&risk, payload) || payload.max_risks.is_some_and(|max| sent >= max) {
+                continue;
+            }
+            let mut risk = [risk];
+            prepared.sources.attribute(&mut risk);
+            register::mark_covered(&mut risk, &prepared.register);
+            sent += 1;
+            on_risk(&risk[0]);
+        }
+    };
+
+    let abandoned = Abandoned::new(prepared.id);
+    let (result, ()) = tokio::join!(analysis, forward);
+    abandoned.disarm();
+    finish_evaluation(state, identity, payload, prepared, result, started).await
+}
+
+fn event(name: &str, data: &impl Serialize) -> Event {
+    Event::default().event(name).json_data(data).unwrap_or_else(|e| {
+        error!("❌ Failed to encode {} event: {:?}", name, e);
+        Event::default().event("error").data(e.to_string())
+    })
+}
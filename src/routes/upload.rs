@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        multipart::{Field, MultipartError},
+        Multipart, State,
+    },
+    http::StatusCode,
+    Extension,
+};
+use serde_json::{Map, Value};
+use tracing::info;
+
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::negotiate::{Encoded, FormatQuery, Negotiated};
+use crate::problem::Problem;
+use crate::ingest::{self, Document};
+use crate::request_id::RequestId;
+use crate::{evaluate, AppState, RiskRequest, RiskResponse};
+
+pub const DEFAULT_MAX_BYTES: usize = 10 * 1024 * 1024;
+/// Room for the multipart framing and the options part on top of the file itself.
+pub const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// `POST /evaluate/upload`: evaluates a project brief uploaded as a file instead of a JSON
+/// string. The `file` part holds the document: plain text, markdown or HTML in any encoding,
+/// a PDF, whose running headers, footers and page numbers are left out, or a Word DOCX, whose
+/// headings are kept as markdown headings. An optional `options` part holds the other
+/// `POST /evaluate` fields as JSON, e.g. `{"provider": "anthropic", "tags": ["2024-Q3"]}`.
+/// Answers like `POST /evaluate`.
+#[utoipa::path(
+    post,
+    path = "/evaluate/upload",
+    tag = "evaluations",
+    params(FormatQuery),
+    request_body(
+        content(("multipart/form-data")),
+        description = "A `file` part with the document and an optional `options` part with other `RiskRequest` fields as JSON"
+    ),
+    responses(
+        (status = 200, description = "The stored evaluation's risks", content(
+            (RiskResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "text/markdown"),
+        )),
+        (status = 415, description = "Unsupported document type", body = Problem, content_type = "application/problem+json"),
+        (status = 400, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "Project text over the input limit", body = Problem, content_type = "application/problem+json"),
+        (status = 502, description = "No provider could evaluate the project", body = Problem, content_type = "application/problem+json"),
+        (status = 504, description = "The provider or the request's deadline timed out", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn evaluate_upload(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Negotiated(format): Negotiated,
+    mut multipart: Multipart,
+) -> Result<(Extension<AuditResource>, Encoded<RiskResponse>), (StatusCode, String)> {
+    let mut document = None;
+    let mut options = Map::new();
+    let max_bytes = state.upload_max_bytes;
+    while let Some(field) = multipart.next_field().await.map_err(|e| multipart_error(e, max_bytes))? {
+        match field.name() {
+            Some("file") => document = Some(read_document(field, max_bytes).await?),
+            Some("options") => {
+                let text = field.text().await.map_err(|e| multipart_error(e, max_bytes))?;
+                options = serde_json::from_str(&text)
+                    .map_err(|e| (StatusCode::BAD_REQUEST, format!("The options part isn't a JSON object: {}", e)))?;
+            }
+            other => return Err((StatusCode::BAD_REQUEST, format!("Unexpected part '{}'", other.unwrap_or_default()))),
+        }
+    }
+    let document = document.ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing the file part".to_string()))?;
+    let payload = with_description(options, document.text, "the file")?;
+
+    let response = evaluate(&state, &identity, &request_id, &payload).await?;
+    Ok((Extension(AuditResource(response.id.to_string())), Encoded(format, response)))
+}
+
+async fn read_document(field: Field<'_>, max_bytes: usize) -> Result<Document, (StatusCode, String)> {
+    let filename = field.file_name().map(str::to_string);
+    let content_type = field.content_type().map(str::to_string);
+    let bytes = read_bytes(field, max_bytes).await?;
+    let size = bytes.len();
+    let document = ingest::extract(bytes, content_type.as_deref(), filename.as_deref()).await?;
+    info!(
+        file = filename.as_deref().unwrap_or_default(),
+        bytes = size,
+        format = ?document.format,
+        encoding = document.encoding.unwrap_or_default(),
+        "📎 Read uploaded document"
+    );
+    Ok(document)
+}
+
+/// A part's content, refused once it's over `max_bytes`.
+pub async fn read_bytes(mut field: Field<'_>, max_bytes: usize) -> Result<Vec<u8>, (StatusCode, String)> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(|e| multipart_error(e, max_bytes))? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(too_large(max_bytes));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// The `POST /evaluate` request made of the other fields and a description read from `source`.
+pub fn with_description(mut options: Map<String, Value>, text: String, source: &str) -> Result<RiskRequest, (StatusCode, String)> {
+    if options.contains_key("description") {
+        return Err((StatusCode::BAD_REQUEST, format!("The description comes from {}; leave it out", source)));
+    }
+    options.insert("description".to_string(), Value::String(text));
+    serde_json::from_value(Value::Object(options)).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid options: {}", e)))
+}
+
+fn too_large(max_bytes: usize) -> (StatusCode, String) {
+    (StatusCode::PAYLOAD_TOO_LARGE, format!("The file is larger than the {} byte limit", max_bytes))
+}
+
+/// The request body limit shows up as a failed read; say which limit it was.
+pub fn multipart_error(error: MultipartError, max_bytes: usize) -> (StatusCode, String) {
+    match error.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => too_large(max_bytes),
+        status => (status, error.body_text()),
+    }
+}
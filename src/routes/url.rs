@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde_json::{Map, Value};
+
+use super::upload::with_description;
+use crate::audit::AuditResource;
+use crate::auth::Identity;
+use crate::negotiate::{Encoded, FormatQuery, Negotiated};
+use crate::problem::Problem;
+use crate::request_id::RequestId;
+use crate::{evaluate, AppState, RiskResponse};
+
+/// `POST /evaluate/url`: evaluates the page at `url`, e.g. a Confluence page, a published
+/// Google Doc or a wiki page, instead of a pasted description. The body is a `POST /evaluate`
+/// request with `url` in place of `description`. Pages are read like uploads, so links to
+/// PDF and DOCX files work too; internal addresses are refused. Answers like `POST /evaluate`.
+#[utoipa::path(
+    post,
+    path = "/evaluate/url",
+    tag = "evaluations",
+    params(FormatQuery),
+    request_body(content = Object, description = "A `RiskRequest` with `url` in place of `description`"),
+    responses(
+        (status = 200, description = "The stored evaluation's risks", content(
+            (RiskResponse = "application/json"),
+            (String = "text/csv"),
+            (String = "text/markdown"),
+        )),
+        (status = 400, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "Project text over the input limit", body = Problem, content_type = "application/problem+json"),
+        (status = 502, description = "No provider could evaluate the project", body = Problem, content_type = "application/problem+json"),
+        (status = 504, description = "The provider or the request's deadline timed out", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn evaluate_url(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    Negotiated(format): Negotiated,
+    Json(mut body): Json<Map<String, Value>>,
+) -> Result<(Extension<AuditResource>, Encoded<RiskResponse>), (StatusCode, String)> {
+    let url = match body.remove("url") {
+        Some(Value::String(url)) => url,
+        Some(_) => return Err((StatusCode::BAD_REQUEST, "url must be a string".to_string())),
+        None => return Err((StatusCode::BAD_REQUEST, "Missing url".to_string())),
+    };
+    let document = state.url_fetcher.fetch(&url).await?;
+    let payload = with_description(body, document.text, "the page")?;
+
+    let response = evaluate(&state, &identity, &request_id, &payload).await?;
+    Ok((Extension(AuditResource(response.id.to_string())), Encoded(format, response)))
+}
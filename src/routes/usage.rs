@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::auth::{Identity, Role};
+use crate::budget;
+use crate::storage::{DailyUsage, UsageTotals};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Another key's ID; admins only.
+    key_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    key_id: String,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    #[serde(flatten)]
+    totals: UsageTotals,
+    total_tokens: i64,
+    /// Monthly token budget; absent when unlimited.
+    budget: Option<i64>,
+    remaining: Option<i64>,
+    /// The same figures for each day of the period the key was used.
+    days: Vec<DailyUsage>,
+}
+
+/// `GET /usage`: the caller's requests, token consumption and estimated spend for the current
+/// month, in total and by day.
+pub async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>, (StatusCode, String)> {
+    let key_id = match query.key_id {
+        Some(key_id) if key_id != identity.key_id && identity.role != Role::Admin => {
+            return Err((StatusCode::FORBIDDEN, "Only admins may view other keys' usage".to_string()));
+        }
+        Some(key_id) => key_id,
+        None => identity.key_id.clone(),
+    };
+
+    let (period_start, period_end) = budget::current_month();
+    let failed = |e: anyhow::Error| {
+        error!("❌ Failed to load usage of {}: {:?}", key_id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load usage".to_string())
+    };
+    let totals = state.storage.usage_between(&identity.tenant, &key_id, period_start, period_end).await.map_err(failed)?;
+    let days = state.storage.daily_usage(&identity.tenant, &key_id, period_start, period_end).await.map_err(failed)?;
+    // Budgets are only known for the caller's own key.
+    let budget = if key_id == identity.key_id { state.budget.for_identity(&identity, &state.tenants) } else { None };
+
+    Ok(Json(UsageResponse {
+        key_id,
+        period_start,
+        period_end,
+        total_tokens: totals.total_tokens(),
+        remaining: budget.map(|budget| (budget - totals.total_tokens()).max(0)),
+        totals,
+        budget,
+        days,
+    }))
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::Profile;
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    version: &'static str,
+    git_commit: &'static str,
+    built_at: Option<DateTime<Utc>>,
+    profile: Profile,
+    /// Tried in order for requests that don't pick a provider.
+    provider_chain: Vec<&'static str>,
+    providers: Vec<ProviderInfo>,
+    /// Models callers may request besides each provider's default.
+    allowed_models: Vec<String>,
+    timeout_secs: u64,
+}
+
+/// What a configured provider runs, never how it authenticates or where it connects.
+#[derive(Debug, Serialize)]
+pub struct ProviderInfo {
+    name: &'static str,
+    default_model: String,
+}
+
+/// `GET /version`: which build this is and which providers and models it is configured with.
+pub async fn version(State(state): State<Arc<AppState>>) -> Json<VersionInfo> {
+    let built_at = env!("BUILD_TIMESTAMP").parse().ok().and_then(|secs| DateTime::from_timestamp(secs, 0));
+    let mut allowed_models: Vec<_> = state.allowed_models.read().unwrap().iter().cloned().collect();
+    allowed_models.sort();
+
+    Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: env!("GIT_COMMIT"),
+        built_at,
+        profile: state.profile,
+        provider_chain: state.providers.chain(None).iter().map(|provider| provider.name()).collect(),
+        providers: state
+            .providers
+            .all()
+            .iter()
+            .map(|provider| ProviderInfo { name: provider.name(), default_model: provider.default_model().to_string() })
+            .collect(),
+        allowed_models,
+        timeout_secs: state.providers.timeout().as_secs(),
+    })
+}
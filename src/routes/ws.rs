@@ -0,0 +1,203 @@
+use std::{collections::VecDeque, future::Future, sync::Arc};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    Extension,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tracing::{error, info};
+
+use super::stream::stream_evaluation;
+use crate::auth::Identity;
+use crate::budget;
+use crate::conversation::Conversation;
+use crate::request_id::RequestId;
+use crate::{prepare_evaluation, AppState, RiskItem, RiskRequest, RiskResponse};
+
+/// What a client sends over the socket, tagged by `type`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Same fields as `POST /evaluate`; starts a new conversation.
+    Evaluate(Box<RiskRequest>),
+    /// A follow-up about the latest evaluation, e.g. "expand on the security risk".
+    Ask { question: String },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    Risk { risk: &'a RiskItem },
+    Done { response: &'a RiskResponse },
+    AnswerDelta { text: &'a str },
+    Answer { text: &'a str },
+    Error { message: &'a str },
+}
+
+/// `GET /ws`: an evaluation channel. The client sends `{"type": "evaluate", ...}` and
+/// receives a `risk` message per streamed risk, then `done` with the stored response; it can
+/// then send `{"type": "ask", "question": ...}` as often as it likes and receives the answer
+/// as `answer_delta` pieces followed by the full `answer`. The conversation lives as long as
+/// the connection, and a new `evaluate` replaces it.
+pub async fn evaluation_socket(
+    State(state): State<Arc<AppState>>,
+    Extension(identity): Extension<Identity>,
+    Extension(request_id): Extension<RequestId>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| converse(socket, state, identity, request_id))
+}
+
+/// Every evaluation on the connection shares the upgrade request's ID.
+async fn converse(socket: WebSocket, state: Arc<AppState>, identity: Identity, request_id: RequestId) {
+    let mut client = Client { socket, backlog: VecDeque::new() };
+    let mut conversation: Option<Conversation> = None;
+
+    while let Some(message) = client.recv().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let outcome = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(ClientMessage::Evaluate(payload)) => evaluate(&mut client, &state, &identity, &request_id, *payload)
+                .await
+                .map(|started| conversation = Some(started)),
+            Ok(ClientMessage::Ask { question }) => match conversation.as_mut() {
+                Some(conversation) => ask(&mut client, &state, &identity, conversation, &question).await,
+                None => Err("Send an evaluation before asking follow-up questions".to_string()),
+            },
+            Err(e) => Err(format!("Invalid message: {}", e)),
+        };
+        if let Err(message) = outcome {
+            client.send(&ServerMessage::Error { message: &message }).await;
+        }
+    }
+}
+
+async fn evaluate(
+    client: &mut Client,
+    state: &AppState,
+    identity: &Identity,
+    request_id: &RequestId,
+    payload: RiskRequest,
+) -> Result<Conversation, String> {
+    let prepared = prepare_evaluation(state, identity, request_id, &payload).await.map_err(|(_, msg)| msg)?;
+
+    let text = prepared.text.clone();
+    let (risk_tx, mut risk_rx) = mpsc::unbounded_channel::<RiskItem>();
+    let evaluation = stream_evaluation(state, identity, &payload, prepared, move |risk| {
+        let _ = risk_tx.send(risk.clone());
+    });
+    let response = client
+        .forwarding(evaluation, &mut risk_rx, |risk| ServerMessage::Risk { risk })
+        .await
+        .ok_or_else(|| "Client disconnected".to_string())?
+        .map_err(|(_, msg)| msg)?;
+    client.send(&ServerMessage::Done { response: &response }).await;
+
+    Conversation::new(
+        &state.providers,
+        &text,
+        &response.risks,
+        &response.provider,
+        response.model.as_deref(),
+    )
+    .map_err(|e| format!("Follow-up questions are unavailable: {}", e))
+}
+
+async fn ask(
+    client: &mut Client,
+    state: &AppState,
+    identity: &Identity,
+    conversation: &mut Conversation,
+    question: &str,
+) -> Result<(), String> {
+    budget::check(state, identity).await.map_err(|(_, msg)| msg)?;
+    let _upstream = state.upstream.admit().await.map_err(|e| e.to_string())?;
+
+    let (chunk_tx, mut chunk_rx) = mpsc::unbounded_channel::<String>();
+    let options = state.analysis();
+    let answer = conversation.ask(question, &options, state.providers.timeout(), chunk_tx);
+    let Some(answer) = client.forwarding(answer, &mut chunk_rx, |text| ServerMessage::AnswerDelta { text }).await else {
+        info!("🔌 Client went away; cancelled follow-up question");
+        return Err("Client disconnected".to_string());
+    };
+    let completion = answer.map_err(|e| {
+        error!("❌ Follow-up question failed: {:?}", e);
+        format!("Follow-up question failed: {}", e)
+    })?;
+
+    let cost = completion.usage.and_then(|usage| state.pricing.estimate(&completion.model, usage));
+    let today = chrono::Utc::now().date_naive();
+    if let Err(e) = state.storage.record_usage(&identity.tenant, &identity.key_id, today, completion.usage, cost).await {
+        error!("❌ Failed to record usage of {}: {:?}", identity.key_id, e);
+    }
+    client.send(&ServerMessage::Answer { text: &completion.content }).await;
+    Ok(())
+}
+
+/// The socket, plus messages that arrived while a reply was streaming; those are handled in
+/// order once it has finished.
+struct Client {
+    socket: WebSocket,
+    backlog: VecDeque<Message>,
+}
+
+impl Client {
+    /// `None` once the client has closed the connection or it has failed.
+    async fn recv(&mut self) -> Option<Message> {
+        match self.backlog.pop_front() {
+            Some(message) => Some(message),
+            None => self.socket.recv().await?.ok(),
+        }
+    }
+
+    /// Runs `work` while sending a `message` for each item it produces on `items`. Returns
+    /// `None` as soon as the client goes away, dropping `work` — and with it any provider
+    /// request in flight — since nobody is left to receive the result.
+    async fn forwarding<T, R>(
+        &mut self,
+        work: impl Future<Output = R>,
+        items: &mut UnboundedReceiver<T>,
+        message: fn(&T) -> ServerMessage<'_>,
+    ) -> Option<R> {
+        tokio::pin!(work);
+        loop {
+            tokio::select! {
+                biased;
+                result = &mut work => {
+                    while let Ok(item) = items.try_recv() {
+                        if !self.send(&message(&item)).await {
+                            return None;
+                        }
+                    }
+                    return Some(result);
+                }
+                Some(item) = items.recv() => {
+                    if !self.send(&message(&item)).await {
+                        return None;
+                    }
+                }
+                received = self.socket.recv() => match received {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return None,
+                    Some(Ok(message)) => self.backlog.push_back(message),
+                },
+            }
+        }
+    }
+
+    /// Whether the message went out.
+    async fn send(&mut self, message: &ServerMessage<'_>) -> bool {
+        match serde_json::to_string(message) {
+            Ok(text) => self.socket.send(Message::Text(text)).await.is_ok(),
+            Err(e) => {
+                error!("❌ Failed to encode WebSocket message: {:?}", e);
+                true
+            }
+        }
+    }
+}
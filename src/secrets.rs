@@ -0,0 +1,274 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::config::{ProvidersConfig, SecretsConfig};
+use crate::providers::sigv4::{self, Credentials};
+use crate::AppState;
+
+const DEFAULT_REFRESH_SECS: u64 = 300;
+const DEFAULT_VAULT_MOUNT: &str = "secret";
+const DEFAULT_AWS_REGION: &str = "us-east-1";
+/// A secrets manager that doesn't answer by then fails the fetch rather than holding up
+/// startup.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The provider settings a secret can fill in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Target {
+    OpenAiApiKey,
+    AnthropicApiKey,
+    AzureApiKey,
+    GeminiApiKey,
+    AwsAccessKeyId,
+    AwsSecretAccessKey,
+}
+
+/// `<secret>#<field>`.
+struct Reference {
+    secret: String,
+    field: Option<String>,
+}
+
+enum Backend {
+    Vault { address: String, token: String, namespace: Option<String>, mount: String },
+    Aws { endpoint: Url, region: String, credentials: Credentials },
+}
+
+/// Provider credentials kept in a secrets manager. They're fetched before the providers are
+/// set up and again every `refresh_secs`; each fetch's values replace what the config files
+/// and environment set, and a change reloads the configuration so the providers use them.
+pub struct SecretStore {
+    client: Client,
+    backend: Backend,
+    keys: Vec<(Target, Reference)>,
+    values: RwLock<HashMap<Target, String>>,
+    interval: Option<Duration>,
+}
+
+impl SecretStore {
+    /// `None` when no backend is configured.
+    pub fn from_config(config: &SecretsConfig, providers: &ProvidersConfig) -> Result<Option<Self>> {
+        let Some(backend) = config.backend.as_deref() else {
+            return Ok(None);
+        };
+        let backend = match backend {
+            "vault" => {
+                let vault = &config.vault;
+                Backend::Vault {
+                    address: vault.address.clone().context("The Vault secrets backend needs VAULT_ADDR")?,
+                    token: vault.token.clone().context("The Vault secrets backend needs VAULT_TOKEN")?,
+                    namespace: vault.namespace.clone(),
+                    mount: vault.mount.clone().unwrap_or_else(|| DEFAULT_VAULT_MOUNT.to_string()),
+                }
+            }
+            "aws" => {
+                let bedrock = &providers.bedrock;
+                let credentials = match (&bedrock.access_key_id, &bedrock.secret_access_key) {
+                    (Some(access_key_id), Some(secret_access_key)) => Credentials {
+                        access_key_id: access_key_id.clone(),
+                        secret_access_key: secret_access_key.clone(),
+                        session_token: bedrock.session_token.clone(),
+                    },
+                    _ => anyhow::bail!("The AWS secrets backend needs AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY"),
+                };
+                let region = config
+                    .aws
+                    .region
+                    .clone()
+                    .or_else(|| bedrock.region.clone())
+                    .unwrap_or_else(|| DEFAULT_AWS_REGION.to_string());
+                let endpoint = match &config.aws.endpoint {
+                    Some(endpoint) => endpoint.clone(),
+                    None => format!("https://secretsmanager.{}.amazonaws.com/", region),
+                };
+                let endpoint = Url::parse(&endpoint).with_context(|| format!("Invalid Secrets Manager endpoint '{}'", endpoint))?;
+                Backend::Aws { endpoint, region, credentials }
+            }
+            other => anyhow::bail!("Unknown secrets backend '{}', expected vault or aws", other),
+        };
+
+        let keys = &config.keys;
+        let keys: Vec<(Target, Reference)> = [
+            (Target::OpenAiApiKey, &keys.openai_api_key),
+            (Target::AnthropicApiKey, &keys.anthropic_api_key),
+            (Target::AzureApiKey, &keys.azure_api_key),
+            (Target::GeminiApiKey, &keys.gemini_api_key),
+            (Target::AwsAccessKeyId, &keys.aws_access_key_id),
+            (Target::AwsSecretAccessKey, &keys.aws_secret_access_key),
+        ]
+        .into_iter()
+        .filter_map(|(target, reference)| Some((target, parse_reference(reference.as_deref()?))))
+        .collect();
+        if keys.is_empty() {
+            anyhow::bail!("A secrets backend is configured but no secrets.keys are");
+        }
+        if matches!(backend, Backend::Vault { .. }) {
+            if let Some((_, reference)) = keys.iter().find(|(_, reference)| reference.field.is_none()) {
+                anyhow::bail!("Vault secret '{}' needs a #field", reference.secret);
+            }
+        }
+
+        let secs = config.refresh_secs.unwrap_or(DEFAULT_REFRESH_SECS);
+        Ok(Some(Self {
+            client: Client::builder().timeout(REQUEST_TIMEOUT).build()?,
+            backend,
+            keys,
+            values: RwLock::new(HashMap::new()),
+            interval: (secs > 0).then(|| Duration::from_secs(secs)),
+        }))
+    }
+
+    fn backend_name(&self) -> &'static str {
+        match self.backend {
+            Backend::Vault { .. } => "Vault",
+            Backend::Aws { .. } => "AWS Secrets Manager",
+        }
+    }
+
+    /// The fetch at startup, which unlike the refreshes has to succeed.
+    pub async fn fetch_initial(&self) -> Result<()> {
+        self.fetch().await?;
+        info!("🔑 Fetched {} provider secrets from {}", self.keys.len(), self.backend_name());
+        Ok(())
+    }
+
+    /// Fetches every configured secret, keeping the previous values unless all of them could
+    /// be read. Returns whether any value changed.
+    async fn fetch(&self) -> Result<bool> {
+        let mut secrets: HashMap<&str, Value> = HashMap::new();
+        let mut values = HashMap::new();
+        for (target, reference) in &self.keys {
+            if !secrets.contains_key(reference.secret.as_str()) {
+                let secret = self.read(&reference.secret).await?;
+                secrets.insert(&reference.secret, secret);
+            }
+            let value = field(&secrets[reference.secret.as_str()], reference)?;
+            values.insert(*target, value);
+        }
+        let mut current = self.values.write().unwrap();
+        let changed = *current != values;
+        *current = values;
+        Ok(changed)
+    }
+
+    /// A Vault secret's fields as an object, or an AWS secret's string.
+    async fn read(&self, secret: &str) -> Result<Value> {
+        match &self.backend {
+            Backend::Vault { address, token, namespace, mount } => {
+                let url = format!("{}/v1/{}/data/{}", address.trim_end_matches('/'), mount, secret);
+                let mut builder = self.client.get(&url).header("x-vault-token", token);
+                if let Some(namespace) = namespace {
+                    builder = builder.header("x-vault-namespace", namespace);
+                }
+                let resp = builder.send().await.with_context(|| format!("Failed to reach Vault at {}", address))?;
+                let status = resp.status();
+                let body: Value = resp.json().await.unwrap_or_default();
+                if !status.is_success() {
+                    anyhow::bail!("Vault answered {} for secret '{}': {}", status, secret, body["errors"]);
+                }
+                Ok(body["data"]["data"].clone())
+            }
+            Backend::Aws { endpoint, region, credentials } => {
+                let host = match endpoint.port() {
+                    Some(port) => format!("{}:{}", endpoint.host_str().unwrap_or_default(), port),
+                    None => endpoint.host_str().unwrap_or_default().to_string(),
+                };
+                let content_type = "application/x-amz-json-1.1";
+                let body = serde_json::to_vec(&json!({ "SecretId": secret }))?;
+                let signed = sigv4::sign_post(credentials, region, "secretsmanager", &host, endpoint.path(), content_type, &body);
+                let mut builder = self
+                    .client
+                    .post(endpoint.clone())
+                    .header("content-type", content_type)
+                    .header("x-amz-target", "secretsmanager.GetSecretValue")
+                    .header("x-amz-date", &signed.amz_date)
+                    .header("authorization", &signed.authorization);
+                if let Some(token) = &signed.security_token {
+                    builder = builder.header("x-amz-security-token", token);
+                }
+                let resp = builder.body(body).send().await.with_context(|| format!("Failed to reach {}", endpoint))?;
+                let status = resp.status();
+                let body: Value = resp.json().await.unwrap_or_default();
+                if !status.is_success() {
+                    let message = body["message"].as_str().or(body["Message"].as_str()).unwrap_or_default();
+                    anyhow::bail!("Secrets Manager answered {} for secret '{}': {} {}", status, secret, body["__type"], message);
+                }
+                match body["SecretString"].as_str() {
+                    Some(value) => Ok(Value::String(value.to_string())),
+                    None => anyhow::bail!("Secret '{}' has no string value", secret),
+                }
+            }
+        }
+    }
+
+    /// Replaces the settings that come from secrets with the last fetched values.
+    pub fn apply(&self, providers: &mut ProvidersConfig) {
+        for (target, value) in self.values.read().unwrap().iter() {
+            let field = match target {
+                Target::OpenAiApiKey => &mut providers.openai.api_key,
+                Target::AnthropicApiKey => &mut providers.anthropic.api_key,
+                Target::AzureApiKey => &mut providers.azure.api_key,
+                Target::GeminiApiKey => &mut providers.gemini.api_key,
+                Target::AwsAccessKeyId => &mut providers.bedrock.access_key_id,
+                Target::AwsSecretAccessKey => &mut providers.bedrock.secret_access_key,
+            };
+            *field = Some(value.clone());
+        }
+    }
+}
+
+fn parse_reference(reference: &str) -> Reference {
+    match reference.rsplit_once('#') {
+        Some((secret, field)) => Reference { secret: secret.to_string(), field: Some(field.to_string()) },
+        None => Reference { secret: reference.to_string(), field: None },
+    }
+}
+
+/// The referenced field of a secret; an AWS secret string holding a JSON object has fields too.
+fn field(secret: &Value, reference: &Reference) -> Result<String> {
+    let Some(name) = &reference.field else {
+        return secret.as_str().map(str::to_string).with_context(|| format!("Secret '{}' isn't a string", reference.secret));
+    };
+    let object = match secret {
+        Value::String(raw) => serde_json::from_str(raw)
+            .with_context(|| format!("Secret '{}' isn't JSON, so it has no field '{}'", reference.secret, name))?,
+        other => other.clone(),
+    };
+    object[name.as_str()]
+        .as_str()
+        .map(str::to_string)
+        .with_context(|| format!("Secret '{}' has no field '{}'", reference.secret, name))
+}
+
+/// Fetches the secrets again every `refresh_secs` (default 300) and reloads the configuration
+/// when one changed. A failed fetch keeps the current values.
+pub fn spawn_refresher(secrets: Arc<SecretStore>, state: Arc<AppState>) {
+    let Some(interval) = secrets.interval else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match secrets.fetch().await {
+                Ok(false) => {}
+                Ok(true) => {
+                    info!("🔑 Provider secrets in {} changed; reloading", secrets.backend_name());
+                    if let Err(e) = state.reloader.reload(&state).await {
+                        error!("❌ Failed to reload configuration, keeping the current one: {:?}", e);
+                    }
+                }
+                Err(e) => error!("❌ Failed to refresh secrets from {}, keeping the current ones: {:?}", secrets.backend_name(), e),
+            }
+        }
+    });
+}
@@ -0,0 +1,177 @@
+use std::{
+    fs,
+    future::Future,
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use axum::Router;
+use hyper::server::conn::http1;
+use hyper_util::{rt::TokioIo, service::TowerToHyperService};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, UnixListener, UnixStream},
+    sync::watch,
+};
+use tokio_rustls::TlsAcceptor;
+use tower_service::Service;
+use tracing::{debug, info, warn};
+
+/// Read and write for the owner and group, so the proxy only needs to share a group.
+const DEFAULT_SOCKET_MODE: u32 = 0o660;
+/// Connections that haven't finished the TLS handshake by then are dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// What the socket's clients appear as to the IP rules; set `trust_forwarded_for` so the
+/// proxy's `X-Forwarded-For` is used instead.
+const UNIX_PEER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+
+pub async fn listen(addr: SocketAddr) -> TcpListener {
+    TcpListener::bind(addr)
+        .await
+        .unwrap_or_else(|e| panic!("❌ Failed to bind {}: {}", addr, e))
+}
+
+/// Serves `app` until a value is sent on `stop`, then finishes the requests already accepted.
+pub async fn serve(listener: TcpListener, app: Router, mut stop: watch::Receiver<()>) -> io::Result<()> {
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            let _ = stop.changed().await;
+        })
+        .await
+}
+
+/// [`serve`] over HTTPS.
+pub async fn serve_tls(listener: TcpListener, app: Router, acceptor: TlsAcceptor, stop: watch::Receiver<()>) -> io::Result<()> {
+    let handshake = move |stream, peer| {
+        let acceptor = acceptor.clone();
+        async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => Some(stream),
+                Ok(Err(e)) => {
+                    debug!("TLS handshake with {} failed: {}", peer, e);
+                    None
+                }
+                Err(_) => {
+                    debug!("TLS handshake with {} timed out", peer);
+                    None
+                }
+            }
+        }
+    };
+    serve_connections(app, stop, || listener.accept(), handshake).await;
+    Ok(())
+}
+
+/// Binds the Unix socket at `path` with `mode` permissions (0660 by default). A socket left
+/// behind by an instance that didn't shut down cleanly is replaced; one that still accepts
+/// connections, or a file that isn't a socket, is an error.
+pub async fn listen_unix(path: &str, mode: Option<u32>) -> Result<UnixSocket> {
+    let path = PathBuf::from(path);
+    if let Ok(metadata) = fs::symlink_metadata(&path) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{} exists and isn't a socket", path.display());
+        }
+        if UnixStream::connect(&path).await.is_ok() {
+            anyhow::bail!("{} is in use by another process", path.display());
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+        info!("🧹 Removed stale socket {}", path.display());
+    }
+    let listener = UnixListener::bind(&path).with_context(|| format!("Failed to bind {}", path.display()))?;
+    let socket = UnixSocket { listener, path };
+    let mode = mode.unwrap_or(DEFAULT_SOCKET_MODE);
+    fs::set_permissions(&socket.path, fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to set permissions {:o} on {}", mode, socket.path.display()))?;
+    Ok(socket)
+}
+
+/// A bound Unix socket; the socket file is removed when it's dropped.
+pub struct UnixSocket {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl UnixSocket {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("⚠️ Failed to remove socket {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// [`serve`] on a Unix socket, removing the socket file once stopped.
+pub async fn serve_unix(socket: UnixSocket, app: Router, stop: watch::Receiver<()>) -> io::Result<()> {
+    let listener = &socket.listener;
+    let accept = || async move { listener.accept().await.map(|(stream, _)| (stream, UNIX_PEER)) };
+    serve_connections(app, stop, accept, |stream, _| std::future::ready(Some(stream))).await;
+    drop(socket);
+    Ok(())
+}
+
+/// The accept loop for the listeners `axum::serve` can't drive: HTTP/1 on every connection
+/// `handshake` turns into a stream, until a value is sent on `stop`. Returns once the requests
+/// already accepted are done.
+async fn serve_connections<I, S, A, H>(
+    app: Router,
+    mut stop: watch::Receiver<()>,
+    mut accept: impl FnMut() -> A,
+    handshake: impl Fn(I, SocketAddr) -> H,
+) where
+    A: Future<Output = io::Result<(I, SocketAddr)>>,
+    H: Future<Output = Option<S>> + Send + 'static,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+    // Every connection holds a receiver; the sender sees them all dropped once they're done.
+    let (open_tx, open_rx) = watch::channel(());
+    loop {
+        let (io, peer) = tokio::select! {
+            accepted = accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Usually out of file descriptors; give connections a moment to close.
+                    warn!("⚠️ Failed to accept a connection: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+            _ = stop.changed() => break,
+        };
+        let Ok(service) = make_service.call(peer).await;
+        let handshake = handshake(io, peer);
+        let mut stop = stop.clone();
+        let open = open_rx.clone();
+        tokio::spawn(async move {
+            let Some(stream) = handshake.await else {
+                return;
+            };
+            let connection = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), TowerToHyperService::new(service))
+                .with_upgrades();
+            tokio::pin!(connection);
+            let result = tokio::select! {
+                result = connection.as_mut() => result,
+                _ = stop.changed() => {
+                    connection.as_mut().graceful_shutdown();
+                    connection.await
+                }
+            };
+            if let Err(e) = result {
+                debug!("Connection with {} ended with an error: {}", peer, e);
+            }
+            drop(open);
+        });
+    }
+    drop(open_rx);
+    open_tx.closed().await;
+}
@@ -0,0 +1,167 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::ToSchema;
+
+/// Risk severity, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, ToSchema)]
+pub enum Severity {
+    Negligible,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Negligible => "Negligible",
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+            Severity::Critical => "Critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    /// Case-insensitive, and accepts the synonyms models tend to use ("severe", "moderate",
+    /// "blocker", ...) as well as a 0–4 numeric rank.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        let normalized = normalized.trim_end_matches(" risk").trim_end_matches(" severity");
+        match normalized {
+            "negligible" | "none" | "info" | "informational" | "trivial" | "0" => Ok(Severity::Negligible),
+            "low" | "minor" | "1" => Ok(Severity::Low),
+            "medium" | "med" | "moderate" | "mid" | "2" => Ok(Severity::Medium),
+            "high" | "major" | "severe" | "serious" | "significant" | "3" => Ok(Severity::High),
+            "critical" | "blocker" | "catastrophic" | "extreme" | "very high" | "4" => Ok(Severity::Critical),
+            _ => Err(format!("unrecognized severity '{}'", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Rank(u64),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Text(s) => s.parse().map_err(serde::de::Error::custom),
+            Raw::Rank(n) => n.to_string().parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// The severity scheme an organization reports in (`SEVERITY_SCALE`). It determines which
+/// labels the model is told to use, which labels appear in responses, and whether a 0–10
+/// score accompanies them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeverityScale {
+    /// low / medium / high
+    Three,
+    /// low / medium / high / critical
+    #[default]
+    Four,
+    /// negligible / low / medium / high / critical
+    Five,
+    /// CVSS v3-style 0.0–10.0 base score, with the qualitative label derived from it.
+    Cvss,
+}
+
+impl FromStr for SeverityScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "3" | "three" => Ok(Self::Three),
+            "4" | "four" => Ok(Self::Four),
+            "5" | "five" => Ok(Self::Five),
+            "cvss" | "numeric" => Ok(Self::Cvss),
+            other => Err(format!("Unknown severity scale '{}'", other)),
+        }
+    }
+}
+
+impl SeverityScale {
+    pub fn levels(self) -> &'static [Severity] {
+        use Severity::*;
+        match self {
+            Self::Three => &[Low, Medium, High],
+            Self::Four => &[Low, Medium, High, Critical],
+            Self::Five | Self::Cvss => &[Negligible, Low, Medium, High, Critical],
+        }
+    }
+
+    /// Always true for CVSS; other scales only carry a score when it was asked for.
+    pub fn uses_score(self, scores_enabled: bool) -> bool {
+        self == Self::Cvss || scores_enabled
+    }
+
+    /// Wording for the system prompt, e.g. `severity (low, medium, high)`.
+    pub fn prompt_fragment(self, scores_enabled: bool) -> String {
+        let labels: Vec<String> = self.levels().iter().map(|s| s.as_str().to_lowercase()).collect();
+        let mut fragment = format!("severity ({})", labels.join(", "));
+        if self == Self::Cvss {
+            fragment.push_str(", score (0.0 to 10.0, a CVSS v3-style base score consistent with the severity)");
+        } else if scores_enabled {
+            fragment.push_str(", score (0.0 to 10.0, higher is more severe)");
+        }
+        fragment
+    }
+
+    /// Snaps a label onto the nearest level this scale has.
+    pub fn clamp(self, severity: Severity) -> Severity {
+        let levels = self.levels();
+        severity.clamp(levels[0], levels[levels.len() - 1])
+    }
+
+    /// Brings a parsed severity/score pair in line with the scale: CVSS derives the label
+    /// from the score, other scales clamp the label and fill in or drop the score.
+    pub fn normalize(self, severity: Severity, score: Option<f32>, scores_enabled: bool) -> (Severity, Option<f32>) {
+        let score = score.map(|s| s.clamp(0.0, 10.0));
+        match self {
+            Self::Cvss => match score {
+                Some(score) => (Self::cvss_label(score), Some(score)),
+                None => (severity, Some(Self::midpoint(severity))),
+            },
+            _ if scores_enabled => (self.clamp(severity), Some(score.unwrap_or_else(|| Self::midpoint(severity)))),
+            _ => (self.clamp(severity), None),
+        }
+    }
+
+    /// CVSS v3 qualitative severity rating bands.
+    fn cvss_label(score: f32) -> Severity {
+        match score {
+            s if s < 0.1 => Severity::Negligible,
+            s if s < 4.0 => Severity::Low,
+            s if s < 7.0 => Severity::Medium,
+            s if s < 9.0 => Severity::High,
+            _ => Severity::Critical,
+        }
+    }
+
+    /// Representative score for a label when the model didn't give one.
+    fn midpoint(severity: Severity) -> f32 {
+        match severity {
+            Severity::Negligible => 0.0,
+            Severity::Low => 2.0,
+            Severity::Medium => 5.5,
+            Severity::High => 8.0,
+            Severity::Critical => 9.5,
+        }
+    }
+}
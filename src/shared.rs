@@ -0,0 +1,198 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use redis::{aio::ConnectionManager, AsyncCommands, Script};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::StorageConfig;
+use crate::storage::Storage;
+
+const DEFAULT_KEY_PREFIX: &str = "risk-evaluator:";
+/// A replica whose heartbeat is older than this is presumed gone, and its jobs orphaned.
+const INSTANCE_TTL_SECS: u64 = 30;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Refills a token bucket kept as a hash, by Redis's clock so replicas' clocks don't matter,
+/// then takes a token. Returns 0, or the milliseconds until a token is available.
+const TAKE_TOKEN: &str = r"
+local burst = tonumber(ARGV[1])
+local per_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or burst
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - updated) * per_ms)
+local wait = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+else
+  wait = math.ceil((1 - tokens) / per_ms)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(burst / per_ms))
+return wait
+";
+
+/// State replicas share through Redis (`REDIS_URL`): cached analyses, rate-limit buckets,
+/// idempotency keys and which replica owns each unfinished job. Cheap to clone.
+#[derive(Clone)]
+pub struct SharedStore {
+    connection: ConnectionManager,
+    prefix: Arc<str>,
+    /// This process, as the owner of the jobs it accepted.
+    instance: Uuid,
+    take_token: Arc<Script>,
+}
+
+impl SharedStore {
+    /// `None` without a `redis_url`. Fails if Redis can't be reached, like the database.
+    pub async fn connect(config: &StorageConfig) -> Result<Option<Self>> {
+        let Some(url) = config.redis_url.as_deref() else {
+            return Ok(None);
+        };
+        let client = redis::Client::open(url).context("Invalid REDIS_URL")?;
+        let connection = ConnectionManager::new(client).await.context("Failed to connect to Redis")?;
+        let store = Self {
+            connection,
+            prefix: config.redis_key_prefix.as_deref().unwrap_or(DEFAULT_KEY_PREFIX).into(),
+            instance: Uuid::new_v4(),
+            take_token: Arc::new(Script::new(TAKE_TOKEN)),
+        };
+        store.heartbeat().await?;
+        info!(instance = %store.instance, "🧵 Sharing state through Redis");
+        Ok(Some(store))
+    }
+
+    /// Round-trips a `PING`, for readiness checks.
+    pub async fn ping(&self) -> Result<()> {
+        let _: String = redis::cmd("PING").query_async(&mut self.connection.clone()).await?;
+        Ok(())
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let value: Option<String> = self.connection.clone().get(self.key(name)).await?;
+        value.map(|value| serde_json::from_str(&value)).transpose().context("Unreadable value in Redis")
+    }
+
+    pub async fn set_json<T: Serialize>(&self, name: &str, value: &T, ttl: Duration) -> Result<()> {
+        let value = serde_json::to_string(value)?;
+        let _: () = self.connection.clone().set_ex(self.key(name), value, ttl.as_secs().max(1)).await?;
+        Ok(())
+    }
+
+    /// Like [`SharedStore::set_json`], unless `name` is already set; false then.
+    pub async fn set_json_nx<T: Serialize>(&self, name: &str, value: &T, ttl: Duration) -> Result<bool> {
+        let value = serde_json::to_string(value)?;
+        let set: Option<String> = redis::cmd("SET")
+            .arg(self.key(name))
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut self.connection.clone())
+            .await?;
+        Ok(set.is_some())
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let _: () = self.connection.clone().del(self.key(name)).await?;
+        Ok(())
+    }
+
+    /// Takes a token from the bucket `name`, holding up to `burst` and refilling at `per_sec`,
+    /// or returns how long until one is available.
+    pub async fn take_token(&self, name: &str, burst: f64, per_sec: f64) -> Result<Result<(), Duration>> {
+        let wait_ms: u64 = self
+            .take_token
+            .key(self.key(name))
+            .arg(burst)
+            .arg(per_sec / 1000.0)
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+        Ok(if wait_ms == 0 { Ok(()) } else { Err(Duration::from_millis(wait_ms)) })
+    }
+
+    /// Records this replica as the owner of an unfinished job, so another one fails it if this
+    /// one goes away.
+    pub async fn claim_job(&self, id: Uuid) -> Result<()> {
+        let _: () = self.connection.clone().hset(self.key("jobs"), id.to_string(), self.instance.to_string()).await?;
+        Ok(())
+    }
+
+    /// Forgets a job's owner once it has finished.
+    pub async fn release_job(&self, id: Uuid) -> Result<()> {
+        let _: () = self.connection.clone().hdel(self.key("jobs"), id.to_string()).await?;
+        Ok(())
+    }
+
+    async fn heartbeat(&self) -> Result<()> {
+        let key = self.key(&format!("instance:{}", self.instance));
+        let _: () = self.connection.clone().set_ex(key, 1, INSTANCE_TTL_SECS).await?;
+        Ok(())
+    }
+
+    /// Jobs whose owner stopped sending heartbeats. Each is handed to one caller only, however
+    /// many replicas look at once.
+    async fn take_orphaned_jobs(&self) -> Result<Vec<Uuid>> {
+        let mut connection = self.connection.clone();
+        let owners: Vec<(String, String)> = connection.hgetall(self.key("jobs")).await?;
+        let mut alive = std::collections::HashMap::new();
+        let mut orphaned = Vec::new();
+        for (job, owner) in owners {
+            let owner_alive = match alive.get(&owner) {
+                Some(&owner_alive) => owner_alive,
+                None => {
+                    let owner_alive: bool = connection.exists(self.key(&format!("instance:{}", owner))).await?;
+                    alive.insert(owner, owner_alive);
+                    owner_alive
+                }
+            };
+            if owner_alive {
+                continue;
+            }
+            let taken: u32 = connection.hdel(self.key("jobs"), &job).await?;
+            match job.parse() {
+                Ok(id) if taken > 0 => orphaned.push(id),
+                Ok(_) => {}
+                Err(_) => warn!("⚠️ Ignoring invalid job ID '{}' in Redis", job),
+            }
+        }
+        Ok(orphaned)
+    }
+}
+
+/// Keeps this replica's heartbeat fresh and fails the jobs of replicas that stopped, which
+/// never finish now that their queue is gone.
+pub fn spawn_heartbeat(shared: SharedStore, storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = shared.heartbeat().await {
+                warn!("⚠️ Failed to refresh the Redis heartbeat: {:?}", e);
+                continue;
+            }
+            let orphaned = match shared.take_orphaned_jobs().await {
+                Ok(orphaned) => orphaned,
+                Err(e) => {
+                    warn!("⚠️ Failed to look for orphaned jobs: {:?}", e);
+                    continue;
+                }
+            };
+            for id in orphaned {
+                match storage.fail_job(id, "Interrupted when its server stopped; please resubmit").await {
+                    Ok(true) => info!(job_id = %id, "🧹 Marked a job orphaned by a stopped replica as failed"),
+                    Ok(false) => {}
+                    Err(e) => error!(job_id = %id, error = ?e, "❌ Failed to fail an orphaned job"),
+                }
+            }
+        }
+    });
+}
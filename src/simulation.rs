@@ -0,0 +1,257 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::LimitsConfig;
+use crate::RiskItem;
+
+const DEFAULT_ITERATIONS: u32 = 10_000;
+const DEFAULT_MAX_ITERATIONS: u32 = 100_000;
+pub const DEFAULT_BINS: usize = 20;
+pub const MAX_BINS: usize = 100;
+/// Chance a risk occurs in one iteration, by likelihood rating: the middle of each band.
+const PROBABILITIES: [f64; 5] = [0.1, 0.3, 0.5, 0.7, 0.9];
+/// What a risk without a likelihood or impact rating is taken to be: possible, moderate.
+const UNRATED: u8 = 3;
+/// `unit` of a simulation over impact ratings rather than cost estimates.
+pub const IMPACT_POINTS: &str = "impact_points";
+
+/// How many iterations `POST /simulate` runs: `simulation_iterations` unless the request asks
+/// for more or fewer, never more than `simulation_max_iterations`.
+pub struct Simulator {
+    default_iterations: u32,
+    max_iterations: u32,
+}
+
+impl Simulator {
+    pub fn from_config(limits: &LimitsConfig) -> Self {
+        let max_iterations = limits.simulation_max_iterations.filter(|&n| n > 0).unwrap_or(DEFAULT_MAX_ITERATIONS);
+        Self {
+            default_iterations: limits.simulation_iterations.filter(|&n| n > 0).unwrap_or(DEFAULT_ITERATIONS).min(max_iterations),
+            max_iterations,
+        }
+    }
+
+    pub fn iterations(&self, requested: Option<u32>) -> Result<u32, String> {
+        match requested {
+            None => Ok(self.default_iterations),
+            Some(n) if n > 0 && n <= self.max_iterations => Ok(n),
+            Some(_) => Err(format!("iterations must be between 1 and {}", self.max_iterations)),
+        }
+    }
+}
+
+/// Aggregate exposure of an evaluation's risks over many simulated runs of the project.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Simulation {
+    pub evaluation_id: Uuid,
+    pub iterations: u32,
+    /// Replays this simulation when sent back.
+    pub seed: u64,
+    /// The currency of the risks' `cost_impact` when every risk has one, else `impact_points`:
+    /// the sum of the 1–5 impact ratings of the risks that occurred.
+    pub unit: String,
+    pub risks: usize,
+    pub mean: f64,
+    pub p50: f64,
+    pub p80: f64,
+    pub p95: f64,
+    /// The worst run.
+    pub max: f64,
+    /// Equal-width bins from the best run to the worst.
+    pub histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HistogramBin {
+    pub from: f64,
+    pub to: f64,
+    /// Runs whose exposure fell in `[from, to)`; the last bin includes `to`.
+    pub count: u32,
+}
+
+/// One risk as the simulation sees it: a chance of occurring, and a triangular distribution
+/// of what it costs when it does.
+struct Distribution {
+    probability: f64,
+    min: f64,
+    likely: f64,
+    max: f64,
+}
+
+impl Distribution {
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        if rng.gen::<f64>() >= self.probability {
+            return 0.0;
+        }
+        let (min, likely, max) = (self.min, self.likely, self.max);
+        if max <= min {
+            return likely;
+        }
+        // Inverse of the triangular CDF.
+        let u = rng.gen::<f64>();
+        let split = (likely - min) / (max - min);
+        if u < split {
+            min + (u * (max - min) * (likely - min)).sqrt()
+        } else {
+            max - ((1.0 - u) * (max - min) * (max - likely)).sqrt()
+        }
+    }
+}
+
+/// The distributions of `risks`, in the currency they're all estimated in or else in impact
+/// points, where a risk rated `n` costs between `n - 1` and `n + 1`, most likely `n`.
+fn distributions(risks: &[RiskItem]) -> (String, Vec<Distribution>) {
+    let currency = risks.first().and_then(|risk| risk.cost_impact.as_ref()).map(|cost| cost.currency.clone());
+    let monetary = currency.filter(|currency| {
+        risks.iter().all(|risk| risk.cost_impact.as_ref().is_some_and(|cost| &cost.currency == currency))
+    });
+    let distributions = risks
+        .iter()
+        .map(|risk| {
+            let likelihood = risk.likelihood.map_or(UNRATED, |rating| rating.value());
+            let probability = PROBABILITIES[usize::from(likelihood - 1)];
+            match (&monetary, &risk.cost_impact) {
+                (Some(_), Some(cost)) => Distribution { probability, min: cost.min, likely: cost.likely, max: cost.max },
+                _ => {
+                    let impact = f64::from(risk.impact.map_or(UNRATED, |rating| rating.value()));
+                    Distribution { probability, min: impact - 1.0, likely: impact, max: impact + 1.0 }
+                }
+            }
+        })
+        .collect();
+    (monetary.unwrap_or_else(|| IMPACT_POINTS.to_string()), distributions)
+}
+
+/// Runs `iterations` simulated projects, each summing what the risks that occurred cost. CPU
+/// bound; callers on the runtime should run it with `spawn_blocking`.
+pub fn run(evaluation_id: Uuid, risks: &[RiskItem], iterations: u32, bins: usize, seed: u64) -> Simulation {
+    let (unit, distributions) = distributions(risks);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut outcomes: Vec<f64> =
+        (0..iterations).map(|_| distributions.iter().map(|risk| risk.sample(&mut rng)).sum()).collect();
+    outcomes.sort_by(f64::total_cmp);
+
+    Simulation {
+        evaluation_id,
+        iterations,
+        seed,
+        unit,
+        risks: risks.len(),
+        mean: outcomes.iter().sum::<f64>() / outcomes.len() as f64,
+        p50: percentile(&outcomes, 0.5),
+        p80: percentile(&outcomes, 0.8),
+        p95: percentile(&outcomes, 0.95),
+        max: outcomes.last().copied().unwrap_or_default(),
+        histogram: histogram(&outcomes, bins),
+    }
+}
+
+/// Nearest-rank percentile of sorted `outcomes`.
+fn percentile(outcomes: &[f64], fraction: f64) -> f64 {
+    let rank = (fraction * outcomes.len() as f64).ceil() as usize;
+    outcomes[rank.clamp(1, outcomes.len()) - 1]
+}
+
+fn histogram(outcomes: &[f64], bins: usize) -> Vec<HistogramBin> {
+    let (low, high) = (outcomes[0], outcomes[outcomes.len() - 1]);
+    if high <= low {
+        return vec![HistogramBin { from: low, to: high, count: outcomes.len() as u32 }];
+    }
+    let width = (high - low) / bins as f64;
+    let mut counts = vec![0u32; bins];
+    for outcome in outcomes {
+        let bin = (((outcome - low) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| HistogramBin { from: low + width * i as f64, to: low + width * (i + 1) as f64, count })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn risk(likelihood: u8, impact: u8, cost: Option<(f64, f64, f64)>) -> RiskItem {
+        let mut risk = json!({
+            "severity": "High",
+            "category": "Schedule",
+            "mitigation": "Add a buffer.",
+            "likelihood": likelihood,
+            "impact": impact,
+        });
+        if let Some((min, likely, max)) = cost {
+            risk["cost_impact"] = json!({ "min": min, "likely": likely, "max": max, "currency": "EUR" });
+        }
+        serde_json::from_value(risk).unwrap()
+    }
+
+    fn risks() -> Vec<RiskItem> {
+        vec![
+            risk(4, 3, Some((1_000.0, 5_000.0, 20_000.0))),
+            risk(2, 5, Some((10_000.0, 40_000.0, 90_000.0))),
+            risk(5, 2, Some((500.0, 800.0, 1_500.0))),
+        ]
+    }
+
+    #[test]
+    fn a_fixed_seed_replays_the_simulation() {
+        let (id, risks) = (Uuid::new_v4(), risks());
+        let first = serde_json::to_value(run(id, &risks, 5_000, 20, 42)).unwrap();
+        let second = serde_json::to_value(run(id, &risks, 5_000, 20, 42)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first["unit"], "EUR");
+        assert_ne!(first, serde_json::to_value(run(id, &risks, 5_000, 20, 43)).unwrap());
+    }
+
+    #[test]
+    fn histogram_counts_every_iteration() {
+        for (iterations, bins) in [(1, 1), (10, 3), (5_000, 20), (10_000, 100)] {
+            let simulation = run(Uuid::new_v4(), &risks(), iterations, bins, 7);
+            assert_eq!(simulation.histogram.iter().map(|bin| bin.count).sum::<u32>(), iterations);
+            assert!(simulation.histogram.len() <= bins);
+        }
+    }
+
+    #[test]
+    fn percentiles_are_ordered() {
+        for seed in 0..20 {
+            let s = run(Uuid::new_v4(), &risks(), 2_000, 20, seed);
+            assert!(s.p50 <= s.p80 && s.p80 <= s.p95 && s.p95 <= s.max, "seed {}: {:?}", seed, s);
+            assert!(s.histogram[0].from <= s.p50);
+        }
+    }
+
+    #[test]
+    fn a_zero_width_range_lands_in_one_bin() {
+        // Every run the risk occurs costs exactly 2,000; the rest cost nothing.
+        let risks = [risk(5, 3, Some((2_000.0, 2_000.0, 2_000.0)))];
+        let simulation = run(Uuid::new_v4(), &risks, 1_000, 20, 1);
+        let (first, last) = (&simulation.histogram[0], &simulation.histogram[19]);
+        assert_eq!((first.from, last.to), (0.0, 2_000.0));
+        assert_eq!(first.count + last.count, 1_000);
+        assert!(last.count > first.count);
+        assert_eq!((simulation.p95, simulation.max), (2_000.0, 2_000.0));
+
+        // With every outcome the same, the whole histogram is that one bin.
+        let risks = [risk(2, 3, Some((0.0, 0.0, 0.0)))];
+        let simulation = run(Uuid::new_v4(), &risks, 1_000, 20, 1);
+        assert_eq!(simulation.histogram.len(), 1);
+        assert_eq!(simulation.histogram[0].count, 1_000);
+        assert_eq!(simulation.max, 0.0);
+    }
+
+    #[test]
+    fn unestimated_risks_are_simulated_in_impact_points() {
+        let risks = [risk(3, 4, None), risk(3, 2, Some((1.0, 2.0, 3.0)))];
+        let simulation = run(Uuid::new_v4(), &risks, 1_000, 10, 3);
+        assert_eq!(simulation.unit, IMPACT_POINTS);
+        assert!(simulation.max <= 8.0);
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+/// The rolling windows `/stats` reports, shortest first.
+pub const WINDOWS: [(&str, Duration); 3] =
+    [("1m", Duration::from_secs(60)), ("5m", Duration::from_secs(300)), ("1h", Duration::from_secs(3600))];
+/// Samples kept per series. Past this many calls an hour, the longer windows only cover the
+/// most recent ones.
+const MAX_SAMPLES: usize = 10_000;
+
+struct Sample {
+    at: Instant,
+    latency_ms: u32,
+    failed: bool,
+}
+
+/// Recent latencies and outcomes, one series per combination of label values, for percentiles
+/// over [`WINDOWS`]. Unlike the `/metrics` histograms this keeps the samples themselves, so the
+/// percentiles are exact rather than bucketed.
+pub struct RollingStats {
+    series: Mutex<BTreeMap<Vec<String>, VecDeque<Sample>>>,
+}
+
+/// One series over one window.
+#[derive(Debug, Serialize)]
+pub struct WindowStats {
+    /// `1m`, `5m` or `1h`.
+    pub window: &'static str,
+    pub count: usize,
+    /// Share of calls that failed, 0.0–1.0.
+    pub error_rate: f64,
+    pub p50_ms: Option<u32>,
+    pub p95_ms: Option<u32>,
+    pub p99_ms: Option<u32>,
+}
+
+impl Default for RollingStats {
+    fn default() -> Self {
+        Self { series: Mutex::new(BTreeMap::new()) }
+    }
+}
+
+impl RollingStats {
+    pub fn record(&self, values: &[&str], latency: Duration, failed: bool) {
+        let now = Instant::now();
+        let key = values.iter().map(|v| v.to_string()).collect();
+        let mut series = self.series.lock().unwrap();
+        let samples = series.entry(key).or_default();
+        samples.push_back(Sample { at: now, latency_ms: latency.as_millis().min(u32::MAX as u128) as u32, failed });
+        prune(samples, now);
+    }
+
+    /// Every series that has samples in the longest window, with its figures for each window.
+    pub fn snapshot(&self) -> Vec<(Vec<String>, Vec<WindowStats>)> {
+        let now = Instant::now();
+        let mut series = self.series.lock().unwrap();
+        series.retain(|_, samples| {
+            prune(samples, now);
+            !samples.is_empty()
+        });
+        series
+            .iter()
+            .map(|(values, samples)| {
+                let windows = WINDOWS
+                    .iter()
+                    .map(|&(name, window)| window_stats(name, samples, now, window))
+                    .collect();
+                (values.clone(), windows)
+            })
+            .collect()
+    }
+}
+
+fn prune(samples: &mut VecDeque<Sample>, now: Instant) {
+    let longest = WINDOWS[WINDOWS.len() - 1].1;
+    while samples.front().is_some_and(|s| now.duration_since(s.at) > longest) || samples.len() > MAX_SAMPLES {
+        samples.pop_front();
+    }
+}
+
+fn window_stats(name: &'static str, samples: &VecDeque<Sample>, now: Instant, window: Duration) -> WindowStats {
+    // Oldest first, so the window is a suffix.
+    let start = samples.partition_point(|s| now.duration_since(s.at) > window);
+    let mut latencies: Vec<u32> = samples.range(start..).map(|s| s.latency_ms).collect();
+    let failed = samples.range(start..).filter(|s| s.failed).count();
+    latencies.sort_unstable();
+
+    let count = latencies.len();
+    // Nearest-rank percentile.
+    let percentile = |p: f64| {
+        let rank = ((p * count as f64).ceil() as usize).clamp(1, count.max(1));
+        latencies.get(rank - 1).copied()
+    };
+    WindowStats {
+        window: name,
+        count,
+        error_rate: if count == 0 { 0.0 } else { failed as f64 / count as f64 },
+        p50_ms: percentile(0.50),
+        p95_ms: percentile(0.95),
+        p99_ms: percentile(0.99),
+    }
+}
@@ -0,0 +1,442 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::auth::Role;
+use crate::config::StorageConfig;
+use crate::extraction::ParsePath;
+use crate::providers::TokenUsage;
+use crate::register::RegisterEntry;
+use crate::severity::Severity;
+use crate::RiskItem;
+
+mod postgres;
+mod sqlite;
+
+pub use postgres::PostgresStorage;
+pub use sqlite::SqliteStorage;
+
+const DEFAULT_DATABASE_URL: &str = "sqlite:evaluations.db";
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+const DEFAULT_PURGE_AFTER_DAYS: i64 = 30;
+const DEFAULT_PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// One `/evaluate` call as persisted: what was asked, what came back, and how.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Evaluation {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub description: String,
+    /// The full request body, including any overrides and filters.
+    pub request: Value,
+    pub risks: Vec<RiskItem>,
+    pub provider: String,
+    pub model: Option<String>,
+    pub parse_path: Option<ParsePath>,
+    pub latency_ms: i64,
+    pub usage: Option<TokenUsage>,
+    /// Hidden from listings unless explicitly requested, but still retrievable by ID.
+    pub archived: bool,
+    pub tags: Vec<String>,
+    pub project_id: Option<Uuid>,
+    /// `X-Request-Id` of the call that produced it.
+    pub request_id: Option<String>,
+    #[serde(skip)]
+    pub tenant_id: String,
+}
+
+/// A database-managed API key. Only the hash of the key is stored.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(skip)]
+    pub key_hash: String,
+    pub role: Role,
+    pub tenant_id: String,
+    /// Overrides the tenant's or the service's monthly token budget for this key.
+    pub monthly_token_budget: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Requests and LLM tokens consumed by one key over a period.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct UsageTotals {
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    /// USD at list price; usage of unpriced models counts as free.
+    pub estimated_cost_usd: f64,
+}
+
+/// One day of a key's [`UsageTotals`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DailyUsage {
+    pub day: NaiveDate,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> i64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// One authenticated request, as recorded in the append-only audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub tenant_id: String,
+    /// [`crate::auth::Identity::key_id`] of the caller.
+    pub key_id: String,
+    pub actor: String,
+    /// Method and route pattern, e.g. `DELETE /evaluations/:id`.
+    pub action: String,
+    /// The evaluation, project or key acted on, if any.
+    pub resource_id: Option<String>,
+    /// HTTP status of the response, so refused attempts are on record too.
+    pub status: i32,
+    /// `X-Request-Id` of the request.
+    pub request_id: Option<String>,
+}
+
+/// Criteria for exporting the audit log; unset fields match everything.
+#[derive(Debug, Default)]
+pub struct AuditFilter {
+    /// Inclusive lower bound on `created_at`.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub to: Option<DateTime<Utc>>,
+    pub key_id: Option<String>,
+}
+
+/// Where an asynchronous evaluation is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            other => anyhow::bail!("Unknown job status '{}'", other),
+        }
+    }
+}
+
+/// Which worker lane an async job waits in. Interactive jobs are always picked first; batch
+/// jobs get whatever the interactive lane leaves, within their own concurrency cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    #[default]
+    Interactive,
+    Batch,
+}
+
+impl JobPriority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobPriority::Interactive => "interactive",
+            JobPriority::Batch => "batch",
+        }
+    }
+}
+
+impl FromStr for JobPriority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "interactive" => Ok(JobPriority::Interactive),
+            "batch" => Ok(JobPriority::Batch),
+            other => anyhow::bail!("Unknown job priority '{}'", other),
+        }
+    }
+}
+
+/// An evaluation submitted via `POST /evaluate/async`, or a row of a `POST /evaluate/batch` CSV.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Job {
+    pub id: Uuid,
+    #[serde(skip)]
+    pub tenant_id: String,
+    pub key_id: String,
+    pub status: JobStatus,
+    pub priority: JobPriority,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// The `RiskResponse`, once the job has succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<crate::RiskResponse>)]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The batch the job is a row of, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<Uuid>,
+    /// The row's line number in the batch's CSV, counting the header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_row: Option<i32>,
+    /// The row's `name` column, so results can be matched to projects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A named initiative whose evaluations are tracked together over time.
+#[derive(Debug, Clone, Serialize)]
+pub struct Project {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub tenant_id: String,
+}
+
+/// Persistence backend for evaluations.
+///
+/// Everything but API key lookup and purging belongs to a tenant: records are written with
+/// their own `tenant_id`, and every read or update takes the caller's tenant and never sees
+/// another tenant's rows.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn insert_evaluation(&self, evaluation: &Evaluation) -> Result<()>;
+
+    /// Soft-deleted evaluations are treated as missing.
+    async fn get_evaluation(&self, tenant: &str, id: Uuid) -> Result<Option<Evaluation>>;
+
+    /// Up to `limit` evaluations matching `filter`, newest first, starting after `after`.
+    async fn list_evaluations(
+        &self,
+        tenant: &str,
+        filter: &EvaluationFilter,
+        after: Option<&Cursor>,
+        limit: u32,
+    ) -> Result<Vec<Evaluation>>;
+
+    /// Soft-deletes an evaluation; `false` if it doesn't exist or is already deleted.
+    async fn delete_evaluation(&self, tenant: &str, id: Uuid) -> Result<bool>;
+
+    /// `false` if the evaluation doesn't exist or is deleted.
+    async fn set_archived(&self, tenant: &str, id: Uuid, archived: bool) -> Result<bool>;
+
+    /// Replaces the evaluation's tags; `false` if it doesn't exist or is deleted.
+    async fn set_tags(&self, tenant: &str, id: Uuid, tags: &[String]) -> Result<bool>;
+
+    async fn insert_project(&self, project: &Project) -> Result<()>;
+
+    async fn get_project(&self, tenant: &str, id: Uuid) -> Result<Option<Project>>;
+
+    /// All projects, newest first.
+    async fn list_projects(&self, tenant: &str) -> Result<Vec<Project>>;
+
+    /// Replaces the project's imported risk register; `false` if the project doesn't exist.
+    async fn set_register(&self, tenant: &str, project_id: Uuid, entries: &[RegisterEntry]) -> Result<bool>;
+
+    /// The project's register (empty if none was imported); `None` if the project doesn't exist.
+    async fn get_register(&self, tenant: &str, project_id: Uuid) -> Result<Option<Vec<RegisterEntry>>>;
+
+    async fn insert_api_key(&self, key: &ApiKey) -> Result<()>;
+
+    /// The unrevoked key with this hash, if any, whatever its tenant.
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>>;
+
+    async fn list_api_keys(&self, tenant: &str) -> Result<Vec<ApiKey>>;
+
+    /// `false` if the key doesn't exist or is already revoked.
+    async fn revoke_api_key(&self, tenant: &str, id: Uuid) -> Result<bool>;
+
+    /// Adds one request, its token usage and their estimated cost to the key's total for `day`.
+    async fn record_usage(
+        &self,
+        tenant: &str,
+        key_id: &str,
+        day: NaiveDate,
+        usage: Option<TokenUsage>,
+        cost_usd: Option<f64>,
+    ) -> Result<()>;
+
+    /// The key's totals for days in `from..to`.
+    async fn usage_between(&self, tenant: &str, key_id: &str, from: NaiveDate, to: NaiveDate) -> Result<UsageTotals>;
+
+    /// The key's totals for each day in `from..to` it was used, oldest first.
+    async fn daily_usage(&self, tenant: &str, key_id: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUsage>>;
+
+    async fn insert_audit_entry(&self, entry: &AuditEntry) -> Result<()>;
+
+    /// Entries matching `filter`, oldest first.
+    async fn list_audit_entries(&self, tenant: &str, filter: &AuditFilter) -> Result<Vec<AuditEntry>>;
+
+    async fn insert_job(&self, job: &Job) -> Result<()>;
+
+    async fn get_job(&self, tenant: &str, id: Uuid) -> Result<Option<Job>>;
+
+    /// The batch's jobs in row order; empty if there's no such batch.
+    async fn list_batch_jobs(&self, tenant: &str, batch_id: Uuid) -> Result<Vec<Job>>;
+
+    /// Writes the job's status, result, error and `updated_at`.
+    async fn update_job(&self, job: &Job) -> Result<()>;
+
+    /// Marks every queued or running job as failed with `error`, returning how many.
+    async fn fail_unfinished_jobs(&self, error: &str) -> Result<u64>;
+
+    /// Marks one job as failed with `error` if it's still queued or running; false otherwise.
+    async fn fail_job(&self, id: Uuid, error: &str) -> Result<bool>;
+
+    /// Permanently removes evaluations soft-deleted before `before`, returning how many.
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<u64>;
+
+    /// Round-trips a trivial query, for readiness checks.
+    async fn ping(&self) -> Result<()>;
+
+    /// Waits for outstanding queries and closes the connections; called once, on shutdown.
+    async fn close(&self);
+}
+
+/// Criteria for browsing stored evaluations; unset fields match everything.
+#[derive(Debug, Default)]
+pub struct EvaluationFilter {
+    /// Inclusive lower bound on `created_at`.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub to: Option<DateTime<Utc>>,
+    /// Only evaluations with at least one risk of this severity.
+    pub severity: Option<Severity>,
+    /// Only evaluations with at least one risk in this category (case-insensitive).
+    pub category: Option<String>,
+    /// Only evaluations carrying this tag (exact match).
+    pub tag: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub include_archived: bool,
+}
+
+/// Keyset position of the last evaluation on a page. Listings are ordered by `created_at` and
+/// then `id`, both descending, so the pair identifies a unique spot even when timestamps tie.
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn after(evaluation: &Evaluation) -> Self {
+        Self { created_at: evaluation.created_at, id: evaluation.id }
+    }
+
+    /// Opaque token handed to clients as `next_cursor`.
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}|{}", self.created_at.to_rfc3339(), self.id))
+    }
+
+    pub fn decode(token: &str) -> Result<Self> {
+        let raw = String::from_utf8(hex::decode(token)?)?;
+        let (created_at, id) = raw.split_once('|').ok_or_else(|| anyhow::anyhow!("Malformed cursor"))?;
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at)?.with_timezone(&Utc),
+            id: id.parse()?,
+        })
+    }
+}
+
+/// Opens the backend selected by `database_url` (defaults to a local SQLite file; `postgres://`
+/// URLs use a pool sized by `database_max_connections`) and runs its migrations.
+pub async fn connect(config: &StorageConfig) -> Result<Arc<dyn Storage>> {
+    let url = config.database_url.as_deref().unwrap_or(DEFAULT_DATABASE_URL);
+
+    if url.starts_with("sqlite:") {
+        return Ok(Arc::new(SqliteStorage::connect(url).await?));
+    }
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        let max_connections = config.database_max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        return Ok(Arc::new(PostgresStorage::connect(url, max_connections).await?));
+    }
+
+    anyhow::bail!("Unsupported DATABASE_URL scheme in '{}'", url)
+}
+
+const MAX_TAGS: usize = 20;
+const MAX_TAG_LEN: usize = 64;
+
+/// Trims and de-duplicates caller-supplied tags, preserving their order.
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tags must be at most {} characters", MAX_TAG_LEN));
+        }
+        if !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("At most {} tags are allowed", MAX_TAGS));
+    }
+    Ok(normalized)
+}
+
+/// Applies an add/remove edit to an existing tag set.
+pub fn edit_tags(mut tags: Vec<String>, add: &[String], remove: &[String]) -> Result<Vec<String>, String> {
+    tags.extend(add.iter().cloned());
+    tags.retain(|tag| !remove.iter().any(|r| r.trim() == tag));
+    normalize_tags(&tags)
+}
+
+/// Hard-deletes soft-deleted evaluations once they are older than `purge_after_days` (default
+/// 30), checking every `purge_interval_secs` (default one hour, also when set to 0).
+pub fn spawn_purge_job(storage: Arc<dyn Storage>, config: &StorageConfig) {
+    let retention = chrono::Duration::days(config.purge_after_days.unwrap_or(DEFAULT_PURGE_AFTER_DAYS));
+    let interval = Duration::from_secs(config.purge_interval_secs.filter(|&n| n > 0).unwrap_or(DEFAULT_PURGE_INTERVAL_SECS));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match storage.purge_deleted(Utc::now() - retention).await {
+                Ok(0) => {}
+                Ok(purged) => info!("🗑️ Purged {} deleted evaluations", purged),
+                Err(e) => warn!("⚠️ Purge job failed: {:?}", e),
+            }
+        }
+    });
+}
+
+/// Rebuilds token usage from its two nullable columns.
+fn usage_from_columns(prompt_tokens: Option<i32>, completion_tokens: Option<i32>) -> Option<TokenUsage> {
+    Some(TokenUsage {
+        prompt_tokens: prompt_tokens? as u32,
+        completion_tokens: completion_tokens? as u32,
+    })
+}
@@ -0,0 +1,546 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow, Postgres};
+use sqlx::types::Json;
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use super::{
+    usage_from_columns, ApiKey, AuditEntry, AuditFilter, Cursor, DailyUsage, Evaluation, EvaluationFilter, Job, Project, Storage, UsageTotals,
+};
+use crate::providers::TokenUsage;
+use crate::register::RegisterEntry;
+use crate::RiskItem;
+
+/// Shared Postgres database for multi-instance deployments.
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    /// Connects a pool of up to `max_connections` to `url` and applies pending migrations.
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .context("Failed to connect to Postgres")?;
+
+        sqlx::migrate!("./migrations/postgres")
+            .run(&pool)
+            .await
+            .context("Failed to run Postgres migrations")?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn insert_evaluation(&self, evaluation: &Evaluation) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO evaluations
+                (id, created_at, description, request, risks, provider, model, parse_path, latency_ms,
+                 prompt_tokens, completion_tokens, tags, project_id, tenant_id, request_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+        )
+        .bind(evaluation.id)
+        .bind(evaluation.created_at)
+        .bind(&evaluation.description)
+        .bind(Json(&evaluation.request))
+        .bind(Json(&evaluation.risks))
+        .bind(&evaluation.provider)
+        .bind(&evaluation.model)
+        .bind(evaluation.parse_path.map(|p| p.as_str()))
+        .bind(evaluation.latency_ms)
+        .bind(evaluation.usage.map(|u| u.prompt_tokens as i32))
+        .bind(evaluation.usage.map(|u| u.completion_tokens as i32))
+        .bind(Json(&evaluation.tags))
+        .bind(evaluation.project_id)
+        .bind(&evaluation.tenant_id)
+        .bind(&evaluation.request_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_evaluation(&self, tenant: &str, id: Uuid) -> Result<Option<Evaluation>> {
+        let row = sqlx::query("SELECT * FROM evaluations WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL")
+            .bind(id)
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(evaluation_from_row).transpose()
+    }
+
+    async fn list_evaluations(
+        &self,
+        tenant: &str,
+        filter: &EvaluationFilter,
+        after: Option<&Cursor>,
+        limit: u32,
+    ) -> Result<Vec<Evaluation>> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM evaluations WHERE deleted_at IS NULL");
+        query.push(" AND tenant_id = ").push_bind(tenant.to_string());
+        if !filter.include_archived {
+            query.push(" AND NOT archived");
+        }
+        if let Some(project_id) = filter.project_id {
+            query.push(" AND project_id = ").push_bind(project_id);
+        }
+        if let Some(from) = filter.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(severity) = filter.severity {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM jsonb_array_elements(risks) AS r WHERE r->>'severity' = ")
+                .push_bind(severity.as_str())
+                .push(")");
+        }
+        if let Some(category) = &filter.category {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM jsonb_array_elements(risks) AS r WHERE lower(r->>'category') = lower(")
+                .push_bind(category.clone())
+                .push("))");
+        }
+        if let Some(tag) = &filter.tag {
+            query.push(" AND tags ? ").push_bind(tag.clone());
+        }
+        if let Some(cursor) = after {
+            query
+                .push(" AND (created_at, id) < (")
+                .push_bind(cursor.created_at)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        query.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(limit as i64);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(evaluation_from_row).collect()
+    }
+
+    async fn delete_evaluation(&self, tenant: &str, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE evaluations SET deleted_at = $1 WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL")
+            .bind(Utc::now())
+            .bind(id)
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_archived(&self, tenant: &str, id: Uuid, archived: bool) -> Result<bool> {
+        let result = sqlx::query("UPDATE evaluations SET archived = $1 WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL")
+            .bind(archived)
+            .bind(id)
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_tags(&self, tenant: &str, id: Uuid, tags: &[String]) -> Result<bool> {
+        let result = sqlx::query("UPDATE evaluations SET tags = $1 WHERE id = $2 AND tenant_id = $3 AND deleted_at IS NULL")
+            .bind(Json(tags))
+            .bind(id)
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_project(&self, project: &Project) -> Result<()> {
+        sqlx::query("INSERT INTO projects (id, name, description, created_at, tenant_id) VALUES ($1, $2, $3, $4, $5)")
+            .bind(project.id)
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(project.created_at)
+            .bind(&project.tenant_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_project(&self, tenant: &str, id: Uuid) -> Result<Option<Project>> {
+        let row = sqlx::query("SELECT * FROM projects WHERE id = $1 AND tenant_id = $2")
+            .bind(id)
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(project_from_row).transpose()
+    }
+
+    async fn list_projects(&self, tenant: &str) -> Result<Vec<Project>> {
+        let rows = sqlx::query("SELECT * FROM projects WHERE tenant_id = $1 ORDER BY created_at DESC")
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(project_from_row).collect()
+    }
+
+    async fn set_register(&self, tenant: &str, project_id: Uuid, entries: &[RegisterEntry]) -> Result<bool> {
+        let result = sqlx::query("UPDATE projects SET register = $1 WHERE id = $2 AND tenant_id = $3")
+            .bind(Json(entries))
+            .bind(project_id)
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_register(&self, tenant: &str, project_id: Uuid) -> Result<Option<Vec<RegisterEntry>>> {
+        let register: Option<Json<Vec<RegisterEntry>>> =
+            sqlx::query_scalar("SELECT register FROM projects WHERE id = $1 AND tenant_id = $2")
+                .bind(project_id)
+                .bind(tenant)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(register.map(|register| register.0))
+    }
+
+    async fn insert_api_key(&self, key: &ApiKey) -> Result<()> {
+        sqlx::query("INSERT INTO api_keys (id, name, key_hash, role, tenant_id, monthly_token_budget, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)")
+            .bind(key.id)
+            .bind(&key.name)
+            .bind(&key.key_hash)
+            .bind(key.role.as_str())
+            .bind(&key.tenant_id)
+            .bind(key.monthly_token_budget)
+            .bind(key.created_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query("SELECT * FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL")
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(api_key_from_row).transpose()
+    }
+
+    async fn list_api_keys(&self, tenant: &str) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query("SELECT * FROM api_keys WHERE tenant_id = $1 ORDER BY created_at DESC")
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(api_key_from_row).collect()
+    }
+
+    async fn revoke_api_key(&self, tenant: &str, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE api_keys SET revoked_at = $1 WHERE id = $2 AND tenant_id = $3 AND revoked_at IS NULL")
+            .bind(Utc::now())
+            .bind(id)
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_usage(
+        &self,
+        tenant: &str,
+        key_id: &str,
+        day: NaiveDate,
+        usage: Option<TokenUsage>,
+        cost_usd: Option<f64>,
+    ) -> Result<()> {
+        let usage = usage.unwrap_or_default();
+        sqlx::query(
+            "INSERT INTO token_usage (key_id, day, tenant_id, requests, prompt_tokens, completion_tokens, cost_usd)
+             VALUES ($1, $2, $3, 1, $4, $5, $6)
+             ON CONFLICT (tenant_id, key_id, day) DO UPDATE SET
+                requests = token_usage.requests + 1,
+                prompt_tokens = token_usage.prompt_tokens + excluded.prompt_tokens,
+                completion_tokens = token_usage.completion_tokens + excluded.completion_tokens,
+                cost_usd = token_usage.cost_usd + excluded.cost_usd",
+        )
+        .bind(key_id)
+        .bind(day)
+        .bind(tenant)
+        .bind(usage.prompt_tokens as i64)
+        .bind(usage.completion_tokens as i64)
+        .bind(cost_usd.unwrap_or(0.0))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn usage_between(&self, tenant: &str, key_id: &str, from: NaiveDate, to: NaiveDate) -> Result<UsageTotals> {
+        let row = sqlx::query(
+            "SELECT COALESCE(SUM(requests), 0)::BIGINT AS requests, COALESCE(SUM(prompt_tokens), 0)::BIGINT AS prompt_tokens, COALESCE(SUM(completion_tokens), 0)::BIGINT AS completion_tokens, COALESCE(SUM(cost_usd), 0)::DOUBLE PRECISION AS cost_usd
+             FROM token_usage WHERE tenant_id = $1 AND key_id = $2 AND day >= $3 AND day < $4",
+        )
+        .bind(tenant)
+        .bind(key_id)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        usage_totals_from_row(&row)
+    }
+
+    async fn daily_usage(&self, tenant: &str, key_id: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUsage>> {
+        let rows = sqlx::query(
+            "SELECT day, requests, prompt_tokens, completion_tokens, cost_usd
+             FROM token_usage WHERE tenant_id = $1 AND key_id = $2 AND day >= $3 AND day < $4 ORDER BY day",
+        )
+        .bind(tenant)
+        .bind(key_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok(DailyUsage { day: row.try_get("day")?, totals: usage_totals_from_row(row)? }))
+            .collect()
+    }
+
+    async fn insert_audit_entry(&self, entry: &AuditEntry) -> Result<()> {
+        sqlx::query("INSERT INTO audit_log (id, created_at, tenant_id, key_id, actor, action, resource_id, status, request_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)")
+            .bind(entry.id)
+            .bind(entry.created_at)
+            .bind(&entry.tenant_id)
+            .bind(&entry.key_id)
+            .bind(&entry.actor)
+            .bind(&entry.action)
+            .bind(&entry.resource_id)
+            .bind(entry.status)
+            .bind(&entry.request_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_audit_entries(&self, tenant: &str, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM audit_log WHERE tenant_id = ");
+        query.push_bind(tenant.to_string());
+        if let Some(from) = filter.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(key_id) = &filter.key_id {
+            query.push(" AND key_id = ").push_bind(key_id.clone());
+        }
+        query.push(" ORDER BY created_at, id");
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(audit_entry_from_row).collect()
+    }
+
+    async fn insert_job(&self, job: &Job) -> Result<()> {
+        sqlx::query("INSERT INTO jobs (id, tenant_id, key_id, status, priority, created_at, updated_at, result, error, batch_id, batch_row, label)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)")
+            .bind(job.id)
+            .bind(&job.tenant_id)
+            .bind(&job.key_id)
+            .bind(job.status.as_str())
+            .bind(job.priority.as_str())
+            .bind(job.created_at)
+            .bind(job.updated_at)
+            .bind(job.result.clone().map(Json))
+            .bind(&job.error)
+            .bind(job.batch_id)
+            .bind(job.batch_row)
+            .bind(&job.label)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_job(&self, tenant: &str, id: Uuid) -> Result<Option<Job>> {
+        let row = sqlx::query("SELECT * FROM jobs WHERE id = $1 AND tenant_id = $2")
+            .bind(id)
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn list_batch_jobs(&self, tenant: &str, batch_id: Uuid) -> Result<Vec<Job>> {
+        let rows = sqlx::query("SELECT * FROM jobs WHERE batch_id = $1 AND tenant_id = $2 ORDER BY batch_row")
+            .bind(batch_id)
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(job_from_row).collect()
+    }
+
+    async fn update_job(&self, job: &Job) -> Result<()> {
+        sqlx::query("UPDATE jobs SET status = $1, updated_at = $2, result = $3, error = $4 WHERE id = $5")
+            .bind(job.status.as_str())
+            .bind(job.updated_at)
+            .bind(job.result.clone().map(Json))
+            .bind(&job.error)
+            .bind(job.id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fail_unfinished_jobs(&self, error: &str) -> Result<u64> {
+        let result = sqlx::query("UPDATE jobs SET status = 'failed', error = $1, updated_at = $2 WHERE status IN ('queued', 'running')")
+            .bind(error)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn fail_job(&self, id: Uuid, error: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE jobs SET status = 'failed', error = $1, updated_at = $2 WHERE id = $3 AND status IN ('queued', 'running')")
+            .bind(error)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM evaluations WHERE deleted_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+fn evaluation_from_row(row: &PgRow) -> Result<Evaluation> {
+    let request: Json<Value> = row.try_get("request")?;
+    let risks: Json<Vec<RiskItem>> = row.try_get("risks")?;
+    let parse_path: Option<String> = row.try_get("parse_path")?;
+    let tags: Json<Vec<String>> = row.try_get("tags")?;
+
+    Ok(Evaluation {
+        id: row.try_get("id")?,
+        created_at: row.try_get("created_at")?,
+        description: row.try_get("description")?,
+        request: request.0,
+        risks: risks.0,
+        provider: row.try_get("provider")?,
+        model: row.try_get("model")?,
+        parse_path: parse_path.map(|p| p.parse()).transpose()?,
+        latency_ms: row.try_get("latency_ms")?,
+        usage: usage_from_columns(row.try_get("prompt_tokens")?, row.try_get("completion_tokens")?),
+        archived: row.try_get("archived")?,
+        tags: tags.0,
+        project_id: row.try_get("project_id")?,
+        request_id: row.try_get("request_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+    })
+}
+
+fn project_from_row(row: &PgRow) -> Result<Project> {
+    Ok(Project {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        created_at: row.try_get("created_at")?,
+        tenant_id: row.try_get("tenant_id")?,
+    })
+}
+
+fn api_key_from_row(row: &PgRow) -> Result<ApiKey> {
+    let role: String = row.try_get("role")?;
+
+    Ok(ApiKey {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        key_hash: row.try_get("key_hash")?,
+        role: role.parse().map_err(anyhow::Error::msg)?,
+        tenant_id: row.try_get("tenant_id")?,
+        monthly_token_budget: row.try_get("monthly_token_budget")?,
+        created_at: row.try_get("created_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+    })
+}
+
+fn usage_totals_from_row(row: &PgRow) -> Result<UsageTotals> {
+    Ok(UsageTotals {
+        requests: row.try_get("requests")?,
+        prompt_tokens: row.try_get("prompt_tokens")?,
+        completion_tokens: row.try_get("completion_tokens")?,
+        estimated_cost_usd: row.try_get("cost_usd")?,
+    })
+}
+
+fn audit_entry_from_row(row: &PgRow) -> Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.try_get("id")?,
+        created_at: row.try_get("created_at")?,
+        tenant_id: row.try_get("tenant_id")?,
+        key_id: row.try_get("key_id")?,
+        actor: row.try_get("actor")?,
+        action: row.try_get("action")?,
+        resource_id: row.try_get("resource_id")?,
+        status: row.try_get("status")?,
+        request_id: row.try_get("request_id")?,
+    })
+}
+
+fn job_from_row(row: &PgRow) -> Result<Job> {
+    let status: String = row.try_get("status")?;
+    let priority: String = row.try_get("priority")?;
+    let result: Option<Json<Value>> = row.try_get("result")?;
+
+    Ok(Job {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        key_id: row.try_get("key_id")?,
+        status: status.parse()?,
+        priority: priority.parse()?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        result: result.map(|Json(r)| r),
+        error: row.try_get("error")?,
+        batch_id: row.try_get("batch_id")?,
+        batch_row: row.try_get("batch_row")?,
+        label: row.try_get("label")?,
+    })
+}
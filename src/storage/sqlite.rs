@@ -0,0 +1,562 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::{QueryBuilder, Row};
+use uuid::Uuid;
+
+use super::{
+    usage_from_columns, ApiKey, AuditEntry, AuditFilter, Cursor, DailyUsage, Evaluation, EvaluationFilter, Job, Project, Storage, UsageTotals,
+};
+use crate::providers::TokenUsage;
+use crate::register::RegisterEntry;
+
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    /// Opens (creating if necessary) the database at `url` and applies pending migrations.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(url)
+            .with_context(|| format!("Invalid SQLite URL '{}'", url))?
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .with_context(|| format!("Failed to open SQLite database '{}'", url))?;
+
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool)
+            .await
+            .context("Failed to run SQLite migrations")?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn insert_evaluation(&self, evaluation: &Evaluation) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO evaluations
+                (id, created_at, description, request, risks, provider, model, parse_path, latency_ms,
+                 prompt_tokens, completion_tokens, tags, project_id, tenant_id, request_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(evaluation.id.to_string())
+        .bind(evaluation.created_at)
+        .bind(&evaluation.description)
+        .bind(serde_json::to_string(&evaluation.request)?)
+        .bind(serde_json::to_string(&evaluation.risks)?)
+        .bind(&evaluation.provider)
+        .bind(&evaluation.model)
+        .bind(evaluation.parse_path.map(|p| p.as_str()))
+        .bind(evaluation.latency_ms)
+        .bind(evaluation.usage.map(|u| u.prompt_tokens as i32))
+        .bind(evaluation.usage.map(|u| u.completion_tokens as i32))
+        .bind(serde_json::to_string(&evaluation.tags)?)
+        .bind(evaluation.project_id.map(|id| id.to_string()))
+        .bind(&evaluation.tenant_id)
+        .bind(&evaluation.request_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_evaluation(&self, tenant: &str, id: Uuid) -> Result<Option<Evaluation>> {
+        let row = sqlx::query("SELECT * FROM evaluations WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL")
+            .bind(id.to_string())
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(evaluation_from_row).transpose()
+    }
+
+    async fn list_evaluations(
+        &self,
+        tenant: &str,
+        filter: &EvaluationFilter,
+        after: Option<&Cursor>,
+        limit: u32,
+    ) -> Result<Vec<Evaluation>> {
+        // Timestamps are stored as RFC 3339 text in a single format, so they compare correctly
+        // as strings.
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM evaluations WHERE deleted_at IS NULL");
+        query.push(" AND tenant_id = ").push_bind(tenant.to_string());
+        if !filter.include_archived {
+            query.push(" AND NOT archived");
+        }
+        if let Some(project_id) = filter.project_id {
+            query.push(" AND project_id = ").push_bind(project_id.to_string());
+        }
+        if let Some(from) = filter.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(severity) = filter.severity {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM json_each(evaluations.risks) WHERE json_extract(value, '$.severity') = ")
+                .push_bind(severity.as_str())
+                .push(")");
+        }
+        if let Some(category) = &filter.category {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM json_each(evaluations.risks) WHERE lower(json_extract(value, '$.category')) = lower(")
+                .push_bind(category.clone())
+                .push("))");
+        }
+        if let Some(tag) = &filter.tag {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM json_each(evaluations.tags) WHERE value = ")
+                .push_bind(tag.clone())
+                .push(")");
+        }
+        if let Some(cursor) = after {
+            query
+                .push(" AND (created_at < ")
+                .push_bind(cursor.created_at)
+                .push(" OR (created_at = ")
+                .push_bind(cursor.created_at)
+                .push(" AND id < ")
+                .push_bind(cursor.id.to_string())
+                .push("))");
+        }
+        query.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(limit as i64);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(evaluation_from_row).collect()
+    }
+
+    async fn delete_evaluation(&self, tenant: &str, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE evaluations SET deleted_at = ? WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL")
+            .bind(Utc::now())
+            .bind(id.to_string())
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_archived(&self, tenant: &str, id: Uuid, archived: bool) -> Result<bool> {
+        let result = sqlx::query("UPDATE evaluations SET archived = ? WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL")
+            .bind(archived)
+            .bind(id.to_string())
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_tags(&self, tenant: &str, id: Uuid, tags: &[String]) -> Result<bool> {
+        let result = sqlx::query("UPDATE evaluations SET tags = ? WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL")
+            .bind(serde_json::to_string(tags)?)
+            .bind(id.to_string())
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_project(&self, project: &Project) -> Result<()> {
+        sqlx::query("INSERT INTO projects (id, name, description, created_at, tenant_id) VALUES (?, ?, ?, ?, ?)")
+            .bind(project.id.to_string())
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(project.created_at)
+            .bind(&project.tenant_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_project(&self, tenant: &str, id: Uuid) -> Result<Option<Project>> {
+        let row = sqlx::query("SELECT * FROM projects WHERE id = ? AND tenant_id = ?")
+            .bind(id.to_string())
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(project_from_row).transpose()
+    }
+
+    async fn list_projects(&self, tenant: &str) -> Result<Vec<Project>> {
+        let rows = sqlx::query("SELECT * FROM projects WHERE tenant_id = ? ORDER BY created_at DESC")
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(project_from_row).collect()
+    }
+
+    async fn set_register(&self, tenant: &str, project_id: Uuid, entries: &[RegisterEntry]) -> Result<bool> {
+        let result = sqlx::query("UPDATE projects SET register = ? WHERE id = ? AND tenant_id = ?")
+            .bind(serde_json::to_string(entries)?)
+            .bind(project_id.to_string())
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_register(&self, tenant: &str, project_id: Uuid) -> Result<Option<Vec<RegisterEntry>>> {
+        let register: Option<String> = sqlx::query_scalar("SELECT register FROM projects WHERE id = ? AND tenant_id = ?")
+            .bind(project_id.to_string())
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        register.map(|register| serde_json::from_str(&register)).transpose().map_err(Into::into)
+    }
+
+    async fn insert_api_key(&self, key: &ApiKey) -> Result<()> {
+        sqlx::query("INSERT INTO api_keys (id, name, key_hash, role, tenant_id, monthly_token_budget, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)")
+            .bind(key.id.to_string())
+            .bind(&key.name)
+            .bind(&key.key_hash)
+            .bind(key.role.as_str())
+            .bind(&key.tenant_id)
+            .bind(key.monthly_token_budget)
+            .bind(key.created_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query("SELECT * FROM api_keys WHERE key_hash = ? AND revoked_at IS NULL")
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(api_key_from_row).transpose()
+    }
+
+    async fn list_api_keys(&self, tenant: &str) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query("SELECT * FROM api_keys WHERE tenant_id = ? ORDER BY created_at DESC")
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(api_key_from_row).collect()
+    }
+
+    async fn revoke_api_key(&self, tenant: &str, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE api_keys SET revoked_at = ? WHERE id = ? AND tenant_id = ? AND revoked_at IS NULL")
+            .bind(Utc::now())
+            .bind(id.to_string())
+            .bind(tenant)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_usage(
+        &self,
+        tenant: &str,
+        key_id: &str,
+        day: NaiveDate,
+        usage: Option<TokenUsage>,
+        cost_usd: Option<f64>,
+    ) -> Result<()> {
+        let usage = usage.unwrap_or_default();
+        sqlx::query(
+            "INSERT INTO token_usage (key_id, day, tenant_id, requests, prompt_tokens, completion_tokens, cost_usd)
+             VALUES (?, ?, ?, 1, ?, ?, ?)
+             ON CONFLICT (tenant_id, key_id, day) DO UPDATE SET
+                requests = token_usage.requests + 1,
+                prompt_tokens = token_usage.prompt_tokens + excluded.prompt_tokens,
+                completion_tokens = token_usage.completion_tokens + excluded.completion_tokens,
+                cost_usd = token_usage.cost_usd + excluded.cost_usd",
+        )
+        .bind(key_id)
+        .bind(day)
+        .bind(tenant)
+        .bind(usage.prompt_tokens as i64)
+        .bind(usage.completion_tokens as i64)
+        .bind(cost_usd.unwrap_or(0.0))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn usage_between(&self, tenant: &str, key_id: &str, from: NaiveDate, to: NaiveDate) -> Result<UsageTotals> {
+        let row = sqlx::query(
+            "SELECT COALESCE(SUM(requests), 0) AS requests, COALESCE(SUM(prompt_tokens), 0) AS prompt_tokens, COALESCE(SUM(completion_tokens), 0) AS completion_tokens, COALESCE(SUM(cost_usd), 0.0) AS cost_usd
+             FROM token_usage WHERE tenant_id = ? AND key_id = ? AND day >= ? AND day < ?",
+        )
+        .bind(tenant)
+        .bind(key_id)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        usage_totals_from_row(&row)
+    }
+
+    async fn daily_usage(&self, tenant: &str, key_id: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUsage>> {
+        let rows = sqlx::query(
+            "SELECT day, requests, prompt_tokens, completion_tokens, cost_usd
+             FROM token_usage WHERE tenant_id = ? AND key_id = ? AND day >= ? AND day < ? ORDER BY day",
+        )
+        .bind(tenant)
+        .bind(key_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok(DailyUsage { day: row.try_get("day")?, totals: usage_totals_from_row(row)? }))
+            .collect()
+    }
+
+    async fn insert_audit_entry(&self, entry: &AuditEntry) -> Result<()> {
+        sqlx::query("INSERT INTO audit_log (id, created_at, tenant_id, key_id, actor, action, resource_id, status, request_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(entry.id.to_string())
+            .bind(entry.created_at)
+            .bind(&entry.tenant_id)
+            .bind(&entry.key_id)
+            .bind(&entry.actor)
+            .bind(&entry.action)
+            .bind(&entry.resource_id)
+            .bind(entry.status)
+            .bind(&entry.request_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_audit_entries(&self, tenant: &str, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM audit_log WHERE tenant_id = ");
+        query.push_bind(tenant.to_string());
+        if let Some(from) = filter.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(key_id) = &filter.key_id {
+            query.push(" AND key_id = ").push_bind(key_id.clone());
+        }
+        query.push(" ORDER BY created_at, id");
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(audit_entry_from_row).collect()
+    }
+
+    async fn insert_job(&self, job: &Job) -> Result<()> {
+        sqlx::query("INSERT INTO jobs (id, tenant_id, key_id, status, priority, created_at, updated_at, result, error, batch_id, batch_row, label)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(job.id.to_string())
+            .bind(&job.tenant_id)
+            .bind(&job.key_id)
+            .bind(job.status.as_str())
+            .bind(job.priority.as_str())
+            .bind(job.created_at)
+            .bind(job.updated_at)
+            .bind(job.result.as_ref().map(Value::to_string))
+            .bind(&job.error)
+            .bind(job.batch_id.map(|id| id.to_string()))
+            .bind(job.batch_row)
+            .bind(&job.label)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_job(&self, tenant: &str, id: Uuid) -> Result<Option<Job>> {
+        let row = sqlx::query("SELECT * FROM jobs WHERE id = ? AND tenant_id = ?")
+            .bind(id.to_string())
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn list_batch_jobs(&self, tenant: &str, batch_id: Uuid) -> Result<Vec<Job>> {
+        let rows = sqlx::query("SELECT * FROM jobs WHERE batch_id = ? AND tenant_id = ? ORDER BY batch_row")
+            .bind(batch_id.to_string())
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(job_from_row).collect()
+    }
+
+    async fn update_job(&self, job: &Job) -> Result<()> {
+        sqlx::query("UPDATE jobs SET status = ?, updated_at = ?, result = ?, error = ? WHERE id = ?")
+            .bind(job.status.as_str())
+            .bind(job.updated_at)
+            .bind(job.result.as_ref().map(Value::to_string))
+            .bind(&job.error)
+            .bind(job.id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fail_unfinished_jobs(&self, error: &str) -> Result<u64> {
+        let result = sqlx::query("UPDATE jobs SET status = 'failed', error = ?, updated_at = ? WHERE status IN ('queued', 'running')")
+            .bind(error)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn fail_job(&self, id: Uuid, error: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE jobs SET status = 'failed', error = ?, updated_at = ? WHERE id = ? AND status IN ('queued', 'running')")
+            .bind(error)
+            .bind(Utc::now())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM evaluations WHERE deleted_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+fn evaluation_from_row(row: &SqliteRow) -> Result<Evaluation> {
+    let id: String = row.try_get("id")?;
+    let request: String = row.try_get("request")?;
+    let risks: String = row.try_get("risks")?;
+    let parse_path: Option<String> = row.try_get("parse_path")?;
+    let tags: String = row.try_get("tags")?;
+    let project_id: Option<String> = row.try_get("project_id")?;
+
+    Ok(Evaluation {
+        id: id.parse()?,
+        created_at: row.try_get("created_at")?,
+        description: row.try_get("description")?,
+        request: serde_json::from_str(&request)?,
+        risks: serde_json::from_str(&risks)?,
+        provider: row.try_get("provider")?,
+        model: row.try_get("model")?,
+        parse_path: parse_path.map(|p| p.parse()).transpose()?,
+        latency_ms: row.try_get("latency_ms")?,
+        usage: usage_from_columns(row.try_get("prompt_tokens")?, row.try_get("completion_tokens")?),
+        archived: row.try_get("archived")?,
+        tags: serde_json::from_str(&tags)?,
+        project_id: project_id.map(|id| id.parse()).transpose()?,
+        request_id: row.try_get("request_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+    })
+}
+
+fn project_from_row(row: &SqliteRow) -> Result<Project> {
+    let id: String = row.try_get("id")?;
+
+    Ok(Project {
+        id: id.parse()?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        created_at: row.try_get("created_at")?,
+        tenant_id: row.try_get("tenant_id")?,
+    })
+}
+
+fn api_key_from_row(row: &SqliteRow) -> Result<ApiKey> {
+    let id: String = row.try_get("id")?;
+    let role: String = row.try_get("role")?;
+
+    Ok(ApiKey {
+        id: id.parse()?,
+        name: row.try_get("name")?,
+        key_hash: row.try_get("key_hash")?,
+        role: role.parse().map_err(anyhow::Error::msg)?,
+        tenant_id: row.try_get("tenant_id")?,
+        monthly_token_budget: row.try_get("monthly_token_budget")?,
+        created_at: row.try_get("created_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+    })
+}
+
+fn usage_totals_from_row(row: &SqliteRow) -> Result<UsageTotals> {
+    Ok(UsageTotals {
+        requests: row.try_get("requests")?,
+        prompt_tokens: row.try_get("prompt_tokens")?,
+        completion_tokens: row.try_get("completion_tokens")?,
+        estimated_cost_usd: row.try_get("cost_usd")?,
+    })
+}
+
+fn audit_entry_from_row(row: &SqliteRow) -> Result<AuditEntry> {
+    let id: String = row.try_get("id")?;
+
+    Ok(AuditEntry {
+        id: id.parse()?,
+        created_at: row.try_get("created_at")?,
+        tenant_id: row.try_get("tenant_id")?,
+        key_id: row.try_get("key_id")?,
+        actor: row.try_get("actor")?,
+        action: row.try_get("action")?,
+        resource_id: row.try_get("resource_id")?,
+        status: row.try_get("status")?,
+        request_id: row.try_get("request_id")?,
+    })
+}
+
+fn job_from_row(row: &SqliteRow) -> Result<Job> {
+    let id: String = row.try_get("id")?;
+    let status: String = row.try_get("status")?;
+    let priority: String = row.try_get("priority")?;
+    let result: Option<String> = row.try_get("result")?;
+    let batch_id: Option<String> = row.try_get("batch_id")?;
+
+    Ok(Job {
+        id: id.parse()?,
+        tenant_id: row.try_get("tenant_id")?,
+        key_id: row.try_get("key_id")?,
+        status: status.parse()?,
+        priority: priority.parse()?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        result: result.map(|r| serde_json::from_str(&r)).transpose()?,
+        error: row.try_get("error")?,
+        batch_id: batch_id.map(|id| id.parse()).transpose()?,
+        batch_row: row.try_get("batch_row")?,
+        label: row.try_get("label")?,
+    })
+}
@@ -0,0 +1,420 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::severity::Severity;
+
+/// More direct dependencies than this is a large surface to keep up to date.
+const LARGE_DIRECT_DEPENDENCIES: usize = 50;
+/// Locked package counts above which the transitive tree is heavy, and very heavy.
+const HEAVY_TREE_PACKAGES: usize = 250;
+const VERY_HEAVY_TREE_PACKAGES: usize = 500;
+
+/// Packages that are unmaintained, deprecated or compromised, with what to do instead.
+const CARGO_UNMAINTAINED: &[(&str, &str)] = &[
+    ("ansi_term", "unmaintained; use nu-ansi-term or anstyle"),
+    ("atty", "unmaintained; use std::io::IsTerminal"),
+    ("dotenv", "unmaintained; use dotenvy"),
+    ("failure", "deprecated; use anyhow or thiserror"),
+    ("instant", "unmaintained; use web-time"),
+    ("mach", "unmaintained; use mach2"),
+    ("memmap", "unmaintained; use memmap2"),
+    ("net2", "deprecated; use socket2"),
+    ("proc-macro-error", "unmaintained; use proc-macro-error2"),
+    ("rustc-serialize", "deprecated; use serde"),
+    ("serde_yaml", "archived by its author; pick a maintained YAML crate"),
+    ("stdweb", "unmaintained; use wasm-bindgen and web-sys"),
+    ("structopt", "in maintenance mode; use clap's derive API"),
+    ("tempdir", "deprecated; use tempfile"),
+    ("term", "unmaintained; use anstyle or crossterm"),
+    ("wee_alloc", "unmaintained and known to leak; use the default allocator"),
+    ("yaml-rust", "unmaintained; use yaml-rust2"),
+];
+const NPM_UNMAINTAINED: &[(&str, &str)] = &[
+    ("babel-eslint", "deprecated; use @babel/eslint-parser"),
+    ("colors", "sabotaged by its author in 1.4.1; pin 1.4.0 or use picocolors"),
+    ("faker", "sabotaged by its author; use @faker-js/faker"),
+    ("gulp-util", "deprecated"),
+    ("istanbul", "deprecated; use nyc or c8"),
+    ("left-pad", "unnecessary; use String.prototype.padStart"),
+    ("moment", "in maintenance mode; use luxon or date-fns"),
+    ("node-sass", "deprecated; use sass"),
+    ("node-uuid", "renamed; use uuid"),
+    ("querystring", "deprecated; use URLSearchParams"),
+    ("request", "deprecated; use fetch, undici or axios"),
+    ("tslint", "deprecated; use eslint with typescript-eslint"),
+    ("uglify-es", "unmaintained; use terser"),
+];
+const PIP_UNMAINTAINED: &[(&str, &str)] = &[
+    ("distribute", "merged into setuptools"),
+    ("mock", "a backport; use unittest.mock"),
+    ("nose", "unmaintained; use pytest"),
+    ("oauth2client", "deprecated; use google-auth"),
+    ("pep8", "renamed; use pycodestyle"),
+    ("pycrypto", "unmaintained with known vulnerabilities; use pycryptodome or cryptography"),
+    ("sklearn", "a deprecated alias; use scikit-learn"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Pip,
+}
+
+impl Ecosystem {
+    fn label(self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "Cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Pip => "pip",
+        }
+    }
+
+    fn unmaintained(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Ecosystem::Cargo => CARGO_UNMAINTAINED,
+            Ecosystem::Npm => NPM_UNMAINTAINED,
+            Ecosystem::Pip => PIP_UNMAINTAINED,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Registry,
+    /// A git repository or URL, which no registry has vetted or can yank.
+    Remote(String),
+    /// A local path, e.g. another workspace member.
+    Local,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: String,
+    /// The version requirement as written; `None` when there's none.
+    pub requirement: Option<String>,
+    pub source: Source,
+    /// Only needed for development, tests or builds.
+    pub dev: bool,
+}
+
+#[derive(Debug)]
+pub struct Manifest {
+    pub ecosystem: Ecosystem,
+    pub file: String,
+    pub dependencies: Vec<Dependency>,
+}
+
+/// A lockfile's contribution: how many packages the whole tree resolves to.
+#[derive(Debug)]
+pub struct Lockfile {
+    pub file: String,
+    pub packages: usize,
+}
+
+pub enum ManifestFile {
+    Manifest(Manifest),
+    Lockfile(Lockfile),
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    Unmaintained,
+    PreRelease,
+    Unbounded,
+    UnvettedSource,
+    LargeDependencyList,
+    HeavyTransitiveTree,
+}
+
+/// A risk the manifest shows without any model involved.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency: Option<String>,
+    pub severity: Severity,
+    pub detail: String,
+}
+
+/// Parses the manifest or lockfile named `file`: `Cargo.toml`, `package.json` or a
+/// `requirements*.txt`, or `Cargo.lock`, `package-lock.json` or `poetry.lock`. `None` for
+/// other names.
+pub fn parse(file: &str, text: &str) -> Option<Result<ManifestFile>> {
+    let name = file.rsplit(['/', '\\']).next().unwrap_or(file);
+    let lower = name.to_ascii_lowercase();
+    let file = name.to_string();
+    let parsed = match lower.as_str() {
+        "cargo.toml" => parse_cargo(text).map(|dependencies| ManifestFile::Manifest(Manifest { ecosystem: Ecosystem::Cargo, file, dependencies })),
+        "package.json" => parse_npm(text).map(|dependencies| ManifestFile::Manifest(Manifest { ecosystem: Ecosystem::Npm, file, dependencies })),
+        _ if lower.starts_with("requirements") && lower.ends_with(".txt") => {
+            Ok(ManifestFile::Manifest(Manifest { ecosystem: Ecosystem::Pip, file, dependencies: parse_requirements(text) }))
+        }
+        "cargo.lock" | "poetry.lock" => count_toml_packages(text).map(|packages| ManifestFile::Lockfile(Lockfile { file, packages })),
+        "package-lock.json" => count_npm_packages(text).map(|packages| ManifestFile::Lockfile(Lockfile { file, packages })),
+        _ => return None,
+    };
+    Some(parsed.with_context(|| format!("Failed to parse {}", name)))
+}
+
+fn parse_cargo(text: &str) -> Result<Vec<Dependency>> {
+    let manifest: toml::Table = toml::from_str(text)?;
+    let mut tables: Vec<(&toml::Value, bool)> = Vec::new();
+    for (key, dev) in [("dependencies", false), ("dev-dependencies", true), ("build-dependencies", true)] {
+        if let Some(table) = manifest.get(key) {
+            tables.push((table, dev));
+        }
+        let targets = manifest.get("target").and_then(toml::Value::as_table).into_iter().flat_map(|targets| targets.values());
+        tables.extend(targets.filter_map(|target| target.get(key)).map(|table| (table, dev)));
+    }
+    if let Some(table) = manifest.get("workspace").and_then(|workspace| workspace.get("dependencies")) {
+        tables.push((table, false));
+    }
+
+    let mut dependencies = Vec::new();
+    for (table, dev) in tables {
+        for (key, spec) in table.as_table().into_iter().flatten() {
+            let dependency = match spec {
+                toml::Value::String(requirement) => {
+                    Dependency { name: key.clone(), requirement: Some(requirement.clone()), source: Source::Registry, dev }
+                }
+                toml::Value::Table(spec) => {
+                    // Inherited from the workspace, where it's listed too.
+                    if spec.get("workspace").and_then(toml::Value::as_bool) == Some(true) {
+                        continue;
+                    }
+                    let source = match (spec.get("git"), spec.get("path")) {
+                        (Some(git), _) => Source::Remote(git.as_str().unwrap_or_default().to_string()),
+                        (None, Some(_)) => Source::Local,
+                        (None, None) => Source::Registry,
+                    };
+                    Dependency {
+                        name: spec.get("package").and_then(toml::Value::as_str).unwrap_or(key).to_string(),
+                        requirement: spec.get("version").and_then(toml::Value::as_str).map(str::to_string),
+                        source,
+                        dev,
+                    }
+                }
+                _ => continue,
+            };
+            dependencies.push(dependency);
+        }
+    }
+    Ok(dependencies)
+}
+
+fn parse_npm(text: &str) -> Result<Vec<Dependency>> {
+    let manifest: Value = serde_json::from_str(text)?;
+    let mut dependencies = Vec::new();
+    for (key, dev) in [("dependencies", false), ("optionalDependencies", false), ("devDependencies", true)] {
+        for (name, spec) in manifest[key].as_object().into_iter().flatten() {
+            let spec = spec.as_str().unwrap_or_default().trim();
+            let remote = ["git", "http:", "https:", "github:", "gitlab:", "bitbucket:"].iter().any(|prefix| spec.starts_with(prefix))
+                // `user/repo` is shorthand for a GitHub repository.
+                || (spec.contains('/') && !spec.starts_with(['.', '~', '/']) && !spec.starts_with("file:") && !spec.starts_with("npm:"));
+            let source = if remote {
+                Source::Remote(spec.to_string())
+            } else if spec.starts_with("file:") || spec.starts_with("link:") || spec.starts_with(['.', '/']) {
+                Source::Local
+            } else {
+                Source::Registry
+            };
+            let requirement = (source == Source::Registry && !spec.is_empty()).then(|| spec.to_string());
+            dependencies.push(Dependency { name: name.clone(), requirement, source, dev });
+        }
+    }
+    Ok(dependencies)
+}
+
+/// One requirement per line; options such as `-r other.txt` and `--hash` are skipped, but
+/// editable and direct URL installs are kept as remote sources.
+fn parse_requirements(text: &str) -> Vec<Dependency> {
+    let mut dependencies = Vec::new();
+    for line in text.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(url) = line.strip_prefix("-e ").or_else(|| line.strip_prefix("--editable ")) {
+            let url = url.trim();
+            let name = url.rsplit_once("#egg=").map_or(url, |(_, name)| name);
+            let source = if url.starts_with('.') || url.starts_with('/') { Source::Local } else { Source::Remote(url.to_string()) };
+            dependencies.push(Dependency { name: name.to_string(), requirement: None, source, dev: false });
+            continue;
+        }
+        if line.starts_with('-') {
+            continue;
+        }
+        // Environment markers don't change what's installed where they apply.
+        let requirement = line.split(';').next().unwrap_or_default().trim();
+        let end = requirement.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))).unwrap_or(requirement.len());
+        let name = &requirement[..end];
+        if name.is_empty() {
+            continue;
+        }
+        let rest = requirement[end..].trim();
+        // Skips extras, e.g. `requests[socks]`.
+        let rest = match rest.strip_prefix('[') {
+            Some(rest) => rest.split_once(']').map_or("", |(_, rest)| rest.trim()),
+            None => rest,
+        };
+        let (requirement, source) = match rest.strip_prefix('@') {
+            Some(url) => (None, Source::Remote(url.trim().to_string())),
+            None => ((!rest.is_empty()).then(|| rest.to_string()), Source::Registry),
+        };
+        dependencies.push(Dependency { name: name.to_string(), requirement, source, dev: false });
+    }
+    dependencies
+}
+
+fn count_toml_packages(text: &str) -> Result<usize> {
+    let lockfile: toml::Table = toml::from_str(text)?;
+    Ok(lockfile.get("package").and_then(toml::Value::as_array).map_or(0, Vec::len))
+}
+
+/// Lockfile version 2 and 3 list every package under `packages`, the root as `""`; version 1
+/// nests them under `dependencies`.
+fn count_npm_packages(text: &str) -> Result<usize> {
+    let lockfile: Value = serde_json::from_str(text)?;
+    if let Some(packages) = lockfile["packages"].as_object() {
+        return Ok(packages.keys().filter(|key| !key.is_empty()).count());
+    }
+    fn count(dependencies: &Value) -> usize {
+        dependencies.as_object().map_or(0, |dependencies| {
+            dependencies.values().map(|dependency| 1 + count(&dependency["dependencies"])).sum()
+        })
+    }
+    Ok(count(&lockfile["dependencies"]))
+}
+
+/// The static findings for `manifest`, and `lockfile` when there is one, most severe first.
+pub fn analyze(manifest: &Manifest, lockfile: Option<&Lockfile>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let finding = |kind, dependency: &Dependency, severity, detail: String| Finding {
+        kind,
+        dependency: Some(dependency.name.clone()),
+        severity,
+        detail,
+    };
+    for dependency in &manifest.dependencies {
+        let name = dependency.name.to_ascii_lowercase();
+        if let Some((_, note)) = manifest.ecosystem.unmaintained().iter().find(|(known, _)| *known == name.replace('_', "-") || *known == name) {
+            let severity = if dependency.dev { Severity::Low } else { Severity::Medium };
+            findings.push(finding(FindingKind::Unmaintained, dependency, severity, format!("{} is {}", dependency.name, note)));
+        }
+        if let Source::Remote(url) = &dependency.source {
+            findings.push(finding(
+                FindingKind::UnvettedSource,
+                dependency,
+                Severity::Medium,
+                format!("{} comes from {} rather than the registry, so it isn't vetted, yanked or audited there", dependency.name, url),
+            ));
+            continue;
+        }
+        if dependency.source == Source::Local {
+            continue;
+        }
+        match dependency.requirement.as_deref().map(str::trim) {
+            None | Some("") | Some("*") | Some("latest") | Some("x") => findings.push(finding(
+                FindingKind::Unbounded,
+                dependency,
+                if dependency.dev { Severity::Low } else { Severity::Medium },
+                format!("{} has no version requirement, so any release, breaking ones included, can be installed", dependency.name),
+            )),
+            Some(requirement) => {
+                if is_unbounded(requirement) {
+                    findings.push(finding(
+                        FindingKind::Unbounded,
+                        dependency,
+                        Severity::Low,
+                        format!("{} only has a lower bound ({}), so future major releases can be installed", dependency.name, requirement),
+                    ));
+                }
+                if is_pre_release(requirement) {
+                    findings.push(finding(
+                        FindingKind::PreRelease,
+                        dependency,
+                        Severity::Low,
+                        format!("{} requires a pre-1.0 release ({}), where minor releases may break its API", dependency.name, requirement),
+                    ));
+                }
+            }
+        }
+    }
+
+    let direct = manifest.dependencies.iter().filter(|dependency| !dependency.dev).count();
+    if direct > LARGE_DIRECT_DEPENDENCIES {
+        findings.push(Finding {
+            kind: FindingKind::LargeDependencyList,
+            dependency: None,
+            severity: Severity::Medium,
+            detail: format!("{} direct dependencies to keep updated and audited", direct),
+        });
+    }
+    if let Some(lockfile) = lockfile.filter(|lockfile| lockfile.packages > HEAVY_TREE_PACKAGES) {
+        let severity = if lockfile.packages > VERY_HEAVY_TREE_PACKAGES { Severity::High } else { Severity::Medium };
+        findings.push(Finding {
+            kind: FindingKind::HeavyTransitiveTree,
+            dependency: None,
+            severity,
+            detail: format!("{} resolves {} packages in total, each a maintainer and release to trust", lockfile.file, lockfile.packages),
+        });
+    }
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    findings
+}
+
+/// `>=1.2` and the like without a matching upper bound.
+fn is_unbounded(requirement: &str) -> bool {
+    let parts: Vec<&str> = requirement.split([',', ' ']).map(str::trim).filter(|part| !part.is_empty()).collect();
+    parts.iter().any(|part| part.starts_with(">=") || (part.starts_with('>') && !part.starts_with(">=")))
+        && !parts.iter().any(|part| part.starts_with('<') || part.starts_with("~=") || part.starts_with("=="))
+        && !requirement.contains("||")
+}
+
+/// Whether the lowest version the requirement allows is a 0.x release.
+fn is_pre_release(requirement: &str) -> bool {
+    requirement
+        .split([',', ' ', '|'])
+        .map(|part| part.trim_start_matches(['^', '~', '=', '>', '<', '!', 'v', ' ']))
+        .find(|part| !part.is_empty())
+        .is_some_and(|version| version == "0" || version.starts_with("0."))
+}
+
+/// What the model is asked to evaluate: the dependency list with the static findings, so its
+/// risks and mitigations build on them.
+pub fn describe(manifest: &Manifest, lockfile: Option<&Lockfile>, findings: &[Finding]) -> String {
+    let dev = manifest.dependencies.iter().filter(|dependency| dependency.dev).count();
+    let mut text = format!(
+        "Dependency manifest {} ({}) with {} dependencies, {} of them for development or builds only",
+        manifest.file,
+        manifest.ecosystem.label(),
+        manifest.dependencies.len(),
+        dev
+    );
+    match lockfile {
+        Some(lockfile) => text.push_str(&format!("; {} resolves {} packages in total.", lockfile.file, lockfile.packages)),
+        None => text.push_str("; no lockfile was provided, so the transitive tree is unknown."),
+    }
+    text.push_str(" Evaluate the project's supply-chain risks and suggest mitigations for them.");
+    if !findings.is_empty() {
+        text.push_str("\n\nStatic analysis found:");
+        for finding in findings {
+            text.push_str(&format!("\n- [{}] {}", finding.severity.as_str(), finding.detail));
+        }
+    }
+    text.push_str("\n\nDependencies:");
+    for dependency in &manifest.dependencies {
+        let version = match &dependency.source {
+            Source::Registry => dependency.requirement.clone().unwrap_or_else(|| "any version".to_string()),
+            Source::Remote(url) => format!("from {}", url),
+            Source::Local => "from a local path".to_string(),
+        };
+        let dev = if dependency.dev { " (development)" } else { "" };
+        text.push_str(&format!("\n- {} {}{}", dependency.name, version, dev));
+    }
+    text
+}
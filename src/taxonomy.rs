@@ -0,0 +1,92 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::RiskItem;
+
+/// Operator-defined set of allowed risk categories, loaded from a TOML file:
+///
+/// ```toml
+/// other = "Other"
+///
+/// [[categories]]
+/// name = "Timeline"
+/// aliases = ["schedule", "deadline"]
+/// description = "Delivery dates, milestones and sequencing"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Taxonomy {
+    pub categories: Vec<Category>,
+    /// Bucket for categories the model invents that don't match any entry.
+    #[serde(default = "default_other")]
+    pub other: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Category {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn default_other() -> String {
+    "Other".to_string()
+}
+
+impl Taxonomy {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read taxonomy file {}", path.display()))?;
+        let taxonomy: Taxonomy = toml::from_str(&text)
+            .with_context(|| format!("Failed to parse taxonomy file {}", path.display()))?;
+        if taxonomy.categories.is_empty() {
+            anyhow::bail!("Taxonomy file {} defines no categories", path.display());
+        }
+        Ok(taxonomy)
+    }
+
+    /// Every category name the model may use, including the "Other" bucket.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.categories.iter().map(|c| c.name.as_str()).collect();
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(&self.other)) {
+            names.push(&self.other);
+        }
+        names
+    }
+
+    /// Sentence appended to the system prompt listing the allowed categories.
+    pub fn prompt_fragment(&self) -> String {
+        let listed: Vec<String> = self
+            .categories
+            .iter()
+            .map(|c| match &c.description {
+                Some(description) => format!("{} ({})", c.name, description),
+                None => c.name.clone(),
+            })
+            .collect();
+        format!(
+            " The category must be exactly one of: {}. Use {} for anything else.",
+            listed.join(", "),
+            self.other
+        )
+    }
+
+    /// Maps a model-supplied category onto the taxonomy by name or alias, ignoring case.
+    pub fn normalize_category(&self, raw: &str) -> String {
+        let raw = raw.trim();
+        self.categories
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(raw) || c.aliases.iter().any(|a| a.eq_ignore_ascii_case(raw)))
+            .map_or_else(|| self.other.clone(), |c| c.name.clone())
+    }
+
+    pub fn normalize(&self, risks: &mut [RiskItem]) {
+        for risk in risks {
+            risk.category = self.normalize_category(&risk.category);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::{env, io::IsTerminal};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use opentelemetry::{global, propagation::Extractor, trace::TracerProvider as _};
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing::{field::Empty, info, info_span, warn, Instrument, Span};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+use crate::logfile::RotatingFile;
+use crate::request_id::RequestId;
+
+/// Log levels when neither `--log-level` nor `RUST_LOG` is given. Raw provider traffic, which contains the caller's
+/// project text, only shows up at `trace`.
+const DEFAULT_LOG_FILTER: &str = "info";
+const SERVICE_NAME: &str = "ai-risk-evaluator";
+
+/// Keeps the span exporter and the log file writer alive; [`Telemetry::shutdown`] flushes them.
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+    log_file: Option<WorkerGuard>,
+}
+
+/// Installs the log subscriber, filtering with `log_level` (from `--log-level`) or else
+/// `RUST_LOG`. Logs go to stdout unless `LOG_STDOUT=false`, and also to a
+/// rotating JSON-lines file when `LOG_FILE` is set (see [`RotatingFile::from_env`]). When
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is set, spans are
+/// also exported over OTLP/HTTP, under `OTEL_SERVICE_NAME` if given, and incoming
+/// `traceparent` headers are honoured.
+pub fn init(log_level: Option<&str>) -> Telemetry {
+    let filter = match log_level {
+        Some(level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER)),
+    };
+    let stdout = env::var("LOG_STDOUT").map_or(true, |v| v != "false" && v != "0");
+    let fmt = stdout.then(|| tracing_subscriber::fmt::layer().with_ansi(std::io::stdout().is_terminal()));
+
+    let (log_file, log_file_error) = match RotatingFile::from_env() {
+        Ok(file) => (file, None),
+        Err(e) => (None, Some(e)),
+    };
+    let log_file_path = log_file.as_ref().map(|file| file.path().display().to_string());
+    let (file_writer, log_file) = log_file.map(tracing_appender::non_blocking).unzip();
+    let file = file_writer.map(|writer| tracing_subscriber::fmt::layer().json().with_writer(writer));
+
+    let exporting = ["OTEL_EXPORTER_OTLP_ENDPOINT", "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"]
+        .iter()
+        .any(|name| env::var(name).is_ok_and(|v| !v.is_empty()));
+    // The blocking HTTP client can't be built on a runtime thread.
+    let exporter = exporting.then(|| std::thread::spawn(|| SpanExporter::builder().with_http().build()).join());
+    let (provider, exporter_error) = match exporter {
+        Some(Ok(Ok(exporter))) => {
+            let mut resource = Resource::builder();
+            if env::var("OTEL_SERVICE_NAME").is_err() {
+                resource = resource.with_service_name(SERVICE_NAME);
+            }
+            let provider = SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(resource.build())
+                .build();
+            global::set_text_map_propagator(TraceContextPropagator::new());
+            (Some(provider), None)
+        }
+        Some(Ok(Err(e))) => (None, Some(e.to_string())),
+        Some(Err(_)) => (None, Some("the exporter panicked while starting".to_string())),
+        None => (None, None),
+    };
+    let otel = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    tracing_subscriber::registry().with(filter).with(fmt).with(file).with(otel).init();
+    if let Some(path) = log_file_path {
+        info!("📝 Logging to {}", path);
+    }
+    if let Some(e) = log_file_error {
+        warn!("⚠️ File logging disabled: {:?}", e);
+    }
+    if provider.is_some() {
+        info!("🔭 Exporting traces over OTLP");
+    }
+    if let Some(e) = exporter_error {
+        warn!("⚠️ Trace export disabled: {}", e);
+    }
+    Telemetry { provider, log_file }
+}
+
+impl Telemetry {
+    /// Sends spans still buffered by the exporter and writes out pending log lines.
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                warn!("⚠️ Failed to flush traces: {}", e);
+            }
+        }
+        drop(self.log_file);
+    }
+}
+
+/// Opens the server span for a request, as a child of the caller's trace when it sent a
+/// `traceparent` header. Every log line the request produces carries its request ID.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let request_id = request.extensions().get::<RequestId>().map_or("", |id| id.0.as_str());
+    let span = info_span!(
+        "request",
+        otel.name = Empty,
+        otel.kind = "server",
+        request_id = %request_id,
+        http.request.method = %method,
+        http.route = Empty,
+        http.response.status_code = Empty,
+    );
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(request.headers())));
+    // Only fails when traces aren't being exported.
+    let _ = span.set_parent(parent);
+
+    let response = next.run(request).instrument(span.clone()).await;
+    span.record("http.response.status_code", response.status().as_u16());
+    response
+}
+
+/// Names the server span after the matched route, e.g. `GET /jobs/:id`, once routing is done;
+/// unrouted requests keep the generic `request` name.
+pub async fn record_route(request: Request, next: Next) -> Response {
+    if let Some(route) = request.extensions().get::<MatchedPath>() {
+        let span = Span::current();
+        span.record("http.route", route.as_str());
+        span.record("otel.name", format!("{} {}", request.method(), route.as_str()));
+    }
+    next.run(request).await
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::HashMap, env, fs, sync::Arc};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::analysis::AnalysisOptions;
+use crate::taxonomy::Taxonomy;
+
+/// Tenant of keys and records that don't name one, including everything stored before
+/// tenants existed.
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Per-tenant overrides, loaded from a TOML file:
+///
+/// ```toml
+/// [[tenants]]
+/// id = "research"
+/// taxonomy_file = "taxonomy.research.toml"
+/// instructions = "Pay particular attention to data-protection risks."
+/// token_budget_per_month = 500000
+/// ```
+#[derive(Debug, Deserialize)]
+struct TenantsFile {
+    tenants: Vec<TenantEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TenantEntry {
+    id: String,
+    #[serde(default)]
+    taxonomy_file: Option<String>,
+    #[serde(default)]
+    instructions: Option<String>,
+    #[serde(default)]
+    token_budget_per_month: Option<i64>,
+}
+
+struct TenantConfig {
+    taxonomy: Option<Arc<Taxonomy>>,
+    /// Appended to the system prompt.
+    instructions: Option<String>,
+    token_budget_per_month: Option<i64>,
+}
+
+/// Tenant-specific analysis settings and budgets. Tenants without an entry, and settings an
+/// entry leaves out, fall back to the service-wide configuration.
+#[derive(Default)]
+pub struct Tenants {
+    configs: HashMap<String, TenantConfig>,
+}
+
+impl Tenants {
+    /// Reads the file named by `TENANTS_FILE`, if set.
+    pub fn from_env() -> Self {
+        let Ok(path) = env::var("TENANTS_FILE") else {
+            return Self::default();
+        };
+        match load(&path) {
+            Ok(configs) => {
+                info!("🏢 Loaded settings for {} tenants from {}", configs.len(), path);
+                Self { configs }
+            }
+            Err(e) => {
+                warn!("⚠️ {:?}, tenants will use the service-wide settings.", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn token_budget(&self, tenant: &str) -> Option<i64> {
+        self.configs.get(tenant).and_then(|config| config.token_budget_per_month)
+    }
+
+    /// Overrides the taxonomy and prompt instructions with the tenant's own.
+    pub fn apply(&self, tenant: &str, options: &mut AnalysisOptions) {
+        let Some(config) = self.configs.get(tenant) else {
+            return;
+        };
+        if let Some(taxonomy) = &config.taxonomy {
+            options.taxonomy = Some(taxonomy.clone());
+        }
+        if let Some(instructions) = &config.instructions {
+            options.instructions = Some(instructions.clone());
+        }
+    }
+}
+
+fn load(path: &str) -> Result<HashMap<String, TenantConfig>> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read tenants file {}", path))?;
+    let file: TenantsFile = toml::from_str(&text).with_context(|| format!("Failed to parse tenants file {}", path))?;
+
+    file.tenants
+        .into_iter()
+        .map(|entry| {
+            let taxonomy = entry
+                .taxonomy_file
+                .map(|path| Taxonomy::load(path).with_context(|| format!("Tenant '{}'", entry.id)))
+                .transpose()?
+                .map(Arc::new);
+            let config = TenantConfig {
+                taxonomy,
+                instructions: entry.instructions,
+                token_budget_per_month: entry.token_budget_per_month,
+            };
+            Ok((entry.id, config))
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+use std::{
+    fs,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use rustls::{
+    crypto::{ring, CryptoProvider},
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    ServerConfig,
+};
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, info};
+
+use crate::config::TlsConfig;
+
+const DEFAULT_RELOAD_SECS: u64 = 60;
+
+/// The certificate served on the API listeners, swapped in place when the files change so
+/// renewals don't need a restart. Connections already open keep the certificate they started
+/// with.
+#[derive(Debug)]
+pub struct Certificates {
+    cert_file: String,
+    key_file: String,
+    provider: Arc<CryptoProvider>,
+    current: RwLock<Arc<CertifiedKey>>,
+    /// The files' modification times as of the last load.
+    seen: Mutex<[Option<SystemTime>; 2]>,
+    interval: Option<Duration>,
+}
+
+impl Certificates {
+    /// `None` when TLS isn't configured.
+    pub fn from_config(config: &TlsConfig) -> Result<Option<Arc<Self>>> {
+        let (cert_file, key_file) = match (&config.cert_file, &config.key_file) {
+            (Some(cert_file), Some(key_file)) => (cert_file.clone(), key_file.clone()),
+            (None, None) => return Ok(None),
+            _ => anyhow::bail!("TLS needs both a certificate and a key file"),
+        };
+        let provider = Arc::new(ring::default_provider());
+        let seen = modification_times(&cert_file, &key_file);
+        let key = load(&cert_file, &key_file, &provider)?;
+        let secs = config.reload_secs.unwrap_or(DEFAULT_RELOAD_SECS);
+        info!("🔐 Serving HTTPS with the certificate in {}", cert_file);
+        Ok(Some(Arc::new(Self {
+            cert_file,
+            key_file,
+            provider,
+            current: RwLock::new(Arc::new(key)),
+            seen: Mutex::new(seen),
+            interval: (secs > 0).then(|| Duration::from_secs(secs)),
+        })))
+    }
+
+    pub fn acceptor(self: &Arc<Self>) -> TlsAcceptor {
+        let mut config = ServerConfig::builder_with_provider(self.provider.clone())
+            .with_safe_default_protocol_versions()
+            .expect("the ring provider supports the default protocol versions")
+            .with_no_client_auth()
+            .with_cert_resolver(self.clone());
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        TlsAcceptor::from(Arc::new(config))
+    }
+
+    /// Loads the files again if either changed, keeping the current certificate when the new
+    /// one doesn't load, e.g. because only one of the two files has been replaced so far.
+    fn reload_if_changed(&self) {
+        let mut seen = self.seen.lock().unwrap();
+        let times = modification_times(&self.cert_file, &self.key_file);
+        if times == *seen {
+            return;
+        }
+        // Remembered even when loading fails, so a half-replaced pair is reported once and
+        // loaded again when the other file changes.
+        *seen = times;
+        match load(&self.cert_file, &self.key_file, &self.provider) {
+            Ok(key) => {
+                *self.current.write().unwrap() = Arc::new(key);
+                info!("🔐 Reloaded the TLS certificate from {}", self.cert_file);
+            }
+            Err(e) => error!("❌ Failed to reload the TLS certificate, keeping the current one: {:?}", e),
+        }
+    }
+}
+
+impl ResolvesServerCert for Certificates {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+fn load(cert_file: &str, key_file: &str, provider: &CryptoProvider) -> Result<CertifiedKey> {
+    let chain = CertificateDer::pem_file_iter(cert_file)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read TLS certificate {}", cert_file))?;
+    if chain.is_empty() {
+        anyhow::bail!("No certificate in {}", cert_file);
+    }
+    let key = PrivateKeyDer::from_pem_file(key_file).with_context(|| format!("Failed to read TLS key {}", key_file))?;
+    CertifiedKey::from_der(chain, key, provider)
+        .with_context(|| format!("TLS key {} doesn't fit certificate {}", key_file, cert_file))
+}
+
+fn modification_times(cert_file: &str, key_file: &str) -> [Option<SystemTime>; 2] {
+    [cert_file, key_file].map(|path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+}
+
+/// Checks for a renewed certificate every `reload_secs` (default 60).
+pub fn spawn_watcher(certificates: Arc<Certificates>) {
+    let Some(interval) = certificates.interval else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            certificates.reload_if_changed();
+        }
+    });
+}
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+use tracing::{info, warn};
+
+use crate::config::AnalysisConfig;
+
+const DEFAULT_MAX_INPUT_TOKENS: usize = 200_000;
+/// Ends a truncated text, so the model knows it's reading part of the project.
+const TRUNCATION_MARKER: &str = "\n\n[The rest of the project text was cut to fit the input limit.]";
+
+/// What happens to a project text over `max_input_tokens`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverLimit {
+    /// Refuse the request with a 413.
+    #[default]
+    Reject,
+    /// Evaluate the beginning of the text, up to the limit.
+    Truncate,
+}
+
+impl FromStr for OverLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "reject" => Ok(Self::Reject),
+            "truncate" => Ok(Self::Truncate),
+            other => Err(format!("Unknown token limit policy '{}'", other)),
+        }
+    }
+}
+
+/// The most tokens a project text, description and documents together, may have. Counted with
+/// OpenAI's `cl100k_base` encoding whatever the provider, which is close enough to the others'
+/// tokenizers for a limit that's meant to catch texts far too long, not to be exact.
+#[derive(Debug, Clone, Copy)]
+pub struct InputLimit {
+    pub max_tokens: usize,
+    pub over_limit: OverLimit,
+}
+
+impl Default for InputLimit {
+    fn default() -> Self {
+        Self { max_tokens: DEFAULT_MAX_INPUT_TOKENS, over_limit: OverLimit::default() }
+    }
+}
+
+impl InputLimit {
+    /// An unknown policy is logged and replaced by rejecting.
+    pub fn from_config(config: &AnalysisConfig) -> Self {
+        let over_limit = match &config.over_token_limit {
+            Some(policy) => policy.parse().unwrap_or_else(|e| {
+                warn!("⚠️ {}, rejecting texts over the limit.", e);
+                OverLimit::default()
+            }),
+            None => OverLimit::default(),
+        };
+        Self { max_tokens: config.max_input_tokens.filter(|&n| n > 0).unwrap_or(DEFAULT_MAX_INPUT_TOKENS), over_limit }
+    }
+
+    /// `text` as it should be evaluated, and whether it was truncated; an error for a text
+    /// over the limit when it isn't truncated. Every token is at least a byte, so texts of at
+    /// most `max_tokens` bytes pass without being counted. Counting is CPU-bound on long texts,
+    /// so it runs on the blocking pool.
+    pub async fn apply(&self, text: String) -> Result<(String, bool), String> {
+        if text.len() <= self.max_tokens {
+            return Ok((text, false));
+        }
+        let limit = *self;
+        tokio::task::spawn_blocking(move || limit.apply_blocking(text))
+            .await
+            .map_err(|e| format!("Failed to count the project text's tokens: {}", e))?
+    }
+
+    fn apply_blocking(self, text: String) -> Result<(String, bool), String> {
+        let bpe = tiktoken_rs::cl100k_base_singleton();
+        let tokens = bpe.encode_ordinary(&text);
+        if tokens.len() <= self.max_tokens {
+            return Ok((text, false));
+        }
+        if self.over_limit == OverLimit::Reject {
+            return Err(format!(
+                "The project text is {} tokens, over the {} token limit; shorten it or leave parts out",
+                tokens.len(),
+                self.max_tokens
+            ));
+        }
+        let kept = self.max_tokens.saturating_sub(bpe.encode_ordinary(TRUNCATION_MARKER).len());
+        let mut end = bpe.decode_bytes(&tokens[..kept]).map_or(0, |bytes| bytes.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        info!(tokens = tokens.len(), kept, "✂️ Truncated the project text to the input token limit");
+        Ok((format!("{}{}", &text[..end], TRUNCATION_MARKER), true))
+    }
+}
@@ -0,0 +1,130 @@
+use std::{env, net::SocketAddr, time::Duration};
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use sha2::Sha256;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::ingest::AddressGuard;
+use crate::ratelimit::ip_list;
+
+const SIGNATURE_HEADER: &str = "x-webhook-signature";
+const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+const JOB_HEADER: &str = "x-job-id";
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Delivers finished async jobs to the caller's `callback_url`. Each POST carries
+///
+/// ```text
+/// X-Webhook-Timestamp: <unix seconds>
+/// X-Webhook-Signature: sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">
+/// ```
+///
+/// keyed with `WEBHOOK_SECRET`, so receivers can check both origin and freshness. Callback
+/// hosts go through an [`AddressGuard`] when the job is submitted and again on every attempt,
+/// so a caller can't have results posted to the internal network.
+#[derive(Clone)]
+pub struct WebhookSender {
+    guard: AddressGuard,
+    secret: String,
+    max_attempts: u32,
+}
+
+impl WebhookSender {
+    /// Enabled by `WEBHOOK_SECRET`; `WEBHOOK_MAX_ATTEMPTS` bounds delivery attempts (default 5).
+    /// `WEBHOOK_ALLOWED_NETS` lists internal addresses or CIDR blocks receivers may still be
+    /// at, comma-separated.
+    pub fn from_env() -> Option<Self> {
+        let secret = env::var("WEBHOOK_SECRET").ok().filter(|s| !s.is_empty())?;
+        let max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        let allowed: Vec<String> = env::var("WEBHOOK_ALLOWED_NETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string)
+            .collect();
+        let guard = AddressGuard::new(ip_list("WEBHOOK_ALLOWED_NETS", &allowed));
+        info!("🪝 Webhooks enabled ({} attempts per delivery)", max_attempts);
+        Some(Self { guard, secret, max_attempts })
+    }
+
+    /// Only absolute `http(s)` URLs whose host resolves to public (or allowed) addresses are
+    /// accepted; internal ones are a 403.
+    pub async fn check_url(&self, url: &str) -> Result<Url, (StatusCode, String)> {
+        let parsed = Url::parse(url).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid callback_url: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err((StatusCode::BAD_REQUEST, "callback_url must be an http or https URL".to_string()));
+        }
+        self.guard.resolve(&parsed).await?;
+        Ok(parsed)
+    }
+
+    /// POSTs `body` until the receiver answers 2xx, backing off exponentially between attempts.
+    /// Client errors other than 408 and 429 are not retried, and neither is a host that now
+    /// resolves to an internal address.
+    pub async fn deliver(&self, job_id: Uuid, url: Url, body: String) {
+        let mut backoff = INITIAL_BACKOFF;
+        for attempt in 1..=self.max_attempts {
+            let sent = match self.guard.resolve(&url).await {
+                Ok(addrs) => self.send(job_id, &url, &addrs, &body).await,
+                Err((StatusCode::FORBIDDEN, message)) => {
+                    error!(%job_id, %url, "❌ {}; giving up on the webhook", message);
+                    return;
+                }
+                Err((_, message)) => Err(anyhow::anyhow!(message)),
+            };
+            match sent {
+                Ok(status) if status.is_success() => {
+                    info!(%job_id, %url, attempt, "🪝 Delivered webhook");
+                    return;
+                }
+                Ok(status) if status.is_client_error() && !matches!(status.as_u16(), 408 | 429) => {
+                    error!(%job_id, %status, "❌ Webhook rejected; giving up");
+                    return;
+                }
+                Ok(status) => warn!(%job_id, %status, attempt, "⚠️ Webhook delivery failed"),
+                Err(e) => warn!(%job_id, error = %e, attempt, "⚠️ Webhook delivery failed"),
+            }
+            if attempt < self.max_attempts {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+        error!(%job_id, attempts = self.max_attempts, "❌ Gave up delivering webhook");
+    }
+
+    /// Connects to `addrs` only, and doesn't follow redirects, which are reported as failures.
+    async fn send(&self, job_id: Uuid, url: &Url, addrs: &[SocketAddr], body: &str) -> Result<reqwest::StatusCode> {
+        let client = AddressGuard::pinned(url, addrs).timeout(ATTEMPT_TIMEOUT).build()?;
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let response = client
+            .post(url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, &timestamp)
+            .header(SIGNATURE_HEADER, format!("sha256={}", self.sign(&timestamp, body)))
+            .header(JOB_HEADER, job_id.to_string())
+            .body(body.to_string())
+            .send()
+            .await?;
+        Ok(response.status())
+    }
+
+    fn sign(&self, timestamp: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
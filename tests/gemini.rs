@@ -0,0 +1,132 @@
+//! Runs the service against a local stand-in for the Generative Language API and checks the
+//! `generateContent` call it makes and what it makes of the reply.
+
+use std::net::{SocketAddr, TcpListener};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+const API_KEY: &str = "gemini-test-key";
+const DESCRIPTION: &str = "Migrate the billing system to a new vendor before the contract ends in March.";
+
+/// What the mock saw of the last `generateContent` call: the model segment, the API key
+/// header and the body.
+type Captured = Arc<Mutex<Option<(String, Option<String>, Value)>>>;
+
+async fn generate_content(
+    State(captured): State<Captured>,
+    Path(target): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let key = headers.get("x-goog-api-key").and_then(|v| v.to_str().ok()).map(str::to_string);
+    *captured.lock().unwrap() = Some((target, key, body));
+    let risks = json!([
+        { "severity": "high", "category": "Schedule", "mitigation": "Negotiate an extension with the current vendor." },
+        { "severity": "medium", "category": "Data", "mitigation": "Reconcile invoices across both systems." },
+    ]);
+    let text = risks.to_string();
+    let (head, tail) = text.split_at(text.len() / 2);
+    Json(json!({
+        "candidates": [{ "content": { "role": "model", "parts": [{ "text": head }, { "text": tail }] } }],
+        "usageMetadata": { "promptTokenCount": 321, "candidatesTokenCount": 54 },
+    }))
+}
+
+/// Serves the mock API on a free port.
+async fn mock_gemini() -> (SocketAddr, Captured) {
+    let captured = Captured::default();
+    let app = Router::new()
+        .route("/v1beta/models", get(|| async { Json(json!({ "models": [] })) }))
+        .route("/v1beta/models/:target", post(generate_content))
+        .with_state(captured.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, captured)
+}
+
+/// The service binary, killed when dropped.
+struct Service {
+    child: Child,
+    addr: SocketAddr,
+}
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn free_port() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+async fn start_service(gemini: SocketAddr) -> Service {
+    let addr = free_port();
+    let child = Command::new(env!("CARGO_BIN_EXE_ai-risk-evaluator"))
+        .env("LLM_PROVIDER", "gemini")
+        .env("GEMINI_API_KEY", API_KEY)
+        .env("GEMINI_BASE_URL", format!("http://{}/v1beta", gemini))
+        .env("GEMINI_MODEL", "gemini-test")
+        .env("AUTH_DISABLED", "1")
+        .env("DATABASE_URL", "sqlite::memory:")
+        .env("BIND_ADDR", addr.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("the service binary starts");
+    let service = Service { child, addr };
+
+    let client = reqwest::Client::new();
+    for _ in 0..100 {
+        if let Ok(resp) = client.get(format!("http://{}/healthz", addr)).send().await {
+            if resp.status().is_success() {
+                return service;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("the service did not come up on {}", addr);
+}
+
+#[tokio::test]
+async fn evaluates_through_generate_content() {
+    let (gemini, captured) = mock_gemini().await;
+    let service = start_service(gemini).await;
+
+    let resp = reqwest::Client::new()
+        .post(format!("http://{}/evaluate", service.addr))
+        .json(&json!({ "description": DESCRIPTION }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "evaluate returned {}", resp.status());
+    let evaluation: Value = resp.json().await.unwrap();
+
+    let (target, key, body) = captured.lock().unwrap().take().expect("generateContent was called");
+    assert_eq!(target, "gemini-test:generateContent");
+    assert_eq!(key.as_deref(), Some(API_KEY));
+
+    let system = body["systemInstruction"]["parts"][0]["text"].as_str().unwrap();
+    assert!(system.contains("severity"));
+    let contents = body["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 1);
+    assert_eq!(contents[0]["role"], "user");
+    assert!(contents[0]["parts"][0]["text"].as_str().unwrap().contains(DESCRIPTION));
+    assert!(body["generationConfig"]["maxOutputTokens"].as_u64().is_some_and(|n| n > 0));
+    assert!(body["generationConfig"]["temperature"].is_number());
+
+    let risks = evaluation["risks"].as_array().unwrap();
+    assert_eq!(risks.len(), 2);
+    assert_eq!(risks[0]["severity"], "High");
+    assert_eq!(risks[0]["category"], "Schedule");
+    assert_eq!(risks[1]["mitigation"], "Reconcile invoices across both systems.");
+}